@@ -0,0 +1,20 @@
+use downdelving::saveload_system;
+use std::path::Path;
+
+const USAGE: &str = "Usage: convert_save INPUT OUTPUT
+
+Rewrites a save in another format, chosen by the output's extension:
+  .json  readable JSON
+  .sav   compact binary";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 2 {
+        println!("{}", USAGE);
+        std::process::exit(2);
+    }
+    if let Err(err) = saveload_system::convert_save(Path::new(&args[0]), Path::new(&args[1])) {
+        eprintln!("Unable to convert {}: {}", args[0], err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,99 @@
+use downdelving::headless::Simulation;
+use downdelving::replay;
+use downdelving::PlayerCommand;
+
+const USAGE: &str = "Usage: headless [--seed N] [--script FILE] [--turns N] [--record FILE]
+       headless --replay FILE
+
+  --seed N        the world seed to generate the game from
+  --script FILE   a JSON array of player commands to run, in order
+  --turns N       skip N extra turns after the script has finished
+  --record FILE   write a replay of the run to FILE
+  --replay FILE   play back a recorded game and check it ends the same way";
+
+fn main() {
+    let mut commands: Vec<PlayerCommand> = Vec::new();
+    let mut turns = 0;
+    let mut seed = None;
+    let mut record = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--script" => {
+                let path = args.next().expect(USAGE);
+                let script = std::fs::read_to_string(&path)
+                    .unwrap_or_else(|err| panic!("Unable to read {}: {}", path, err));
+                commands.extend(
+                    serde_json::from_str::<Vec<PlayerCommand>>(&script)
+                        .unwrap_or_else(|err| panic!("Unable to parse {}: {}", path, err)),
+                );
+            }
+            "--seed" => {
+                seed = Some(
+                    args.next()
+                        .and_then(|n| n.parse::<u64>().ok())
+                        .expect(USAGE),
+                );
+            }
+            "--record" => {
+                record = Some(args.next().expect(USAGE));
+            }
+            "--replay" => {
+                let path = args.next().expect(USAGE);
+                std::process::exit(play_replay(&path));
+            }
+            "--turns" => {
+                turns = args
+                    .next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .expect(USAGE);
+            }
+            _ => {
+                println!("{}", USAGE);
+                return;
+            }
+        }
+    }
+    commands.extend(std::iter::repeat_n(PlayerCommand::SkipTurn, turns));
+
+    let mut simulation = match seed {
+        Some(seed) => Simulation::with_seed(seed),
+        None => Simulation::new(),
+    };
+    simulation.run(&commands);
+    if let Some(path) = record {
+        let replay = replay::capture(&simulation.state.ecs);
+        std::fs::write(&path, serde_json::to_string(&replay).unwrap())
+            .unwrap_or_else(|err| panic!("Unable to write {}: {}", path, err));
+    }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&simulation.report()).unwrap()
+    );
+}
+
+fn play_replay(path: &str) -> i32 {
+    let replay = match replay::load_replay(path) {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    match replay::verify(&replay) {
+        Ok(simulation) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&simulation.report()).unwrap()
+            );
+            0
+        }
+        Err(problems) => {
+            for problem in problems.iter() {
+                eprintln!("{}", problem);
+            }
+            1
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use downdelving::biome;
+use downdelving::map_builders::export::export_build;
+use downdelving::map_builders::{depth_builder, BuilderChain};
+use downdelving::mapgen_analysis::{analyze_map, MapReport};
+use downdelving::raws;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const USAGE: &str =
+    "Usage: mapgen-analyzer [--depth N | --biome NAME | --random] [--runs N] [--seed N]
+                       [--width N] [--height N] [--json] [--export DIR]
+
+  --depth N       build levels at depth N, as the game would (the default is 1)
+  --biome NAME    build the named biome from the raws, at its shallowest depth unless --depth
+                  is given; a biome only found in a branch is built as that branch's level
+  --random        build with the random builder
+  --runs N        how many maps to build (the default is 20)
+  --seed N        the seed of the first map; each run after it adds one (the default is 1)
+  --width N       the map width (the default is 80)
+  --height N      the map height (the default is 50)
+  --json          print every map's report as JSON instead of a summary
+  --export DIR    write every map, and each step of building it, to DIR as text and as REX
+                  Paint .xp files named after the seed
+
+Exits with 1 if any map failed to build or came out degenerate.";
+
+fn main() {
+    let mut depth: Option<i32> = None;
+    let mut biome_name = None;
+    let mut random = false;
+    let mut runs: u64 = 20;
+    let mut seed: u64 = 1;
+    let mut width: i32 = 80;
+    let mut height: i32 = 50;
+    let mut json = false;
+    let mut export: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => depth = Some(number(args.next())),
+            "--biome" => biome_name = Some(args.next().expect(USAGE)),
+            "--random" => random = true,
+            "--runs" => runs = number(args.next()),
+            "--seed" => seed = number(args.next()),
+            "--width" => width = number(args.next()),
+            "--height" => height = number(args.next()),
+            "--json" => json = true,
+            "--export" => export = Some(PathBuf::from(args.next().expect(USAGE))),
+            _ => {
+                println!("{}", USAGE);
+                return;
+            }
+        }
+    }
+
+    raws::load_raws();
+    let make_chain: Box<dyn Fn() -> BuilderChain> = if let Some(name) = biome_name {
+        let biome = raws::get_biome(&raws::RAWS.lock().unwrap(), &name)
+            .unwrap_or_else(|| panic!("There's no biome called {}", name));
+        let depth = depth.unwrap_or(biome.min_depth);
+        let level = if biome.branch_only {
+            raws::branch_level_for_biome(&raws::RAWS.lock().unwrap(), &biome.name)
+        } else {
+            None
+        };
+        Box::new(move || {
+            let mut chain = biome::biome_builder(&biome, depth, width, height);
+            if let Some(level) = &level {
+                chain.build_data.map.level = level.clone();
+            }
+            chain
+        })
+    } else if random {
+        let depth = depth.unwrap_or(1);
+        Box::new(move || biome::random_builder(depth, width, height))
+    } else {
+        let depth = depth.unwrap_or(1);
+        Box::new(move || depth_builder(depth, width, height))
+    };
+    let depth = depth.unwrap_or(1);
+    let record_history = export.is_some();
+    let make_chain = move || {
+        let mut chain = make_chain();
+        chain.build_data.record_history = record_history;
+        chain
+    };
+
+    // Failures are reported with the rest of the results, not as they happen.
+    std::panic::set_hook(Box::new(|_| {}));
+    let mut reports = Vec::new();
+    for run in 0..runs {
+        let (report, build_data) = analyze_map(&make_chain, depth, seed + run);
+        if let (Some(directory), Some(build_data)) = (&export, build_data) {
+            let name = format!("seed-{}", report.seed);
+            if let Err(err) = export_build(&build_data, directory, &name) {
+                eprintln!("Unable to export {}: {}", name, err);
+            }
+        }
+        reports.push(report);
+    }
+    let _ = std::panic::take_hook();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+    } else {
+        print_summary(&reports);
+    }
+    if reports
+        .iter()
+        .any(|report| report.failure.is_some() || !report.problems.is_empty())
+    {
+        std::process::exit(1);
+    }
+}
+
+fn number<T: std::str::FromStr>(arg: Option<String>) -> T {
+    arg.and_then(|n| n.parse::<T>().ok()).expect(USAGE)
+}
+
+fn print_summary(reports: &[MapReport]) {
+    for report in reports.iter() {
+        if let Some(failure) = &report.failure {
+            println!("seed {:>6}: FAILED: {}", report.seed, failure);
+            continue;
+        }
+        println!(
+            "seed {:>6}: {:<28} walkable {:>5.1}%  regions {:>3} -> {}  path {:>6}  spawns {:>3}",
+            report.seed,
+            report.map_name,
+            report.walkable_ratio * 100.0,
+            report
+                .regions_before_cull
+                .map(|regions| regions.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            report.regions,
+            report
+                .path_length
+                .map(|length| format!("{:.1}", length))
+                .unwrap_or_else(|| "-".to_string()),
+            report.spawns.values().sum::<usize>(),
+        );
+        for problem in report.problems.iter() {
+            println!("              {}", problem);
+        }
+    }
+
+    let built = reports
+        .iter()
+        .filter(|report| report.failure.is_none())
+        .collect::<Vec<&MapReport>>();
+    let degenerate = built
+        .iter()
+        .filter(|report| !report.problems.is_empty())
+        .count();
+    println!();
+    println!(
+        "{} maps: {} failed, {} degenerate",
+        reports.len(),
+        reports.len() - built.len(),
+        degenerate
+    );
+    if built.is_empty() {
+        return;
+    }
+
+    let walkable = built
+        .iter()
+        .map(|report| report.walkable_ratio * 100.0)
+        .collect::<Vec<f32>>();
+    print_range("walkable %", &walkable);
+    let paths = built
+        .iter()
+        .filter_map(|report| report.path_length)
+        .collect::<Vec<f32>>();
+    print_range("path length", &paths);
+
+    let mut spawns: BTreeMap<&str, usize> = BTreeMap::new();
+    for report in built.iter() {
+        for (name, count) in report.spawns.iter() {
+            *spawns.entry(name).or_insert(0) += count;
+        }
+    }
+    println!("spawns per map:");
+    for (name, count) in spawns.iter() {
+        println!("  {:>6.2}  {}", *count as f32 / built.len() as f32, name);
+    }
+}
+
+fn print_range(label: &str, values: &[f32]) {
+    if values.is_empty() {
+        println!("{:<12} -", label);
+        return;
+    }
+    let min = values.iter().cloned().fold(f32::MAX, f32::min);
+    let max = values.iter().cloned().fold(f32::MIN, f32::max);
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    println!(
+        "{:<12} min {:.1}  mean {:.1}  max {:.1}",
+        label, min, mean, max
+    );
+}
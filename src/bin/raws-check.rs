@@ -0,0 +1,18 @@
+use downdelving::raws;
+
+/// Reads the raws, with any mods in ./mods, and lists every problem with them.
+fn main() {
+    let problems = match raws::read_raws() {
+        Ok(raws) => raws::check_raws(&raws),
+        Err(problems) => problems,
+    };
+    if problems.is_empty() {
+        println!("No problems found.");
+        return;
+    }
+    for problem in problems.iter() {
+        println!("{}", problem);
+    }
+    println!("{} problem(s) found.", problems.len());
+    std::process::exit(1);
+}
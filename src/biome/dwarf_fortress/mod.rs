@@ -1,4 +0,0 @@
-pub mod default;
-pub use default::*;
-pub mod upper_reaches;
-pub use upper_reaches::*;
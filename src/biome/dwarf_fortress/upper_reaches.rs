@@ -1,23 +0,0 @@
-use crate::map_builders::{
-    AreaEndingPosition, AreaStartingPosition, BuilderChain, CaveDecorator, CaveTransition,
-    CellularAutomataBuilder, CullUnreachable, VoronoiSpawning, XEnd, XStart, YEnd, YStart,
-};
-
-pub fn dwarf_fortress_upper_reaches_builder(
-    new_depth: i32,
-    width: i32,
-    height: i32,
-) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dwarf Fort - Upper Reaches");
-    chain.start_with(CellularAutomataBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain.with(CaveDecorator::new());
-    chain.with(CaveTransition::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaEndingPosition::new(XEnd::RIGHT, YEnd::CENTER));
-    chain
-}
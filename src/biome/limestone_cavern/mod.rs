@@ -1,4 +0,0 @@
-pub mod default;
-pub use default::*;
-pub mod deep;
-pub use deep::*;
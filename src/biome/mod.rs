@@ -1,14 +1,22 @@
-pub mod dark_elf_city;
-pub use dark_elf_city::*;
-pub mod dwarf_fortress;
-pub use dwarf_fortress::*;
-pub mod forest;
-pub use forest::*;
-pub mod human_town;
-pub use human_town::*;
-pub mod limestone_cavern;
-pub use limestone_cavern::*;
-pub mod mushroom_forest;
-pub use mushroom_forest::*;
 pub mod random;
 pub use random::*;
+
+use crate::map_builders::{named, BuilderChain};
+use crate::raws::Biome;
+
+/// Builds the chain a biome from the raws describes. Raws with steps that can't be made are
+/// refused when they're loaded, so this doesn't fail.
+pub fn biome_builder(biome: &Biome, new_depth: i32, width: i32, height: i32) -> BuilderChain {
+    let mut chain = BuilderChain::new(new_depth, width, height, &biome.map_name);
+    chain.build_data.biome_tags = biome.tags.clone();
+    chain.build_data.map.theme = biome.theme.clone();
+    chain.build_data.map.ambient_light = biome
+        .ambient_light
+        .as_ref()
+        .map(|color| rltk::RGB::from_hex(color).expect("Bad color"));
+    chain.start_with(named::initial_builder(&biome.initial).unwrap());
+    for step in biome.builders.iter() {
+        chain.with(named::meta_builder(step).unwrap());
+    }
+    chain
+}
@@ -1,6 +0,0 @@
-pub mod default;
-pub use default::*;
-pub mod entrance;
-pub use entrance::*;
-pub mod exit;
-pub use exit::*;
@@ -1,3 +1,5 @@
+use crate::dice::Dice;
+use crate::map::{HazardKind, LevelId};
 use rltk::{Point, RGB};
 use serde::{Deserialize, Serialize};
 use specs::error::NoError;
@@ -22,7 +24,7 @@ pub struct TileSize {
 pub struct OtherLevelPosition {
     pub x: i32,
     pub y: i32,
-    pub depth: i32,
+    pub level: LevelId,
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
@@ -87,6 +89,10 @@ pub struct Viewshed {
 pub struct LightSource {
     pub color: RGB,
     pub range: i32,
+    /// How bright it is where it starts; 1.0 is as bright as daylight.
+    pub intensity: f32,
+    /// How much it flickers, from 0.0 for a steady light to 1.0.
+    pub flicker: f32,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -116,7 +122,7 @@ pub struct ApplyMove {
 pub struct ApplyTeleport {
     pub dest_x: i32,
     pub dest_y: i32,
-    pub dest_depth: i32,
+    pub dest_level: LevelId,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -395,9 +401,7 @@ pub enum WeaponAttribute {
 pub struct Weapon {
     pub range: Option<i32>,
     pub attribute: WeaponAttribute,
-    pub damage_n_dice: i32,
-    pub damage_die_type: i32,
-    pub damage_bonus: i32,
+    pub damage: Dice,
     pub hit_bonus: i32,
     pub proc_chance: Option<f32>,
     pub proc_target: Option<String>,
@@ -412,9 +416,7 @@ pub struct Wearable {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NaturalAttack {
     pub name: String,
-    pub damage_n_dice: i32,
-    pub damage_die_type: i32,
-    pub damage_bonus: i32,
+    pub damage: Dice,
     pub hit_bonus: i32,
 }
 
@@ -458,6 +460,23 @@ pub struct Bleeds {
     pub color: RGB,
 }
 
+/// Digs through rock: a mob that tunnels, an equipped tool, or a spell. `power` is how much
+/// digging it does to a tile each time.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Digger {
+    pub power: i32,
+}
+
+/// Burns away when fire reaches it, feeding the fire.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Flammable {}
+
+/// Leaves a hazard, like fire or a pool of acid, on the tiles it hits.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct LeavesHazard {
+    pub kind: HazardKind,
+}
+
 #[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
 pub enum HungerState {
     WellFed,
@@ -485,7 +504,7 @@ pub struct TownPortal {}
 pub struct TeleportTo {
     pub x: i32,
     pub y: i32,
-    pub depth: i32,
+    pub level: LevelId,
     pub player_only: bool,
 }
 
@@ -519,6 +538,11 @@ pub struct SerializationHelper {
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct DMSerializationHelper {
     pub map: crate::map::MasterDungeonMap,
-    pub log: Vec<Vec<crate::gamelog::LogFragment>>,
+    pub log: Vec<crate::gamelog::LogEntry>,
     pub events: HashMap<String, i32>,
+    pub world_seed: crate::rng::WorldSeed,
+    pub rng: crate::rng::RngStreams,
+    pub recording: crate::replay::InputRecording,
+    /// The marker the next saved entity will get.
+    pub next_marker: u64,
 }
@@ -87,4 +87,7 @@ pub fn register_all(ecs: &mut World) {
     ecs.register::<Target>();
     ecs.register::<WantsToShoot>();
     ecs.register::<Bleeds>();
+    ecs.register::<Digger>();
+    ecs.register::<Flammable>();
+    ecs.register::<LeavesHazard>();
 }
@@ -19,6 +19,8 @@ pub fn delete_the_dead(ecs: &mut World) {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
+                                .entity(entity)
                                 .color(rltk::RED)
                                 .append(&victim_name.name)
                                 .append("is dead!")
@@ -108,7 +110,7 @@ pub fn delete_the_dead(ecs: &mut World) {
         let death_effects = ecs.read_storage::<OnDeath>();
         if let Some(death_effect) = death_effects.get(*victim) {
             for effect in death_effect.abilities.iter() {
-                if crate::rng::roll_dice(1, 100) <= (effect.chance * 100.0) as i32 {
+                if crate::rng::combat::roll_dice(1, 100) <= (effect.chance * 100.0) as i32 {
                     let map = ecs.fetch::<Map>();
                     if let Some(pos) = ecs.read_storage::<Position>().get(*victim) {
                         let spell_entity =
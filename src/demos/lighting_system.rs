@@ -5,7 +5,7 @@ use crate::map_builders::level_builder;
 use crate::perception::field_of_view::field_of_view as shadowcasting_fov;
 use crate::RunState;
 use derivative::Derivative;
-use map::{get_tile_renderable, TileType};
+use map::{get_tile_renderable, LevelId, TileType};
 use rltk::prelude::field_of_view as bracket_fov;
 use rltk::prelude::*;
 use rltk::NavigationPath;
@@ -55,7 +55,7 @@ pub fn idx_xy(width: usize, idx: usize) -> (i32, i32) {
 
 impl DemoState {
     pub fn new(width: i32, height: i32) -> DemoState {
-        let mut builder = level_builder(47, width, height);
+        let mut builder = level_builder(&LevelId::main(47), width, height);
         builder.build_map();
         let position = builder
             .build_data
@@ -78,7 +78,7 @@ impl DemoState {
     }
 
     pub fn recreate_map(&mut self) {
-        let mut builder = level_builder(47, self.width, self.height);
+        let mut builder = level_builder(&LevelId::main(47), self.width, self.height);
         builder.build_map();
         self.map = builder.build_data.map;
         let position = builder
@@ -102,7 +102,7 @@ impl DemoState {
         let current_position = idx_xy(self.width.try_into().unwrap(), self.player_position);
         let new_position = (current_position.0 + delta_x, current_position.1 + delta_y);
         let new_idx = xy_idx(self.width, new_position.0, new_position.1);
-        if self.map.tiles[new_idx] == TileType::Floor {
+        if self.map.tiles[new_idx] == TileType::FLOOR {
             self.player_position = new_idx;
         }
         self.mode = Mode::Waiting;
@@ -200,7 +200,7 @@ impl DemoState {
 
 impl BaseMap for DemoState {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.map.tiles[idx] == TileType::Wall
+        self.map.tiles[idx] == TileType::WALL
     }
 }
 
@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// The most dice a single term can roll, so a typo can't hang the game.
+const MAX_DICE: i32 = 1000;
+/// How many times one exploding die may explode.
+const MAX_EXPLOSIONS: i32 = 100;
+/// The largest a term's multiplier may be.
+const MAX_MULTIPLIER: i32 = 1000;
+
+/// A dice expression from the raws, such as `1d6+1`, `2d6+1d4-2`, `4d6kh3`, `1d6!` or `2d8*2`.
+///
+/// Terms are added or subtracted in order. A dice term is `NdM`, optionally followed by `!` to
+/// roll again and add whenever a die shows its highest face, and by `khK` or `klK` to keep only
+/// the highest or lowest `K` dice. Any term can be multiplied with `*N`.
+///
+/// Dice are saved as their text, so saves stay readable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dice {
+    terms: Vec<DiceTerm>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct DiceTerm {
+    /// 1 when the term is added, -1 when it's subtracted.
+    sign: i32,
+    multiplier: i32,
+    kind: TermKind,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TermKind {
+    Constant(i32),
+    Roll {
+        count: i32,
+        sides: i32,
+        exploding: bool,
+        keep: Option<Keep>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Keep {
+    Highest(i32),
+    Lowest(i32),
+}
+
+impl Dice {
+    /// `n_dice` dice with `die_type` sides, plus `bonus`.
+    pub fn new(n_dice: i32, die_type: i32, bonus: i32) -> Dice {
+        Dice {
+            terms: vec![DiceTerm {
+                sign: 1,
+                multiplier: 1,
+                kind: TermKind::Roll {
+                    count: n_dice,
+                    sides: die_type,
+                    exploding: false,
+                    keep: None,
+                },
+            }],
+        }
+        .with_bonus(bonus)
+    }
+
+    /// The same expression with `bonus` added to its constant part.
+    pub fn with_bonus(mut self, bonus: i32) -> Dice {
+        let mut constant = bonus;
+        self.terms.retain(|term| match term.kind {
+            TermKind::Constant(value) => {
+                constant =
+                    constant.saturating_add(value.saturating_mul(term.sign * term.multiplier));
+                false
+            }
+            TermKind::Roll { .. } => true,
+        });
+        if constant != 0 || self.terms.is_empty() {
+            self.terms.push(DiceTerm {
+                sign: if constant < 0 { -1 } else { 1 },
+                multiplier: 1,
+                kind: TermKind::Constant(constant.abs()),
+            });
+        }
+        self
+    }
+
+    /// Rolls the expression, using `roll_dice(n, sides)` for the dice so the caller picks which
+    /// random number stream they come from.
+    pub fn roll_with<F: FnMut(i32, i32) -> i32>(&self, mut roll_dice: F) -> i32 {
+        let mut total: i32 = 0;
+        for term in self.terms.iter() {
+            let value = match term.kind {
+                TermKind::Constant(value) => value,
+                TermKind::Roll {
+                    count,
+                    sides,
+                    exploding: false,
+                    keep: None,
+                } => roll_dice(count, sides),
+                TermKind::Roll {
+                    count,
+                    sides,
+                    exploding,
+                    keep,
+                } => {
+                    let mut rolls = (0..count)
+                        .map(|_| {
+                            let mut roll = roll_dice(1, sides);
+                            let mut last = roll;
+                            let mut explosions = 0;
+                            while exploding && last == sides && explosions < MAX_EXPLOSIONS {
+                                last = roll_dice(1, sides);
+                                roll += last;
+                                explosions += 1;
+                            }
+                            roll
+                        })
+                        .collect::<Vec<i32>>();
+                    rolls.sort_unstable();
+                    match keep {
+                        None => rolls.iter().sum(),
+                        Some(Keep::Highest(n)) => rolls.iter().rev().take(n as usize).sum(),
+                        Some(Keep::Lowest(n)) => rolls.iter().take(n as usize).sum(),
+                    }
+                }
+            };
+            // Parsed expressions can't overflow, but ones built with `new` aren't checked.
+            total = total.saturating_add(value.saturating_mul(term.sign * term.multiplier));
+        }
+        total
+    }
+}
+
+impl DiceTerm {
+    /// The furthest from zero this term can roll, or `None` if that doesn't fit in an `i32`.
+    fn largest(&self) -> Option<i32> {
+        let value = match self.kind {
+            TermKind::Constant(value) => value,
+            TermKind::Roll {
+                count,
+                sides,
+                exploding,
+                ..
+            } => {
+                let explosions = if exploding { MAX_EXPLOSIONS } else { 0 };
+                count.checked_mul(sides.checked_mul(explosions + 1)?)?
+            }
+        };
+        value.checked_mul(self.multiplier)
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, term) in self.terms.iter().enumerate() {
+            if term.sign < 0 {
+                write!(f, "-")?;
+            } else if i > 0 {
+                write!(f, "+")?;
+            }
+            match term.kind {
+                TermKind::Constant(value) => write!(f, "{}", value)?,
+                TermKind::Roll {
+                    count,
+                    sides,
+                    exploding,
+                    keep,
+                } => {
+                    write!(f, "{}d{}", count, sides)?;
+                    if exploding {
+                        write!(f, "!")?;
+                    }
+                    match keep {
+                        Some(Keep::Highest(n)) => write!(f, "kh{}", n)?,
+                        Some(Keep::Lowest(n)) => write!(f, "kl{}", n)?,
+                        None => {}
+                    }
+                }
+            }
+            if term.multiplier != 1 {
+                write!(f, "*{}", term.multiplier)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Dice {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Dice, String> {
+        let mut parser = Parser {
+            chars: text.chars().filter(|c| !c.is_whitespace()).collect(),
+            pos: 0,
+        };
+        if parser.chars.is_empty() {
+            return Err("the dice expression is empty".to_string());
+        }
+
+        let mut terms = Vec::new();
+        let mut sign = if parser.eat('-') { -1 } else { 1 };
+        loop {
+            terms.push(parser.term(sign)?);
+            if parser.eat('+') {
+                sign = 1;
+            } else if parser.eat('-') {
+                sign = -1;
+            } else {
+                break;
+            }
+        }
+        if let Some(c) = parser.peek() {
+            return Err(format!("{} has an unexpected '{}'", text, c));
+        }
+        // Every roll then fits in an i32, however the dice land.
+        terms
+            .iter()
+            .try_fold(0i32, |total, term| total.checked_add(term.largest()?))
+            .ok_or_else(|| format!("{} can roll more than the game can count", text))?;
+        Ok(Dice { terms })
+    }
+}
+
+impl TryFrom<String> for Dice {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Dice, String> {
+        text.parse()
+    }
+}
+
+impl From<Dice> for String {
+    fn from(dice: Dice) -> String {
+        dice.to_string()
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Option<i32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn expect_number(&mut self, what: &str) -> Result<i32, String> {
+        self.number()
+            .ok_or_else(|| format!("expected {} at position {}", what, self.pos + 1))
+    }
+
+    fn term(&mut self, sign: i32) -> Result<DiceTerm, String> {
+        let count = self.number();
+        let kind =
+            if self.eat('d') {
+                let count = count.unwrap_or(1);
+                let sides = self.expect_number("the number of sides")?;
+                if !(1..=MAX_DICE).contains(&count) {
+                    return Err(format!("{} isn't between 1 and {} dice", count, MAX_DICE));
+                }
+                if sides < 1 {
+                    return Err("dice need at least one side".to_string());
+                }
+                let exploding = self.eat('!');
+                if exploding && sides < 2 {
+                    return Err("a one-sided die can't explode".to_string());
+                }
+                let keep = if self.eat('k') {
+                    let highest = !self.eat('l');
+                    if highest {
+                        self.eat('h');
+                    }
+                    let n = self.expect_number("how many dice to keep")?;
+                    if !(1..=count).contains(&n) {
+                        return Err(format!("can't keep {} of {} dice", n, count));
+                    }
+                    Some(if highest {
+                        Keep::Highest(n)
+                    } else {
+                        Keep::Lowest(n)
+                    })
+                } else {
+                    None
+                };
+                TermKind::Roll {
+                    count,
+                    sides,
+                    exploding,
+                    keep,
+                }
+            } else {
+                TermKind::Constant(count.ok_or_else(|| {
+                    format!("expected a number or dice at position {}", self.pos + 1)
+                })?)
+            };
+
+        let mut multiplier: i32 = 1;
+        while self.eat('*') {
+            let factor = self.expect_number("a multiplier")?;
+            if !(1..=MAX_MULTIPLIER).contains(&factor) {
+                return Err(format!(
+                    "a multiplier of {} isn't between 1 and {}",
+                    factor, MAX_MULTIPLIER
+                ));
+            }
+            multiplier = multiplier
+                .checked_mul(factor)
+                .ok_or_else(|| format!("the multiplier at position {} is too large", self.pos))?;
+        }
+        Ok(DiceTerm {
+            sign,
+            multiplier,
+            kind,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dice(text: &str) -> Dice {
+        text.parse().unwrap()
+    }
+
+    /// A roller that hands out `rolls` one die at a time, in order.
+    fn rolling(rolls: &[i32]) -> impl FnMut(i32, i32) -> i32 + '_ {
+        let mut rolls = rolls.iter();
+        move |n, _| {
+            (0..n)
+                .map(|_| *rolls.next().expect("ran out of rolls"))
+                .sum()
+        }
+    }
+
+    #[test]
+    fn expressions_read_back_as_they_were_written() {
+        for text in [
+            "1d6+1",
+            "2d6+1d4-2",
+            "4d6kh3",
+            "3d6kl1",
+            "1d6!",
+            "2d8*2",
+            "-1d4+2",
+            "5",
+        ] {
+            assert_eq!(dice(text).to_string(), text);
+        }
+        assert_eq!(dice(" 2d6 + 1 ").to_string(), "2d6+1");
+        assert_eq!(dice("d6").to_string(), "1d6");
+        assert_eq!(dice("4d6k3"), dice("4d6kh3"));
+    }
+
+    #[test]
+    fn bonuses_fold_into_the_constant() {
+        assert_eq!(Dice::new(1, 8, 2).to_string(), "1d8+2");
+        assert_eq!(Dice::new(1, 4, 0).to_string(), "1d4");
+        assert_eq!(dice("1d6+2").with_bonus(-3).to_string(), "1d6-1");
+        assert_eq!(dice("1d6+1+1").with_bonus(0).to_string(), "1d6+2");
+    }
+
+    #[test]
+    fn rolls_add_up_term_by_term() {
+        assert_eq!(dice("2d6+1d4-2").roll_with(|n, sides| n * sides), 14);
+        assert_eq!(dice("2d8*2").roll_with(rolling(&[3, 2])), 10);
+        assert_eq!(dice("4d6kh3").roll_with(rolling(&[1, 4, 2, 3])), 9);
+        assert_eq!(dice("4d6kl1").roll_with(rolling(&[5, 4, 2, 3])), 2);
+        assert_eq!(dice("1d6!").roll_with(rolling(&[6, 6, 2])), 14);
+        assert_eq!(
+            dice("1d2!").roll_with(|_, sides| sides),
+            2 * (MAX_EXPLOSIONS + 1)
+        );
+    }
+
+    #[test]
+    fn bad_expressions_are_refused() {
+        for text in [
+            "", "2d", "1d0", "0d6", "1001d6", "1d1!", "2d6kh3", "1d6x", "+", "1d6*",
+        ] {
+            assert!(text.parse::<Dice>().is_err(), "{:?} parsed", text);
+        }
+    }
+
+    #[test]
+    fn expressions_that_could_overflow_are_refused() {
+        for text in [
+            "1d6*0",
+            "1d6*1001",
+            "99999*99999d6",
+            "1d6*1000*1000*1000*1000",
+            "1000d1000000000",
+            "1000d100000!",
+            "2147483647+1",
+            "99999999999",
+        ] {
+            assert!(text.parse::<Dice>().is_err(), "{:?} parsed", text);
+        }
+        assert_eq!(
+            dice("1000d1000*2").roll_with(|n, sides| n * sides),
+            2 * 1000 * 1000
+        );
+        assert!("1000d1000!*20".parse::<Dice>().is_ok());
+        assert!("1000d1000!*30".parse::<Dice>().is_err());
+    }
+
+    #[test]
+    fn dice_are_saved_as_their_text() {
+        assert_eq!(
+            serde_json::to_value(dice("2d6+1")).unwrap(),
+            serde_json::json!("2d6+1")
+        );
+        assert_eq!(
+            serde_json::from_value::<Dice>(serde_json::json!("1d4!")).unwrap(),
+            dice("1d4!")
+        );
+        assert!(serde_json::from_value::<Dice>(serde_json::json!("lots")).is_err());
+    }
+}
@@ -74,7 +74,8 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
     let mut pools = ecs.write_storage::<Pools>();
 
     if let Some(pos) = entity_position(ecs, target) {
-        crate::spatial::remove_entity(target, pos as usize);
+        ecs.fetch_mut::<crate::spatial::SpatialIndex>()
+            .remove_entity(target, pos as usize);
     }
 
     if let Some(source) = effect.creator {
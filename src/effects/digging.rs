@@ -0,0 +1,54 @@
+use super::*;
+use crate::map::TileType;
+use crate::spatial::SpatialIndex;
+
+/// Digs into a tile. Digging adds up over turns, and once a tile has had as much as it is hard,
+/// it crumbles into its rubble.
+pub fn dig(ecs: &mut World, effect: &EffectSpawner, tile_idx: i32) {
+    let power = match effect.effect_type {
+        EffectType::Dig { power } => power,
+        _ => return,
+    };
+    let by_player = effect.creator == Some(*ecs.fetch::<Entity>());
+    let mut map = ecs.fetch_mut::<Map>();
+    let idx = tile_idx as usize;
+    let x = tile_idx % map.width;
+    let y = tile_idx / map.width;
+    let tile = map.tiles[idx];
+    let hardness = match map.dig_hardness(idx) {
+        Some(hardness) => hardness,
+        None => return,
+    };
+    let tile_name = tile.name().to_lowercase();
+
+    let progress = map.dig_progress.entry(idx).or_insert(0);
+    *progress += power;
+    if *progress < hardness {
+        if by_player {
+            crate::gamelog::Logger::new()
+                .append(format!("You dig into the {}.", tile_name))
+                .log();
+        }
+        return;
+    }
+
+    map.dig_progress.remove(&idx);
+    let rubble = tile.rubble().unwrap_or(TileType::FLOOR);
+    map.tiles[idx] = rubble;
+    map.view_blocked.remove(&idx);
+    if by_player || map.visible_tiles[idx] {
+        map.revealed_tiles[idx] = true;
+        crate::gamelog::Logger::new()
+            .append(format!(
+                "The {} crumbles into {}.",
+                tile_name,
+                rubble.name().to_lowercase()
+            ))
+            .log();
+    }
+    ecs.fetch_mut::<SpatialIndex>().update_terrain(&map, idx);
+    std::mem::drop(map);
+
+    // Anyone close enough may now be able to see through the gap
+    dirty_viewsheds_near(ecs, Point::new(x, y));
+}
@@ -0,0 +1,33 @@
+use super::*;
+use crate::components::Viewshed;
+use crate::map::TileType;
+use crate::raws::{spawn_named_entity, SpawnType, RAWS};
+
+pub fn reveal_secret_door(ecs: &mut World, tile_idx: i32) {
+    let mut map = ecs.fetch_mut::<Map>();
+    let idx = tile_idx as usize;
+    if map.tiles[idx] != TileType::SECRET_DOOR {
+        return;
+    }
+    map.tiles[idx] = TileType::FLOOR;
+    map.revealed_tiles[idx] = true;
+    let x = tile_idx % map.width;
+    let y = tile_idx / map.width;
+    std::mem::drop(map);
+
+    spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        ecs,
+        "Door",
+        SpawnType::AtPosition { x, y },
+    );
+    crate::gamelog::Logger::new()
+        .color(rltk::CYAN)
+        .append("You found a secret door!")
+        .log();
+
+    // Whoever can see the door may now be able to see through it
+    for viewshed in (&mut ecs.write_storage::<Viewshed>()).join() {
+        viewshed.dirty = true;
+    }
+}
@@ -0,0 +1,34 @@
+use super::*;
+use crate::map::Hazard;
+
+/// Leaves a hazard on a tile. Walls and the like can't hold one, and water puts out fire and
+/// washes away acid.
+pub fn leave_hazard(ecs: &mut World, effect: &EffectSpawner, tile_idx: i32) {
+    let kind = match effect.effect_type {
+        EffectType::Hazard { kind } => kind,
+        _ => return,
+    };
+    let mut map = ecs.fetch_mut::<Map>();
+    let idx = tile_idx as usize;
+    let x = tile_idx % map.width;
+    let y = tile_idx / map.width;
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+        return;
+    }
+    let tile = map.tiles[idx];
+    if tile.is_opaque() || (tile.is_liquid() && !kind.is_gas()) {
+        return;
+    }
+    let hazard = Hazard::new(kind);
+    match map.hazards.get(&idx) {
+        Some(old) if old.kind == kind && old.strength >= hazard.strength => {}
+        _ => {
+            map.hazards.insert(idx, hazard);
+        }
+    }
+    std::mem::drop(map);
+
+    if kind.blocks_vision() {
+        dirty_viewsheds_near(ecs, Point::new(x, y));
+    }
+}
@@ -1,20 +1,32 @@
-use crate::map::Map;
+use crate::map::{LevelId, Map};
 use specs::prelude::*;
+use std::cell::RefCell;
 use std::collections::{HashSet, VecDeque};
-use std::sync::Mutex;
 mod damage;
+mod digging;
+mod discovery;
 mod experience;
+mod hazards;
 mod targeting;
 pub use targeting::*;
 mod hunger;
 mod movement;
 mod particles;
+mod registry;
+pub use registry::*;
 mod triggers;
-use crate::components::AttributeBonus;
+use crate::components::{AttributeBonus, Position, Viewshed};
+use crate::map::HazardKind;
 use rltk::Point;
 
-lazy_static! {
-    pub static ref EFFECT_QUEUE: Mutex<VecDeque<EffectSpawner>> = Mutex::new(VecDeque::new());
+thread_local! {
+    /// Effects waiting to be applied in the game running on this thread; see `game_locals`.
+    static EFFECT_QUEUE: RefCell<VecDeque<EffectSpawner>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Makes `queue` the active effect queue, handing back the one that was active.
+pub(crate) fn swap_effect_queue(queue: VecDeque<EffectSpawner>) -> VecDeque<EffectSpawner> {
+    EFFECT_QUEUE.with(|current| current.replace(queue))
 }
 
 #[derive(Debug)]
@@ -66,7 +78,7 @@ pub enum EffectType {
     TeleportTo {
         x: i32,
         y: i32,
-        depth: i32,
+        level: LevelId,
         player_only: bool,
     },
     AttributeEffect {
@@ -80,6 +92,13 @@ pub enum EffectType {
     DamageOverTime {
         damage: i32,
     },
+    RevealSecretDoor,
+    Dig {
+        power: i32,
+    },
+    Hazard {
+        kind: HazardKind,
+    },
 }
 
 impl EffectType {
@@ -117,17 +136,20 @@ pub struct EffectSpawner {
 }
 
 pub fn add_effect(creator: Option<Entity>, effect_type: EffectType, targets: Targets) {
-    EFFECT_QUEUE.lock().unwrap().push_back(EffectSpawner {
-        creator,
-        effect_type,
-        targets,
-        dedupe: HashSet::new(),
+    EFFECT_QUEUE.with(|queue| {
+        queue.borrow_mut().push_back(EffectSpawner {
+            creator,
+            effect_type,
+            targets,
+            dedupe: HashSet::new(),
+        })
     });
 }
 
 pub fn run_effects_queue(ecs: &mut World) {
     loop {
-        let effect: Option<EffectSpawner> = EFFECT_QUEUE.lock().unwrap().pop_front();
+        let effect: Option<EffectSpawner> =
+            EFFECT_QUEUE.with(|queue| queue.borrow_mut().pop_front());
         if let Some(mut effect) = effect {
             target_applicator(ecs, &mut effect);
         } else {
@@ -159,7 +181,9 @@ fn target_applicator(ecs: &mut World, effect: &mut EffectSpawner) {
 
 fn affect_tile(ecs: &mut World, effect: &mut EffectSpawner, tile_idx: i32) {
     if effect.effect_type.affects_entities() {
-        let content = crate::spatial::get_tile_content_clone(tile_idx as usize);
+        let content = ecs
+            .fetch::<crate::spatial::SpatialIndex>()
+            .get_tile_content_clone(tile_idx as usize);
         content
             .iter()
             .for_each(|entity| affect_entity(ecs, effect, *entity));
@@ -169,6 +193,9 @@ fn affect_tile(ecs: &mut World, effect: &mut EffectSpawner, tile_idx: i32) {
         EffectType::Bloodstain { .. } => damage::bloodstain(ecs, tile_idx, &effect),
         EffectType::Particle { .. } => particles::particle_to_tile(ecs, tile_idx, &effect),
         EffectType::ParticleProjectile { .. } => particles::projectile(ecs, tile_idx, &effect),
+        EffectType::RevealSecretDoor => discovery::reveal_secret_door(ecs, tile_idx),
+        EffectType::Dig { .. } => digging::dig(ecs, effect, tile_idx),
+        EffectType::Hazard { .. } => hazards::leave_hazard(ecs, effect, tile_idx),
         _ => {}
     }
 }
@@ -193,6 +220,11 @@ fn affect_entity(ecs: &mut World, effect: &mut EffectSpawner, target: Entity) {
                 particles::particle_to_tile(ecs, pos, &effect)
             }
         }
+        EffectType::Hazard { .. } => {
+            if let Some(pos) = entity_position(ecs, target) {
+                hazards::leave_hazard(ecs, effect, pos)
+            }
+        }
         EffectType::WellFed => hunger::well_fed(ecs, effect, target),
         EffectType::Healing { .. } => damage::heal_damage(ecs, effect, target),
         EffectType::Mana { .. } => damage::restore_mana(ecs, effect, target),
@@ -204,3 +236,14 @@ fn affect_entity(ecs: &mut World, effect: &mut EffectSpawner, target: Entity) {
         _ => {}
     }
 }
+
+/// Makes everything that can see `point` look again, after what blocks the view there changed.
+fn dirty_viewsheds_near(ecs: &World, point: Point) {
+    let positions = ecs.read_storage::<Position>();
+    for (viewshed, pos) in (&mut ecs.write_storage::<Viewshed>(), &positions).join() {
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(point, Point::new(pos.x, pos.y));
+        if distance <= viewshed.range as f32 {
+            viewshed.dirty = true;
+        }
+    }
+}
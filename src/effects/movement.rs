@@ -6,7 +6,7 @@ pub fn apply_teleport(ecs: &mut World, destination: &EffectSpawner, target: Enti
     if let EffectType::TeleportTo {
         x,
         y,
-        depth,
+        level,
         player_only,
     } = &destination.effect_type
     {
@@ -18,7 +18,7 @@ pub fn apply_teleport(ecs: &mut World, destination: &EffectSpawner, target: Enti
                     ApplyTeleport {
                         dest_x: *x,
                         dest_y: *y,
-                        dest_depth: *depth,
+                        dest_level: level.clone(),
                     },
                 )
                 .expect("Unable to insert");
@@ -0,0 +1,387 @@
+use super::{triggers, Targets};
+use crate::components::*;
+use crate::map::HazardKind;
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// What a raws effect key expects as its value.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum EffectParam {
+    /// The value is ignored.
+    Flag,
+    Integer,
+    Number,
+    Text,
+    /// `glyph;#rrggbb;lifetime_ms`
+    Particle,
+    /// `fire`, `smoke`, `poison_gas` or `acid`
+    Hazard,
+}
+
+impl EffectParam {
+    pub fn check(self, value: &str) -> Result<(), String> {
+        match self {
+            EffectParam::Flag | EffectParam::Text => Ok(()),
+            EffectParam::Integer => parse_integer(value).map(|_| ()),
+            EffectParam::Number => parse_number(value).map(|_| ()),
+            EffectParam::Particle => parse_particle(value).map(|_| ()),
+            EffectParam::Hazard => parse_hazard(value).map(|_| ()),
+        }
+    }
+}
+
+type BuildFn = for<'a> fn(EntityBuilder<'a>, &str) -> EntityBuilder<'a>;
+type FireFn = fn(&mut World, Option<Entity>, Entity, &Targets) -> bool;
+
+pub struct EffectHandler {
+    pub name: &'static str,
+    pub param: EffectParam,
+    /// Adds the effect's components to something spawned from the raws, once its value has been
+    /// checked. Effects without one can't be named in the raws; their components come from
+    /// elsewhere.
+    pub build: Option<BuildFn>,
+    /// Fires the effect if `entity` carries its components, returning whether anything happened.
+    /// Modifiers that only change how other effects are aimed don't have one.
+    pub fire: Option<FireFn>,
+}
+
+impl EffectHandler {
+    pub fn new(name: &'static str, param: EffectParam) -> EffectHandler {
+        EffectHandler {
+            name,
+            param,
+            build: None,
+            fire: None,
+        }
+    }
+
+    pub fn builds(mut self, build: BuildFn) -> Self {
+        self.build = Some(build);
+        self
+    }
+
+    pub fn fires(mut self, fire: FireFn) -> Self {
+        self.fire = Some(fire);
+        self
+    }
+}
+
+/// Maps effect names to their handlers. Effects fire in the order they were registered.
+pub struct EffectRegistry {
+    handlers: Vec<EffectHandler>,
+    index: HashMap<&'static str, usize>,
+}
+
+impl EffectRegistry {
+    pub fn new() -> EffectRegistry {
+        EffectRegistry {
+            handlers: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, handler: EffectHandler) {
+        assert!(
+            !self.index.contains_key(handler.name),
+            "Effect {} is registered twice",
+            handler.name
+        );
+        self.index.insert(handler.name, self.handlers.len());
+        self.handlers.push(handler);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EffectHandler> {
+        self.index.get(name).map(|i| &self.handlers[*i])
+    }
+
+    /// Checks an effect entry from the raws: the key has to name an effect that can be built, and
+    /// the value has to be what that effect expects.
+    pub fn check(&self, name: &str, value: &str) -> Result<(), String> {
+        match self.get(name) {
+            Some(handler) if handler.build.is_some() => handler
+                .param
+                .check(value)
+                .map_err(|err| format!("effect {} has a bad value: {}", name, err)),
+            _ => Err(format!("unknown effect {}", name)),
+        }
+    }
+
+    /// Adds an effect entry from the raws to `eb`. Entries are checked when the raws are loaded,
+    /// so this doesn't check them again.
+    pub fn build<'a>(&self, eb: EntityBuilder<'a>, name: &str, value: &str) -> EntityBuilder<'a> {
+        match self.get(name).and_then(|handler| handler.build) {
+            Some(build) => build(eb, value),
+            None => eb,
+        }
+    }
+
+    /// Fires every effect `entity` carries, returning whether any of them did something.
+    pub fn fire(
+        &self,
+        ecs: &mut World,
+        creator: Option<Entity>,
+        entity: Entity,
+        targets: &Targets,
+    ) -> bool {
+        let mut did_something = false;
+        for fire in self.handlers.iter().filter_map(|handler| handler.fire) {
+            did_something |= fire(ecs, creator, entity, targets);
+        }
+        did_something
+    }
+}
+
+impl Default for EffectRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+lazy_static! {
+    pub static ref EFFECTS: EffectRegistry = builtin_effects();
+}
+
+fn builtin_effects() -> EffectRegistry {
+    use EffectParam::*;
+    let mut registry = EffectRegistry::new();
+    let handlers = vec![
+        EffectHandler::new("particle", Particle)
+            .builds(|eb, value| eb.with(particle_burst(value)))
+            .fires(triggers::particle_burst),
+        EffectHandler::new("particle_line", Particle)
+            .builds(|eb, value| eb.with(particle_line(value)))
+            .fires(triggers::particle_line),
+        EffectHandler::new("food", Flag)
+            .builds(|eb, _| eb.with(ProvidesFood {}))
+            .fires(triggers::food),
+        EffectHandler::new("magic_mapping", Flag)
+            .builds(|eb, _| eb.with(MagicMapper {}))
+            .fires(triggers::magic_mapping),
+        EffectHandler::new("remove_curse", Flag)
+            .builds(|eb, _| eb.with(ProvidesRemoveCurse {}))
+            .fires(triggers::remove_curse),
+        EffectHandler::new("identify", Flag)
+            .builds(|eb, _| eb.with(ProvidesIdentification {}))
+            .fires(triggers::identify),
+        EffectHandler::new("town_portal", Flag)
+            .builds(|eb, _| eb.with(TownPortal {}))
+            .fires(triggers::town_portal),
+        EffectHandler::new("provides_healing", Integer)
+            .builds(|eb, value| {
+                eb.with(ProvidesHealing {
+                    heal_amount: integer(value),
+                })
+            })
+            .fires(triggers::healing),
+        EffectHandler::new("provides_mana", Integer)
+            .builds(|eb, value| {
+                eb.with(ProvidesMana {
+                    mana_amount: integer(value),
+                })
+            })
+            .fires(triggers::mana),
+        EffectHandler::new("damage", Integer)
+            .builds(|eb, value| {
+                eb.with(InflictsDamage {
+                    damage: integer(value),
+                })
+            })
+            .fires(triggers::damage),
+        EffectHandler::new("confusion", Integer)
+            .builds(|eb, value| {
+                eb.with(Confusion {}).with(Duration {
+                    turns: integer(value),
+                })
+            })
+            .fires(triggers::confusion),
+        EffectHandler::new("teleport", Flag).fires(triggers::teleport),
+        EffectHandler::new("attributes", Flag).fires(triggers::attribute_modifiers),
+        EffectHandler::new("teach_spell", Text)
+            .builds(|eb, value| {
+                eb.with(TeachesSpell {
+                    spell: value.to_string(),
+                })
+            })
+            .fires(triggers::teach_spell),
+        EffectHandler::new("slow", Number)
+            .builds(|eb, value| {
+                eb.with(Slow {
+                    initiative_penalty: number(value),
+                })
+            })
+            .fires(triggers::slow),
+        EffectHandler::new("damage_over_time", Integer)
+            .builds(|eb, value| {
+                eb.with(DamageOverTime {
+                    damage: integer(value),
+                })
+            })
+            .fires(triggers::damage_over_time),
+        EffectHandler::new("dig", Integer)
+            .builds(|eb, value| {
+                eb.with(Digger {
+                    power: integer(value),
+                })
+            })
+            .fires(triggers::dig),
+        EffectHandler::new("hazard", Hazard)
+            .builds(|eb, value| {
+                eb.with(LeavesHazard {
+                    kind: parse_hazard(value).unwrap(),
+                })
+            })
+            .fires(triggers::hazard),
+        EffectHandler::new("ranged", Integer).builds(|eb, value| {
+            eb.with(Ranged {
+                range: integer(value),
+            })
+        }),
+        EffectHandler::new("area_of_effect", Integer).builds(|eb, value| {
+            eb.with(AreaOfEffect {
+                radius: integer(value),
+            })
+        }),
+        EffectHandler::new("single_activation", Flag).builds(|eb, _| eb.with(SingleActivation {})),
+        EffectHandler::new("target_self", Flag).builds(|eb, _| eb.with(AlwaysTargetsSelf {})),
+    ];
+    for handler in handlers {
+        registry.register(handler);
+    }
+    registry
+}
+
+fn parse_integer(value: &str) -> Result<i32, String> {
+    value
+        .parse::<i32>()
+        .map_err(|_| format!("{} isn't a whole number", value))
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .map_err(|_| format!("{} isn't a number", value))
+}
+
+fn parse_particle(value: &str) -> Result<(rltk::FontCharType, rltk::RGB, f32), String> {
+    let tokens: Vec<_> = value.split(';').collect();
+    if tokens.len() != 3 {
+        return Err(format!("{} isn't glyph;color;lifetime", value));
+    }
+    let glyph = tokens[0]
+        .chars()
+        .next()
+        .ok_or_else(|| "the particle has no glyph".to_string())?;
+    let color = rltk::RGB::from_hex(tokens[1])
+        .map_err(|_| format!("{} isn't a #rrggbb color", tokens[1]))?;
+    Ok((rltk::to_cp437(glyph), color, parse_number(tokens[2])?))
+}
+
+fn parse_hazard(value: &str) -> Result<HazardKind, String> {
+    HazardKind::from_name(value).ok_or_else(|| format!("{} isn't a kind of hazard", value))
+}
+
+// Values are checked when the raws are loaded, so building from them can't fail.
+
+fn integer(value: &str) -> i32 {
+    parse_integer(value).unwrap()
+}
+
+fn number(value: &str) -> f32 {
+    parse_number(value).unwrap()
+}
+
+fn particle_burst(value: &str) -> SpawnParticleBurst {
+    let (glyph, color, lifetime_ms) = parse_particle(value).unwrap();
+    SpawnParticleBurst {
+        glyph,
+        color,
+        lifetime_ms,
+    }
+}
+
+fn particle_line(value: &str) -> SpawnParticleLine {
+    let (glyph, color, lifetime_ms) = parse_particle(value).unwrap();
+    SpawnParticleLine {
+        glyph,
+        color,
+        lifetime_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raws_entries_are_checked_against_their_handler() {
+        assert!(EFFECTS.check("damage", "8").is_ok());
+        assert!(EFFECTS.check("particle", "*;#FF4000;200.0").is_ok());
+        assert!(EFFECTS.check("hazard", "poison_gas").is_ok());
+
+        assert_eq!(
+            EFFECTS.check("fireball", "1"),
+            Err("unknown effect fireball".to_string())
+        );
+        for (name, value) in [
+            ("damage", "lots"),
+            ("slow", "fast"),
+            ("particle", "*;orange;200.0"),
+            ("hazard", "lava"),
+        ] {
+            let err = EFFECTS.check(name, value).unwrap_err();
+            assert!(err.starts_with(&format!("effect {} has a bad value", name)));
+        }
+        // Teleporting is fired by items that carry a destination, and can't come from the raws
+        assert!(EFFECTS.check("teleport", "").is_err());
+    }
+
+    #[test]
+    fn building_an_effect_adds_its_components() {
+        let mut ecs = World::new();
+        crate::components::register_all(&mut ecs);
+        let eb = ecs.create_entity();
+        let eb = EFFECTS.build(eb, "damage", "8");
+        let eb = EFFECTS.build(eb, "confusion", "3");
+        let entity = EFFECTS.build(eb, "hazard", "acid").build();
+
+        assert_eq!(
+            ecs.read_storage::<InflictsDamage>()
+                .get(entity)
+                .unwrap()
+                .damage,
+            8
+        );
+        assert!(ecs.read_storage::<Confusion>().get(entity).is_some());
+        assert_eq!(ecs.read_storage::<Duration>().get(entity).unwrap().turns, 3);
+        assert_eq!(
+            ecs.read_storage::<LeavesHazard>().get(entity).unwrap().kind,
+            HazardKind::Acid
+        );
+    }
+
+    #[test]
+    fn raws_naming_an_unknown_effect_are_refused() {
+        let mut raws = crate::raws::read_raws().unwrap();
+        let potion = raws
+            .items
+            .iter_mut()
+            .find(|item| item.name == "Health Potion")
+            .unwrap();
+        potion
+            .consumable
+            .as_mut()
+            .unwrap()
+            .effects
+            .insert("explode".to_string(), "1".to_string());
+        assert!(crate::raws::check_raws(&raws)
+            .contains(&"Item Health Potion: unknown effect explode".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Effect damage is registered twice")]
+    fn an_effect_can_only_be_registered_once() {
+        let mut registry = EffectRegistry::new();
+        registry.register(EffectHandler::new("damage", EffectParam::Integer));
+        registry.register(EffectHandler::new("damage", EffectParam::Integer));
+    }
+}
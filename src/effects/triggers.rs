@@ -8,6 +8,8 @@ pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ec
         if c.charges < 1 {
             // Cancel
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Loot)
+                .entity(item)
                 .item_name(&ecs.read_storage::<Name>().get(item).unwrap().name)
                 .append("is out of charges!")
                 .log();
@@ -23,7 +25,7 @@ pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ec
     // If it was a consumable, then it gets deleted
     if did_something {
         if let Some(c) = ecs.read_storage::<Consumable>().get(item) {
-            rltk::console::log(format!("{}", c.max_charges));
+            crate::console_log(format!("{}", c.max_charges));
             if c.max_charges < 2 {
                 ecs.entities().delete(item).expect("Delete Failed");
             }
@@ -89,16 +91,21 @@ pub fn trigger(creator: Option<Entity>, trigger: Entity, targets: &Targets, ecs:
     }
 }
 
-#[allow(clippy::cognitive_complexity)]
 fn event_trigger(
     creator: Option<Entity>,
     entity: Entity,
     targets: &Targets,
     ecs: &mut World,
 ) -> bool {
-    let mut did_something = false;
+    EFFECTS.fire(ecs, creator, entity, targets)
+}
 
-    // Simple particle spawn
+pub(super) fn particle_burst(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
     if let Some(part) = ecs.read_storage::<SpawnParticleBurst>().get(entity) {
         add_effect(
             creator,
@@ -111,8 +118,15 @@ fn event_trigger(
             targets.clone(),
         );
     }
+    false
+}
 
-    // Line particle spawn
+pub(super) fn particle_line(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
     if let Some(part) = ecs.read_storage::<SpawnParticleLine>().get(entity) {
         if let Some(start_pos) = targeting::find_item_position(ecs, entity, creator) {
             match targets {
@@ -135,19 +149,37 @@ fn event_trigger(
             }
         }
     }
+    false
+}
 
-    // Providing food
+pub(super) fn food(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
         add_effect(creator, EffectType::WellFed, targets.clone());
         let names = ecs.read_storage::<Name>();
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Loot)
+            .entity(entity)
             .append("You eat the")
             .item_name(&names.get(entity).unwrap().name)
             .log();
         did_something = true;
     }
+    did_something
+}
 
-    // Magic mapper
+pub(super) fn magic_mapping(
+    ecs: &mut World,
+    _creator: Option<Entity>,
+    entity: Entity,
+    _targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if ecs.read_storage::<MagicMapper>().get(entity).is_some() {
         let mut runstate = ecs.fetch_mut::<RunState>();
         crate::gamelog::Logger::new()
@@ -158,8 +190,16 @@ fn event_trigger(
         };
         did_something = true;
     }
+    did_something
+}
 
-    // Remove Curse
+pub(super) fn remove_curse(
+    ecs: &mut World,
+    _creator: Option<Entity>,
+    entity: Entity,
+    _targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if ecs
         .read_storage::<ProvidesRemoveCurse>()
         .get(entity)
@@ -171,8 +211,16 @@ fn event_trigger(
         };
         did_something = true;
     }
+    did_something
+}
 
-    // Identify Item
+pub(super) fn identify(
+    ecs: &mut World,
+    _creator: Option<Entity>,
+    entity: Entity,
+    _targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if ecs
         .read_storage::<ProvidesIdentification>()
         .get(entity)
@@ -184,8 +232,16 @@ fn event_trigger(
         };
         did_something = true;
     }
+    did_something
+}
 
-    // Town Portal
+pub(super) fn town_portal(
+    ecs: &mut World,
+    _creator: Option<Entity>,
+    entity: Entity,
+    _targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if ecs.read_storage::<TownPortal>().get(entity).is_some() {
         let map = ecs.fetch::<Map>();
         if map.depth == 1 {
@@ -203,8 +259,16 @@ fn event_trigger(
             did_something = true;
         }
     }
+    did_something
+}
 
-    // Healing
+pub(super) fn healing(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(heal) = ecs.read_storage::<ProvidesHealing>().get(entity) {
         add_effect(
             creator,
@@ -215,8 +279,16 @@ fn event_trigger(
         );
         did_something = true;
     }
+    did_something
+}
 
-    // Mana
+pub(super) fn mana(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(mana) = ecs.read_storage::<ProvidesMana>().get(entity) {
         add_effect(
             creator,
@@ -227,8 +299,16 @@ fn event_trigger(
         );
         did_something = true;
     }
+    did_something
+}
 
-    // Damage
+pub(super) fn damage(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
         add_effect(
             creator,
@@ -239,8 +319,16 @@ fn event_trigger(
         );
         did_something = true;
     }
+    did_something
+}
 
-    // Confusion
+pub(super) fn confusion(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(_confusion) = ecs.read_storage::<Confusion>().get(entity) {
         if let Some(duration) = ecs.read_storage::<Duration>().get(entity) {
             add_effect(
@@ -253,23 +341,39 @@ fn event_trigger(
             did_something = true;
         }
     }
+    did_something
+}
 
-    // Teleport
+pub(super) fn teleport(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(teleport) = ecs.read_storage::<TeleportTo>().get(entity) {
         add_effect(
             creator,
             EffectType::TeleportTo {
                 x: teleport.x,
                 y: teleport.y,
-                depth: teleport.depth,
+                level: teleport.level.clone(),
                 player_only: teleport.player_only,
             },
             targets.clone(),
         );
         did_something = true;
     }
+    did_something
+}
 
-    // Attribute Modifiers
+pub(super) fn attribute_modifiers(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(attr) = ecs.read_storage::<AttributeBonus>().get(entity) {
         add_effect(
             creator,
@@ -282,8 +386,16 @@ fn event_trigger(
         );
         did_something = true;
     }
+    did_something
+}
 
-    // Learn spells
+pub(super) fn teach_spell(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    _targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(spell) = ecs.read_storage::<TeachesSpell>().get(entity) {
         if let Some(known) = ecs.write_storage::<KnownSpells>().get_mut(creator.unwrap()) {
             if let Some(spell_entity) = crate::raws::find_spell_entity(ecs, &spell.spell) {
@@ -305,8 +417,16 @@ fn event_trigger(
         }
         did_something = true;
     }
+    did_something
+}
 
-    // Slow
+pub(super) fn slow(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(slow) = ecs.read_storage::<Slow>().get(entity) {
         add_effect(
             creator,
@@ -317,8 +437,16 @@ fn event_trigger(
         );
         did_something = true;
     }
+    did_something
+}
 
-    // Damage Over Time
+pub(super) fn damage_over_time(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
     if let Some(damage) = ecs.read_storage::<DamageOverTime>().get(entity) {
         add_effect(
             creator,
@@ -329,7 +457,44 @@ fn event_trigger(
         );
         did_something = true;
     }
+    did_something
+}
+
+pub(super) fn dig(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
+    if let Some(digger) = ecs.read_storage::<Digger>().get(entity) {
+        add_effect(
+            creator,
+            EffectType::Dig {
+                power: digger.power,
+            },
+            targets.clone(),
+        );
+        did_something = true;
+    }
+    did_something
+}
 
+pub(super) fn hazard(
+    ecs: &mut World,
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+) -> bool {
+    let mut did_something = false;
+    if let Some(hazard) = ecs.read_storage::<LeavesHazard>().get(entity) {
+        add_effect(
+            creator,
+            EffectType::Hazard { kind: hazard.kind },
+            targets.clone(),
+        );
+        did_something = true;
+    }
     did_something
 }
 
@@ -0,0 +1,117 @@
+use crate::effects::EffectSpawner;
+use crate::gamelog::LogEntry;
+use crate::rng::{self, RngStreams, WorldSeed};
+use specs::World;
+use std::collections::{HashMap, VecDeque};
+
+/// The parts of a game that code deep in map generation, combat and logging reaches without the
+/// World: its random streams, event counts, queued effects and log. They're active on the game's thread while it
+/// runs; in between, each World keeps its own as a resource, so games sharing a process, or a
+/// thread, never draw each other's rolls, count each other's events, apply each other's effects
+/// or log each other's messages.
+pub struct GameLocals {
+    rng: RngStreams,
+    events: HashMap<String, i32>,
+    effects: VecDeque<EffectSpawner>,
+    log: Vec<LogEntry>,
+}
+
+impl GameLocals {
+    pub fn new(world_seed: &WorldSeed) -> GameLocals {
+        GameLocals {
+            rng: RngStreams::seeded(world_seed),
+            events: HashMap::new(),
+            effects: VecDeque::new(),
+            log: Vec::new(),
+        }
+    }
+}
+
+/// Makes the world's game locals active, returning the ones that were, or `None` if the world's
+/// already are.
+pub fn enter(ecs: &mut World) -> Option<GameLocals> {
+    let locals = ecs.remove::<GameLocals>()?;
+    Some(swap(locals))
+}
+
+/// Puts the world's game locals back in it and restores the `outer` ones `enter` returned.
+pub fn leave(ecs: &mut World, outer: Option<GameLocals>) {
+    if let Some(outer) = outer {
+        ecs.insert(swap(outer));
+    }
+}
+
+/// Runs `f` with the world's game locals active. Calls nest.
+pub fn with_game_locals<R>(ecs: &mut World, f: impl FnOnce(&mut World) -> R) -> R {
+    let outer = enter(ecs);
+    let result = f(ecs);
+    leave(ecs, outer);
+    result
+}
+
+fn swap(locals: GameLocals) -> GameLocals {
+    GameLocals {
+        rng: rng::swap_streams(locals.rng),
+        events: crate::gamelog::swap_events(locals.events),
+        effects: crate::effects::swap_effect_queue(locals.effects),
+        log: crate::gamelog::swap_log(locals.log),
+    }
+}
+
+/// The world's random streams, whether or not its game locals are active.
+pub fn rng_streams(ecs: &World) -> RngStreams {
+    match ecs.try_fetch::<GameLocals>() {
+        Some(locals) => locals.rng.clone(),
+        None => rng::clone_streams(),
+    }
+}
+
+/// The world's log, whether or not its game locals are active.
+pub fn log(ecs: &World) -> Vec<LogEntry> {
+    match ecs.try_fetch::<GameLocals>() {
+        Some(locals) => locals.log.clone(),
+        None => crate::gamelog::clone_log(),
+    }
+}
+
+/// The world's event counts, whether or not its game locals are active.
+pub fn events(ecs: &World) -> HashMap<String, i32> {
+    match ecs.try_fetch::<GameLocals>() {
+        Some(locals) => locals.events.clone(),
+        None => crate::gamelog::clone_events(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::{add_effect, swap_effect_queue, EffectType, Targets};
+    use crate::headless::Simulation;
+
+    fn logged(text: &str) -> bool {
+        crate::gamelog::clone_log()
+            .iter()
+            .any(|entry| entry.fragments.iter().any(|fragment| fragment.text == text))
+    }
+
+    #[test]
+    fn each_world_keeps_its_own_effects_and_log() {
+        let mut first = Simulation::with_seed(1);
+        let mut second = Simulation::with_seed(2);
+        with_game_locals(&mut first.state.ecs, |_| {
+            crate::gamelog::Logger::new()
+                .append("Only the first game sees this")
+                .log();
+            add_effect(None, EffectType::WellFed, Targets::Tile { tile_idx: 0 });
+        });
+
+        with_game_locals(&mut second.state.ecs, |_| {
+            assert!(!logged("Only the first game sees this"));
+            assert!(swap_effect_queue(VecDeque::new()).is_empty());
+        });
+        with_game_locals(&mut first.state.ecs, |_| {
+            assert!(logged("Only the first game sees this"));
+            assert_eq!(swap_effect_queue(VecDeque::new()).len(), 1);
+        });
+    }
+}
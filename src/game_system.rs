@@ -28,6 +28,13 @@ pub fn mana_at_level(intelligence: i32, level: i32) -> i32 {
     mana_per_level(intelligence) * level
 }
 
+/// Whether a character with the given intelligence bonus notices a secret door or a hidden thing.
+/// Searching right next to it is far likelier to turn it up than catching sight of it in passing.
+pub fn notices_secret(intelligence_bonus: i32, searching: bool) -> bool {
+    let target = if searching { 10 } else { 20 };
+    crate::rng::roll_dice(1, 20) + intelligence_bonus >= target
+}
+
 pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
     if skills.skills.contains_key(&skill) {
         skills.skills[&skill]
@@ -35,3 +42,24 @@ pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
         -4
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn searching_turns_up_secrets_far_more_often_than_a_glance() {
+        crate::rng::reseed(&crate::rng::WorldSeed::new(5));
+        let found = |intelligence_bonus, searching| {
+            (0..1000)
+                .filter(|_| notices_secret(intelligence_bonus, searching))
+                .count()
+        };
+        let glanced = found(0, false);
+        let searched = found(0, true);
+        assert!(glanced > 0);
+        assert!(searched > glanced * 5);
+        assert!(found(4, true) > searched);
+        assert_eq!(found(-20, true), 0);
+    }
+}
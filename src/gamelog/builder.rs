@@ -1,9 +1,12 @@
-use super::{append_entry, LogFragment};
+use super::{append_entry, get_event_count, LogCategory, LogEntry, LogFragment};
 use rltk::prelude::*;
+use specs::prelude::*;
 
 pub struct Logger {
     current_color: RGB,
     fragments: Vec<LogFragment>,
+    category: LogCategory,
+    entities: Vec<u32>,
 }
 
 impl Logger {
@@ -11,9 +14,22 @@ impl Logger {
         Logger {
             current_color: RGB::named(rltk::WHITE),
             fragments: Vec::new(),
+            category: LogCategory::System,
+            entities: Vec::new(),
         }
     }
 
+    pub fn category(mut self, category: LogCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Notes that the entry is about `entity`; it doesn't change the text.
+    pub fn entity(mut self, entity: Entity) -> Self {
+        self.entities.push(entity.id());
+        self
+    }
+
     pub fn color(mut self, color: (u8, u8, u8)) -> Self {
         self.current_color = RGB::named(color);
         self
@@ -28,7 +44,12 @@ impl Logger {
     }
 
     pub fn log(self) {
-        append_entry(self.fragments)
+        append_entry(LogEntry {
+            turn: get_event_count("Turn"),
+            category: self.category,
+            entities: self.entities,
+            fragments: self.fragments,
+        })
     }
 
     pub fn npc_name<T: ToString>(mut self, text: T) -> Self {
@@ -1,43 +1,38 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::Mutex;
 
-lazy_static! {
-    static ref EVENTS: Mutex<HashMap<String, i32>> = Mutex::new(HashMap::new());
+thread_local! {
+    /// Counts for the game running on this thread, since it started; see `game_locals`.
+    static EVENTS: RefCell<HashMap<String, i32>> = RefCell::new(HashMap::new());
 }
 
 pub fn clear_events() {
-    EVENTS.lock().unwrap().clear();
+    EVENTS.with(|events| events.borrow_mut().clear());
 }
 
 pub fn record_event<T: ToString>(event: T, n: i32) {
-    let event_name = event.to_string();
-    let mut events_lock = EVENTS.lock();
-    let events = events_lock.as_mut().unwrap();
-    if let Some(e) = events.get_mut(&event_name) {
-        *e += n;
-    } else {
-        events.insert(event_name, n);
-    }
+    EVENTS.with(|events| *events.borrow_mut().entry(event.to_string()).or_insert(0) += n);
 }
 
 pub fn get_event_count<T: ToString>(event: T) -> i32 {
-    let event_name = event.to_string();
-    let events_lock = EVENTS.lock();
-    let events = events_lock.unwrap();
-    if let Some(e) = events.get(&event_name) {
-        *e
-    } else {
-        0
-    }
+    EVENTS.with(|events| {
+        events
+            .borrow()
+            .get(&event.to_string())
+            .copied()
+            .unwrap_or(0)
+    })
 }
 
 pub fn clone_events() -> HashMap<String, i32> {
-    EVENTS.lock().unwrap().clone()
+    EVENTS.with(|events| events.borrow().clone())
 }
 
 pub fn load_events(events: HashMap<String, i32>) {
-    EVENTS.lock().unwrap().clear();
-    events.iter().for_each(|(k, v)| {
-        EVENTS.lock().unwrap().insert(k.to_string(), *v);
-    });
+    EVENTS.with(|current| *current.borrow_mut() = events);
+}
+
+/// Makes `events` the active counts, handing back the counts that were active.
+pub(crate) fn swap_events(events: HashMap<String, i32>) -> HashMap<String, i32> {
+    EVENTS.with(|current| current.replace(events))
 }
@@ -1,30 +1,26 @@
-use super::LogFragment;
+use super::LogEntry;
 use rltk::prelude::*;
-use std::sync::Mutex;
+use std::cell::RefCell;
 
-lazy_static! {
-    static ref LOG: Mutex<Vec<Vec<LogFragment>>> = Mutex::new(Vec::new());
+thread_local! {
+    /// The log of the game running on this thread; see `game_locals`.
+    static LOG: RefCell<Vec<LogEntry>> = const { RefCell::new(Vec::new()) };
 }
 
-pub fn append_entry(fragments: Vec<LogFragment>) {
-    LOG.lock().unwrap().push(fragments);
+pub fn append_entry(entry: LogEntry) {
+    LOG.with(|log| log.borrow_mut().push(entry));
 }
 
 pub fn clear_log() {
-    LOG.lock().unwrap().clear();
+    LOG.with(|log| log.borrow_mut().clear());
 }
 
 pub fn print_log(console: &mut Box<dyn Console>, pos: Point) {
     let mut y = pos.y;
     let mut x = pos.x;
-    LOG.lock()
-        .unwrap()
-        .iter()
-        .rev()
-        .take(6)
-        .rev()
-        .for_each(|log| {
-            log.iter().for_each(|frag| {
+    LOG.with(|log| {
+        log.borrow().iter().rev().take(6).rev().for_each(|log| {
+            log.fragments.iter().for_each(|frag| {
                 console.print_color(
                     x,
                     y,
@@ -37,14 +33,23 @@ pub fn print_log(console: &mut Box<dyn Console>, pos: Point) {
             });
             y += 1;
             x = pos.x;
-        });
+        })
+    });
 }
 
-pub fn clone_log() -> Vec<Vec<crate::gamelog::LogFragment>> {
-    LOG.lock().unwrap().clone()
+pub fn clone_log() -> Vec<LogEntry> {
+    LOG.with(|log| log.borrow().clone())
 }
 
-pub fn restore_log(log: &mut Vec<Vec<crate::gamelog::LogFragment>>) {
-    LOG.lock().unwrap().clear();
-    LOG.lock().unwrap().append(log);
+pub fn restore_log(log: &mut Vec<LogEntry>) {
+    LOG.with(|current| {
+        let mut current = current.borrow_mut();
+        current.clear();
+        current.append(log);
+    });
+}
+
+/// Makes `log` the active log, handing back the one that was active.
+pub(crate) fn swap_log(log: Vec<LogEntry>) -> Vec<LogEntry> {
+    LOG.with(|current| current.replace(log))
 }
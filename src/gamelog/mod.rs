@@ -2,6 +2,7 @@ use rltk::prelude::*;
 mod builder;
 pub use builder::*;
 mod logstore;
+pub(crate) use logstore::swap_log;
 use logstore::*;
 pub use logstore::{clear_log, clone_log, print_log, restore_log};
 use serde::{Deserialize, Serialize};
@@ -13,3 +14,54 @@ pub struct LogFragment {
     pub color: RGB,
     pub text: String,
 }
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LogCategory {
+    Combat,
+    Loot,
+    Dialogue,
+    System,
+    Quips,
+}
+
+pub const LOG_CATEGORIES: [LogCategory; 5] = [
+    LogCategory::Combat,
+    LogCategory::Loot,
+    LogCategory::Dialogue,
+    LogCategory::System,
+    LogCategory::Quips,
+];
+
+impl LogCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            LogCategory::Combat => "Combat",
+            LogCategory::Loot => "Loot",
+            LogCategory::Dialogue => "Dialogue",
+            LogCategory::System => "System",
+            LogCategory::Quips => "Quips",
+        }
+    }
+}
+
+/// One line of the game log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    /// The turn it was logged on, as counted by the "Turn" event.
+    pub turn: i32,
+    pub category: LogCategory,
+    /// The ids of the entities it mentions.
+    pub entities: Vec<u32>,
+    pub fragments: Vec<LogFragment>,
+}
+
+impl LogEntry {
+    /// The entry as plain text, the way it's printed.
+    pub fn text(&self) -> String {
+        self.fragments
+            .iter()
+            .map(|frag| frag.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+}
@@ -13,11 +13,13 @@ pub enum CheatMenuResult {
     GodMode,
     LevelUp,
     Eat,
+    ReloadRaws,
+    ReloadRawsAndMobs,
 }
 
 pub fn show_cheat_mode(_gs: &mut State, ctx: &mut Rltk) -> CheatMenuResult {
     let mut draw_batch = DrawBatch::new();
-    let count = 6;
+    let count = 8;
     let mut y = (25 - (count / 2)) as i32;
     menu_box(&mut draw_batch, 15, y, 31, (count + 3) as i32, "Cheating!");
     draw_batch.print_color(
@@ -43,6 +45,16 @@ pub fn show_cheat_mode(_gs: &mut State, ctx: &mut Rltk) -> CheatMenuResult {
     menu_option(&mut draw_batch, 17, y, to_cp437('L'), "Level up");
     y += 1;
     menu_option(&mut draw_batch, 17, y, to_cp437('E'), "Eat");
+    y += 1;
+    menu_option(&mut draw_batch, 17, y, to_cp437('W'), "Reload the raws");
+    y += 1;
+    menu_option(
+        &mut draw_batch,
+        17,
+        y,
+        to_cp437('M'),
+        "Reload raws, rebuild monsters",
+    );
 
     draw_batch
         .submit(6000)
@@ -58,6 +70,8 @@ pub fn show_cheat_mode(_gs: &mut State, ctx: &mut Rltk) -> CheatMenuResult {
             VirtualKeyCode::G => CheatMenuResult::GodMode,
             VirtualKeyCode::L => CheatMenuResult::LevelUp,
             VirtualKeyCode::E => CheatMenuResult::Eat,
+            VirtualKeyCode::W => CheatMenuResult::ReloadRaws,
+            VirtualKeyCode::M => CheatMenuResult::ReloadRawsAndMobs,
             VirtualKeyCode::Escape => CheatMenuResult::Cancel,
             _ => CheatMenuResult::NoResponse,
         },
@@ -214,22 +214,7 @@ fn equipped(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) ->
             y += 1;
 
             if let Some(weapon) = weapon.get(entity) {
-                let mut weapon_info = if weapon.damage_bonus < 0 {
-                    format!(
-                        "┤ {} ({}d{}{})",
-                        &name, weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus
-                    )
-                } else if weapon.damage_bonus == 0 {
-                    format!(
-                        "┤ {} ({}d{})",
-                        &name, weapon.damage_n_dice, weapon.damage_die_type
-                    )
-                } else {
-                    format!(
-                        "┤ {} ({}d{}+{})",
-                        &name, weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus
-                    )
-                };
+                let mut weapon_info = format!("┤ {} ({})", &name, weapon.damage);
 
                 if let Some(range) = weapon.range {
                     weapon_info += &format!(" (range: {}, F to fire, V cycle targets)", range);
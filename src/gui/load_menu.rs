@@ -0,0 +1,76 @@
+use super::menu_option;
+use crate::saveload_system::SaveSlot;
+use rltk::prelude::*;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LoadMenuResult {
+    NoResponse,
+    Cancel,
+    Selected { slot: usize },
+}
+
+pub fn load_menu(ctx: &mut Rltk, slots: &[SaveSlot], error: Option<&String>) -> LoadMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let black = RGB::named(rltk::BLACK);
+    let yellow_on_black = ColorPair::new(RGB::named(rltk::YELLOW), black);
+    let gray_on_black = ColorPair::new(RGB::named(rltk::GRAY), black);
+    let red_on_black = ColorPair::new(RGB::named(rltk::RED), black);
+    let count = slots.len();
+
+    let y = (25 - (count / 2)) as i32;
+    draw_batch.draw_box(
+        Rect::with_size(5, y - 2, 70, (count + 3) as i32),
+        ColorPair::new(RGB::named(rltk::WHITE), black),
+    );
+    draw_batch.print_color(Point::new(8, y - 2), "Load Game", yellow_on_black);
+    draw_batch.print_color(
+        Point::new(8, y + count as i32 + 1),
+        "[Esc] cancel",
+        yellow_on_black,
+    );
+    if let Some(error) = error {
+        draw_batch.print_color(Point::new(5, y + count as i32 + 3), error, red_on_black);
+    }
+
+    for (j, slot) in slots.iter().enumerate() {
+        let y = y + j as i32;
+        match &slot.header {
+            Ok(header) => menu_option(
+                &mut draw_batch,
+                7,
+                y,
+                97 + j as rltk::FontCharType,
+                header.metadata.describe(),
+            ),
+            Err(err) => {
+                menu_option(&mut draw_batch, 7, y, 97 + j as rltk::FontCharType, "");
+                draw_batch.print_color(
+                    Point::new(12, y),
+                    format!("{}: {}", slot.name, err),
+                    gray_on_black,
+                );
+            }
+        }
+    }
+
+    draw_batch
+        .submit(6000)
+        .map_err(|err| println!("{:?}", err))
+        .ok();
+
+    match ctx.key {
+        None => LoadMenuResult::NoResponse,
+        Some(key) => match key {
+            VirtualKeyCode::Escape => LoadMenuResult::Cancel,
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return LoadMenuResult::Selected {
+                        slot: selection as usize,
+                    };
+                }
+                LoadMenuResult::NoResponse
+            }
+        },
+    }
+}
@@ -0,0 +1,204 @@
+use crate::gamelog::{self, LogEntry, LOG_CATEGORIES};
+use crate::State;
+use rltk::prelude::*;
+
+/// How many log entries fit on the screen at once.
+const LOG_VIEWER_ROWS: usize = 50;
+
+/// What the scrollback is showing: which categories, what it's searching for, and how far back
+/// it's scrolled. It's kept between viewings, so filters stay as the player left them.
+pub struct LogViewer {
+    shown: [bool; LOG_CATEGORIES.len()],
+    search: String,
+    typing: bool,
+    scroll: usize,
+}
+
+impl LogViewer {
+    pub fn new() -> LogViewer {
+        LogViewer {
+            shown: [true; LOG_CATEGORIES.len()],
+            search: String::new(),
+            typing: false,
+            scroll: 0,
+        }
+    }
+
+    /// Starts the next viewing at the newest entries.
+    pub fn open(&mut self) {
+        self.scroll = 0;
+        self.typing = false;
+    }
+
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let shown = LOG_CATEGORIES
+            .iter()
+            .position(|category| *category == entry.category)
+            .map(|i| self.shown[i])
+            .unwrap_or(true);
+        shown
+            && (self.search.is_empty()
+                || entry
+                    .text()
+                    .to_lowercase()
+                    .contains(&self.search.to_lowercase()))
+    }
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogViewerResult {
+    NoResponse,
+    Close,
+}
+
+pub fn show_log_viewer(gs: &mut State, ctx: &mut Rltk) -> LogViewerResult {
+    let viewer = &mut gs.main_game_state.log_viewer;
+    let entries = gamelog::clone_log()
+        .into_iter()
+        .filter(|entry| viewer.matches(entry))
+        .collect::<Vec<LogEntry>>();
+    let max_scroll = entries.len().saturating_sub(LOG_VIEWER_ROWS);
+    viewer.scroll = usize::min(viewer.scroll, max_scroll);
+
+    let mut draw_batch = DrawBatch::new();
+    let black = RGB::named(rltk::BLACK);
+    let white_on_black = ColorPair::new(RGB::named(rltk::WHITE), black);
+    let yellow_on_black = ColorPair::new(RGB::named(rltk::YELLOW), black);
+    let gray_on_black = ColorPair::new(RGB::named(rltk::GRAY), black);
+    let dark_gray_on_black = ColorPair::new(RGB::named(rltk::DARK_GRAY), black);
+
+    draw_batch.draw_box(Rect::with_size(0, 0, 79, 59), white_on_black);
+    draw_batch.print_color(Point::new(3, 0), "Message Log", yellow_on_black);
+
+    let mut x = 2;
+    for (i, category) in LOG_CATEGORIES.iter().enumerate() {
+        let label = format!("[{}] {}", i + 1, category.name());
+        let color = if viewer.shown[i] {
+            white_on_black
+        } else {
+            dark_gray_on_black
+        };
+        draw_batch.print_color(Point::new(x, 2), &label, color);
+        x += label.len() as i32 + 2;
+    }
+    let search = if viewer.typing {
+        format!("Search: {}_", viewer.search)
+    } else {
+        format!("Search: {}", viewer.search)
+    };
+    draw_batch.print_color(Point::new(2, 3), search, white_on_black);
+
+    let first = entries.len() - viewer.scroll - usize::min(entries.len(), LOG_VIEWER_ROWS);
+    for (row, entry) in entries.iter().skip(first).take(LOG_VIEWER_ROWS).enumerate() {
+        let y = 5 + row as i32;
+        draw_batch.print_color(
+            Point::new(2, y),
+            format!("{:>5}", entry.turn),
+            gray_on_black,
+        );
+        let mut x = 8;
+        for frag in entry.fragments.iter() {
+            if x >= 78 {
+                break;
+            }
+            let text: String = frag.text.chars().take((78 - x) as usize).collect();
+            draw_batch.print_color(Point::new(x, y), &text, ColorPair::new(frag.color, black));
+            x += text.len() as i32 + 1;
+        }
+    }
+    if entries.is_empty() {
+        draw_batch.print_color(Point::new(8, 5), "No matching messages.", gray_on_black);
+    }
+
+    draw_batch.print_color(
+        Point::new(2, 57),
+        "Up/Down, PgUp/PgDn, Home/End: scroll  1-5: filter  /: search  Escape: close",
+        gray_on_black,
+    );
+
+    draw_batch
+        .submit(6000)
+        .map_err(|err| println!("{:?}", err))
+        .ok();
+
+    match ctx.key {
+        None => LogViewerResult::NoResponse,
+        Some(key) if viewer.typing => {
+            match key {
+                VirtualKeyCode::Return | VirtualKeyCode::Escape => viewer.typing = false,
+                VirtualKeyCode::Back => {
+                    viewer.search.pop();
+                }
+                _ => {
+                    if let Some(c) = search_char(key) {
+                        viewer.search.push(c);
+                    }
+                }
+            }
+            viewer.scroll = 0;
+            LogViewerResult::NoResponse
+        }
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape | VirtualKeyCode::M => return LogViewerResult::Close,
+                VirtualKeyCode::Up => viewer.scroll = usize::min(viewer.scroll + 1, max_scroll),
+                VirtualKeyCode::Down => viewer.scroll = viewer.scroll.saturating_sub(1),
+                VirtualKeyCode::PageUp => {
+                    viewer.scroll = usize::min(viewer.scroll + LOG_VIEWER_ROWS, max_scroll)
+                }
+                VirtualKeyCode::PageDown => {
+                    viewer.scroll = viewer.scroll.saturating_sub(LOG_VIEWER_ROWS)
+                }
+                VirtualKeyCode::Home => viewer.scroll = max_scroll,
+                VirtualKeyCode::End => viewer.scroll = 0,
+                VirtualKeyCode::Slash => viewer.typing = true,
+                VirtualKeyCode::Back => viewer.search.clear(),
+                _ => {
+                    let category = match key {
+                        VirtualKeyCode::Key1 => Some(0),
+                        VirtualKeyCode::Key2 => Some(1),
+                        VirtualKeyCode::Key3 => Some(2),
+                        VirtualKeyCode::Key4 => Some(3),
+                        VirtualKeyCode::Key5 => Some(4),
+                        _ => None,
+                    };
+                    if let Some(i) = category {
+                        viewer.shown[i] = !viewer.shown[i];
+                        viewer.scroll = 0;
+                    }
+                }
+            }
+            LogViewerResult::NoResponse
+        }
+    }
+}
+
+/// The character a key types into the search box, if any. Searches ignore case.
+fn search_char(key: VirtualKeyCode) -> Option<char> {
+    let letter = rltk::letter_to_option(key);
+    if letter >= 0 {
+        return Some((b'a' + letter as u8) as char);
+    }
+    match key {
+        VirtualKeyCode::Space => Some(' '),
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some('0'),
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some('1'),
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some('2'),
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some('3'),
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some('4'),
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some('5'),
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some('6'),
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some('7'),
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some('8'),
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some('9'),
+        VirtualKeyCode::Minus => Some('-'),
+        VirtualKeyCode::Apostrophe => Some('\''),
+        _ => None,
+    }
+}
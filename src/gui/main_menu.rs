@@ -3,7 +3,9 @@ use rltk::prelude::*;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuSelection {
+    Continue,
     NewGame,
+    SeededGame,
     LoadGame,
     Demos,
     Quit,
@@ -18,6 +20,7 @@ pub enum MainMenuResult {
 pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     let mut draw_batch = DrawBatch::new();
     let save_exists = crate::saveload_system::does_save_exist();
+    let autosave_exists = crate::saveload_system::does_autosave_exist();
     let runstate = gs.ecs.fetch::<RunState>();
     let assets = gs.ecs.fetch::<RexAssets>();
     let black = RGB::named(rltk::BLACK);
@@ -29,7 +32,7 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     let white_on_black = ColorPair::new(RGB::named(rltk::WHITE), black);
     ctx.render_xp_sprite(&assets.menu, 0, 0);
 
-    draw_batch.draw_double_box(Rect::with_size(24, 18, 31, 11), wheat_on_black);
+    draw_batch.draw_double_box(Rect::with_size(24, 18, 31, 12), wheat_on_black);
 
     draw_batch.print_color_centered(20, "Downdelving", yellow_on_black);
     draw_batch.print_color_centered(21, "by Nathan Douglas", cyan_on_black);
@@ -40,6 +43,19 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
         menu_selection: selection,
     } = *runstate
     {
+        if autosave_exists {
+            draw_batch.print_color_centered(
+                y,
+                "Continue",
+                if selection == MainMenuSelection::Continue {
+                    magenta_on_black
+                } else {
+                    white_on_black
+                },
+            );
+            y += 1;
+        }
+
         draw_batch.print_color_centered(
             y,
             "Begin New Game",
@@ -51,6 +67,17 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
         );
         y += 1;
 
+        draw_batch.print_color_centered(
+            y,
+            "Begin Seeded Game",
+            if selection == MainMenuSelection::SeededGame {
+                magenta_on_black
+            } else {
+                white_on_black
+            },
+        );
+        y += 1;
+
         if save_exists {
             draw_batch.print_color_centered(
                 y,
@@ -105,13 +132,18 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                 VirtualKeyCode::Up => {
                     let mut newselection;
                     match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
+                        MainMenuSelection::Continue => newselection = MainMenuSelection::Quit,
+                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Continue,
+                        MainMenuSelection::SeededGame => newselection = MainMenuSelection::NewGame,
+                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::SeededGame,
                         MainMenuSelection::Demos => newselection = MainMenuSelection::LoadGame,
                         MainMenuSelection::Quit => newselection = MainMenuSelection::Demos,
                     }
                     if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::NewGame;
+                        newselection = MainMenuSelection::SeededGame;
+                    }
+                    if newselection == MainMenuSelection::Continue && !autosave_exists {
+                        newselection = MainMenuSelection::Quit;
                     }
                     return MainMenuResult::NoSelection {
                         selected: newselection,
@@ -120,14 +152,19 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                 VirtualKeyCode::Down => {
                     let mut newselection;
                     match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
+                        MainMenuSelection::Continue => newselection = MainMenuSelection::NewGame,
+                        MainMenuSelection::NewGame => newselection = MainMenuSelection::SeededGame,
+                        MainMenuSelection::SeededGame => newselection = MainMenuSelection::LoadGame,
                         MainMenuSelection::LoadGame => newselection = MainMenuSelection::Demos,
                         MainMenuSelection::Demos => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
+                        MainMenuSelection::Quit => newselection = MainMenuSelection::Continue,
                     }
                     if newselection == MainMenuSelection::LoadGame && !save_exists {
                         newselection = MainMenuSelection::Demos;
                     }
+                    if newselection == MainMenuSelection::Continue && !autosave_exists {
+                        newselection = MainMenuSelection::NewGame;
+                    }
                     return MainMenuResult::NoSelection {
                         selected: newselection,
                     };
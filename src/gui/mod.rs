@@ -20,11 +20,17 @@ mod main_menu;
 pub use main_menu::*;
 mod demo_menu;
 pub use demo_menu::*;
+mod seed_menu;
+pub use seed_menu::*;
+mod load_menu;
+pub use load_menu::*;
 mod game_over_menu;
 pub use game_over_menu::*;
 mod cheat_menu;
 pub use cheat_menu::*;
 mod vendor_menu;
 pub use vendor_menu::*;
+mod log_viewer;
+pub use log_viewer::*;
 mod menus;
 pub use menus::*;
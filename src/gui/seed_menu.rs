@@ -0,0 +1,61 @@
+use crate::rex_assets::RexAssets;
+use crate::State;
+use rltk::prelude::*;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SeedMenuResult {
+    NoResponse { seed: u64 },
+    Cancel,
+    Selected { seed: u64 },
+}
+
+pub fn seed_menu(gs: &mut State, ctx: &mut Rltk, seed: u64) -> SeedMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let assets = gs.ecs.fetch::<RexAssets>();
+    let black = RGB::named(rltk::BLACK);
+    let wheat_on_black = ColorPair::new(RGB::named(rltk::WHEAT), black);
+    let yellow_on_black = ColorPair::new(RGB::named(rltk::YELLOW), black);
+    let magenta_on_black = ColorPair::new(RGB::named(rltk::MAGENTA), black);
+    let gray_on_black = ColorPair::new(RGB::named(rltk::GRAY), black);
+    ctx.render_xp_sprite(&assets.menu, 0, 0);
+
+    draw_batch.draw_double_box(Rect::with_size(24, 18, 31, 11), wheat_on_black);
+
+    draw_batch.print_color_centered(20, "World Seed", yellow_on_black);
+    draw_batch.print_color_centered(22, "Type a number and press Enter", gray_on_black);
+    draw_batch.print_color_centered(23, "Escape to cancel", gray_on_black);
+    draw_batch.print_color_centered(25, format!("{}_", seed), magenta_on_black);
+
+    draw_batch
+        .submit(6000)
+        .map_err(|err| println!("{:?}", err))
+        .ok();
+
+    match ctx.key {
+        None => SeedMenuResult::NoResponse { seed },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => SeedMenuResult::Cancel,
+            VirtualKeyCode::Return => SeedMenuResult::Selected { seed },
+            VirtualKeyCode::Back => SeedMenuResult::NoResponse { seed: seed / 10 },
+            _ => {
+                let digit = match key {
+                    VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some(0),
+                    VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some(1),
+                    VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some(2),
+                    VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some(3),
+                    VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some(4),
+                    VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some(5),
+                    VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some(6),
+                    VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some(7),
+                    VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some(8),
+                    VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some(9),
+                    _ => None,
+                };
+                let seed = digit
+                    .and_then(|digit| seed.checked_mul(10)?.checked_add(digit))
+                    .unwrap_or(seed);
+                SeedMenuResult::NoResponse { seed }
+            }
+        },
+    }
+}
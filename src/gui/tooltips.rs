@@ -82,7 +82,8 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     }
 
     let mut tip_boxes: Vec<Tooltip> = Vec::new();
-    crate::spatial::for_each_tile_content(mouse_idx, |entity| {
+    let spatial = ecs.fetch::<crate::spatial::SpatialIndex>();
+    spatial.for_each_tile_content(mouse_idx, |entity| {
         if hidden.get(entity).is_some() {
             return;
         }
@@ -142,6 +143,22 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         tip_boxes.push(tip);
     });
 
+    // With nothing standing there, describe the ground
+    let tile = map.tiles[mouse_idx];
+    let description = tile.description();
+    if tip_boxes.is_empty() && !description.is_empty() {
+        let mut tip = Tooltip::new();
+        tip.add(tile.name());
+        tip.add(description);
+        tip_boxes.push(tip);
+    }
+
+    if let Some(hazard) = map.hazards.get(&mouse_idx) {
+        let mut tip = Tooltip::new();
+        tip.add(hazard.kind.name());
+        tip_boxes.push(tip);
+    }
+
     if tip_boxes.is_empty() {
         return;
     }
@@ -0,0 +1,243 @@
+use rltk::Point;
+use serde::Serialize;
+use specs::prelude::*;
+use std::collections::HashMap;
+
+use crate::damage_system;
+use crate::game_locals;
+use crate::main_game;
+use crate::player::{self, PlayerCommand};
+use crate::rng::WorldSeed;
+use crate::systems::particle_system;
+use crate::{MainGameRunState, Map, Name, Pools, RunState, State};
+
+/// How much time each headless step pretends has passed, so particles still age out.
+const FRAME_TIME_MS: f32 = 1000.0 / 60.0;
+
+/// Drives a game without a rendering context, one player command at a time.
+pub struct Simulation {
+    pub state: State,
+    pub commands_run: usize,
+}
+
+/// A snapshot of the interesting parts of the ECS after a simulation has run.
+#[derive(Serialize, Debug)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub commands_run: usize,
+    pub game_over: bool,
+    pub depth: i32,
+    pub map_name: String,
+    pub player_position: (i32, i32),
+    pub hit_points: (i32, i32),
+    pub mana: (i32, i32),
+    pub level: i32,
+    pub xp: i32,
+    pub gold: f32,
+    pub entity_count: usize,
+    pub visible_entities: Vec<String>,
+    pub events: HashMap<String, i32>,
+}
+
+impl Simulation {
+    /// Starts a new game on the first level with a random world seed.
+    pub fn new() -> Simulation {
+        Simulation::with_seed(WorldSeed::random().seed)
+    }
+
+    /// Starts a new game on the first level, exactly as the main menu would, except that it
+    /// never autosaves.
+    pub fn with_seed(seed: u64) -> Simulation {
+        let mut state = State::new();
+        state.main_game_state.autosave.enabled = false;
+        let mut simulation = Simulation {
+            state,
+            commands_run: 0,
+        };
+        simulation.with_game_locals(|simulation| {
+            let newrunstate = simulation.state.start_new_game(WorldSeed::new(seed));
+            simulation.settle(newrunstate);
+        });
+        simulation
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        *self.state.ecs.fetch::<RunState>()
+            == RunState::MainGame {
+                runstate: MainGameRunState::GameOver,
+            }
+    }
+
+    /// Applies a single command and runs the world until the player is asked for input again.
+    pub fn step(&mut self, command: PlayerCommand) {
+        if self.is_game_over() {
+            return;
+        }
+        self.with_game_locals(|simulation| {
+            let newrunstate = player::execute_command(&mut simulation.state.ecs, command);
+            simulation.commands_run += 1;
+            simulation.settle(newrunstate);
+        });
+    }
+
+    /// Runs `f` with this game's random streams and event counts active, so other simulations
+    /// don't disturb them.
+    fn with_game_locals(&mut self, f: impl FnOnce(&mut Simulation)) {
+        let outer = game_locals::enter(&mut self.state.ecs);
+        f(self);
+        game_locals::leave(&mut self.state.ecs, outer);
+    }
+
+    /// Runs every command in order, stopping early if the player dies.
+    pub fn run(&mut self, commands: &[PlayerCommand]) {
+        for command in commands.iter() {
+            if self.is_game_over() {
+                break;
+            }
+            self.step(command.clone());
+        }
+    }
+
+    /// Keeps advancing the state machine until it needs the player again. There is nobody to
+    /// answer menus, so any menu that opens is treated as cancelled.
+    fn settle(&mut self, runstate: RunState) {
+        let mut newrunstate = runstate;
+        loop {
+            // The systems read the state from the world, where the game leaves it between frames
+            {
+                let mut runwriter = self.state.ecs.write_resource::<RunState>();
+                *runwriter = newrunstate;
+            }
+            newrunstate = match newrunstate {
+                RunState::MainGame { runstate } => match runstate {
+                    MainGameRunState::AwaitingInput | MainGameRunState::GameOver => break,
+                    MainGameRunState::MapGeneration => {
+                        self.state.main_game_state.mapgen_next_state.unwrap()
+                    }
+                    MainGameRunState::ShowInventory
+                    | MainGameRunState::ShowDropItem
+                    | MainGameRunState::ShowRemoveItem
+                    | MainGameRunState::ShowTargeting { .. }
+                    | MainGameRunState::ShowCheatMenu
+                    | MainGameRunState::ShowVendor { .. }
+                    | MainGameRunState::ShowRemoveCurse
+                    | MainGameRunState::ShowIdentify
+                    | MainGameRunState::SaveGame => RunState::MainGame {
+                        runstate: MainGameRunState::AwaitingInput,
+                    },
+                    _ => main_game::advance(&mut self.state, newrunstate),
+                },
+                _ => break,
+            };
+
+            {
+                let mut runwriter = self.state.ecs.write_resource::<RunState>();
+                *runwriter = newrunstate;
+            }
+            damage_system::delete_the_dead(&mut self.state.ecs);
+            particle_system::update_particles(&mut self.state.ecs, FRAME_TIME_MS);
+            newrunstate = *self.state.ecs.fetch::<RunState>();
+        }
+        let mut runwriter = self.state.ecs.write_resource::<RunState>();
+        *runwriter = newrunstate;
+    }
+
+    pub fn report(&self) -> SimulationReport {
+        let ecs = &self.state.ecs;
+        let map = ecs.fetch::<Map>();
+        let player_pos = ecs.fetch::<Point>();
+        let player_entity = ecs.fetch::<Entity>();
+        let pools = ecs.read_storage::<Pools>();
+        let player_pools = pools.get(*player_entity).unwrap();
+
+        let mut visible_entities = Vec::new();
+        {
+            let names = ecs.read_storage::<Name>();
+            let viewsheds = ecs.read_storage::<crate::Viewshed>();
+            let positions = ecs.read_storage::<crate::Position>();
+            if let Some(viewshed) = viewsheds.get(*player_entity) {
+                for (entity, name, pos) in (&ecs.entities(), &names, &positions).join() {
+                    if entity != *player_entity
+                        && viewshed.visible_tiles.contains(&Point::new(pos.x, pos.y))
+                    {
+                        visible_entities.push(name.name.clone());
+                    }
+                }
+            }
+        }
+        visible_entities.sort();
+
+        SimulationReport {
+            seed: ecs.fetch::<WorldSeed>().seed,
+            commands_run: self.commands_run,
+            game_over: self.is_game_over(),
+            depth: map.depth,
+            map_name: map.name.clone(),
+            player_position: (player_pos.x, player_pos.y),
+            hit_points: (player_pools.hit_points.current, player_pools.hit_points.max),
+            mana: (player_pools.mana.current, player_pools.mana.max),
+            level: player_pools.level,
+            xp: player_pools.xp,
+            gold: player_pools.gold,
+            entity_count: ecs.entities().join().count(),
+            visible_entities,
+            events: crate::game_locals::events(ecs),
+        }
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cheat;
+
+    fn play(seed: u64) -> SimulationReport {
+        let mut simulation = Simulation::with_seed(seed);
+        let mut commands = vec![
+            PlayerCommand::Move {
+                delta_x: 1,
+                delta_y: 0,
+            },
+            PlayerCommand::Cheat {
+                cheat: Cheat::GodMode,
+            },
+            PlayerCommand::Cheat {
+                cheat: Cheat::TeleportToExit,
+            },
+        ];
+        commands.extend(std::iter::repeat_n(PlayerCommand::SkipTurn, 10));
+        simulation.run(&commands);
+        simulation.report()
+    }
+
+    #[test]
+    fn a_seeded_game_settles_and_steps() {
+        let simulation = Simulation::with_seed(42);
+        assert!(!simulation.is_game_over());
+        assert_eq!(simulation.commands_run, 0);
+        let report = simulation.report();
+        assert_eq!(report.seed, 42);
+        assert_eq!(report.depth, 1);
+        assert!(report.entity_count > 1);
+
+        let report = play(42);
+        assert_eq!(report.commands_run, 13);
+        assert!(!report.game_over);
+        assert_eq!(report.depth, 2);
+    }
+
+    #[test]
+    fn the_same_seed_plays_the_same_game() {
+        let (first, second) = (play(7), play(7));
+        assert_eq!(first.player_position, second.player_position);
+        assert_eq!(first.hit_points, second.hit_points);
+        assert_eq!(first.entity_count, second.entity_count);
+        assert_eq!(first.visible_entities, second.visible_entities);
+    }
+}
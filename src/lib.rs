@@ -0,0 +1,344 @@
+extern crate derivative;
+extern crate serde;
+#[macro_use]
+extern crate lazy_static;
+
+use rltk::{GameState, Point, Rltk};
+use specs::prelude::*;
+use specs::saveload::SimpleMarkerAllocator;
+
+mod components;
+pub use components::*;
+mod map;
+pub use map::*;
+mod player;
+pub use player::{Cheat, PlayerCommand};
+mod rect;
+pub use rect::Rect;
+pub mod biome;
+mod damage_system;
+mod demos;
+pub mod dice;
+mod game_system;
+mod gamelog;
+mod gui;
+pub mod headless;
+pub mod main_game;
+pub use main_game::MainGameRunState;
+pub mod map_builders;
+pub mod mapgen_analysis;
+pub mod perception;
+pub mod random_table;
+pub mod raws;
+pub mod rex_assets;
+pub mod saveload_system;
+mod spawner;
+pub use game_system::*;
+pub mod effects;
+mod game_locals;
+pub mod replay;
+pub mod rng;
+pub mod spatial;
+mod systems;
+pub mod vendor;
+use rex_assets::RexAssets;
+use systems::particle_system::ParticleBuilder;
+
+/// Writes a message for developers, not players. Natively it goes to stderr, leaving stdout to
+/// tools that print their results there.
+pub fn console_log<S: ToString>(message: S) {
+    #[cfg(target_arch = "wasm32")]
+    rltk::console::log(message);
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message.to_string());
+}
+
+const SHOW_MAPGEN_VISUALIZER: bool = false;
+const SHOW_FPS: bool = true;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunState {
+    MainMenu {
+        menu_selection: gui::MainMenuSelection,
+    },
+    DemoMenu {
+        menu_selection: gui::DemoMenuSelection,
+    },
+    SeedMenu {
+        seed: u64,
+    },
+    LoadMenu,
+    Demo {
+        demo: demos::Demo,
+    },
+    MainGame {
+        runstate: MainGameRunState,
+    },
+}
+
+pub struct State {
+    pub ecs: World,
+    main_game_state: main_game::MainGameState,
+    dispatcher: Box<dyn systems::UnifiedDispatcher + 'static>,
+}
+
+impl State {
+    /// Builds the ECS world, loads the raws and registers every resource the game expects.
+    pub fn new() -> State {
+        let mut gs = State {
+            ecs: World::new(),
+            main_game_state: main_game::MainGameState {
+                mapgen_next_state: Some(RunState::MainMenu {
+                    menu_selection: gui::MainMenuSelection::NewGame,
+                }),
+                mapgen_index: 0,
+                mapgen_history: Vec::new(),
+                mapgen_timer: 0.0,
+                world_seed: None,
+                save_slots: Vec::new(),
+                load_error: None,
+                autosave: saveload_system::AutosaveSchedule::new(),
+                log_viewer: gui::LogViewer::new(),
+            },
+            dispatcher: systems::build(),
+        };
+        register_all(&mut gs.ecs);
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+        raws::load_raws();
+
+        let world_seed = rng::WorldSeed::random();
+        gs.ecs.insert(world_seed);
+        gs.ecs.insert(game_locals::GameLocals::new(&world_seed));
+        gs.ecs.insert(replay::InputRecording::default());
+        gs.ecs.insert(map::MasterDungeonMap::new());
+        gs.ecs.insert(Map::new(1, 64, 64, "New Map"));
+        gs.ecs.insert(spatial::SpatialIndex::new(64 * 64));
+        gs.ecs.insert(spatial::FrozenSpatialIndexes::default());
+        gs.ecs.insert(perception::lighting::LightFlicker::default());
+        gs.ecs.insert(Point::new(0, 0));
+        let player_entity = spawner::player(&mut gs.ecs, 0, 0);
+        gs.ecs.insert(player_entity);
+        gs.ecs.insert(RunState::MainGame {
+            runstate: MainGameRunState::MapGeneration {},
+        });
+        gs.ecs.insert(ParticleBuilder::new());
+        gs.ecs.insert(RexAssets::new());
+
+        gs
+    }
+
+    /// Starts a fresh game built from `world_seed`, returning the state to continue in.
+    pub fn start_new_game(&mut self, world_seed: rng::WorldSeed) -> RunState {
+        saveload_system::new_game(&mut self.ecs, world_seed);
+        self.main_game_state.autosave.reset();
+        self.main_game_state
+            .generate_world_map(&mut self.ecs, &LevelId::main(1));
+        self.main_game_state.mapgen_next_state = Some(RunState::MainGame {
+            runstate: MainGameRunState::PreRun,
+        });
+        RunState::MainGame {
+            runstate: MainGameRunState::MapGeneration,
+        }
+    }
+
+    /// Uses `seed` for every new game started from the main menu, instead of a random one.
+    pub fn set_world_seed(&mut self, seed: u64) {
+        self.main_game_state.world_seed = Some(rng::WorldSeed::new(seed));
+    }
+
+    fn run_systems(&mut self) {
+        self.dispatcher.run_now(&mut self.ecs);
+        self.ecs.maintain();
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut Rltk) {
+        let outer = game_locals::enter(&mut self.ecs);
+        self.tick_with_locals(ctx);
+        game_locals::leave(&mut self.ecs, outer);
+    }
+}
+
+impl State {
+    #[allow(clippy::cognitive_complexity)]
+    fn tick_with_locals(&mut self, ctx: &mut Rltk) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(0);
+        ctx.cls();
+        systems::particle_system::update_particles(&mut self.ecs, ctx.frame_time_ms);
+        self.ecs
+            .write_resource::<perception::lighting::LightFlicker>()
+            .advance(ctx.frame_time_ms);
+
+        // Draw the game map unless we're in one of a few states.
+        match newrunstate {
+            RunState::MainMenu { .. } => {}
+            RunState::DemoMenu { .. } => {}
+            RunState::SeedMenu { .. } => {}
+            RunState::LoadMenu => {}
+            RunState::Demo { .. } => {}
+            RunState::MainGame { runstate } => match runstate {
+                MainGameRunState::GameOver { .. } => {}
+                _ => {
+                    camera::render_camera(&self.ecs, ctx);
+                    gui::draw_ui(&self.ecs, ctx);
+                }
+            },
+        }
+
+        match newrunstate {
+            RunState::MainGame { .. } => {
+                newrunstate = main_game::tick(self, ctx, &newrunstate);
+            }
+            RunState::MainMenu { .. } => {
+                let result = gui::main_menu(self, ctx);
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::MainMenuResult::Selected { selected } => match selected {
+                        gui::MainMenuSelection::Continue => {
+                            match saveload_system::continue_game(&mut self.ecs) {
+                                Ok(()) => {
+                                    self.main_game_state.autosave.reset();
+                                    newrunstate = RunState::MainGame {
+                                        runstate: MainGameRunState::AwaitingInput,
+                                    };
+                                }
+                                Err(err) => {
+                                    crate::console_log(format!("Unable to continue: {}", err));
+                                    self.main_game_state.save_slots = saveload_system::list_saves();
+                                    self.main_game_state.load_error = Some(err.to_string());
+                                    newrunstate = RunState::LoadMenu;
+                                }
+                            }
+                        }
+                        gui::MainMenuSelection::NewGame => {
+                            let world_seed = self
+                                .main_game_state
+                                .world_seed
+                                .unwrap_or_else(rng::WorldSeed::random);
+                            newrunstate = self.start_new_game(world_seed);
+                        }
+                        gui::MainMenuSelection::SeededGame => {
+                            newrunstate = RunState::SeedMenu { seed: 0 };
+                        }
+                        gui::MainMenuSelection::LoadGame => {
+                            self.main_game_state.save_slots = saveload_system::list_saves();
+                            self.main_game_state.load_error = None;
+                            newrunstate = RunState::LoadMenu;
+                        }
+                        gui::MainMenuSelection::Demos => {
+                            newrunstate = RunState::DemoMenu {
+                                menu_selection: gui::DemoMenuSelection::Exit,
+                            }
+                        }
+                        gui::MainMenuSelection::Quit => {
+                            ::std::process::exit(0);
+                        }
+                    },
+                }
+            }
+            RunState::DemoMenu { .. } => {
+                let result = gui::demo_menu(self, ctx);
+                match result {
+                    gui::DemoMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::DemoMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::DemoMenuResult::Selected { selected } => {
+                        newrunstate = demos::select_demo(selected);
+                    }
+                }
+            }
+            RunState::SeedMenu { seed } => {
+                let result = gui::seed_menu(self, ctx, seed);
+                match result {
+                    gui::SeedMenuResult::NoResponse { seed } => {
+                        newrunstate = RunState::SeedMenu { seed };
+                    }
+                    gui::SeedMenuResult::Cancel => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::SeededGame,
+                        };
+                    }
+                    gui::SeedMenuResult::Selected { seed } => {
+                        newrunstate = self.start_new_game(rng::WorldSeed::new(seed));
+                    }
+                }
+            }
+            RunState::LoadMenu => {
+                let result = gui::load_menu(
+                    ctx,
+                    &self.main_game_state.save_slots,
+                    self.main_game_state.load_error.as_ref(),
+                );
+                match result {
+                    gui::LoadMenuResult::NoResponse => {}
+                    gui::LoadMenuResult::Cancel => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::LoadGame,
+                        };
+                    }
+                    gui::LoadMenuResult::Selected { slot } => {
+                        let slot = self.main_game_state.save_slots[slot].clone();
+                        match saveload_system::load_game(&mut self.ecs, &slot) {
+                            Ok(()) => {
+                                newrunstate = RunState::MainGame {
+                                    runstate: MainGameRunState::AwaitingInput,
+                                };
+                                if let Err(err) = saveload_system::delete_save(&slot) {
+                                    crate::console_log(format!(
+                                        "Unable to delete {}: {}",
+                                        slot.name, err
+                                    ));
+                                }
+                            }
+                            Err(err) => {
+                                crate::console_log(format!(
+                                    "Unable to load {}: {}",
+                                    slot.name, err
+                                ));
+                                self.main_game_state.load_error = Some(err.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::Demo { .. } => {
+                newrunstate = demos::tick(self, ctx, &newrunstate);
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage_system::delete_the_dead(&mut self.ecs);
+
+        rltk::render_draw_buffer(ctx)
+            .map_err(|err| println!("{:?}", err))
+            .ok();
+        if SHOW_FPS {
+            ctx.print(1, 59, &format!("FPS: {}", ctx.fps));
+        }
+    }
+}
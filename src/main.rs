@@ -1,177 +1,5 @@
-extern crate derivative;
-extern crate serde;
-#[macro_use]
-extern crate lazy_static;
-
-use rltk::{GameState, Point, Rltk};
-use specs::prelude::*;
-use specs::saveload::SimpleMarkerAllocator;
-
-mod components;
-pub use components::*;
-mod map;
-pub use map::*;
-mod player;
-mod rect;
-pub use rect::Rect;
-pub mod biome;
-mod damage_system;
-mod demos;
-mod game_system;
-mod gamelog;
-mod gui;
-pub mod main_game;
-pub use main_game::MainGameRunState;
-pub mod map_builders;
-pub mod perception;
-pub mod random_table;
-pub mod raws;
-pub mod rex_assets;
-pub mod saveload_system;
-mod spawner;
-pub use game_system::*;
-pub mod effects;
-pub mod rng;
-pub mod spatial;
-mod systems;
-pub mod vendor;
-use rex_assets::RexAssets;
-use systems::particle_system::ParticleBuilder;
-
-const SHOW_MAPGEN_VISUALIZER: bool = false;
-const SHOW_FPS: bool = true;
-
-#[derive(PartialEq, Copy, Clone)]
-pub enum RunState {
-    MainMenu {
-        menu_selection: gui::MainMenuSelection,
-    },
-    DemoMenu {
-        menu_selection: gui::DemoMenuSelection,
-    },
-    Demo {
-        demo: demos::Demo,
-    },
-    MainGame {
-        runstate: MainGameRunState,
-    },
-}
-
-pub struct State {
-    pub ecs: World,
-    main_game_state: main_game::MainGameState,
-    dispatcher: Box<dyn systems::UnifiedDispatcher + 'static>,
-}
-
-impl State {
-    fn run_systems(&mut self) {
-        self.dispatcher.run_now(&mut self.ecs);
-        self.ecs.maintain();
-    }
-}
-
-impl GameState for State {
-    #[allow(clippy::cognitive_complexity)]
-    fn tick(&mut self, ctx: &mut Rltk) {
-        let mut newrunstate;
-        {
-            let runstate = self.ecs.fetch::<RunState>();
-            newrunstate = *runstate;
-        }
-
-        ctx.set_active_console(1);
-        ctx.cls();
-        ctx.set_active_console(0);
-        ctx.cls();
-        systems::particle_system::update_particles(&mut self.ecs, ctx);
-
-        // Draw the game map unless we're in one of a few states.
-        match newrunstate {
-            RunState::MainMenu { .. } => {}
-            RunState::DemoMenu { .. } => {}
-            RunState::Demo { .. } => {}
-            RunState::MainGame { runstate } => match runstate {
-                MainGameRunState::GameOver { .. } => {}
-                _ => {
-                    camera::render_camera(&self.ecs, ctx);
-                    gui::draw_ui(&self.ecs, ctx);
-                }
-            },
-        }
-
-        match newrunstate {
-            RunState::MainGame { .. } => {
-                newrunstate = main_game::tick(self, ctx, &newrunstate);
-            }
-            RunState::MainMenu { .. } => {
-                let result = gui::main_menu(self, ctx);
-                match result {
-                    gui::MainMenuResult::NoSelection { selected } => {
-                        newrunstate = RunState::MainMenu {
-                            menu_selection: selected,
-                        }
-                    }
-                    gui::MainMenuResult::Selected { selected } => match selected {
-                        gui::MainMenuSelection::NewGame => {
-                            saveload_system::new_game(&mut self.ecs);
-                            self.main_game_state.generate_world_map(&mut self.ecs, 1, 0);
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::MapGeneration,
-                            };
-                            self.main_game_state.mapgen_next_state = Some(RunState::MainGame {
-                                runstate: MainGameRunState::PreRun,
-                            });
-                        }
-                        gui::MainMenuSelection::LoadGame => {
-                            saveload_system::load_game(&mut self.ecs);
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::AwaitingInput,
-                            };
-                            saveload_system::delete_save();
-                        }
-                        gui::MainMenuSelection::Demos => {
-                            newrunstate = RunState::DemoMenu {
-                                menu_selection: gui::DemoMenuSelection::Exit,
-                            }
-                        }
-                        gui::MainMenuSelection::Quit => {
-                            ::std::process::exit(0);
-                        }
-                    },
-                }
-            }
-            RunState::DemoMenu { .. } => {
-                let result = gui::demo_menu(self, ctx);
-                match result {
-                    gui::DemoMenuResult::NoSelection { selected } => {
-                        newrunstate = RunState::DemoMenu {
-                            menu_selection: selected,
-                        }
-                    }
-                    gui::DemoMenuResult::Selected { selected } => {
-                        newrunstate = demos::select_demo(selected);
-                    }
-                }
-            }
-            RunState::Demo { .. } => {
-                newrunstate = demos::tick(self, ctx, &newrunstate);
-            }
-        }
-
-        {
-            let mut runwriter = self.ecs.write_resource::<RunState>();
-            *runwriter = newrunstate;
-        }
-        damage_system::delete_the_dead(&mut self.ecs);
-
-        rltk::render_draw_buffer(ctx)
-            .map_err(|err| println!("{:?}", err))
-            .ok();
-        if SHOW_FPS {
-            ctx.print(1, 59, &format!("FPS: {}", ctx.fps));
-        }
-    }
-}
+use downdelving::saveload_system::{self, SaveFormat};
+use downdelving::State;
 
 fn main() -> rltk::BError {
     use rltk::RltkBuilder;
@@ -183,33 +11,21 @@ fn main() -> rltk::BError {
         .with_vsync(false)
         .build()?;
     context.with_post_scanlines(false);
-    let mut gs = State {
-        ecs: World::new(),
-        main_game_state: main_game::MainGameState {
-            mapgen_next_state: Some(RunState::MainMenu {
-                menu_selection: gui::MainMenuSelection::NewGame,
-            }),
-            mapgen_index: 0,
-            mapgen_history: Vec::new(),
-            mapgen_timer: 0.0,
-        },
-        dispatcher: systems::build(),
-    };
-    register_all(&mut gs.ecs);
-    gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
-
-    raws::load_raws();
-
-    gs.ecs.insert(map::MasterDungeonMap::new());
-    gs.ecs.insert(Map::new(1, 64, 64, "New Map"));
-    gs.ecs.insert(Point::new(0, 0));
-    let player_entity = spawner::player(&mut gs.ecs, 0, 0);
-    gs.ecs.insert(player_entity);
-    gs.ecs.insert(RunState::MainGame {
-        runstate: MainGameRunState::MapGeneration {},
-    });
-    gs.ecs.insert(ParticleBuilder::new());
-    gs.ecs.insert(RexAssets::new());
+    let mut gs = State::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            match args.next().and_then(|seed| seed.parse::<u64>().ok()) {
+                Some(seed) => gs.set_world_seed(seed),
+                None => downdelving::console_log("--seed expects a number"),
+            }
+        } else if arg == "--save-format" {
+            match args.next().as_deref().and_then(SaveFormat::from_extension) {
+                Some(format) => saveload_system::set_save_format(format),
+                None => downdelving::console_log("--save-format expects json or sav"),
+            }
+        }
+    }
 
     rltk::main_loop(context, gs)
 }
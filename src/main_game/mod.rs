@@ -6,10 +6,12 @@ use crate::components::*;
 use crate::gamelog;
 use crate::gui;
 use crate::map;
-use crate::map::{freeze_level_entities, Map, MasterDungeonMap};
+use crate::map::{freeze_level_entities, LevelId, Map};
 use crate::player;
 use crate::player::*;
+use crate::replay;
 use crate::saveload_system;
+use crate::spatial;
 use crate::spawner;
 use crate::vendor;
 use crate::vendor::VendorMode;
@@ -35,9 +37,10 @@ pub enum MainGameRunState {
     MapGeneration,
     ShowCheatMenu,
     ShowVendor { vendor: Entity, mode: VendorMode },
-    TeleportingToOtherLevel { x: i32, y: i32, depth: i32 },
+    TeleportingToOtherLevel,
     ShowRemoveCurse,
     ShowIdentify,
+    ShowLog,
 }
 
 pub struct MainGameState {
@@ -45,18 +48,23 @@ pub struct MainGameState {
     pub mapgen_history: Vec<Map>,
     pub mapgen_index: usize,
     pub mapgen_timer: f32,
+    pub world_seed: Option<crate::rng::WorldSeed>,
+    pub save_slots: Vec<saveload_system::SaveSlot>,
+    pub load_error: Option<String>,
+    pub autosave: saveload_system::AutosaveSchedule,
+    pub log_viewer: gui::LogViewer,
 }
 
 impl MainGameState {
-    pub fn goto_level(&mut self, ecs: &mut World, offset: i32) {
+    pub fn goto_level(&mut self, ecs: &mut World, level: &LevelId) {
         freeze_level_entities(ecs);
 
         // Build a new map and place the player
-        let current_depth = ecs.fetch::<Map>().depth;
-        self.generate_world_map(ecs, current_depth + offset, offset);
+        self.generate_world_map(ecs, level);
 
         // Notify the player
         gamelog::Logger::new().append("You change level.").log();
+        self.autosave.level_changed();
     }
 
     pub fn game_over_cleanup(&mut self, ecs: &mut World) {
@@ -78,16 +86,17 @@ impl MainGameState {
 
         // Replace the world maps
         ecs.insert(map::MasterDungeonMap::new());
+        ecs.insert(spatial::FrozenSpatialIndexes::default());
 
         // Build a new map and place the player
-        self.generate_world_map(ecs, 1, 0);
+        self.generate_world_map(ecs, &LevelId::main(1));
     }
 
-    pub fn generate_world_map(&mut self, ecs: &mut World, new_depth: i32, offset: i32) {
+    pub fn generate_world_map(&mut self, ecs: &mut World, level: &LevelId) {
         self.mapgen_index = 0;
         self.mapgen_timer = 0.0;
         self.mapgen_history.clear();
-        let map_building_info = map::level_transition(ecs, new_depth, offset);
+        let map_building_info = map::level_transition(ecs, level);
         if let Some(history) = map_building_info {
             self.mapgen_history = history;
         } else {
@@ -100,8 +109,6 @@ impl MainGameState {
             .color(rltk::YELLOW)
             .append("Downdelving")
             .log();
-
-        gamelog::clear_events();
     }
 }
 
@@ -114,7 +121,7 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
         RunState::MainGame {
             runstate: inner_runstate,
         } => match inner_runstate {
-            MainGameRunState::GameOver { .. } => {}
+            MainGameRunState::GameOver { .. } | MainGameRunState::ShowLog => {}
             _ => {
                 camera::render_camera(&state.ecs, ctx);
                 gui::draw_ui(&state.ecs, ctx);
@@ -157,89 +164,8 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                 }
             }
-
-            MainGameRunState::PreRun => {
-                state.run_systems();
-                state.ecs.maintain();
-                newrunstate = RunState::MainGame {
-                    runstate: MainGameRunState::AwaitingInput,
-                };
-            }
-
             MainGameRunState::AwaitingInput => {
                 newrunstate = player_input(state, ctx);
-                if newrunstate
-                    != (RunState::MainGame {
-                        runstate: MainGameRunState::AwaitingInput,
-                    })
-                {
-                    crate::gamelog::record_event("Turn", 1);
-                }
-            }
-
-            MainGameRunState::Ticking => {
-                let mut should_change_target = false;
-                while newrunstate
-                    == (RunState::MainGame {
-                        runstate: MainGameRunState::Ticking,
-                    })
-                {
-                    state.run_systems();
-                    state.ecs.maintain();
-                    match *state.ecs.fetch::<RunState>() {
-                        RunState::MainGame {
-                            runstate: MainGameRunState::AwaitingInput,
-                        } => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::AwaitingInput,
-                            };
-                            should_change_target = true;
-                        }
-                        RunState::MainGame {
-                            runstate: MainGameRunState::MagicMapReveal { .. },
-                        } => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::MagicMapReveal { row: 0 },
-                            }
-                        }
-                        RunState::MainGame {
-                            runstate: MainGameRunState::TownPortal,
-                        } => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::TownPortal,
-                            }
-                        }
-                        RunState::MainGame {
-                            runstate: MainGameRunState::TeleportingToOtherLevel { x, y, depth },
-                        } => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::TeleportingToOtherLevel { x, y, depth },
-                            }
-                        }
-                        RunState::MainGame {
-                            runstate: MainGameRunState::ShowRemoveCurse,
-                        } => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::ShowRemoveCurse,
-                            }
-                        }
-                        RunState::MainGame {
-                            runstate: MainGameRunState::ShowIdentify,
-                        } => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::ShowIdentify,
-                            }
-                        }
-                        _ => {
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::Ticking,
-                            }
-                        }
-                    }
-                }
-                if should_change_target {
-                    player::end_turn_targeting(&mut state.ecs);
-                }
             }
             MainGameRunState::ShowInventory => {
                 let result = gui::show_inventory(state, ctx);
@@ -262,19 +188,12 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                                 },
                             };
                         } else {
-                            let mut intent = state.ecs.write_storage::<WantsToUseItem>();
-                            intent
-                                .insert(
-                                    *state.ecs.fetch::<Entity>(),
-                                    WantsToUseItem {
-                                        item: item_entity,
-                                        target: None,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::Ticking,
+                            std::mem::drop(is_ranged);
+                            let command = PlayerCommand::UseItem {
+                                item: crate::replay::entity_ref(&state.ecs, item_entity),
+                                target: None,
                             };
+                            newrunstate = execute_command(&mut state.ecs, command);
                         }
                     }
                 }
@@ -289,62 +208,66 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                     gui::CheatMenuResult::NoResponse => {}
                     gui::CheatMenuResult::TeleportToExit => {
-                        state.main_game_state.goto_level(&mut state.ecs, 1);
-                        state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
-                            runstate: MainGameRunState::PreRun,
-                        });
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::MapGeneration,
-                        };
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat {
+                                cheat: Cheat::TeleportToExit,
+                            },
+                        );
                     }
                     gui::CheatMenuResult::Heal => {
-                        let player = state.ecs.fetch::<Entity>();
-                        let mut pools = state.ecs.write_storage::<Pools>();
-                        let mut player_pools = pools.get_mut(*player).unwrap();
-                        player_pools.hit_points.current = player_pools.hit_points.max;
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::AwaitingInput,
-                        };
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat { cheat: Cheat::Heal },
+                        );
                     }
                     gui::CheatMenuResult::Reveal => {
-                        let mut map = state.ecs.fetch_mut::<Map>();
-                        for v in map.revealed_tiles.iter_mut() {
-                            *v = true;
-                        }
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::AwaitingInput,
-                        };
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat {
+                                cheat: Cheat::Reveal,
+                            },
+                        );
                     }
                     gui::CheatMenuResult::GodMode => {
-                        let player = state.ecs.fetch::<Entity>();
-                        let mut pools = state.ecs.write_storage::<Pools>();
-                        let mut player_pools = pools.get_mut(*player).unwrap();
-                        player_pools.god_mode = true;
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::AwaitingInput,
-                        };
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat {
+                                cheat: Cheat::GodMode,
+                            },
+                        );
                     }
                     gui::CheatMenuResult::LevelUp => {
-                        let player = state.ecs.fetch::<Entity>();
-                        crate::effects::add_effect(
-                            None,
-                            crate::effects::EffectType::AddExperienceLevel,
-                            crate::effects::Targets::Single { target: *player },
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat {
+                                cheat: Cheat::LevelUp,
+                            },
                         );
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
-                        };
                     }
                     gui::CheatMenuResult::Eat => {
-                        let player = state.ecs.fetch::<Entity>();
-                        crate::effects::add_effect(
-                            None,
-                            crate::effects::EffectType::WellFed,
-                            crate::effects::Targets::Single { target: *player },
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat { cheat: Cheat::Eat },
+                        );
+                    }
+                    gui::CheatMenuResult::ReloadRaws => {
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat {
+                                cheat: Cheat::ReloadRaws {
+                                    rebuild_mobs: false,
+                                },
+                            },
+                        );
+                    }
+                    gui::CheatMenuResult::ReloadRawsAndMobs => {
+                        newrunstate = execute_command(
+                            &mut state.ecs,
+                            PlayerCommand::Cheat {
+                                cheat: Cheat::ReloadRaws { rebuild_mobs: true },
+                            },
                         );
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
-                        };
                     }
                 }
             }
@@ -358,17 +281,10 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = state.ecs.write_storage::<WantsToDropItem>();
-                        intent
-                            .insert(
-                                *state.ecs.fetch::<Entity>(),
-                                WantsToDropItem { item: item_entity },
-                            )
-                            .expect("Unable to insert intent");
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
+                        let command = PlayerCommand::DropItem {
+                            item: crate::replay::entity_ref(&state.ecs, result.1.unwrap()),
                         };
+                        newrunstate = execute_command(&mut state.ecs, command);
                     }
                 }
             }
@@ -382,17 +298,10 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = state.ecs.write_storage::<WantsToRemoveItem>();
-                        intent
-                            .insert(
-                                *state.ecs.fetch::<Entity>(),
-                                WantsToRemoveItem { item: item_entity },
-                            )
-                            .expect("Unable to insert intent");
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
+                        let command = PlayerCommand::RemoveItem {
+                            item: crate::replay::entity_ref(&state.ecs, result.1.unwrap()),
                         };
+                        newrunstate = execute_command(&mut state.ecs, command);
                     }
                 }
             }
@@ -406,11 +315,10 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        state.ecs.write_storage::<CursedItem>().remove(item_entity);
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
+                        let command = PlayerCommand::RemoveCurse {
+                            item: crate::replay::entity_ref(&state.ecs, result.1.unwrap()),
                         };
+                        newrunstate = execute_command(&mut state.ecs, command);
                     }
                 }
             }
@@ -424,14 +332,10 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        if let Some(name) = state.ecs.read_storage::<Name>().get(item_entity) {
-                            let mut dm = state.ecs.fetch_mut::<MasterDungeonMap>();
-                            dm.identified_items.insert(name.name.clone());
-                        }
-                        newrunstate = RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
+                        let command = PlayerCommand::Identify {
+                            item: crate::replay::entity_ref(&state.ecs, result.1.unwrap()),
                         };
+                        newrunstate = execute_command(&mut state.ecs, command);
                     }
                 }
             }
@@ -445,40 +349,31 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        if state
+                        let is_spell = state
                             .ecs
                             .read_storage::<SpellTemplate>()
                             .get(item)
-                            .is_some()
-                        {
-                            let mut intent = state.ecs.write_storage::<WantsToCastSpell>();
-                            intent
-                                .insert(
-                                    *state.ecs.fetch::<Entity>(),
-                                    WantsToCastSpell {
-                                        spell: item,
-                                        target: result.1,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::Ticking,
-                            };
+                            .is_some();
+                        let command = if is_spell {
+                            PlayerCommand::CastSpellAt {
+                                spell: crate::replay::entity_ref(&state.ecs, item),
+                                target: result.1,
+                            }
                         } else {
-                            let mut intent = state.ecs.write_storage::<WantsToUseItem>();
-                            intent
-                                .insert(
-                                    *state.ecs.fetch::<Entity>(),
-                                    WantsToUseItem {
-                                        item,
-                                        target: result.1,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = RunState::MainGame {
-                                runstate: MainGameRunState::Ticking,
-                            };
-                        }
+                            PlayerCommand::UseItem {
+                                item: crate::replay::entity_ref(&state.ecs, item),
+                                target: result.1,
+                            }
+                        };
+                        newrunstate = execute_command(&mut state.ecs, command);
+                    }
+                }
+            }
+            MainGameRunState::ShowLog => {
+                let result = gui::show_log_viewer(state, ctx);
+                if result == gui::LogViewerResult::Close {
+                    newrunstate = RunState::MainGame {
+                        runstate: MainGameRunState::AwaitingInput,
                     }
                 }
             }
@@ -487,12 +382,15 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                 newrunstate =
                     vendor::handle_vendor_result(&mut state.ecs, vendor, newrunstate, result);
             }
-
             MainGameRunState::GameOver => {
                 let result = gui::game_over(ctx);
                 match result {
                     gui::GameOverResult::NoSelection => {}
                     gui::GameOverResult::QuitToMenu => {
+                        replay::save_replay(&state.ecs);
+                        if let Err(err) = saveload_system::delete_autosaves(&state.ecs) {
+                            crate::console_log(format!("Unable to delete the autosaves: {}", err));
+                        }
                         state.main_game_state.game_over_cleanup(&mut state.ecs);
                         newrunstate = RunState::MainGame {
                             runstate: MainGameRunState::MapGeneration,
@@ -503,40 +401,129 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     }
                 }
             }
+            _ => {
+                newrunstate = advance(state, newrunstate);
+            }
+        },
+        _ => {}
+    }
+
+    newrunstate
+}
+
+/// Advances the main game through every state that doesn't need a rendering context.
+#[allow(clippy::cognitive_complexity)]
+pub fn advance(state: &mut State, runstate: RunState) -> RunState {
+    let mut newrunstate = runstate;
+
+    if let RunState::MainGame {
+        runstate: inner_runstate,
+    } = newrunstate
+    {
+        match inner_runstate {
+            MainGameRunState::PreRun => {
+                state.run_systems();
+                state.ecs.maintain();
+                autosave_if_due(state);
+                newrunstate = RunState::MainGame {
+                    runstate: MainGameRunState::AwaitingInput,
+                };
+            }
+            MainGameRunState::Ticking => {
+                let mut should_change_target = false;
+                while newrunstate
+                    == (RunState::MainGame {
+                        runstate: MainGameRunState::Ticking,
+                    })
+                {
+                    state.run_systems();
+                    state.ecs.maintain();
+                    match *state.ecs.fetch::<RunState>() {
+                        RunState::MainGame {
+                            runstate: MainGameRunState::AwaitingInput,
+                        } => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::AwaitingInput,
+                            };
+                            should_change_target = true;
+                        }
+                        RunState::MainGame {
+                            runstate: MainGameRunState::MagicMapReveal { .. },
+                        } => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::MagicMapReveal { row: 0 },
+                            }
+                        }
+                        RunState::MainGame {
+                            runstate: MainGameRunState::TownPortal,
+                        } => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::TownPortal,
+                            }
+                        }
+                        RunState::MainGame {
+                            runstate: MainGameRunState::TeleportingToOtherLevel,
+                        } => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::TeleportingToOtherLevel,
+                            }
+                        }
+                        RunState::MainGame {
+                            runstate: MainGameRunState::ShowRemoveCurse,
+                        } => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::ShowRemoveCurse,
+                            }
+                        }
+                        RunState::MainGame {
+                            runstate: MainGameRunState::ShowIdentify,
+                        } => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::ShowIdentify,
+                            }
+                        }
+                        _ => {
+                            newrunstate = RunState::MainGame {
+                                runstate: MainGameRunState::Ticking,
+                            }
+                        }
+                    }
+                }
+                if should_change_target {
+                    player::end_turn_targeting(&mut state.ecs);
+                    state.main_game_state.autosave.turn_passed();
+                    autosave_if_due(state);
+                }
+            }
             MainGameRunState::SaveGame => {
-                saveload_system::save_game(&mut state.ecs);
+                if let Err(err) = saveload_system::save_game(&mut state.ecs) {
+                    crate::console_log(format!("Unable to save the game: {}", err));
+                }
+                replay::save_replay(&state.ecs);
                 newrunstate = RunState::MainMenu {
                     menu_selection: gui::MainMenuSelection::LoadGame,
                 };
             }
             MainGameRunState::NextLevel => {
-                state.main_game_state.goto_level(&mut state.ecs, 1);
-                state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
-                    runstate: MainGameRunState::PreRun,
-                });
-                newrunstate = RunState::MainGame {
-                    runstate: MainGameRunState::MapGeneration,
+                let destination = {
+                    let map = state.ecs.fetch::<Map>();
+                    let player_pos = state.ecs.fetch::<rltk::Point>();
+                    map::level_down_from(&map, map.xy_idx(player_pos.x, player_pos.y))
                 };
+                newrunstate = change_level(state, destination, "There is no way down from here.");
             }
             MainGameRunState::PreviousLevel => {
-                state.main_game_state.goto_level(&mut state.ecs, -1);
-                state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
-                    runstate: MainGameRunState::PreRun,
-                });
-                newrunstate = RunState::MainGame {
-                    runstate: MainGameRunState::MapGeneration,
-                };
+                let destination = map::level_up_from(&state.ecs.fetch::<Map>());
+                newrunstate = change_level(state, destination, "There is no way up from here.");
             }
             MainGameRunState::TownPortal => {
                 // Spawn the portal
                 spawner::spawn_town_portal(&mut state.ecs);
 
                 // Transition
-                let map_depth = state.ecs.fetch::<Map>().depth;
-                let destination_offset = 0 - (map_depth - 1);
                 state
                     .main_game_state
-                    .goto_level(&mut state.ecs, destination_offset);
+                    .goto_level(&mut state.ecs, &LevelId::main(1));
                 state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
                     runstate: MainGameRunState::PreRun,
                 });
@@ -544,26 +531,33 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     runstate: MainGameRunState::MapGeneration,
                 };
             }
-            MainGameRunState::TeleportingToOtherLevel { x, y, depth } => {
-                state.main_game_state.goto_level(&mut state.ecs, depth - 1);
-                let player_entity = state.ecs.fetch::<Entity>();
-                if let Some(pos) = state
+            MainGameRunState::TeleportingToOtherLevel => {
+                // The movement system left the player's destination on them
+                let player_entity = *state.ecs.fetch::<Entity>();
+                let destination = state
                     .ecs
-                    .write_storage::<Position>()
-                    .get_mut(*player_entity)
-                {
-                    pos.x = x;
-                    pos.y = y;
-                }
-                let mut ppos = state.ecs.fetch_mut::<rltk::Point>();
-                ppos.x = x;
-                ppos.y = y;
-                state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
-                    runstate: MainGameRunState::PreRun,
-                });
+                    .write_storage::<OtherLevelPosition>()
+                    .remove(player_entity);
                 newrunstate = RunState::MainGame {
-                    runstate: MainGameRunState::MapGeneration,
+                    runstate: MainGameRunState::Ticking,
                 };
+                if let Some(OtherLevelPosition { x, y, level }) = destination {
+                    state.main_game_state.goto_level(&mut state.ecs, &level);
+                    if let Some(pos) = state.ecs.write_storage::<Position>().get_mut(player_entity)
+                    {
+                        pos.x = x;
+                        pos.y = y;
+                    }
+                    let mut ppos = state.ecs.fetch_mut::<rltk::Point>();
+                    ppos.x = x;
+                    ppos.y = y;
+                    state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
+                        runstate: MainGameRunState::PreRun,
+                    });
+                    newrunstate = RunState::MainGame {
+                        runstate: MainGameRunState::MapGeneration,
+                    };
+                }
             }
             MainGameRunState::MagicMapReveal { row } => {
                 let mut map = state.ecs.fetch_mut::<Map>();
@@ -581,9 +575,40 @@ pub fn tick(state: &mut State, ctx: &mut Rltk, runstate: &RunState) -> RunState
                     };
                 }
             }
-        },
-        _ => {}
+            _ => {}
+        }
     }
 
     newrunstate
 }
+
+fn autosave_if_due(state: &mut State) {
+    if state.main_game_state.autosave.is_due() {
+        if let Err(err) = saveload_system::autosave(&mut state.ecs) {
+            crate::console_log(format!("Unable to autosave: {}", err));
+        }
+        state.main_game_state.autosave.reset();
+    }
+}
+
+/// Moves the player to `destination` and shows the level being built, or if there's nowhere to
+/// go, says so and carries on.
+fn change_level(state: &mut State, destination: Option<LevelId>, nowhere: &str) -> RunState {
+    match destination {
+        Some(level) => {
+            state.main_game_state.goto_level(&mut state.ecs, &level);
+            state.main_game_state.mapgen_next_state = Some(RunState::MainGame {
+                runstate: MainGameRunState::PreRun,
+            });
+            RunState::MainGame {
+                runstate: MainGameRunState::MapGeneration,
+            }
+        }
+        None => {
+            gamelog::Logger::new().append(nowhere).log();
+            RunState::MainGame {
+                runstate: MainGameRunState::Ticking,
+            }
+        }
+    }
+}
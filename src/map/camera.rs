@@ -1,4 +1,5 @@
 use crate::map::get_tile_renderable;
+use crate::perception::lighting::LightFlicker;
 use crate::{Hidden, Map, Position, Renderable, Target, TileSize};
 use rltk::prelude::*;
 use specs::prelude::*;
@@ -24,6 +25,7 @@ const SHOW_BOUNDARIES: bool = false;
 pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
     let mut draw_batch = DrawBatch::new();
     let map = ecs.fetch::<Map>();
+    let flicker = ecs.fetch::<LightFlicker>();
     let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs, ctx);
 
     // Render the Map
@@ -36,7 +38,12 @@ pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
             if tx > 0 && tx < map_width && ty > 0 && ty < map_height {
                 let idx = map.xy_idx(tx, ty);
                 if map.revealed_tiles[idx] {
-                    let renderable = get_tile_renderable(idx, &*map);
+                    let mut renderable = get_tile_renderable(idx, &*map);
+                    if map.visible_tiles[idx] {
+                        let dimmed = flicker.factor(idx);
+                        renderable.fg = renderable.fg * dimmed;
+                        renderable.bg = renderable.bg * dimmed;
+                    }
                     draw_batch.set(
                         Point::new(x + 1, y + 1),
                         ColorPair::new(renderable.fg, renderable.bg),
@@ -1,6 +1,7 @@
-use super::{Map, TileType};
+use super::{LevelId, Map, TileType};
 use crate::components::{OtherLevelPosition, Position, Viewshed};
 use crate::map_builders::level_builder;
+use crate::spatial::{FrozenSpatialIndexes, SpatialIndex};
 use rltk::Point;
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
@@ -8,7 +9,9 @@ use std::collections::{HashMap, HashSet};
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
 pub struct MasterDungeonMap {
-    maps: HashMap<i32, Map>,
+    #[serde(with = "maps_by_level")]
+    maps: HashMap<LevelId, Map>,
+    #[serde(serialize_with = "super::sorted_set")]
     pub identified_items: HashSet<String>,
     pub scroll_mappings: HashMap<String, String>,
     pub potion_mappings: HashMap<String, String>,
@@ -40,16 +43,39 @@ impl MasterDungeonMap {
     }
 
     pub fn store_map(&mut self, map: &Map) {
-        self.maps.insert(map.depth, map.clone());
+        self.maps.insert(map.level.clone(), map.clone());
     }
 
-    pub fn get_map(&self, depth: i32) -> Option<Map> {
-        if self.maps.contains_key(&depth) {
-            let result = self.maps[&depth].clone();
-            Some(result)
-        } else {
-            None
-        }
+    pub fn get_map(&self, level: &LevelId) -> Option<Map> {
+        self.maps.get(level).cloned()
+    }
+}
+
+/// JSON keys have to be strings, so the maps are saved as a list; each one knows its level.
+mod maps_by_level {
+    use super::{LevelId, Map};
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        maps: &HashMap<LevelId, Map>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut list: Vec<&Map> = maps.values().collect();
+        list.sort_by(|a, b| {
+            (&a.level.branch, a.level.level).cmp(&(&b.level.branch, b.level.level))
+        });
+        serializer.collect_seq(list)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<LevelId, Map>, D::Error> {
+        let list = Vec::<Map>::deserialize(deserializer)?;
+        Ok(list
+            .into_iter()
+            .map(|map| (map.level.clone(), map))
+            .collect())
     }
 }
 
@@ -125,26 +151,52 @@ fn make_potion_name(used_names: &mut HashSet<String>) -> String {
     }
 }
 
-fn transition_to_new_map(ecs: &mut World, new_depth: i32) -> Vec<Map> {
-    let mut builder = level_builder(new_depth, 80, 50);
+fn transition_to_new_map(ecs: &mut World, level: &LevelId) -> Vec<Map> {
+    let world_seed = *ecs.fetch::<crate::rng::WorldSeed>();
+    crate::rng::reseed_level(&world_seed, level);
+    let above_level = crate::raws::level_above(&crate::raws::RAWS.lock().unwrap(), level);
+    let above = above_level
+        .as_ref()
+        .and_then(|above_level| ecs.read_resource::<MasterDungeonMap>().get_map(above_level));
+    let departure = above_level
+        .as_ref()
+        .and_then(|above_level| departure_point(ecs, above_level));
+    let mut builder = level_builder(level, 80, 50);
+    if let Some(above) = &above {
+        builder.build_data.stairs_above = stairs_to(above, level);
+    }
     builder.build_map();
-    if new_depth > 1 {
+    if above_level.is_some() && builder.build_data.connected_stairs.is_empty() {
         if let Some(pos) = &builder.build_data.starting_position {
             let up_idx = builder.build_data.map.xy_idx(pos.x, pos.y);
-            builder.build_data.map.tiles[up_idx] = TileType::UpStairs;
+            builder.build_data.map.tiles[up_idx] = TileType::UP_STAIRS;
         }
     }
+
+    // Link the stairs above to the ones they come out at, and arrive by the pair the player took
+    let mut arrival = None;
+    if let Some(mut above) = above {
+        let width = builder.build_data.map.width;
+        for (stairs, idx) in builder.build_data.connected_stairs.iter() {
+            let below = Point::new(*idx as i32 % width, *idx as i32 / width);
+            above
+                .stair_links
+                .insert(above.xy_idx(stairs.x, stairs.y), below);
+            if departure == Some(*stairs) {
+                arrival = Some(below);
+            }
+        }
+        ecs.write_resource::<MasterDungeonMap>().store_map(&above);
+    }
+
     let mapgen_history = builder.build_data.history.clone();
     let player_start;
     {
         let mut worldmap_resource = ecs.write_resource::<Map>();
         *worldmap_resource = builder.build_data.map.clone();
-        player_start = builder
-            .build_data
-            .starting_position
-            .as_mut()
-            .unwrap()
-            .clone();
+        *ecs.write_resource::<SpatialIndex>() = SpatialIndex::for_map(&worldmap_resource);
+        let start = builder.build_data.starting_position.as_ref().unwrap();
+        player_start = arrival.unwrap_or_else(|| Point::new(start.x, start.y));
     }
 
     // Spawn bad guys
@@ -176,36 +228,59 @@ fn transition_to_new_map(ecs: &mut World, new_depth: i32) -> Vec<Map> {
     mapgen_history
 }
 
-fn transition_to_existing_map(ecs: &mut World, new_depth: i32, offset: i32) {
+fn transition_to_existing_map(ecs: &mut World, level: &LevelId) {
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
-    let map = dungeon_master.get_map(new_depth).unwrap();
-    let mut worldmap_resource = ecs.write_resource::<Map>();
+    let map = dungeon_master.get_map(level).unwrap();
     let player_entity = ecs.fetch::<Entity>();
+    let (leaving_level, leaving_depth) = {
+        let leaving = ecs.fetch::<Map>();
+        (leaving.level.clone(), leaving.depth)
+    };
 
-    // Find the down stairs and place the player
-    let w = map.width;
-    let stair_type = if offset < 0 {
-        TileType::DownStairs
+    // Come out at the other end of the stairs the player took, or at the first stairs going back
+    // the way they came if those aren't linked
+    let linked = dungeon_master
+        .get_map(&leaving_level)
+        .zip(departure_point(ecs, &leaving_level))
+        .and_then(|(leaving, departure)| {
+            let idx = leaving.xy_idx(departure.x, departure.y);
+            let leads_here = match leaving.tiles[idx] {
+                TileType::DOWN_STAIRS => level_down_from(&leaving, idx).as_ref() == Some(level),
+                TileType::UP_STAIRS => level_up_from(&leaving).as_ref() == Some(level),
+                _ => false,
+            };
+            if leads_here {
+                leaving.stair_links.get(&idx).cloned()
+            } else {
+                None
+            }
+        });
+    let way_back = if map.depth < leaving_depth {
+        let stairs = stairs_to(&map, &leaving_level);
+        if stairs.is_empty() {
+            stairs_of_type(&map, TileType::DOWN_STAIRS)
+        } else {
+            stairs
+        }
     } else {
-        TileType::UpStairs
+        stairs_of_type(&map, TileType::UP_STAIRS)
     };
-    for (idx, tt) in map.tiles.iter().enumerate() {
-        if *tt == stair_type {
-            let mut player_position = ecs.write_resource::<Point>();
-            *player_position = Point::new(idx as i32 % w, idx as i32 / w);
-            let mut position_components = ecs.write_storage::<Position>();
-            let player_pos_comp = position_components.get_mut(*player_entity);
-            if let Some(player_pos_comp) = player_pos_comp {
-                player_pos_comp.x = idx as i32 % w;
-                player_pos_comp.y = idx as i32 / w;
-                if new_depth == 1 {
-                    player_pos_comp.x -= 1;
-                }
+    let destination = linked.or_else(|| way_back.last().cloned());
+    if let Some(destination) = destination {
+        let mut player_position = ecs.write_resource::<Point>();
+        *player_position = destination;
+        let mut position_components = ecs.write_storage::<Position>();
+        let player_pos_comp = position_components.get_mut(*player_entity);
+        if let Some(player_pos_comp) = player_pos_comp {
+            player_pos_comp.x = destination.x;
+            player_pos_comp.y = destination.y;
+            if *level == LevelId::main(1) {
+                player_pos_comp.x -= 1;
             }
         }
     }
 
-    *worldmap_resource = map;
+    *ecs.write_resource::<Map>() = map;
 
     // Mark the player's visibility as dirty
     let mut viewshed_components = ecs.write_storage::<Viewshed>();
@@ -215,13 +290,66 @@ fn transition_to_existing_map(ecs: &mut World, new_depth: i32, offset: i32) {
     }
 }
 
+/// Where the player is leaving from, if they're on `level`.
+fn departure_point(ecs: &World, level: &LevelId) -> Option<Point> {
+    if ecs.fetch::<Map>().level == *level {
+        Some(*ecs.fetch::<Point>())
+    } else {
+        None
+    }
+}
+
+fn stairs_of_type(map: &Map, stair_type: TileType) -> Vec<Point> {
+    map.tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == stair_type)
+        .map(|(idx, _)| Point::new(idx as i32 % map.width, idx as i32 / map.width))
+        .collect()
+}
+
+/// The down stairs on `map` that lead to `level`.
+fn stairs_to(map: &Map, level: &LevelId) -> Vec<Point> {
+    let below = crate::raws::level_below(&crate::raws::RAWS.lock().unwrap(), &map.level);
+    map.tiles
+        .iter()
+        .enumerate()
+        .filter(|(idx, tile)| {
+            **tile == TileType::DOWN_STAIRS
+                && match map.branch_stairs.get(idx) {
+                    Some(branch) => branch == level,
+                    None => below.as_ref() == Some(level),
+                }
+        })
+        .map(|(idx, _)| Point::new(idx as i32 % map.width, idx as i32 / map.width))
+        .collect()
+}
+
+/// The level going down from tile `idx` of `map` leads to: the branch its stairs go into, if they
+/// do, or otherwise the level below.
+pub fn level_down_from(map: &Map, idx: usize) -> Option<LevelId> {
+    match map.branch_stairs.get(&idx) {
+        Some(branch) => Some(branch.clone()),
+        None => crate::raws::level_below(&crate::raws::RAWS.lock().unwrap(), &map.level),
+    }
+}
+
+/// The level going up from `map` leads to.
+pub fn level_up_from(map: &Map) -> Option<LevelId> {
+    crate::raws::level_above(&crate::raws::RAWS.lock().unwrap(), &map.level)
+}
+
 pub fn freeze_level_entities(ecs: &mut World) {
+    // Keep what happened to the level, such as what's been seen and dug, for when the player is back
+    ecs.write_resource::<MasterDungeonMap>()
+        .store_map(&ecs.fetch::<Map>());
+
     // Obtain ECS access
     let entities = ecs.entities();
     let mut positions = ecs.write_storage::<Position>();
     let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
     let player_entity = ecs.fetch::<Entity>();
-    let map_depth = ecs.fetch::<Map>().depth;
+    let map_level = ecs.fetch::<Map>().level.clone();
 
     // Find positions and make OtherLevelPosition
     let mut pos_to_delete: Vec<Entity> = Vec::new();
@@ -233,7 +361,7 @@ pub fn freeze_level_entities(ecs: &mut World) {
                     OtherLevelPosition {
                         x: pos.x,
                         y: pos.y,
-                        depth: map_depth,
+                        level: map_level.clone(),
                     },
                 )
                 .expect("Insert fail");
@@ -245,6 +373,17 @@ pub fn freeze_level_entities(ecs: &mut World) {
     for p in pos_to_delete.iter() {
         positions.remove(*p);
     }
+
+    // Keep the level's index, minus the player who is leaving it
+    let mut index = (*ecs.fetch::<SpatialIndex>()).clone();
+    if let Some(pos) = positions.get(*player_entity) {
+        let idx = ecs.fetch::<Map>().xy_idx(pos.x, pos.y);
+        if idx < index.size() {
+            index.remove_entity(*player_entity, idx);
+        }
+    }
+    ecs.write_resource::<FrozenSpatialIndexes>()
+        .freeze(map_level, index);
 }
 
 pub fn thaw_level_entities(ecs: &mut World) {
@@ -253,12 +392,12 @@ pub fn thaw_level_entities(ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
     let player_entity = ecs.fetch::<Entity>();
-    let map_depth = ecs.fetch::<Map>().depth;
+    let map_level = ecs.fetch::<Map>().level.clone();
 
     // Find OtherLevelPosition
     let mut pos_to_delete: Vec<Entity> = Vec::new();
     for (entity, pos) in (&entities, &other_level_positions).join() {
-        if entity != *player_entity && pos.depth == map_depth {
+        if entity != *player_entity && pos.level == map_level {
             positions
                 .insert(entity, Position { x: pos.x, y: pos.y })
                 .expect("Insert fail");
@@ -270,19 +409,117 @@ pub fn thaw_level_entities(ecs: &mut World) {
     for p in pos_to_delete.iter() {
         other_level_positions.remove(*p);
     }
+
+    // Pick the level's index back up, if it was kept
+    let index = ecs
+        .write_resource::<FrozenSpatialIndexes>()
+        .thaw(&map_level)
+        .unwrap_or_else(|| SpatialIndex::for_map(&ecs.fetch::<Map>()));
+    *ecs.write_resource::<SpatialIndex>() = index;
 }
 
-pub fn level_transition(ecs: &mut World, new_depth: i32, offset: i32) -> Option<Vec<Map>> {
+pub fn level_transition(ecs: &mut World, level: &LevelId) -> Option<Vec<Map>> {
     // Obtain the master dungeon map
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
 
     // Do we already have a map?
-    if dungeon_master.get_map(new_depth).is_some() {
+    if dungeon_master.get_map(level).is_some() {
         std::mem::drop(dungeon_master);
-        transition_to_existing_map(ecs, new_depth, offset);
+        transition_to_existing_map(ecs, level);
         None
     } else {
         std::mem::drop(dungeon_master);
-        Some(transition_to_new_map(ecs, new_depth))
+        Some(transition_to_new_map(ecs, level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::Simulation;
+    use crate::spatial::FrozenSpatialIndexes;
+    use crate::{Cheat, PlayerCommand};
+
+    fn teleport_to_exit(simulation: &mut Simulation) {
+        simulation.step(PlayerCommand::Cheat {
+            cheat: Cheat::TeleportToExit,
+        });
+    }
+
+    #[test]
+    fn a_level_left_behind_keeps_its_index_until_the_player_is_back() {
+        let mut simulation = Simulation::with_seed(42);
+        simulation.step(PlayerCommand::Cheat {
+            cheat: Cheat::GodMode,
+        });
+        teleport_to_exit(&mut simulation);
+        {
+            let ecs = &simulation.state.ecs;
+            assert_eq!(ecs.fetch::<Map>().level, LevelId::main(2));
+            let frozen = ecs.fetch::<FrozenSpatialIndexes>();
+            let index = frozen.get(&LevelId::main(1)).unwrap();
+            let player = *ecs.fetch::<Entity>();
+            let other_level_positions = ecs.read_storage::<OtherLevelPosition>();
+            let width = ecs
+                .fetch::<MasterDungeonMap>()
+                .get_map(&LevelId::main(1))
+                .unwrap()
+                .width;
+            let mut indexed = 0;
+            for idx in 0..index.size() {
+                for entity in index.get_tile_content_clone(idx) {
+                    assert_ne!(entity, player);
+                    let pos = other_level_positions.get(entity).unwrap();
+                    assert_eq!(pos.level, LevelId::main(1));
+                    assert_eq!((pos.y * width + pos.x) as usize, idx);
+                    indexed += 1;
+                }
+            }
+            assert!(indexed > 0);
+        }
+
+        simulation.step(PlayerCommand::PreviousLevel);
+        let ecs = &simulation.state.ecs;
+        assert_eq!(ecs.fetch::<Map>().level, LevelId::main(1));
+        let frozen = ecs.fetch::<FrozenSpatialIndexes>();
+        assert!(frozen.get(&LevelId::main(1)).is_none());
+        assert!(frozen.get(&LevelId::main(2)).is_some());
+    }
+
+    #[test]
+    fn a_level_is_kept_as_it_was_left() {
+        let mut simulation = Simulation::with_seed(42);
+        simulation.step(PlayerCommand::Cheat {
+            cheat: Cheat::GodMode,
+        });
+        let gravel = TileType::named("Gravel");
+        let (dug, digging) = {
+            let mut map = simulation.state.ecs.fetch_mut::<Map>();
+            let (dug, digging) = (map.xy_idx(1, 1), map.xy_idx(2, 1));
+            map.tiles[dug] = gravel;
+            map.revealed_tiles[dug] = true;
+            map.dig_progress.insert(digging, 2);
+            (dug, digging)
+        };
+
+        teleport_to_exit(&mut simulation);
+        {
+            let ecs = &simulation.state.ecs;
+            assert_eq!(ecs.fetch::<Map>().level, LevelId::main(2));
+            let left = ecs
+                .fetch::<MasterDungeonMap>()
+                .get_map(&LevelId::main(1))
+                .unwrap();
+            assert_eq!(left.tiles[dug], gravel);
+            assert!(left.revealed_tiles[dug]);
+            assert_eq!(left.dig_progress.get(&digging), Some(&2));
+        }
+
+        simulation.step(PlayerCommand::PreviousLevel);
+        let map = simulation.state.ecs.fetch::<Map>();
+        assert_eq!(map.level, LevelId::main(1));
+        assert_eq!(map.tiles[dug], gravel);
+        assert!(map.revealed_tiles[dug]);
+        assert_eq!(map.dig_progress.get(&digging), Some(&2));
     }
 }
@@ -0,0 +1,94 @@
+use rltk::RGB;
+use serde::{Deserialize, Serialize};
+
+/// Something lingering on a tile: fire, a cloud of gas, or a spilled liquid.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HazardKind {
+    Fire,
+    Smoke,
+    PoisonGas,
+    Acid,
+}
+
+impl HazardKind {
+    /// The kind named in the raws: `fire`, `smoke`, `poison_gas` or `acid`.
+    pub fn from_name(name: &str) -> Option<HazardKind> {
+        match name {
+            "fire" => Some(HazardKind::Fire),
+            "smoke" => Some(HazardKind::Smoke),
+            "poison_gas" => Some(HazardKind::PoisonGas),
+            "acid" => Some(HazardKind::Acid),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HazardKind::Fire => "Fire",
+            HazardKind::Smoke => "Smoke",
+            HazardKind::PoisonGas => "Poison Gas",
+            HazardKind::Acid => "Acid",
+        }
+    }
+
+    /// How strong the hazard is when something leaves it behind. Fire burns for about that many
+    /// turns; gas and liquid spread it out over more tiles before fading.
+    pub fn strength(self) -> i32 {
+        match self {
+            HazardKind::Fire => 3,
+            HazardKind::Smoke => 6,
+            HazardKind::PoisonGas => 16,
+            HazardKind::Acid => 8,
+        }
+    }
+
+    /// Gases drift about and thin out, and can't be seen through.
+    pub fn is_gas(self) -> bool {
+        matches!(self, HazardKind::Smoke | HazardKind::PoisonGas)
+    }
+
+    pub fn blocks_vision(self) -> bool {
+        self.is_gas()
+    }
+
+    /// Poison gas goes up in flames when fire reaches it.
+    pub fn is_flammable(self) -> bool {
+        self == HazardKind::PoisonGas
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub strength: i32,
+}
+
+impl Hazard {
+    pub fn new(kind: HazardKind) -> Hazard {
+        Hazard {
+            kind,
+            strength: kind.strength(),
+        }
+    }
+
+    /// Draws the hazard over a visible tile. Fire replaces the tile, gas and liquid tint it.
+    pub fn paint(&self, glyph: &mut rltk::FontCharType, fg: &mut RGB, bg: &mut RGB) {
+        match self.kind {
+            HazardKind::Fire => {
+                *glyph = rltk::to_cp437('^');
+                *fg = if self.strength > 2 {
+                    RGB::named(rltk::YELLOW)
+                } else {
+                    RGB::named(rltk::ORANGE)
+                };
+                *bg = RGB::named(rltk::DARK_RED);
+            }
+            HazardKind::Smoke => *bg = RGB::from_f32(0.35, 0.35, 0.35),
+            HazardKind::PoisonGas => *bg = RGB::from_f32(0.1, 0.4, 0.1),
+            HazardKind::Acid => {
+                *glyph = rltk::to_cp437('~');
+                *fg = RGB::named(rltk::LIME_GREEN);
+            }
+        }
+    }
+}
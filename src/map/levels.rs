@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// The branch the game starts in, running down from the town. Its levels are numbered by depth.
+pub const MAIN_BRANCH: &str = "Dungeon";
+
+/// Which level a map is: its branch of the dungeon, and how far down that branch it is, from 1.
+#[derive(PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LevelId {
+    pub branch: String,
+    pub level: i32,
+}
+
+impl LevelId {
+    pub fn new<S: ToString>(branch: S, level: i32) -> LevelId {
+        LevelId {
+            branch: branch.to_string(),
+            level,
+        }
+    }
+
+    /// The level at `depth` on the main line.
+    pub fn main(depth: i32) -> LevelId {
+        LevelId::new(MAIN_BRANCH, depth)
+    }
+
+    pub fn is_main(&self) -> bool {
+        self.branch == MAIN_BRANCH
+    }
+}
+
+impl std::fmt::Display for LevelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.branch, self.level)
+    }
+}
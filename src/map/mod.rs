@@ -1,12 +1,22 @@
 use rltk::{Algorithm2D, BaseMap, Point};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use std::collections::{HashMap, HashSet};
 mod tiletype;
-pub use tiletype::TileType;
+pub use tiletype::{
+    register_tile_kinds, theme_names, Diggable, TileAppearance, TileKind, TileType, BUILDER_TILES,
+    CORE_TILES,
+};
 mod themes;
 pub use themes::*;
+mod hazards;
+pub use hazards::{Hazard, HazardKind};
 mod dungeon;
-pub use dungeon::{freeze_level_entities, level_transition, thaw_level_entities, MasterDungeonMap};
+pub use dungeon::{
+    freeze_level_entities, level_down_from, level_transition, level_up_from, thaw_level_entities,
+    MasterDungeonMap,
+};
+mod levels;
+pub use levels::{LevelId, MAIN_BRANCH};
 pub mod camera;
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
@@ -17,11 +27,29 @@ pub struct Map {
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
     pub depth: i32,
+    /// Which level of the dungeon this is; `depth` is how deep, and so how dangerous, it is.
+    pub level: LevelId,
     pub bloodstains: HashMap<usize, rltk::RGB>,
+    #[serde(serialize_with = "sorted_set")]
     pub view_blocked: HashSet<usize>,
     pub name: String,
     pub outdoors: bool,
+    /// How much light each tile gets, from the ambient light and every light source.
     pub light: Vec<rltk::RGB>,
+    /// The light everywhere on the level before any lamps are lit, from its biome.
+    pub ambient_light: Option<rltk::RGB>,
+    /// Where each staircase comes out: on the level below for down stairs, above for up stairs.
+    pub stair_links: HashMap<usize, Point>,
+    /// The down stairs that lead into another branch, and the level they lead to.
+    pub branch_stairs: HashMap<usize, LevelId>,
+    /// The theme from `raws/tiles.json` the level is drawn with, or none for the plain look.
+    pub theme: Option<String>,
+    /// Parts of the level drawn with another theme, later ones on top of earlier ones.
+    pub theme_regions: Vec<ThemeRegion>,
+    /// How much digging each partly dug tile has had so far.
+    pub dig_progress: HashMap<usize, i32>,
+    /// Fire, gas and spilled liquid, which the hazard system spreads and burns out each turn.
+    pub hazards: HashMap<usize, Hazard>,
 }
 
 impl Map {
@@ -29,115 +57,79 @@ impl Map {
         (y as usize * self.width as usize) + x as usize
     }
 
-    fn is_exit_valid(&self, x: i32, y: i32) -> bool {
-        if x < 1 || x > self.width - 1 || y < 1 || y > self.height - 1 {
-            return false;
+    /// How much digging it takes to break through tile `idx`, if it can be dug at all. The walls
+    /// around the edge of the map never can.
+    pub fn dig_hardness(&self, idx: usize) -> Option<i32> {
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        if x < 1 || x > self.width - 2 || y < 1 || y > self.height - 2 {
+            return None;
         }
-        let idx = self.xy_idx(x, y);
-        !crate::spatial::is_blocked(idx)
-    }
-
-    pub fn populate_blocked(&mut self) {
-        crate::spatial::populate_blocked_from_map(self);
+        self.tiles[idx].hardness()
     }
 
-    pub fn populate_blocked_multi(&mut self, width: i32, height: i32) {
-        self.populate_blocked();
-        for y in 1..self.height - 1 {
-            for x in 1..self.width - 1 {
-                let idx = self.xy_idx(x, y);
-                if !crate::spatial::is_blocked(idx) {
-                    for cy in 0..height {
-                        for cx in 0..width {
-                            let tx = x + cx;
-                            let ty = y + cy;
-                            if tx < self.width - 1 && ty < self.height - 1 {
-                                let tidx = self.xy_idx(tx, ty);
-                                if crate::spatial::is_blocked(tidx) {
-                                    crate::spatial::set_blocked(idx, true);
-                                }
-                            } else {
-                                crate::spatial::set_blocked(idx, true);
-                            }
-                        }
-                    }
-                }
-            }
+    /// The light everywhere on the level before any lamps are lit. Levels whose biome doesn't say
+    /// are in daylight outdoors and dark underground.
+    pub fn ambient(&self) -> rltk::RGB {
+        match self.ambient_light {
+            Some(light) => light,
+            None if self.outdoors => rltk::RGB::from_f32(1.0, 1.0, 1.0),
+            None => rltk::RGB::from_f32(0.0, 0.0, 0.0),
         }
     }
 
-    pub fn clear_content_index(&mut self) {
-        crate::spatial::clear();
-    }
-
     /// Generates an empty map, consisting entirely of solid walls
     pub fn new<S: ToString>(new_depth: i32, width: i32, height: i32, name: S) -> Map {
         let map_tile_count = (width * height) as usize;
-        crate::spatial::set_size(map_tile_count);
         Map {
-            tiles: vec![TileType::Wall; map_tile_count],
+            tiles: vec![TileType::WALL; map_tile_count],
             width,
             height,
             revealed_tiles: vec![false; map_tile_count],
             visible_tiles: vec![false; map_tile_count],
             depth: new_depth,
+            level: LevelId::main(new_depth),
             bloodstains: HashMap::new(),
             view_blocked: HashSet::new(),
             name: name.to_string(),
             outdoors: true,
-            light: vec![rltk::RGB::from_f32(0.0, 0.0, 0.0); map_tile_count],
+            light: vec![rltk::RGB::from_f32(1.0, 1.0, 1.0); map_tile_count],
+            ambient_light: None,
+            stair_links: HashMap::new(),
+            branch_stairs: HashMap::new(),
+            theme: None,
+            theme_regions: Vec::new(),
+            dig_progress: HashMap::new(),
+            hazards: HashMap::new(),
         }
     }
 }
 
+/// Saves a set in order, so the same world always saves, and hashes, the same.
+pub(crate) fn sorted_set<S: Serializer, T: Ord + Serialize>(
+    set: &HashSet<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut items: Vec<&T> = set.iter().collect();
+    items.sort();
+    serializer.collect_seq(items)
+}
+
+// Pathfinding goes through `SpatialIndex::pathing`, which knows which tiles are blocked.
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
         if idx > 0 && idx < self.tiles.len() {
-            self.tiles[idx].is_opaque() || self.view_blocked.contains(&idx)
+            self.tiles[idx].is_opaque()
+                || self.view_blocked.contains(&idx)
+                || self
+                    .hazards
+                    .get(&idx)
+                    .is_some_and(|h| h.kind.blocks_vision())
         } else {
             true
         }
     }
 
-    fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
-        const DIAGONAL_COST: f32 = 1.5;
-        let mut exits = rltk::SmallVec::new();
-        let x = idx as i32 % self.width;
-        let y = idx as i32 / self.width;
-        let tt = self.tiles[idx as usize];
-        let w = self.width as usize;
-
-        // Cardinal directions
-        if self.is_exit_valid(x - 1, y) {
-            exits.push((idx - 1, tt.get_movement_cost()))
-        };
-        if self.is_exit_valid(x + 1, y) {
-            exits.push((idx + 1, tt.get_movement_cost()))
-        };
-        if self.is_exit_valid(x, y - 1) {
-            exits.push((idx - w, tt.get_movement_cost()))
-        };
-        if self.is_exit_valid(x, y + 1) {
-            exits.push((idx + w, tt.get_movement_cost()))
-        };
-
-        // Diagonals
-        if self.is_exit_valid(x - 1, y - 1) {
-            exits.push(((idx - w) - 1, tt.get_movement_cost() * DIAGONAL_COST));
-        }
-        if self.is_exit_valid(x + 1, y - 1) {
-            exits.push(((idx - w) + 1, tt.get_movement_cost() * DIAGONAL_COST));
-        }
-        if self.is_exit_valid(x - 1, y + 1) {
-            exits.push(((idx + w) - 1, tt.get_movement_cost() * DIAGONAL_COST));
-        }
-        if self.is_exit_valid(x + 1, y + 1) {
-            exits.push(((idx + w) + 1, tt.get_movement_cost() * DIAGONAL_COST));
-        }
-
-        exits
-    }
-
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
         let w = self.width as usize;
         let p1 = Point::new(idx1 % w, idx1 / w);
@@ -151,3 +143,23 @@ impl Algorithm2D for Map {
         Point::new(self.width, self.height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_walls_around_the_edge_can_never_be_dug() {
+        crate::raws::load_raws();
+        let map = Map::new(1, 10, 8, "Solid Rock");
+        let hardness = TileType::WALL.hardness();
+        assert!(hardness.is_some());
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let edge = x == 0 || x == map.width - 1 || y == 0 || y == map.height - 1;
+                let expected = if edge { None } else { hardness };
+                assert_eq!(map.dig_hardness(map.xy_idx(x, y)), expected);
+            }
+        }
+    }
+}
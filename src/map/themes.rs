@@ -1,33 +1,21 @@
 use rltk::RGB;
+use serde::{Deserialize, Serialize};
 
-use super::{Map, TileType};
+use super::Map;
 use crate::components::Renderable;
+use crate::Rect;
+
+/// Part of a map drawn with a different theme to the rest of it, like the fort at the edge of
+/// the mushroom grove.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThemeRegion {
+    pub area: Rect,
+    /// The theme from `raws/tiles.json`, or none for the plain look.
+    pub theme: Option<String>,
+}
 
 pub fn get_tile_renderable(idx: usize, map: &Map) -> Renderable {
-    let mut result = match map.depth {
-        9 => get_mushroom_tile_renderable(idx, map),
-        8 => get_mushroom_tile_renderable(idx, map),
-        7 => {
-            let x = idx as i32 % map.width;
-            if x > map.width - 16 {
-                get_default_tile_renderable(idx, map)
-            } else {
-                get_mushroom_tile_renderable(idx, map)
-            }
-        }
-        5 => {
-            let x = idx as i32 % map.width;
-            if x < map.width / 2 {
-                get_limestone_cavern_tile_renderable(idx, map)
-            } else {
-                get_default_tile_renderable(idx, map)
-            }
-        }
-        4 => get_limestone_cavern_tile_renderable(idx, map),
-        3 => get_limestone_cavern_tile_renderable(idx, map),
-        2 => get_forest_tile_renderable(idx, map),
-        _ => get_default_tile_renderable(idx, map),
-    };
+    let mut result = get_themed_tile_renderable(idx, map);
 
     if map.bloodstains.contains_key(&idx) {
         result.bg = map.bloodstains[&idx];
@@ -35,276 +23,74 @@ pub fn get_tile_renderable(idx: usize, map: &Map) -> Renderable {
     if !map.visible_tiles[idx] {
         result.fg = result.fg.to_greyscale();
         result.bg = RGB::from_f32(0., 0., 0.); // Don't show stains out of visual range
-    } else if !map.outdoors {
+    } else {
         result.fg = result.fg * map.light[idx];
         result.bg = result.bg * map.light[idx];
     }
-
-    result
-}
-
-fn get_forest_tile_renderable(idx: usize, map: &Map) -> Renderable {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => {
-            glyph = rltk::to_cp437('♣');
-            fg = RGB::from_f32(0.0, 0.6, 0.0);
-        }
-        TileType::Bridge => {
-            glyph = rltk::to_cp437('.');
-            fg = RGB::named(rltk::CHOCOLATE);
-        }
-        TileType::Road => {
-            glyph = rltk::to_cp437('≡');
-            fg = RGB::named(rltk::YELLOW);
-        }
-        TileType::Grass => {
-            glyph = rltk::to_cp437('"');
-            fg = RGB::named(rltk::GREEN);
-        }
-        TileType::ShallowWater => {
-            glyph = rltk::to_cp437('~');
-            fg = RGB::named(rltk::CYAN);
-        }
-        TileType::DeepWater => {
-            glyph = rltk::to_cp437('~');
-            fg = RGB::named(rltk::BLUE);
-        }
-        TileType::Gravel => {
-            glyph = rltk::to_cp437(';');
-            fg = RGB::from_f32(0.5, 0.5, 0.5);
-        }
-        TileType::DownStairs => {
-            glyph = rltk::to_cp437('>');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        TileType::UpStairs => {
-            glyph = rltk::to_cp437('<');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        _ => {
-            glyph = rltk::to_cp437('"');
-            fg = RGB::from_f32(0.0, 0.5, 0.0);
-        }
-    }
-
-    Renderable {
-        glyph,
-        fg,
-        bg,
-        render_order: i32::MAX,
-    }
-}
-
-fn get_mushroom_tile_renderable(idx: usize, map: &Map) -> Renderable {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => {
-            glyph = rltk::to_cp437('♠');
-            fg = RGB::from_f32(1.0, 0.0, 1.0);
-        }
-        TileType::Bridge => {
-            glyph = rltk::to_cp437('.');
-            fg = RGB::named(rltk::GREEN);
-        }
-        TileType::Road => {
-            glyph = rltk::to_cp437('≡');
-            fg = RGB::named(rltk::CHOCOLATE);
-        }
-        TileType::Grass => {
-            glyph = rltk::to_cp437('"');
-            fg = RGB::named(rltk::GREEN);
-        }
-        TileType::ShallowWater => {
-            glyph = rltk::to_cp437('~');
-            fg = RGB::named(rltk::CYAN);
-        }
-        TileType::DeepWater => {
-            glyph = rltk::to_cp437('~');
-            fg = RGB::named(rltk::BLUE);
-        }
-        TileType::Gravel => {
-            glyph = rltk::to_cp437(';');
-            fg = RGB::from_f32(0.5, 0.5, 0.5);
-        }
-        TileType::DownStairs => {
-            glyph = rltk::to_cp437('>');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        TileType::UpStairs => {
-            glyph = rltk::to_cp437('<');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        _ => {
-            glyph = rltk::to_cp437('"');
-            fg = RGB::from_f32(0.0, 0.6, 0.0);
+    if map.visible_tiles[idx] {
+        if let Some(hazard) = map.hazards.get(&idx) {
+            hazard.paint(&mut result.glyph, &mut result.fg, &mut result.bg);
         }
     }
 
-    Renderable {
-        glyph,
-        fg,
-        bg,
-        render_order: i32::MAX,
-    }
+    result
 }
 
-fn get_limestone_cavern_tile_renderable(idx: usize, map: &Map) -> Renderable {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => {
-            glyph = rltk::to_cp437('▒');
-            fg = RGB::from_f32(0.7, 0.7, 0.7);
-        }
-        TileType::Bridge => {
-            glyph = rltk::to_cp437('.');
-            fg = RGB::named(rltk::CHOCOLATE);
-        }
-        TileType::Road => {
-            glyph = rltk::to_cp437('≡');
-            fg = RGB::named(rltk::YELLOW);
-        }
-        TileType::Grass => {
-            glyph = rltk::to_cp437('"');
-            fg = RGB::named(rltk::GREEN);
-        }
-        TileType::ShallowWater => {
-            glyph = rltk::to_cp437('░');
-            fg = RGB::named(rltk::CYAN);
-        }
-        TileType::DeepWater => {
-            glyph = rltk::to_cp437('▓');
-            fg = RGB::from_f32(0.2, 0.2, 1.0);
-        }
-        TileType::Gravel => {
-            glyph = rltk::to_cp437(';');
-            fg = RGB::from_f32(0.5, 0.5, 0.5);
-        }
-        TileType::DownStairs => {
-            glyph = rltk::to_cp437('>');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        TileType::UpStairs => {
-            glyph = rltk::to_cp437('<');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        TileType::Stalactite => {
-            glyph = rltk::to_cp437('╨');
-            fg = RGB::from_f32(0.7, 0.7, 0.7);
-        }
-        TileType::Stalagmite => {
-            glyph = rltk::to_cp437('╥');
-            fg = RGB::from_f32(0.7, 0.7, 0.7);
-        }
-        _ => {
-            glyph = rltk::to_cp437('\'');
-            fg = RGB::from_f32(0.4, 0.4, 0.4);
-        }
-    }
+/// The tile as the level's theme draws it, before visibility and lighting are applied.
+pub fn get_themed_tile_renderable(idx: usize, map: &Map) -> Renderable {
+    let theme = theme_at(idx, map);
+    let appearance = map.tiles[idx].appearance(theme);
+    let glyph = if appearance.joins {
+        let x = idx as i32 % map.width;
+        let y = idx as i32 / map.width;
+        wall_glyph(map, x, y, theme, appearance.glyph)
+    } else {
+        appearance.glyph
+    };
 
     Renderable {
         glyph,
-        fg,
-        bg,
+        fg: appearance.fg,
+        bg: appearance.bg,
         render_order: i32::MAX,
     }
 }
 
-pub fn get_default_tile_renderable(idx: usize, map: &Map) -> Renderable {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Floor => {
-            glyph = rltk::to_cp437('.');
-            fg = RGB::from_f32(0.0, 0.5, 0.5);
-        }
-        TileType::WoodFloor => {
-            glyph = rltk::to_cp437('░');
-            fg = RGB::named(rltk::CHOCOLATE);
-        }
-        TileType::Wall => {
-            let x = idx as i32 % map.width;
-            let y = idx as i32 / map.width;
-            glyph = wall_glyph(&*map, x, y);
-            fg = RGB::from_f32(0., 1.0, 0.);
-        }
-        TileType::DownStairs => {
-            glyph = rltk::to_cp437('>');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        TileType::UpStairs => {
-            glyph = rltk::to_cp437('<');
-            fg = RGB::from_f32(0., 1.0, 1.0);
-        }
-        TileType::Bridge => {
-            glyph = rltk::to_cp437('.');
-            fg = RGB::named(rltk::CHOCOLATE);
-        }
-        TileType::Road => {
-            glyph = rltk::to_cp437('≡');
-            fg = RGB::named(rltk::GRAY);
-        }
-        TileType::Grass => {
-            glyph = rltk::to_cp437('"');
-            fg = RGB::named(rltk::GREEN);
-        }
-        TileType::ShallowWater => {
-            glyph = rltk::to_cp437('~');
-            fg = RGB::named(rltk::CYAN);
-        }
-        TileType::DeepWater => {
-            glyph = rltk::to_cp437('~');
-            fg = RGB::named(rltk::BLUE);
-        }
-        TileType::Gravel => {
-            glyph = rltk::to_cp437(';');
-            fg = RGB::from_f32(0.5, 0.5, 0.5);
-        }
-        TileType::Stalactite => {
-            glyph = rltk::to_cp437('╨');
-            fg = RGB::from_f32(0.5, 0.5, 0.5);
-        }
-        TileType::Stalagmite => {
-            glyph = rltk::to_cp437('╥');
-            fg = RGB::from_f32(0.5, 0.5, 0.5);
-        }
-    }
-
-    Renderable {
-        glyph,
-        fg,
-        bg,
-        render_order: i32::MAX,
+/// Which of the themes in `raws/tiles.json` the tile is drawn with, if any.
+fn theme_at(idx: usize, map: &Map) -> Option<&str> {
+    let x = idx as i32 % map.width;
+    let y = idx as i32 / map.width;
+    let region = map.theme_regions.iter().rev().find(|region| {
+        x >= region.area.x1 && x < region.area.x2 && y >= region.area.y1 && y < region.area.y2
+    });
+    match region {
+        Some(region) => region.theme.as_deref(),
+        None => map.theme.as_deref(),
     }
 }
 
-fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
+fn wall_glyph(
+    map: &Map,
+    x: i32,
+    y: i32,
+    theme: Option<&str>,
+    edge_glyph: rltk::FontCharType,
+) -> rltk::FontCharType {
     if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 as i32 {
-        return 35;
+        return edge_glyph;
     }
     let mut mask: u8 = 0;
 
-    if is_revealed_and_wall(map, x, y - 1) {
+    if is_revealed_and_joins(map, x, y - 1, theme) {
         mask += 1;
     }
-    if is_revealed_and_wall(map, x, y + 1) {
+    if is_revealed_and_joins(map, x, y + 1, theme) {
         mask += 2;
     }
-    if is_revealed_and_wall(map, x - 1, y) {
+    if is_revealed_and_joins(map, x - 1, y, theme) {
         mask += 4;
     }
-    if is_revealed_and_wall(map, x + 1, y) {
+    if is_revealed_and_joins(map, x + 1, y, theme) {
         mask += 8;
     }
 
@@ -329,7 +115,81 @@ fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
     }
 }
 
-fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
+fn is_revealed_and_joins(map: &Map, x: i32, y: i32, theme: Option<&str>) -> bool {
     let idx = map.xy_idx(x, y);
-    map.tiles[idx] == TileType::Wall && map.revealed_tiles[idx]
+    map.revealed_tiles[idx] && map.tiles[idx].appearance(theme).joins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileType;
+
+    /// A 12x6 cavern of floor inside a ring of wall, every tile of it seen.
+    fn cavern() -> Map {
+        crate::raws::load_raws();
+        let mut map = Map::new(1, 12, 6, "Theme Test");
+        map.theme = Some("cavern".to_string());
+        for y in 1..5 {
+            for x in 1..11 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::FLOOR;
+            }
+        }
+        for revealed in map.revealed_tiles.iter_mut() {
+            *revealed = true;
+        }
+        map
+    }
+
+    fn glyph_at(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
+        get_themed_tile_renderable(map.xy_idx(x, y), map).glyph
+    }
+
+    #[test]
+    fn tiles_are_drawn_with_the_theme_of_the_region_they_are_in() {
+        let mut map = cavern();
+        map.theme_regions.push(ThemeRegion {
+            area: Rect::new(6, 0, 6, 6),
+            theme: None,
+        });
+        map.theme_regions.push(ThemeRegion {
+            area: Rect::new(9, 0, 3, 6),
+            theme: Some("forest".to_string()),
+        });
+
+        let floor = |theme| TileType::FLOOR.appearance(theme).glyph;
+        assert_eq!(glyph_at(&map, 2, 2), floor(Some("cavern")));
+        assert_eq!(glyph_at(&map, 5, 2), floor(Some("cavern")));
+        assert_eq!(glyph_at(&map, 6, 2), floor(None));
+        // Later regions are drawn over earlier ones
+        assert_eq!(glyph_at(&map, 9, 2), floor(Some("forest")));
+        assert_eq!(
+            get_themed_tile_renderable(map.xy_idx(9, 2), &map).fg,
+            TileType::FLOOR.appearance(Some("forest")).fg
+        );
+    }
+
+    #[test]
+    fn walls_join_up_where_the_theme_draws_them_as_lines() {
+        let mut map = cavern();
+        let pillar = map.xy_idx(4, 3);
+        map.tiles[pillar] = TileType::WALL;
+        // The cavern draws walls as solid rock, which doesn't join
+        assert_eq!(
+            glyph_at(&map, 4, 3),
+            TileType::WALL.appearance(Some("cavern")).glyph
+        );
+
+        map.theme = None;
+        assert_eq!(glyph_at(&map, 4, 3), 9);
+        let below = map.xy_idx(4, 4);
+        map.tiles[below] = TileType::WALL;
+        assert_eq!(glyph_at(&map, 4, 3), 186);
+        // Only walls the player has seen are joined up to
+        map.revealed_tiles[below] = false;
+        assert_eq!(glyph_at(&map, 4, 3), 9);
+        // The edge of the map is left as it is
+        assert_eq!(glyph_at(&map, 4, 0), TileType::WALL.appearance(None).glyph);
+    }
 }
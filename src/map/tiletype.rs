@@ -1,51 +1,373 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
-pub enum TileType {
-    Wall,
-    Stalactite,
-    Stalagmite,
-    Floor,
-    DownStairs,
-    Road,
-    Grass,
-    ShallowWater,
-    DeepWater,
-    WoodFloor,
-    Bridge,
-    Gravel,
-    UpStairs,
+use rltk::{FontCharType, RGB};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::RwLock;
+
+/// A kind of map tile, as defined in `raws/tiles.json`. The kinds the game itself relies on have
+/// constants; builders look the rest up by name. Tiles are saved by name.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct TileType(u16);
+
+/// How a kind of tile is drawn, either normally or under a theme.
+#[derive(Copy, Clone, Debug)]
+pub struct TileAppearance {
+    pub glyph: FontCharType,
+    pub fg: RGB,
+    pub bg: RGB,
+    /// Drawn as a line joining up with the neighbouring tiles that join too, like walls.
+    pub joins: bool,
+}
+
+/// Everything the game knows about a kind of tile.
+#[derive(Clone, Debug)]
+pub struct TileKind {
+    pub name: String,
+    pub walkable: bool,
+    pub opaque: bool,
+    pub movement_cost: f32,
+    pub liquid: bool,
+    pub flammable: bool,
+    /// The name of the tile left once it has burned.
+    pub burns_to: Option<String>,
+    /// Shown in a tooltip, if there is one.
+    pub description: String,
+    pub digging: Option<Diggable>,
+    pub appearance: TileAppearance,
+    /// How the tile looks under a theme, where that differs from `appearance`.
+    pub themes: HashMap<String, TileAppearance>,
+}
+
+/// How a kind of tile is dug through.
+#[derive(Clone, Debug)]
+pub struct Diggable {
+    pub hardness: i32,
+    /// The name of the tile left behind.
+    pub rubble: String,
+}
+
+/// The tile kinds with constants, in the order of those constants.
+pub const CORE_TILES: [&str; 5] = ["Wall", "Floor", "Down Stairs", "Up Stairs", "Secret Door"];
+
+/// The other tile kinds the map builders lay by name. The raws are checked for these, and the
+/// core tiles, when they load.
+pub const BUILDER_TILES: [&str; 9] = [
+    "Bridge",
+    "Deep Water",
+    "Grass",
+    "Gravel",
+    "Road",
+    "Shallow Water",
+    "Stalactite",
+    "Stalagmite",
+    "Wood Floor",
+];
+
+/// How many tile kinds there can be.
+const MAX_TILE_KINDS: usize = 1024;
+
+struct TileKinds {
+    kinds: Vec<TileKind>,
+    by_name: HashMap<String, TileType>,
+}
+
+/// The parts of a tile kind that pathfinding and field of view look up for every tile, copied out
+/// of the registry whenever it changes so that reading them doesn't take its lock.
+struct TileFlags {
+    flags: AtomicU8,
+    movement_cost: AtomicU32,
+}
+
+const WALKABLE: u8 = 1;
+const OPAQUE: u8 = 2;
+const LIQUID: u8 = 4;
+const FLAMMABLE: u8 = 8;
+
+impl TileFlags {
+    const fn new() -> TileFlags {
+        TileFlags {
+            flags: AtomicU8::new(0),
+            movement_cost: AtomicU32::new(0),
+        }
+    }
+
+    fn set(&self, kind: &TileKind) {
+        let flags = [
+            (kind.walkable, WALKABLE),
+            (kind.opaque, OPAQUE),
+            (kind.liquid, LIQUID),
+            (kind.flammable, FLAMMABLE),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+        self.flags.store(flags, Ordering::Relaxed);
+        self.movement_cost
+            .store(kind.movement_cost.to_bits(), Ordering::Relaxed);
+    }
+
+    fn has_flag(&self, flag: u8) -> bool {
+        self.flags.load(Ordering::Relaxed) & flag != 0
+    }
+
+    fn movement_cost(&self) -> f32 {
+        f32::from_bits(self.movement_cost.load(Ordering::Relaxed))
+    }
+}
+
+static TILE_FLAGS: [TileFlags; MAX_TILE_KINDS] = [const { TileFlags::new() }; MAX_TILE_KINDS];
+
+lazy_static! {
+    static ref TILE_KINDS: RwLock<TileKinds> = RwLock::new(TileKinds::new());
+}
+
+impl TileKinds {
+    fn new() -> TileKinds {
+        TileKinds {
+            kinds: Vec::new(),
+            by_name: HashMap::new(),
+        }
+    }
+
+    /// Adds `kinds`, replacing the kinds with the same names, and copies their flags out into
+    /// `flags`.
+    fn register(&mut self, kinds: Vec<TileKind>, flags: &[TileFlags]) {
+        if self.kinds.is_empty() {
+            for name in CORE_TILES.iter() {
+                let kind = kinds
+                    .iter()
+                    .find(|kind| kind.name == *name)
+                    .expect("The raws are missing a core tile")
+                    .clone();
+                let tile = TileType(self.kinds.len() as u16);
+                self.by_name.insert(kind.name.clone(), tile);
+                self.kinds.push(kind);
+            }
+        }
+        for kind in kinds {
+            match self.by_name.get(&kind.name) {
+                Some(tile) => {
+                    let idx = tile.0 as usize;
+                    self.kinds[idx] = kind;
+                }
+                None => {
+                    assert!(
+                        self.kinds.len() < MAX_TILE_KINDS,
+                        "There are more than {} tile kinds",
+                        MAX_TILE_KINDS
+                    );
+                    let tile = TileType(self.kinds.len() as u16);
+                    self.by_name.insert(kind.name.clone(), tile);
+                    self.kinds.push(kind);
+                }
+            }
+        }
+        for (kind, flags) in self.kinds.iter().zip(flags.iter()) {
+            flags.set(kind);
+        }
+    }
+}
+
+/// Makes `kinds` the tile kinds the game uses. A kind keeps its `TileType` when the raws are
+/// reloaded, and kinds that have gone keep their old definition, so maps already built stay as
+/// they were.
+pub fn register_tile_kinds(kinds: Vec<TileKind>) {
+    TILE_KINDS.write().unwrap().register(kinds, &TILE_FLAGS);
+}
+
+/// Every theme some tile kind is drawn differently under, in alphabetical order.
+pub fn theme_names() -> Vec<String> {
+    let registry = TILE_KINDS.read().unwrap();
+    let mut names: Vec<String> = registry
+        .kinds
+        .iter()
+        .flat_map(|kind| kind.themes.keys().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
 }
 
 impl TileType {
-    pub fn is_walkable(self: TileType) -> bool {
-        match self {
-            TileType::Floor
-            | TileType::DownStairs
-            | TileType::Road
-            | TileType::Grass
-            | TileType::ShallowWater
-            | TileType::WoodFloor
-            | TileType::Bridge
-            | TileType::Gravel
-            | TileType::UpStairs => true,
-            _ => false,
+    pub const WALL: TileType = TileType(0);
+    pub const FLOOR: TileType = TileType(1);
+    pub const DOWN_STAIRS: TileType = TileType(2);
+    pub const UP_STAIRS: TileType = TileType(3);
+    /// A door that looks and acts like wall until it's found.
+    pub const SECRET_DOOR: TileType = TileType(4);
+
+    /// The tile kind called `name`, if the raws define one.
+    pub fn find(name: &str) -> Option<TileType> {
+        TILE_KINDS.read().unwrap().by_name.get(name).copied()
+    }
+
+    /// The tile kind called `name`, which builders use for the tiles they lay. Every name they
+    /// use is checked for when the raws load, so there's always one.
+    pub fn named(name: &str) -> TileType {
+        TileType::find(name).unwrap_or_else(|| panic!("There's no tile called [{}]", name))
+    }
+
+    fn has_flag(self, flag: u8) -> bool {
+        TILE_FLAGS[self.0 as usize].has_flag(flag)
+    }
+
+    fn with_kind<T, F: FnOnce(&TileKind) -> T>(self, f: F) -> T {
+        f(&TILE_KINDS.read().unwrap().kinds[self.0 as usize])
+    }
+
+    pub fn name(self) -> String {
+        self.with_kind(|kind| kind.name.clone())
+    }
+
+    pub fn description(self) -> String {
+        self.with_kind(|kind| kind.description.clone())
+    }
+
+    pub fn is_walkable(self) -> bool {
+        self.has_flag(WALKABLE)
+    }
+
+    pub fn is_opaque(self) -> bool {
+        self.has_flag(OPAQUE)
+    }
+
+    pub fn get_movement_cost(self) -> f32 {
+        TILE_FLAGS[self.0 as usize].movement_cost()
+    }
+
+    pub fn is_liquid(self) -> bool {
+        self.has_flag(LIQUID)
+    }
+
+    pub fn is_flammable(self) -> bool {
+        self.has_flag(FLAMMABLE)
+    }
+
+    /// The tile left once a flammable tile has burned.
+    pub fn burnt(self) -> TileType {
+        match self.with_kind(|kind| kind.burns_to.clone()) {
+            Some(name) => TileType::named(&name),
+            None => TileType::FLOOR,
         }
     }
 
-    pub fn is_opaque(self: TileType) -> bool {
-        match self {
-            TileType::Wall | TileType::Stalactite | TileType::Stalagmite => true,
-            _ => false,
+    /// How much digging it takes to break through the tile, if it can be dug at all.
+    pub fn hardness(self) -> Option<i32> {
+        self.with_kind(|kind| kind.digging.as_ref().map(|digging| digging.hardness))
+    }
+
+    /// The tile left behind once this one has been dug through.
+    pub fn rubble(self) -> Option<TileType> {
+        let rubble = self.with_kind(|kind| kind.digging.as_ref().map(|d| d.rubble.clone()));
+        rubble.map(|name| TileType::named(&name))
+    }
+
+    /// How the tile looks under `theme`, or normally without one.
+    pub fn appearance(self, theme: Option<&str>) -> TileAppearance {
+        self.with_kind(|kind| {
+            theme
+                .and_then(|theme| kind.themes.get(theme))
+                .copied()
+                .unwrap_or(kind.appearance)
+        })
+    }
+}
+
+impl Serialize for TileType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for TileType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TileType, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        TileType::find(&name).ok_or_else(|| de::Error::custom(format!("unknown tile {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_core_tiles_have_their_constants() {
+        crate::raws::load_raws();
+        for (i, name) in CORE_TILES.iter().enumerate() {
+            assert_eq!(TileType::named(name), TileType(i as u16));
+            assert_eq!(TileType(i as u16).name(), *name);
+        }
+        for name in BUILDER_TILES.iter() {
+            assert_eq!(TileType::named(name).name(), *name);
         }
     }
 
-    pub fn get_movement_cost(self: TileType) -> f32 {
-        match self {
-            TileType::Road => 0.8,
-            TileType::Grass => 1.1,
-            TileType::ShallowWater => 1.2,
-            _ => 1.0,
+    #[test]
+    fn flags_are_copied_out_of_every_kind() {
+        crate::raws::load_raws();
+        let registry = TILE_KINDS.read().unwrap();
+        for (i, kind) in registry.kinds.iter().enumerate() {
+            let tile = TileType(i as u16);
+            assert_eq!(tile.is_walkable(), kind.walkable, "{}", kind.name);
+            assert_eq!(tile.is_opaque(), kind.opaque, "{}", kind.name);
+            assert_eq!(tile.is_liquid(), kind.liquid, "{}", kind.name);
+            assert_eq!(tile.is_flammable(), kind.flammable, "{}", kind.name);
+            assert_eq!(
+                tile.get_movement_cost(),
+                kind.movement_cost,
+                "{}",
+                kind.name
+            );
         }
     }
+
+    #[test]
+    fn a_kind_keeps_its_tile_type_when_it_changes() {
+        // A registry of its own, so the game's tile kinds are left as the raws made them
+        crate::raws::load_raws();
+        let raws_kinds = TILE_KINDS.read().unwrap().kinds.clone();
+        let flags: Vec<TileFlags> = raws_kinds.iter().map(|_| TileFlags::new()).collect();
+        let mut registry = TileKinds::new();
+        registry.register(raws_kinds.clone(), &flags);
+        assert_eq!(registry.by_name["Floor"], TileType::FLOOR);
+
+        let mut kind = raws_kinds[TileType::FLOOR.0 as usize].clone();
+        kind.name = "Registry Test Floor".to_string();
+        kind.walkable = true;
+        let flags: Vec<TileFlags> = (0..=raws_kinds.len()).map(|_| TileFlags::new()).collect();
+        registry.register(vec![kind.clone()], &flags);
+        let tile = registry.by_name["Registry Test Floor"];
+        assert_eq!(tile.0 as usize, raws_kinds.len());
+        assert!(flags[tile.0 as usize].has_flag(WALKABLE));
+
+        kind.walkable = false;
+        kind.movement_cost = 3.0;
+        registry.register(vec![kind], &flags);
+        assert_eq!(registry.by_name["Registry Test Floor"], tile);
+        assert!(!flags[tile.0 as usize].has_flag(WALKABLE));
+        assert_eq!(flags[tile.0 as usize].movement_cost(), 3.0);
+
+        // Reloading the raws doesn't drop it, so maps that use it stay as they were
+        registry.register(raws_kinds, &flags);
+        assert_eq!(registry.by_name.get("Registry Test Floor"), Some(&tile));
+        assert_eq!(TileType::find("Registry Test Floor"), None);
+    }
+
+    #[test]
+    fn tiles_are_saved_by_name() {
+        crate::raws::load_raws();
+        let tile = TileType::named("Shallow Water");
+        let saved = serde_json::to_value(tile).unwrap();
+        assert_eq!(saved, serde_json::json!("Shallow Water"));
+        assert_eq!(serde_json::from_value::<TileType>(saved).unwrap(), tile);
+        assert!(serde_json::from_value::<TileType>(serde_json::json!("Lava Lake")).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "There's no tile called [Lava Lake]")]
+    fn an_unknown_name_is_a_bug() {
+        crate::raws::load_raws();
+        TileType::named("Lava Lake");
+    }
 }
@@ -68,7 +68,7 @@ impl AreaEndingPosition {
 
         available_floors.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
 
-        build_data.map.tiles[available_floors[0].0] = TileType::DownStairs;
+        build_data.map.tiles[available_floors[0].0] = TileType::DOWN_STAIRS;
         build_data.take_snapshot();
     }
 }
@@ -0,0 +1,99 @@
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use crate::spatial::SpatialIndex;
+
+/// Adds a staircase down into each of `branch_entrances`, as far as it can from the start and the
+/// other stairs, and records where it leads in `map.branch_stairs`. The chain runs it last when
+/// the level has branches hanging off it.
+pub struct BranchStairs {}
+
+impl MetaMapBuilder for BranchStairs {
+    fn build_map(&mut self, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl BranchStairs {
+    pub fn new() -> Box<BranchStairs> {
+        Box::new(BranchStairs {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
+        let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
+        let index = SpatialIndex::for_map(&build_data.map);
+
+        for entrance in build_data.branch_entrances.clone() {
+            // Measure from the start and every staircase, so the new one is out of the way of them
+            let mut sources = vec![start_idx];
+            for (idx, tile) in build_data.map.tiles.iter().enumerate() {
+                if *tile == TileType::DOWN_STAIRS || *tile == TileType::UP_STAIRS {
+                    sources.push(idx);
+                }
+            }
+            let dijkstra_map = rltk::DijkstraMap::new(
+                build_data.map.width as usize,
+                build_data.map.height as usize,
+                &sources,
+                &index.pathing(&build_data.map),
+                3000.0,
+            );
+
+            let farthest = build_data
+                .map
+                .tiles
+                .iter()
+                .enumerate()
+                .filter(|(idx, tile)| {
+                    tile.is_walkable()
+                        && **tile != TileType::DOWN_STAIRS
+                        && **tile != TileType::UP_STAIRS
+                        && dijkstra_map.map[*idx] != f32::MAX
+                })
+                .max_by(|a, b| {
+                    dijkstra_map.map[a.0]
+                        .partial_cmp(&dijkstra_map.map[b.0])
+                        .unwrap()
+                })
+                .map(|(idx, _)| idx);
+
+            if let Some(idx) = farthest {
+                build_data.map.tiles[idx] = TileType::DOWN_STAIRS;
+                build_data.map.branch_stairs.insert(idx, entrance);
+                build_data
+                    .spawn_list
+                    .retain(|(spawn_idx, _)| *spawn_idx != idx);
+                build_data.take_snapshot();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::LevelId;
+
+    #[test]
+    fn a_level_with_a_branch_off_it_gets_stairs_down_into_the_branch() {
+        crate::raws::load_raws();
+        crate::rng::reseed(&crate::rng::WorldSeed::new(6));
+        let mut chain = super::super::level_builder(&LevelId::main(6), 80, 50);
+        chain.build_map();
+        let map = &chain.build_data.map;
+
+        assert_eq!(map.branch_stairs.len(), 1);
+        let (idx, level) = map.branch_stairs.iter().next().unwrap();
+        assert_eq!(*level, LevelId::new("Dragon's Lair", 1));
+        assert_eq!(map.tiles[*idx], super::TileType::DOWN_STAIRS);
+        // The way on down the main line is still there
+        assert!(map
+            .tiles
+            .iter()
+            .enumerate()
+            .any(|(i, tile)| *tile == super::TileType::DOWN_STAIRS && i != *idx));
+        assert!(chain
+            .build_data
+            .spawn_list
+            .iter()
+            .all(|(spawn_idx, _)| spawn_idx != idx));
+    }
+}
@@ -43,7 +43,7 @@ impl BspInteriorBuilder {
                     if idx > 0
                         && idx < ((build_data.map.width * build_data.map.height) - 1) as usize
                     {
-                        build_data.map.tiles[idx] = TileType::Floor;
+                        build_data.map.tiles[idx] = TileType::FLOOR;
                     }
                 }
             }
@@ -54,12 +54,14 @@ impl BspInteriorBuilder {
         for i in 0..rooms.len() - 1 {
             let room = rooms[i];
             let next_room = rooms[i + 1];
-            let start_x = room.x1 + (crate::rng::roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
-            let start_y = room.y1 + (crate::rng::roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
+            let start_x =
+                room.x1 + (crate::rng::mapgen::roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
+            let start_y =
+                room.y1 + (crate::rng::mapgen::roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
             let end_x = next_room.x1
-                + (crate::rng::roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
+                + (crate::rng::mapgen::roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
             let end_y = next_room.y1
-                + (crate::rng::roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
+                + (crate::rng::mapgen::roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
             draw_corridor(&mut build_data.map, start_x, start_y, end_x, end_y);
             build_data.take_snapshot();
         }
@@ -78,7 +80,7 @@ impl BspInteriorBuilder {
         let half_width = width / 2;
         let half_height = height / 2;
 
-        let split = crate::rng::roll_dice(1, 4);
+        let split = crate::rng::mapgen::roll_dice(1, 4);
 
         if split <= 2 {
             // Horizontal split
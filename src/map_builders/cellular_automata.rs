@@ -29,39 +29,39 @@ impl CellularAutomataBuilder {
             for x in 1..build_data.map.width - 1 {
                 let idx = build_data.map.xy_idx(x, y);
                 let mut neighbors = 0;
-                if build_data.map.tiles[idx - 1] == TileType::Wall {
+                if build_data.map.tiles[idx - 1] == TileType::WALL {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx + 1] == TileType::Wall {
+                if build_data.map.tiles[idx + 1] == TileType::WALL {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx - build_data.map.width as usize] == TileType::Wall {
+                if build_data.map.tiles[idx - build_data.map.width as usize] == TileType::WALL {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx + build_data.map.width as usize] == TileType::Wall {
+                if build_data.map.tiles[idx + build_data.map.width as usize] == TileType::WALL {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx - (build_data.map.width as usize - 1)] == TileType::Wall
+                if build_data.map.tiles[idx - (build_data.map.width as usize - 1)] == TileType::WALL
                 {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx - (build_data.map.width as usize + 1)] == TileType::Wall
+                if build_data.map.tiles[idx - (build_data.map.width as usize + 1)] == TileType::WALL
                 {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx + (build_data.map.width as usize - 1)] == TileType::Wall
+                if build_data.map.tiles[idx + (build_data.map.width as usize - 1)] == TileType::WALL
                 {
                     neighbors += 1;
                 }
-                if build_data.map.tiles[idx + (build_data.map.width as usize + 1)] == TileType::Wall
+                if build_data.map.tiles[idx + (build_data.map.width as usize + 1)] == TileType::WALL
                 {
                     neighbors += 1;
                 }
 
                 if neighbors > 4 || neighbors == 0 {
-                    newtiles[idx] = TileType::Wall;
+                    newtiles[idx] = TileType::WALL;
                 } else {
-                    newtiles[idx] = TileType::Floor;
+                    newtiles[idx] = TileType::FLOOR;
                 }
             }
         }
@@ -75,12 +75,12 @@ impl CellularAutomataBuilder {
         // First we completely randomize the map, setting 55% of it to be floor.
         for y in 1..build_data.map.height - 1 {
             for x in 1..build_data.map.width - 1 {
-                let roll = crate::rng::roll_dice(1, 100);
+                let roll = crate::rng::mapgen::roll_dice(1, 100);
                 let idx = build_data.map.xy_idx(x, y);
                 if roll > 55 {
-                    build_data.map.tiles[idx] = TileType::Floor
+                    build_data.map.tiles[idx] = TileType::FLOOR
                 } else {
-                    build_data.map.tiles[idx] = TileType::Wall
+                    build_data.map.tiles[idx] = TileType::WALL
                 }
             }
         }
@@ -16,9 +16,9 @@ pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) -> Vec<u
         let idx = map.xy_idx(x, y);
         if idx > 0
             && idx < map.width as usize * map.height as usize
-            && map.tiles[idx as usize] != TileType::Floor
+            && map.tiles[idx as usize] != TileType::FLOOR
         {
-            map.tiles[idx as usize] = TileType::Floor;
+            map.tiles[idx as usize] = TileType::FLOOR;
             corridor.push(idx as usize);
         }
     }
@@ -31,10 +31,10 @@ pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) -> Vec<usi
         let idx = map.xy_idx(x, y);
         if idx > 0
             && idx < map.width as usize * map.height as usize
-            && map.tiles[idx as usize] != TileType::Floor
+            && map.tiles[idx as usize] != TileType::FLOOR
         {
             corridor.push(idx);
-            map.tiles[idx as usize] = TileType::Floor;
+            map.tiles[idx as usize] = TileType::FLOOR;
         }
     }
     corridor
@@ -57,9 +57,9 @@ pub fn draw_corridor(map: &mut Map, x1: i32, y1: i32, x2: i32, y2: i32) -> Vec<u
         }
 
         let idx = map.xy_idx(x, y);
-        if map.tiles[idx] != TileType::Floor {
+        if map.tiles[idx] != TileType::FLOOR {
             corridor.push(idx);
-            map.tiles[idx] = TileType::Floor;
+            map.tiles[idx] = TileType::FLOOR;
         }
     }
 
@@ -110,7 +110,7 @@ fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
     match brush_size {
         1 => {
             let digger_idx = map.xy_idx(x, y);
-            map.tiles[digger_idx] = TileType::Floor;
+            map.tiles[digger_idx] = TileType::FLOOR;
         }
 
         _ => {
@@ -123,7 +123,7 @@ fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
                         && brush_y < map.height - 1
                     {
                         let idx = map.xy_idx(brush_x, brush_y);
-                        map.tiles[idx] = TileType::Floor;
+                        map.tiles[idx] = TileType::FLOOR;
                     }
                 }
             }
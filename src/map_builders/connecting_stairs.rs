@@ -0,0 +1,99 @@
+use super::{BuilderMap, MetaMapBuilder, Position, TileType};
+use crate::spatial::SpatialIndex;
+use rltk::Point;
+
+/// Puts an up staircase as close as it can to each of the down staircases on the level above, and
+/// links each one back to the stairs above it in `map.stair_links`; every pair it makes goes in
+/// `connected_stairs`. The chain runs it last when it's given `stairs_above`.
+pub struct ConnectingStairs {}
+
+impl MetaMapBuilder for ConnectingStairs {
+    fn build_map(&mut self, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl ConnectingStairs {
+    pub fn new() -> Box<ConnectingStairs> {
+        Box::new(ConnectingStairs {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        // Only tiles the rest of the level can be reached from will do
+        let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
+        let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
+        let index = SpatialIndex::for_map(&build_data.map);
+        let dijkstra_map = rltk::DijkstraMap::new(
+            build_data.map.width as usize,
+            build_data.map.height as usize,
+            &[start_idx],
+            &index.pathing(&build_data.map),
+            3000.0,
+        );
+
+        let mut placed: Vec<(Point, usize)> = Vec::new();
+        for above in build_data.stairs_above.clone().iter() {
+            // A row of stairs above, like the town gate, shares one staircase down here
+            let neighbour = placed
+                .iter()
+                .find(|(other, _)| (other.x - above.x).abs() <= 1 && (other.y - above.y).abs() <= 1)
+                .map(|(_, idx)| *idx);
+            let stairs_idx = match neighbour {
+                Some(idx) => idx,
+                None => match self.nearest_free_tile(build_data, &dijkstra_map, *above) {
+                    Some(idx) => {
+                        build_data.map.tiles[idx] = TileType::UP_STAIRS;
+                        build_data.map.stair_links.insert(idx, *above);
+                        build_data
+                            .spawn_list
+                            .retain(|(spawn_idx, _)| *spawn_idx != idx);
+                        idx
+                    }
+                    None => continue,
+                },
+            };
+            placed.push((*above, stairs_idx));
+        }
+        build_data.connected_stairs = placed.clone();
+
+        // Arrive by the first of them, unless the level transition knows better
+        if let Some((_, idx)) = placed.first() {
+            build_data.starting_position = Some(Position {
+                x: *idx as i32 % build_data.map.width,
+                y: *idx as i32 / build_data.map.width,
+            });
+        }
+        build_data.take_snapshot();
+    }
+
+    fn nearest_free_tile(
+        &self,
+        build_data: &BuilderMap,
+        dijkstra_map: &rltk::DijkstraMap,
+        target: Point,
+    ) -> Option<usize> {
+        build_data
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(idx, tile)| {
+                tile.is_walkable()
+                    && **tile != TileType::DOWN_STAIRS
+                    && **tile != TileType::UP_STAIRS
+                    && dijkstra_map.map[*idx] != f32::MAX
+            })
+            .map(|(idx, _)| {
+                let distance = rltk::DistanceAlg::PythagorasSquared.distance2d(
+                    Point::new(
+                        idx as i32 % build_data.map.width,
+                        idx as i32 / build_data.map.width,
+                    ),
+                    target,
+                );
+                (idx, distance)
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(idx, _)| idx)
+    }
+}
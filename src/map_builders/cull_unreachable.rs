@@ -1,4 +1,5 @@
 use super::{BuilderMap, MetaMapBuilder, TileType};
+use crate::spatial::SpatialIndex;
 
 pub struct CullUnreachable {}
 
@@ -17,21 +18,21 @@ impl CullUnreachable {
     fn build(&mut self, build_data: &mut BuilderMap) {
         let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
         let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
-        build_data.map.populate_blocked();
+        let index = SpatialIndex::for_map(&build_data.map);
         let map_starts: Vec<usize> = vec![start_idx];
         let dijkstra_map = rltk::DijkstraMap::new(
             build_data.map.width as usize,
             build_data.map.height as usize,
             &map_starts,
-            &build_data.map,
+            &index.pathing(&build_data.map),
             1000.0,
         );
         for (i, tile) in build_data.map.tiles.iter_mut().enumerate() {
-            if *tile == TileType::Floor {
+            if *tile == TileType::FLOOR {
                 let distance_to_start = dijkstra_map.map[i];
                 // We can't get to this tile - so we'll make it a wall
                 if distance_to_start == std::f32::MAX {
-                    *tile = TileType::Wall;
+                    *tile = TileType::WALL;
                 }
             }
         }
@@ -1,4 +1,5 @@
 use super::{BuilderMap, MetaMapBuilder, TileType};
+use crate::spatial::SpatialIndex;
 
 pub struct DistantExit {}
 
@@ -17,18 +18,18 @@ impl DistantExit {
     fn build(&mut self, build_data: &mut BuilderMap) {
         let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
         let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
-        build_data.map.populate_blocked();
+        let index = SpatialIndex::for_map(&build_data.map);
         let map_starts: Vec<usize> = vec![start_idx];
         let dijkstra_map = rltk::DijkstraMap::new(
             build_data.map.width as usize,
             build_data.map.height as usize,
             &map_starts,
-            &build_data.map,
+            &index.pathing(&build_data.map),
             3000.0,
         );
         let mut exit_tile = (0, 0.0f32);
         for (i, tile) in build_data.map.tiles.iter_mut().enumerate() {
-            if *tile == TileType::Floor {
+            if *tile == TileType::FLOOR {
                 let distance_to_start = dijkstra_map.map[i];
                 if distance_to_start != std::f32::MAX {
                     // If it is further away than our current exit candidate, move the exit
@@ -42,7 +43,7 @@ impl DistantExit {
 
         // Place a staircase
         let stairs_idx = exit_tile.0;
-        build_data.map.tiles[stairs_idx] = TileType::DownStairs;
+        build_data.map.tiles[stairs_idx] = TileType::DOWN_STAIRS;
         build_data.take_snapshot();
     }
 }
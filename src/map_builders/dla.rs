@@ -101,11 +101,11 @@ impl DLABuilder {
             .map
             .xy_idx(starting_position.x, starting_position.y);
         build_data.take_snapshot();
-        build_data.map.tiles[start_idx] = TileType::Floor;
-        build_data.map.tiles[start_idx - 1] = TileType::Floor;
-        build_data.map.tiles[start_idx + 1] = TileType::Floor;
-        build_data.map.tiles[start_idx - build_data.map.width as usize] = TileType::Floor;
-        build_data.map.tiles[start_idx + build_data.map.width as usize] = TileType::Floor;
+        build_data.map.tiles[start_idx] = TileType::FLOOR;
+        build_data.map.tiles[start_idx - 1] = TileType::FLOOR;
+        build_data.map.tiles[start_idx + 1] = TileType::FLOOR;
+        build_data.map.tiles[start_idx - build_data.map.width as usize] = TileType::FLOOR;
+        build_data.map.tiles[start_idx + build_data.map.width as usize] = TileType::FLOOR;
 
         // Random walker
         let total_tiles = build_data.map.width * build_data.map.height;
@@ -114,20 +114,22 @@ impl DLABuilder {
             .map
             .tiles
             .iter()
-            .filter(|a| **a == TileType::Floor)
+            .filter(|a| **a == TileType::FLOOR)
             .count();
         while floor_tile_count < desired_floor_tiles {
             match self.algorithm {
                 DLAAlgorithm::WalkInwards => {
-                    let mut digger_x = crate::rng::roll_dice(1, build_data.map.width - 3) + 1;
-                    let mut digger_y = crate::rng::roll_dice(1, build_data.map.height - 3) + 1;
+                    let mut digger_x =
+                        crate::rng::mapgen::roll_dice(1, build_data.map.width - 3) + 1;
+                    let mut digger_y =
+                        crate::rng::mapgen::roll_dice(1, build_data.map.height - 3) + 1;
                     let mut prev_x = digger_x;
                     let mut prev_y = digger_y;
                     let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
-                    while build_data.map.tiles[digger_idx] == TileType::Wall {
+                    while build_data.map.tiles[digger_idx] == TileType::WALL {
                         prev_x = digger_x;
                         prev_y = digger_y;
-                        let stagger_direction = crate::rng::roll_dice(1, 4);
+                        let stagger_direction = crate::rng::mapgen::roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if digger_x > 2 {
@@ -165,8 +167,8 @@ impl DLABuilder {
                     let mut digger_x = starting_position.x;
                     let mut digger_y = starting_position.y;
                     let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
-                    while build_data.map.tiles[digger_idx] == TileType::Floor {
-                        let stagger_direction = crate::rng::roll_dice(1, 4);
+                    while build_data.map.tiles[digger_idx] == TileType::FLOOR {
+                        let stagger_direction = crate::rng::mapgen::roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if digger_x > 2 {
@@ -201,8 +203,10 @@ impl DLABuilder {
                 }
 
                 DLAAlgorithm::CentralAttractor => {
-                    let mut digger_x = crate::rng::roll_dice(1, build_data.map.width - 3) + 1;
-                    let mut digger_y = crate::rng::roll_dice(1, build_data.map.height - 3) + 1;
+                    let mut digger_x =
+                        crate::rng::mapgen::roll_dice(1, build_data.map.width - 3) + 1;
+                    let mut digger_y =
+                        crate::rng::mapgen::roll_dice(1, build_data.map.height - 3) + 1;
                     let mut prev_x = digger_x;
                     let mut prev_y = digger_y;
                     let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
@@ -213,7 +217,7 @@ impl DLABuilder {
                         rltk::Point::new(starting_position.x, starting_position.y),
                     );
 
-                    while build_data.map.tiles[digger_idx] == TileType::Wall && !path.is_empty() {
+                    while build_data.map.tiles[digger_idx] == TileType::WALL && !path.is_empty() {
                         prev_x = digger_x;
                         prev_y = digger_y;
                         digger_x = path[0].x;
@@ -237,7 +241,7 @@ impl DLABuilder {
                 .map
                 .tiles
                 .iter()
-                .filter(|a| **a == TileType::Floor)
+                .filter(|a| **a == TileType::FLOOR)
                 .count();
         }
     }
@@ -30,25 +30,25 @@ impl DoorPlacement {
         let y = (idx / build_data.map.width as usize) as i32;
 
         // Check for east-west door possibility
-        if build_data.map.tiles[idx] == TileType::Floor
-            && (x > 1 && build_data.map.tiles[idx - 1] == TileType::Floor)
-            && (x < build_data.map.width - 2 && build_data.map.tiles[idx + 1] == TileType::Floor)
+        if build_data.map.tiles[idx] == TileType::FLOOR
+            && (x > 1 && build_data.map.tiles[idx - 1] == TileType::FLOOR)
+            && (x < build_data.map.width - 2 && build_data.map.tiles[idx + 1] == TileType::FLOOR)
             && (y > 1
-                && build_data.map.tiles[idx - build_data.map.width as usize] == TileType::Wall)
+                && build_data.map.tiles[idx - build_data.map.width as usize] == TileType::WALL)
             && (y < build_data.map.height - 2
-                && build_data.map.tiles[idx + build_data.map.width as usize] == TileType::Wall)
+                && build_data.map.tiles[idx + build_data.map.width as usize] == TileType::WALL)
         {
             return true;
         }
 
         // Check for north-south door possibility
-        if build_data.map.tiles[idx] == TileType::Floor
-            && (x > 1 && build_data.map.tiles[idx - 1] == TileType::Wall)
-            && (x < build_data.map.width - 2 && build_data.map.tiles[idx + 1] == TileType::Wall)
+        if build_data.map.tiles[idx] == TileType::FLOOR
+            && (x > 1 && build_data.map.tiles[idx - 1] == TileType::WALL)
+            && (x < build_data.map.width - 2 && build_data.map.tiles[idx + 1] == TileType::WALL)
             && (y > 1
-                && build_data.map.tiles[idx - build_data.map.width as usize] == TileType::Floor)
+                && build_data.map.tiles[idx - build_data.map.width as usize] == TileType::FLOOR)
             && (y < build_data.map.height - 2
-                && build_data.map.tiles[idx + build_data.map.width as usize] == TileType::Floor)
+                && build_data.map.tiles[idx + build_data.map.width as usize] == TileType::FLOOR)
         {
             return true;
         }
@@ -71,9 +71,9 @@ impl DoorPlacement {
             // There are no corridors - scan for possible places
             let tiles = build_data.map.tiles.clone();
             for (i, tile) in tiles.iter().enumerate() {
-                if *tile == TileType::Floor
+                if *tile == TileType::FLOOR
                     && self.door_possible(build_data, i)
-                    && crate::rng::roll_dice(1, 3) == 1
+                    && crate::rng::mapgen::roll_dice(1, 3) == 1
                 {
                     build_data.spawn_list.push((i, "Door".to_string()));
                 }
@@ -113,7 +113,7 @@ impl DrunkardsWalkBuilder {
         let start_idx = build_data
             .map
             .xy_idx(starting_position.x, starting_position.y);
-        build_data.map.tiles[start_idx] = TileType::Floor;
+        build_data.map.tiles[start_idx] = TileType::FLOOR;
 
         let total_tiles = build_data.map.width * build_data.map.height;
         let desired_floor_tiles = (self.settings.floor_percent * total_tiles as f32) as usize;
@@ -121,7 +121,7 @@ impl DrunkardsWalkBuilder {
             .map
             .tiles
             .iter()
-            .filter(|a| **a == TileType::Floor)
+            .filter(|a| **a == TileType::FLOOR)
             .count();
         let mut digger_count = 0;
         while floor_tile_count < desired_floor_tiles {
@@ -138,8 +138,8 @@ impl DrunkardsWalkBuilder {
                         drunk_x = starting_position.x;
                         drunk_y = starting_position.y;
                     } else {
-                        drunk_x = crate::rng::roll_dice(1, build_data.map.width - 3) + 1;
-                        drunk_y = crate::rng::roll_dice(1, build_data.map.height - 3) + 1;
+                        drunk_x = crate::rng::mapgen::roll_dice(1, build_data.map.width - 3) + 1;
+                        drunk_y = crate::rng::mapgen::roll_dice(1, build_data.map.height - 3) + 1;
                     }
                 }
             }
@@ -147,7 +147,7 @@ impl DrunkardsWalkBuilder {
 
             while drunk_life > 0 {
                 let drunk_idx = build_data.map.xy_idx(drunk_x, drunk_y);
-                if build_data.map.tiles[drunk_idx] == TileType::Wall {
+                if build_data.map.tiles[drunk_idx] == TileType::WALL {
                     did_something = true;
                 }
                 paint(
@@ -157,9 +157,9 @@ impl DrunkardsWalkBuilder {
                     drunk_x,
                     drunk_y,
                 );
-                build_data.map.tiles[drunk_idx] = TileType::DownStairs;
+                build_data.map.tiles[drunk_idx] = TileType::DOWN_STAIRS;
 
-                let stagger_direction = crate::rng::roll_dice(1, 4);
+                let stagger_direction = crate::rng::mapgen::roll_dice(1, 4);
                 match stagger_direction {
                     1 => {
                         if drunk_x > 2 {
@@ -191,15 +191,15 @@ impl DrunkardsWalkBuilder {
 
             digger_count += 1;
             for t in build_data.map.tiles.iter_mut() {
-                if *t == TileType::DownStairs {
-                    *t = TileType::Floor;
+                if *t == TileType::DOWN_STAIRS {
+                    *t = TileType::FLOOR;
                 }
             }
             floor_tile_count = build_data
                 .map
                 .tiles
                 .iter()
-                .filter(|a| **a == TileType::Floor)
+                .filter(|a| **a == TileType::FLOOR)
                 .count();
         }
     }
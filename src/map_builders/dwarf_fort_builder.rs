@@ -15,10 +15,14 @@ impl DragonsLair {
     }
 
     fn build(&mut self, build_data: &mut BuilderMap) {
-        build_data.map.depth = 7;
         build_data.take_snapshot();
 
-        let mut builder = BuilderChain::new(6, build_data.width, build_data.height, "New Map");
+        let mut builder = BuilderChain::new(
+            build_data.map.depth,
+            build_data.width,
+            build_data.height,
+            "New Map",
+        );
         builder.start_with(DLABuilder::insectoid());
         builder.build_map();
 
@@ -30,8 +34,8 @@ impl DragonsLair {
 
         // Merge the maps
         for (idx, tt) in build_data.map.tiles.iter_mut().enumerate() {
-            if *tt == TileType::Wall && builder.build_data.map.tiles[idx] == TileType::Floor {
-                *tt = TileType::Floor;
+            if *tt == TileType::WALL && builder.build_data.map.tiles[idx] == TileType::FLOOR {
+                *tt = TileType::FLOOR;
             }
         }
         build_data.take_snapshot();
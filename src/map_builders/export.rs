@@ -0,0 +1,187 @@
+use super::{BuilderMap, Map, Position, TileType};
+use crate::map::get_themed_tile_renderable;
+use rltk::rex::{XpColor, XpFile};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Writes the finished map as `<name>.txt` and `<name>.xp` in `directory`, and each snapshot in
+/// its history as `<name>-step-NN.txt` and `.xp`. Returns the paths written.
+pub fn export_build(
+    build_data: &BuilderMap,
+    directory: &Path,
+    name: &str,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
+    let mut written = Vec::new();
+    for (step, snapshot) in build_data.history.iter().enumerate() {
+        let stem = format!("{}-step-{:02}", name, step + 1);
+        written.append(&mut export_map(snapshot, None, directory, &stem)?);
+    }
+    written.append(&mut export_map(
+        &build_data.map,
+        build_data.starting_position.as_ref(),
+        directory,
+        name,
+    )?);
+    Ok(written)
+}
+
+fn export_map(
+    map: &Map,
+    start: Option<&Position>,
+    directory: &Path,
+    stem: &str,
+) -> io::Result<Vec<PathBuf>> {
+    let text_path = directory.join(format!("{}.txt", stem));
+    fs::write(&text_path, map_to_text(map, start))?;
+    let xp_path = directory.join(format!("{}.xp", stem));
+    map_to_xp(map, start).write(&mut File::create(&xp_path)?)?;
+    Ok(vec![text_path, xp_path])
+}
+
+/// The map as a grid of the glyphs its theme draws, one line per row, with `@` at the start.
+pub fn map_to_text(map: &Map, start: Option<&Position>) -> String {
+    let map = fully_revealed(map);
+    let mut text = String::with_capacity(map.tiles.len() + map.height as usize);
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if is_start(start, x, y) {
+                text.push('@');
+            } else {
+                let glyph = get_themed_tile_renderable(map.xy_idx(x, y), &map).glyph;
+                text.push(rltk::to_char(glyph as u8));
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// The map as a one-layer REX Paint image, coloured by its theme. Floors, walls, the down stairs
+/// and the start use the characters `PrefabBuilder::rex_level` reads, so a polished export loads
+/// straight back in as a prefab level; other tiles keep their theme glyph.
+pub fn map_to_xp(map: &Map, start: Option<&Position>) -> XpFile {
+    let map = fully_revealed(map);
+    let mut xp = XpFile::new(map.width as usize, map.height as usize);
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let idx = map.xy_idx(x, y);
+            let renderable = get_themed_tile_renderable(idx, &map);
+            let ch = if is_start(start, x, y) {
+                '@' as u32
+            } else {
+                match map.tiles[idx] {
+                    TileType::FLOOR => ' ' as u32,
+                    TileType::WALL | TileType::SECRET_DOOR => '#' as u32,
+                    TileType::DOWN_STAIRS => '>' as u32,
+                    _ => renderable.glyph as u32,
+                }
+            };
+            if let Some(cell) = xp.layers[0].get_mut(x as usize, y as usize) {
+                cell.ch = ch;
+                cell.fg = XpColor::from(renderable.fg);
+                cell.bg = XpColor::from(renderable.bg);
+            }
+        }
+    }
+    xp
+}
+
+/// Wall glyphs depend on which neighbours are revealed, so exports reveal everything first.
+fn fully_revealed(map: &Map) -> Map {
+    let mut map = map.clone();
+    for revealed in map.revealed_tiles.iter_mut() {
+        *revealed = true;
+    }
+    map
+}
+
+fn is_start(start: Option<&Position>, x: i32, y: i32) -> bool {
+    start.is_some_and(|start| start.x == x && start.y == y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::{BuilderChain, CellularAutomataBuilder};
+
+    /// A 6x4 room: walls all round, floor inside, the down stairs at (4, 2) and the start at (1, 1).
+    fn room() -> (Map, Position) {
+        crate::raws::load_raws();
+        let mut map = Map::new(1, 6, 4, "Export Test");
+        for y in 1..3 {
+            for x in 1..5 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::FLOOR;
+            }
+        }
+        let exit = map.xy_idx(4, 2);
+        map.tiles[exit] = TileType::DOWN_STAIRS;
+        (map, Position { x: 1, y: 1 })
+    }
+
+    #[test]
+    fn text_has_a_line_per_row_with_the_start_marked() {
+        let (map, start) = room();
+        let text = map_to_text(&map, Some(&start));
+        let rows: Vec<Vec<char>> = text.lines().map(|row| row.chars().collect()).collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows.iter().all(|row| row.len() == 6));
+        assert_eq!(rows[1][1], '@');
+        assert_eq!(rows[1][2], '.');
+        assert_eq!(rows[2][4], '>');
+        // Without a start, its tile is drawn as whatever is there
+        assert_eq!(
+            map_to_text(&map, None)
+                .lines()
+                .nth(1)
+                .unwrap()
+                .chars()
+                .nth(1),
+            Some('.')
+        );
+    }
+
+    #[test]
+    fn rex_paint_files_use_the_characters_prefabs_are_read_with() {
+        let (map, start) = room();
+        let mut bytes = Vec::new();
+        map_to_xp(&map, Some(&start)).write(&mut bytes).unwrap();
+        let xp = XpFile::read(&mut &bytes[..]).unwrap();
+
+        let layer = &xp.layers[0];
+        assert_eq!((layer.width, layer.height), (6, 4));
+        let ch = |x, y| char::from_u32(layer.get(x, y).unwrap().ch).unwrap();
+        assert_eq!(ch(0, 0), '#');
+        assert_eq!(ch(1, 1), '@');
+        assert_eq!(ch(2, 1), ' ');
+        assert_eq!(ch(4, 2), '>');
+    }
+
+    #[test]
+    fn each_step_of_a_build_is_exported() {
+        crate::raws::load_raws();
+        let mut chain = BuilderChain::new(1, 40, 30, "Export Test");
+        chain.build_data.record_history = true;
+        chain.start_with(CellularAutomataBuilder::new());
+        chain.build_map();
+        let steps = chain.build_data.history.len();
+        assert!(steps > 0);
+
+        let directory =
+            std::env::temp_dir().join(format!("downdelving-export-{}", std::process::id()));
+        let written = export_build(&chain.build_data, &directory, "caves").unwrap();
+        assert_eq!(written.len(), 2 * (steps + 1));
+        assert!(written.iter().all(|path| path.exists()));
+        assert!(written[0].ends_with("caves-step-01.txt"));
+        assert_eq!(
+            fs::read_to_string(directory.join("caves.txt")).unwrap(),
+            map_to_text(
+                &chain.build_data.map,
+                chain.build_data.starting_position.as_ref()
+            )
+        );
+        fs::remove_dir_all(&directory).ok();
+    }
+}
@@ -1,4 +1,5 @@
 use super::{BuilderMap, MetaMapBuilder, TileType};
+use crate::spatial::SpatialIndex;
 
 pub struct YellowBrickRoad {}
 
@@ -46,8 +47,8 @@ impl YellowBrickRoad {
             return;
         }
         let idx = build_data.map.xy_idx(x, y);
-        if build_data.map.tiles[idx] != TileType::DownStairs {
-            build_data.map.tiles[idx] = TileType::Road;
+        if build_data.map.tiles[idx] != TileType::DOWN_STAIRS {
+            build_data.map.tiles[idx] = TileType::named("Road");
         }
     }
 
@@ -62,8 +63,8 @@ impl YellowBrickRoad {
         );
         let end_idx = build_data.map.xy_idx(end_x, end_y);
 
-        build_data.map.populate_blocked();
-        let path = rltk::a_star_search(start_idx, end_idx, &build_data.map);
+        let index = SpatialIndex::for_map(&build_data.map);
+        let path = rltk::a_star_search(start_idx, end_idx, &index.pathing(&build_data.map));
         if !path.success {
             panic!("No valid path for the road");
         }
@@ -76,11 +77,11 @@ impl YellowBrickRoad {
             self.paint_road(build_data, x, y - 1);
             self.paint_road(build_data, x, y + 1);
         }
-        build_data.map.tiles[end_idx] = TileType::DownStairs;
+        build_data.map.tiles[end_idx] = TileType::DOWN_STAIRS;
         build_data.take_snapshot();
 
         // Place exit
-        let exit_dir = crate::rng::roll_dice(1, 2);
+        let exit_dir = crate::rng::mapgen::roll_dice(1, 2);
         let (seed_x, seed_y, stream_startx, stream_starty) = if exit_dir == 1 {
             (build_data.map.width - 1, 1, 0, build_data.height - 1)
         } else {
@@ -98,13 +99,14 @@ impl YellowBrickRoad {
 
         let (stream_x, stream_y) = self.find_exit(build_data, stream_startx, stream_starty);
         let stream_idx = build_data.map.xy_idx(stream_x, stream_y) as usize;
-        let stream = rltk::a_star_search(stairs_idx, stream_idx, &mut build_data.map);
+        let stream = rltk::a_star_search(stairs_idx, stream_idx, &index.pathing(&build_data.map));
+        let shallow_water = TileType::named("Shallow Water");
         for tile in stream.steps.iter() {
-            if build_data.map.tiles[*tile as usize] == TileType::Floor {
-                build_data.map.tiles[*tile as usize] = TileType::ShallowWater;
+            if build_data.map.tiles[*tile as usize] == TileType::FLOOR {
+                build_data.map.tiles[*tile as usize] = shallow_water;
             }
         }
-        build_data.map.tiles[stairs_idx] = TileType::DownStairs;
+        build_data.map.tiles[stairs_idx] = TileType::DOWN_STAIRS;
         build_data.take_snapshot();
     }
 }
@@ -19,39 +19,44 @@ impl CaveDecorator {
 
     fn build(&mut self, build_data: &mut BuilderMap) {
         let old_map = build_data.map.clone();
+        let gravel = TileType::named("Gravel");
+        let shallow_water = TileType::named("Shallow Water");
+        let deep_water = TileType::named("Deep Water");
+        let stalactite = TileType::named("Stalactite");
+        let stalagmite = TileType::named("Stalagmite");
         for (idx, tt) in build_data.map.tiles.iter_mut().enumerate() {
             // Gravel Spawning
-            if *tt == TileType::Floor && crate::rng::roll_dice(1, 6) == 1 {
-                *tt = TileType::Gravel;
-            } else if *tt == TileType::Floor && crate::rng::roll_dice(1, 10) == 1 {
+            if *tt == TileType::FLOOR && crate::rng::mapgen::roll_dice(1, 6) == 1 {
+                *tt = gravel;
+            } else if *tt == TileType::FLOOR && crate::rng::mapgen::roll_dice(1, 10) == 1 {
                 // Spawn passable pools
-                *tt = TileType::ShallowWater;
-            } else if *tt == TileType::Wall {
+                *tt = shallow_water;
+            } else if *tt == TileType::WALL {
                 // Spawn deep pools and stalactites
                 let mut neighbors = 0;
                 let x = idx as i32 % old_map.width;
                 let y = idx as i32 / old_map.width;
-                if x > 0 && old_map.tiles[idx - 1] == TileType::Wall {
+                if x > 0 && old_map.tiles[idx - 1] == TileType::WALL {
                     neighbors += 1;
                 }
-                if x < old_map.width - 2 && old_map.tiles[idx + 1] == TileType::Wall {
+                if x < old_map.width - 2 && old_map.tiles[idx + 1] == TileType::WALL {
                     neighbors += 1;
                 }
-                if y > 0 && old_map.tiles[idx - old_map.width as usize] == TileType::Wall {
+                if y > 0 && old_map.tiles[idx - old_map.width as usize] == TileType::WALL {
                     neighbors += 1;
                 }
                 if y < old_map.height - 2
-                    && old_map.tiles[idx + old_map.width as usize] == TileType::Wall
+                    && old_map.tiles[idx + old_map.width as usize] == TileType::WALL
                 {
                     neighbors += 1;
                 }
                 if neighbors == 2 {
-                    *tt = TileType::DeepWater;
+                    *tt = deep_water;
                 } else if neighbors == 1 {
-                    let roll = crate::rng::roll_dice(1, 4);
+                    let roll = crate::rng::mapgen::roll_dice(1, 4);
                     match roll {
-                        1 => *tt = TileType::Stalactite,
-                        2 => *tt = TileType::Stalagmite,
+                        1 => *tt = stalactite,
+                        2 => *tt = stalagmite,
                         _ => {}
                     }
                 }
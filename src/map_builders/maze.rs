@@ -135,7 +135,8 @@ impl Grid {
                 return Some(neighbors[0]);
             } else {
                 return Some(
-                    neighbors[(crate::rng::roll_dice(1, neighbors.len() as i32) - 1) as usize],
+                    neighbors
+                        [(crate::rng::mapgen::roll_dice(1, neighbors.len() as i32) - 1) as usize],
                 );
             }
         }
@@ -183,7 +184,7 @@ impl Grid {
     fn copy_to_map(&self, map: &mut Map) {
         // Clear the map
         for i in map.tiles.iter_mut() {
-            *i = TileType::Wall;
+            *i = TileType::WALL;
         }
 
         for cell in self.cells.iter() {
@@ -191,18 +192,18 @@ impl Grid {
             let y = cell.row + 1;
             let idx = map.xy_idx(x * 2, y * 2);
 
-            map.tiles[idx] = TileType::Floor;
+            map.tiles[idx] = TileType::FLOOR;
             if !cell.walls[TOP] {
-                map.tiles[idx - map.width as usize] = TileType::Floor
+                map.tiles[idx - map.width as usize] = TileType::FLOOR
             }
             if !cell.walls[RIGHT] {
-                map.tiles[idx + 1] = TileType::Floor
+                map.tiles[idx + 1] = TileType::FLOOR
             }
             if !cell.walls[BOTTOM] {
-                map.tiles[idx + map.width as usize] = TileType::Floor
+                map.tiles[idx + map.width as usize] = TileType::FLOOR
             }
             if !cell.walls[LEFT] {
-                map.tiles[idx - 1] = TileType::Floor
+                map.tiles[idx - 1] = TileType::FLOOR
             }
         }
     }
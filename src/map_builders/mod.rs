@@ -1,20 +1,24 @@
-use super::{spawner, Map, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER};
+use super::{spawner, LevelId, Map, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER};
 use specs::prelude::*;
 pub mod area_ending_point;
 pub mod area_starting_points;
+pub mod branch_stairs;
 pub mod bsp_dungeon;
 pub mod bsp_interior;
 pub mod cellular_automata;
 pub mod common;
+pub mod connecting_stairs;
 pub mod cull_unreachable;
 pub mod distant_exit;
 pub mod dla;
 pub mod door_placement;
 pub mod drunkard;
 pub mod dwarf_fort_builder;
+pub mod export;
 pub mod forest;
 pub mod limestone_cavern;
 pub mod maze;
+pub mod named;
 pub mod prefab_builder;
 pub mod room_based_spawner;
 pub mod room_based_stairs;
@@ -28,17 +32,21 @@ pub mod rooms_corridors_bsp;
 pub mod rooms_corridors_dogleg;
 pub mod rooms_corridors_lines;
 pub mod rooms_corridors_nearest;
+pub mod secret_doors;
 pub mod simple_map;
+pub mod theme_painter;
 pub mod town;
 pub mod voronoi;
 pub mod voronoi_spawning;
 pub mod waveform_collapse;
 pub use area_ending_point::*;
 pub use area_starting_points::{AreaStartingPosition, XStart, YStart};
+pub use branch_stairs::BranchStairs;
 pub use bsp_dungeon::BspDungeonBuilder;
 pub use bsp_interior::BspInteriorBuilder;
 pub use cellular_automata::CellularAutomataBuilder;
 pub use common::*;
+pub use connecting_stairs::ConnectingStairs;
 pub use cull_unreachable::CullUnreachable;
 pub use distant_exit::DistantExit;
 pub use dla::DLABuilder;
@@ -61,7 +69,9 @@ pub use rooms_corridors_bsp::BspCorridors;
 pub use rooms_corridors_dogleg::DoglegCorridors;
 pub use rooms_corridors_lines::StraightLineCorridors;
 pub use rooms_corridors_nearest::NearestCorridors;
+pub use secret_doors::SecretDoors;
 pub use simple_map::SimpleMapBuilder;
+pub use theme_painter::{ThemePainter, ThemeWidth};
 pub use town::TownBuilder;
 pub use voronoi::VoronoiCellBuilder;
 pub use voronoi_spawning::VoronoiSpawning;
@@ -74,13 +84,24 @@ pub struct BuilderMap {
     pub rooms: Option<Vec<Rect>>,
     pub corridors: Option<Vec<Vec<usize>>>,
     pub history: Vec<Map>,
+    /// Whether `take_snapshot` records into `history`; on for the visualizer and map exports.
+    pub record_history: bool,
+    /// The tags of the biome being built, so builders can pick content that belongs in it.
+    pub biome_tags: Vec<String>,
+    /// Where the stairs down to this level are on the level above, so the up stairs can be put
+    /// beneath them.
+    pub stairs_above: Vec<rltk::Point>,
+    /// Each of `stairs_above` and the index of the up stairs it leads to, once they're placed.
+    pub connected_stairs: Vec<(rltk::Point, usize)>,
+    /// The first levels of the branches entered from this one, which each get a staircase down.
+    pub branch_entrances: Vec<LevelId>,
     pub width: i32,
     pub height: i32,
 }
 
 impl BuilderMap {
     fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
+        if self.record_history {
             let mut snapshot = self.map.clone();
             for v in snapshot.revealed_tiles.iter_mut() {
                 *v = true;
@@ -108,6 +129,11 @@ impl BuilderChain {
                 rooms: None,
                 corridors: None,
                 history: Vec::new(),
+                record_history: SHOW_MAPGEN_VISUALIZER,
+                biome_tags: Vec::new(),
+                stairs_above: Vec::new(),
+                connected_stairs: Vec::new(),
+                branch_entrances: Vec::new(),
                 width,
                 height,
             },
@@ -126,18 +152,35 @@ impl BuilderChain {
     }
 
     pub fn build_map(&mut self) {
+        self.build_map_inspecting(|_, _| {});
+    }
+
+    /// Builds the map like `build_map`, calling `inspect` with each builder's name before it runs
+    /// and with `"Finished"` once they all have.
+    pub fn build_map_inspecting<F: FnMut(&str, &BuilderMap)>(&mut self, mut inspect: F) {
         match &mut self.starter {
             None => panic!("Cannot run a map builder chain without a starting build system"),
             Some(starter) => {
                 // Build the starting map
+                inspect(starter.name(), &self.build_data);
                 starter.build_map(&mut self.build_data);
             }
         }
 
+        // Line the level up with the one above, once everything else is in place
+        if !self.build_data.stairs_above.is_empty() {
+            self.builders.push(ConnectingStairs::new());
+        }
+        if !self.build_data.branch_entrances.is_empty() {
+            self.builders.push(BranchStairs::new());
+        }
+
         // Build additional layers in turn
         for metabuilder in self.builders.iter_mut() {
+            inspect(metabuilder.name(), &self.build_data);
             metabuilder.build_map(&mut self.build_data);
         }
+        inspect("Finished", &self.build_data);
     }
 
     pub fn spawn_entities(&mut self, ecs: &mut World) {
@@ -149,25 +192,52 @@ impl BuilderChain {
 
 pub trait InitialMapBuilder {
     fn build_map(&mut self, build_data: &mut BuilderMap);
+
+    fn name(&self) -> &'static str {
+        builder_name::<Self>()
+    }
 }
 
 pub trait MetaMapBuilder {
     fn build_map(&mut self, build_data: &mut BuilderMap);
+
+    fn name(&self) -> &'static str {
+        builder_name::<Self>()
+    }
+}
+
+/// A builder's type name without its module path, as the raws name it.
+fn builder_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// The chain `level` is built with: a side branch's level is built as the biome the branch names
+/// for it, and a main line level as its depth's.
+pub fn level_builder(level: &LevelId, width: i32, height: i32) -> BuilderChain {
+    crate::console_log(format!("Level: {}", level));
+    let (depth, biome, entrances) = {
+        let raws = crate::raws::RAWS.lock().unwrap();
+        (
+            crate::raws::level_depth(&raws, level),
+            crate::raws::biome_for_level(&raws, level),
+            crate::raws::branch_entrances(&raws, level),
+        )
+    };
+    let mut chain = match biome {
+        Some(biome) => crate::biome::biome_builder(&biome, depth, width, height),
+        None => depth_builder(depth, width, height),
+    };
+    chain.build_data.map.level = level.clone();
+    chain.build_data.branch_entrances = entrances;
+    chain
 }
 
-pub fn level_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    rltk::console::log(format!("Depth: {}", new_depth));
-    match new_depth {
-        1 => crate::biome::town_builder(new_depth, width, height),
-        2 => crate::biome::forest_builder(new_depth, width, height),
-        3 => crate::biome::limestone_cavern_builder(new_depth, width, height),
-        4 => crate::biome::limestone_deep_cavern_builder(new_depth, width, height),
-        5 => crate::biome::dwarf_fortress_upper_reaches_builder(new_depth, width, height),
-        6 => crate::biome::dwarf_fortress_builder(new_depth, width, height),
-        7 => crate::biome::mushroom_entrance(new_depth, width, height),
-        8 => crate::biome::mushroom_builder(new_depth, width, height),
-        9 => crate::biome::mushroom_exit(new_depth, width, height),
-        10 => crate::biome::dark_elf_city(new_depth, width, height),
-        _ => crate::biome::random_builder(new_depth, width, height),
+/// The chain a level at `new_depth` is built with: its biome from the raws, or a random one.
+pub fn depth_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
+    let biome = crate::raws::biome_for_depth(&crate::raws::RAWS.lock().unwrap(), new_depth);
+    match biome {
+        Some(biome) => crate::biome::biome_builder(&biome, new_depth, width, height),
+        None => crate::biome::random_builder(new_depth, width, height),
     }
 }
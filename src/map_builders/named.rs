@@ -0,0 +1,276 @@
+use super::prefab_builder::{prefab_levels, prefab_sections};
+use super::*;
+use crate::raws::BuilderStep;
+
+/// Makes the first builder of a chain from its name in the raws.
+pub fn initial_builder(step: &BuilderStep) -> Result<Box<dyn InitialMapBuilder>, String> {
+    let builder: Box<dyn InitialMapBuilder> = match step.builder.as_str() {
+        "BspDungeonBuilder" => BspDungeonBuilder::new(),
+        "BspInteriorBuilder" => BspInteriorBuilder::new(),
+        "CellularAutomataBuilder" => CellularAutomataBuilder::new(),
+        "DLABuilder" => dla(step)?,
+        "DrunkardsWalkBuilder" => drunkards_walk(step)?,
+        "MazeBuilder" => MazeBuilder::new(),
+        "PrefabBuilder" => prefab(step)?,
+        "SimpleMapBuilder" => SimpleMapBuilder::new(),
+        "TownBuilder" => TownBuilder::new(),
+        "VoronoiCellBuilder" => match preset(step) {
+            None => VoronoiCellBuilder::new(),
+            Some("pythagoras") => VoronoiCellBuilder::pythagoras(),
+            Some("manhattan") => VoronoiCellBuilder::manhattan(),
+            Some(other) => return Err(unknown_preset(step, other)),
+        },
+        _ => return Err(format!("{} isn't a starting builder", step.builder)),
+    };
+    Ok(builder)
+}
+
+/// Makes a builder that works on an existing map from its name in the raws.
+pub fn meta_builder(step: &BuilderStep) -> Result<Box<dyn MetaMapBuilder>, String> {
+    let builder: Box<dyn MetaMapBuilder> = match step.builder.as_str() {
+        "AreaEndingPosition" => {
+            let x = match param(step, "x")? {
+                "left" => XEnd::LEFT,
+                "center" => XEnd::CENTER,
+                "right" => XEnd::RIGHT,
+                other => return Err(bad_param(step, "x", other)),
+            };
+            let y = match param(step, "y")? {
+                "top" => YEnd::TOP,
+                "center" => YEnd::CENTER,
+                "bottom" => YEnd::BOTTOM,
+                other => return Err(bad_param(step, "y", other)),
+            };
+            AreaEndingPosition::new(x, y)
+        }
+        "AreaStartingPosition" => {
+            let x = match param(step, "x")? {
+                "left" => XStart::LEFT,
+                "center" => XStart::CENTER,
+                "right" => XStart::RIGHT,
+                other => return Err(bad_param(step, "x", other)),
+            };
+            let y = match param(step, "y")? {
+                "top" => YStart::TOP,
+                "center" => YStart::CENTER,
+                "bottom" => YStart::BOTTOM,
+                other => return Err(bad_param(step, "y", other)),
+            };
+            AreaStartingPosition::new(x, y)
+        }
+        "BspCorridors" => BspCorridors::new(),
+        "CaveDecorator" => CaveDecorator::new(),
+        "CaveTransition" => CaveTransition::new(),
+        "CellularAutomataBuilder" => CellularAutomataBuilder::new(),
+        "CorridorSpawner" => CorridorSpawner::new(),
+        "CullUnreachable" => CullUnreachable::new(),
+        "DLABuilder" => dla(step)?,
+        "DistantExit" => DistantExit::new(),
+        "DoglegCorridors" => DoglegCorridors::new(),
+        "DoorPlacement" => DoorPlacement::new(),
+        "DragonSpawner" => DragonSpawner::new(),
+        "DragonsLair" => DragonsLair::new(),
+        "DrunkardsWalkBuilder" => drunkards_walk(step)?,
+        "NearestCorridors" => NearestCorridors::new(),
+        "PrefabBuilder" => prefab(step)?,
+        "RoomBasedSpawner" => RoomBasedSpawner::new(),
+        "RoomBasedStairs" => RoomBasedStairs::new(),
+        "RoomBasedStartingPosition" => RoomBasedStartingPosition::new(),
+        "RoomCornerRounder" => RoomCornerRounder::new(),
+        "RoomDrawer" => RoomDrawer::new(),
+        "RoomExploder" => RoomExploder::new(),
+        "RoomSorter" => {
+            let sort = match param(step, "sort")? {
+                "leftmost" => RoomSort::LEFTMOST,
+                "rightmost" => RoomSort::RIGHTMOST,
+                "topmost" => RoomSort::TOPMOST,
+                "bottommost" => RoomSort::BOTTOMMOST,
+                "central" => RoomSort::CENTRAL,
+                other => return Err(bad_param(step, "sort", other)),
+            };
+            RoomSorter::new(sort)
+        }
+        "SecretDoors" => SecretDoors::new(),
+        "StraightLineCorridors" => StraightLineCorridors::new(),
+        "ThemePainter" => theme_painter(step)?,
+        "VoronoiSpawning" => VoronoiSpawning::new(),
+        "WaveformCollapseBuilder" => WaveformCollapseBuilder::new(),
+        "YellowBrickRoad" => YellowBrickRoad::new(),
+        _ => {
+            return Err(format!(
+                "{} isn't a builder that can follow another",
+                step.builder
+            ))
+        }
+    };
+    Ok(builder)
+}
+
+/// `x` is the side of the map the band is on and `width` is `half` or a number of columns. Without
+/// a `theme`, the band has the plain look.
+fn theme_painter(step: &BuilderStep) -> Result<Box<ThemePainter>, String> {
+    let side = match param(step, "x")? {
+        "left" => XStart::LEFT,
+        "center" => XStart::CENTER,
+        "right" => XStart::RIGHT,
+        other => return Err(bad_param(step, "x", other)),
+    };
+    let width = match param(step, "width")? {
+        "half" => ThemeWidth::Half,
+        other => match other.parse() {
+            Ok(columns) if columns > 0 => ThemeWidth::Columns(columns),
+            _ => return Err(bad_param(step, "width", other)),
+        },
+    };
+    Ok(ThemePainter::new(
+        side,
+        width,
+        step.params.get("theme").cloned(),
+    ))
+}
+
+fn dla(step: &BuilderStep) -> Result<Box<DLABuilder>, String> {
+    match preset(step) {
+        None => Ok(DLABuilder::new()),
+        Some("walk_inwards") => Ok(DLABuilder::walk_inwards()),
+        Some("walk_outwards") => Ok(DLABuilder::walk_outwards()),
+        Some("heavy_erosion") => Ok(DLABuilder::heavy_erosion()),
+        Some("central_attractor") => Ok(DLABuilder::central_attractor()),
+        Some("insectoid") => Ok(DLABuilder::insectoid()),
+        Some(other) => Err(unknown_preset(step, other)),
+    }
+}
+
+fn drunkards_walk(step: &BuilderStep) -> Result<Box<DrunkardsWalkBuilder>, String> {
+    match param(step, "preset")? {
+        "open_area" => Ok(DrunkardsWalkBuilder::open_area()),
+        "open_halls" => Ok(DrunkardsWalkBuilder::open_halls()),
+        "winding_passages" => Ok(DrunkardsWalkBuilder::winding_passages()),
+        "fat_passages" => Ok(DrunkardsWalkBuilder::fat_passages()),
+        "fearful_symmetry" => Ok(DrunkardsWalkBuilder::fearful_symmetry()),
+        other => Err(unknown_preset(step, other)),
+    }
+}
+
+/// A prefab is a whole `level`, a `section` placed on the map, or, by default, vaults in rooms.
+fn prefab(step: &BuilderStep) -> Result<Box<PrefabBuilder>, String> {
+    if let Some(level) = step.params.get("level") {
+        return match level.as_str() {
+            "WFC_POPULATED" => Ok(PrefabBuilder::constant(prefab_levels::WFC_POPULATED)),
+            other => Err(bad_param(step, "level", other)),
+        };
+    }
+    if let Some(section) = step.params.get("section") {
+        return match section.as_str() {
+            "UNDERGROUND_FORT" => Ok(PrefabBuilder::sectional(prefab_sections::UNDERGROUND_FORT)),
+            "ORC_CAMP" => Ok(PrefabBuilder::sectional(prefab_sections::ORC_CAMP)),
+            "DROW_ENTRY" => Ok(PrefabBuilder::sectional(prefab_sections::DROW_ENTRY)),
+            other => Err(bad_param(step, "section", other)),
+        };
+    }
+    Ok(PrefabBuilder::vaults())
+}
+
+fn preset(step: &BuilderStep) -> Option<&str> {
+    step.params.get("preset").map(|preset| preset.as_str())
+}
+
+fn param<'a>(step: &'a BuilderStep, name: &str) -> Result<&'a str, String> {
+    step.params
+        .get(name)
+        .map(|value| value.as_str())
+        .ok_or_else(|| format!("{} needs a {}", step.builder, name))
+}
+
+fn unknown_preset(step: &BuilderStep, preset: &str) -> String {
+    format!("{} has no preset {}", step.builder, preset)
+}
+
+fn bad_param(step: &BuilderStep, name: &str, value: &str) -> String {
+    format!("{} can't have {} {}", step.builder, name, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn step(step: serde_json::Value) -> BuilderStep {
+        serde_json::from_value(step).unwrap()
+    }
+
+    fn refusal<T>(made: Result<T, String>) -> String {
+        match made {
+            Ok(_) => panic!("the builder was made"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn every_biome_in_the_raws_names_builders_that_exist() {
+        for biome in crate::raws::read_raws().unwrap().biomes.iter() {
+            assert!(initial_builder(&biome.initial).is_ok(), "{}", biome.name);
+            for builder in biome.builders.iter() {
+                assert!(
+                    meta_builder(builder).is_ok(),
+                    "{}: {}",
+                    biome.name,
+                    builder.builder
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_builders_and_parameters_are_refused() {
+        assert_eq!(
+            refusal(initial_builder(&step(json!({ "builder": "Nope" })))),
+            "Nope isn't a starting builder"
+        );
+        assert_eq!(
+            refusal(initial_builder(&step(json!({ "builder": "DistantExit" })))),
+            "DistantExit isn't a starting builder"
+        );
+        assert_eq!(
+            refusal(meta_builder(&step(json!({ "builder": "RoomSorter" })))),
+            "RoomSorter needs a sort"
+        );
+        assert_eq!(
+            refusal(meta_builder(&step(
+                json!({ "builder": "RoomSorter", "sort": "sideways" })
+            ))),
+            "RoomSorter can't have sort sideways"
+        );
+        assert_eq!(
+            refusal(initial_builder(&step(
+                json!({ "builder": "DrunkardsWalkBuilder", "preset": "staggering" })
+            ))),
+            "DrunkardsWalkBuilder has no preset staggering"
+        );
+        assert!(meta_builder(&step(
+            json!({ "builder": "ThemePainter", "x": "left", "width": "0" })
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn a_biome_from_the_raws_builds_its_level() {
+        crate::raws::load_raws();
+        crate::rng::reseed(&crate::rng::WorldSeed::new(3));
+        let biome = {
+            let raws = crate::raws::RAWS.lock().unwrap();
+            assert!(crate::raws::biome_for_depth(&raws, 50).is_none());
+            crate::raws::biome_for_depth(&raws, 3).unwrap()
+        };
+        assert_eq!(biome.name, "Limestone Caverns");
+
+        let mut chain = crate::biome::biome_builder(&biome, 3, 80, 50);
+        chain.build_map();
+        let map = &chain.build_data.map;
+        assert_eq!(map.name, biome.map_name);
+        assert_eq!(map.theme, biome.theme);
+        assert!(!map.outdoors);
+        assert!(chain.build_data.starting_position.is_some());
+        assert!(map.tiles.contains(&crate::map::TileType::DOWN_STAIRS));
+    }
+}
@@ -1,6 +1,5 @@
 use super::{BuilderMap, InitialMapBuilder, MetaMapBuilder, Position, TileType};
 pub mod prefab_levels;
-pub mod prefab_rooms;
 pub mod prefab_sections;
 use std::collections::HashSet;
 
@@ -89,55 +88,55 @@ impl PrefabBuilder {
             return;
         }
         match ch {
-            ' ' => build_data.map.tiles[idx] = TileType::Floor,
-            '#' => build_data.map.tiles[idx] = TileType::Wall,
-            '≈' => build_data.map.tiles[idx] = TileType::DeepWater,
+            ' ' => build_data.map.tiles[idx] = TileType::FLOOR,
+            '#' => build_data.map.tiles[idx] = TileType::WALL,
+            '≈' => build_data.map.tiles[idx] = TileType::named("Deep Water"),
             '@' => {
                 let x = idx as i32 % build_data.map.width;
                 let y = idx as i32 / build_data.map.width;
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.starting_position = Some(Position {
                     x: x as i32,
                     y: y as i32,
                 });
             }
-            '>' => build_data.map.tiles[idx] = TileType::DownStairs,
+            '>' => build_data.map.tiles[idx] = TileType::DOWN_STAIRS,
             'e' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Dark Elf".to_string()));
             }
             'g' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Goblin".to_string()));
             }
             'o' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Orc".to_string()));
             }
             'O' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Orc Leader".to_string()));
             }
             '^' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Bear Trap".to_string()));
             }
             '%' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Rations".to_string()));
             }
             '!' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data
                     .spawn_list
                     .push((idx, "Health Potion".to_string()));
             }
             '☼' => {
-                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::FLOOR;
                 build_data.spawn_list.push((idx, "Watch Fire".to_string()));
             }
             _ => {
-                rltk::console::log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
+                crate::console_log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
             }
         }
     }
@@ -268,79 +267,44 @@ impl PrefabBuilder {
     }
 
     fn apply_room_vaults(&mut self, build_data: &mut BuilderMap) {
-        use prefab_rooms::*;
-
         // Apply the previous builder, and keep all entities it spawns (for now)
         self.apply_previous_iteration(|_x, _y| true, build_data);
 
         // Do we want a vault at all?
-        let vault_roll = crate::rng::roll_dice(1, 6) + build_data.map.depth;
+        let vault_roll = crate::rng::mapgen::roll_dice(1, 6) + build_data.map.depth;
         if vault_roll < 4 {
             return;
         }
 
-        // Note that this is a place-holder and will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
-
-        // Filter the vault list down to ones that are applicable to the current depth
-        let mut possible_vaults: Vec<&PrefabRoom> = master_vault_list
-            .iter()
-            .filter(|v| {
-                build_data.map.depth >= v.first_depth && build_data.map.depth <= v.last_depth
-            })
-            .collect();
-
+        let mut possible_vaults = crate::raws::vaults_for_depth(
+            &crate::raws::RAWS.lock().unwrap(),
+            build_data.map.depth,
+            &build_data.biome_tags,
+        );
         if possible_vaults.is_empty() {
             return;
         } // Bail out if there's nothing to build
 
-        let n_vaults = i32::min(crate::rng::roll_dice(1, 3), possible_vaults.len() as i32);
+        let n_vaults = i32::min(
+            crate::rng::mapgen::roll_dice(1, 3),
+            possible_vaults.len() as i32,
+        );
         let mut used_tiles: HashSet<usize> = HashSet::new();
 
         for _i in 0..n_vaults {
-            let vault_index = if possible_vaults.len() == 1 {
-                0
-            } else {
-                (crate::rng::roll_dice(1, possible_vaults.len() as i32) - 1) as usize
-            };
-            let vault = possible_vaults[vault_index];
-
-            // We'll make a list of places in which the vault could fit
-            let mut vault_positions: Vec<Position> = Vec::new();
-
-            let mut idx = 0usize;
-            loop {
-                let x = (idx % build_data.map.width as usize) as i32;
-                let y = (idx / build_data.map.width as usize) as i32;
-
-                // Check that we won't overflow the map
-                if x > 1
-                    && (x + vault.width as i32) < build_data.map.width - 2
-                    && y > 1
-                    && (y + vault.height as i32) < build_data.map.height - 2
-                {
-                    let mut possible = true;
-                    for ty in 0..vault.height as i32 {
-                        for tx in 0..vault.width as i32 {
-                            let idx = build_data.map.xy_idx(tx + x, ty + y);
-                            if build_data.map.tiles[idx] != TileType::Floor {
-                                possible = false;
-                            }
-                            if used_tiles.contains(&idx) {
-                                possible = false;
-                            }
+            let vault_index = pick_vault(&possible_vaults);
+            let vault = &possible_vaults[vault_index];
+            let orientations = vault_orientations(vault);
+
+            // We'll make a list of places, and ways round, in which the vault could fit
+            let mut vault_positions: Vec<(Position, usize)> = Vec::new();
+            for y in 0..build_data.map.height {
+                for x in 0..build_data.map.width {
+                    for (orientation, grid) in orientations.iter().enumerate() {
+                        if vault_fits(grid, x, y, &used_tiles, build_data) {
+                            vault_positions.push((Position { x, y }, orientation));
                         }
                     }
-
-                    if possible {
-                        vault_positions.push(Position { x, y });
-                        break;
-                    }
-                }
-
-                idx += 1;
-                if idx >= build_data.map.tiles.len() - 1 {
-                    break;
                 }
             }
 
@@ -348,37 +312,40 @@ impl PrefabBuilder {
                 let pos_idx = if vault_positions.len() == 1 {
                     0
                 } else {
-                    (crate::rng::roll_dice(1, vault_positions.len() as i32) - 1) as usize
+                    (crate::rng::mapgen::roll_dice(1, vault_positions.len() as i32) - 1) as usize
                 };
-                let pos = &vault_positions[pos_idx];
-
-                let chunk_x = pos.x;
-                let chunk_y = pos.y;
-
-                let width = build_data.map.width; // The borrow checker really doesn't like it
-                let height = build_data.map.height; // when we access `self` inside the `retain`
-                build_data.spawn_list.retain(|e| {
-                    let idx = e.0 as i32;
-                    let x = idx % width;
-                    let y = idx / height;
-                    x < chunk_x
-                        || x > chunk_x + vault.width as i32
-                        || y < chunk_y
-                        || y > chunk_y + vault.height as i32
-                });
-
-                let string_vec = PrefabBuilder::read_ascii_to_vec(vault.template);
-                let mut i = 0;
-                for ty in 0..vault.height {
-                    for tx in 0..vault.width {
-                        let idx = build_data
-                            .map
-                            .xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                        self.char_to_map(string_vec[i], idx, build_data);
-                        used_tiles.insert(idx);
-                        i += 1;
+                let (pos, orientation) = &vault_positions[pos_idx];
+                let grid = &orientations[*orientation];
+
+                let mut vault_tiles = HashSet::new();
+                for (ty, row) in grid.iter().enumerate() {
+                    for tx in 0..row.len() {
+                        vault_tiles
+                            .insert(build_data.map.xy_idx(pos.x + tx as i32, pos.y + ty as i32));
+                    }
+                }
+                build_data
+                    .spawn_list
+                    .retain(|(idx, _name)| !vault_tiles.contains(idx));
+
+                for (ty, row) in grid.iter().enumerate() {
+                    for (tx, ch) in row.iter().enumerate() {
+                        let idx = build_data.map.xy_idx(pos.x + tx as i32, pos.y + ty as i32);
+                        match vault.legend_for(*ch) {
+                            Some(legend) => {
+                                build_data.map.tiles[idx] = TileType::named(&legend.tile);
+                                if let Some(spawn) = legend.spawn {
+                                    build_data.spawn_list.push((idx, spawn));
+                                }
+                            }
+                            None => crate::console_log(format!(
+                                "Unknown glyph in vault {}: {}",
+                                vault.name, ch
+                            )),
+                        }
                     }
                 }
+                used_tiles.extend(vault_tiles);
                 build_data.take_snapshot();
 
                 possible_vaults.remove(vault_index);
@@ -386,3 +353,208 @@ impl PrefabBuilder {
         }
     }
 }
+
+/// Picks a vault at random, favouring the ones with more weight.
+fn pick_vault(vaults: &[crate::raws::Vault]) -> usize {
+    if vaults.len() == 1 {
+        return 0;
+    }
+    let total_weight: i32 = vaults.iter().map(|vault| vault.weight).sum();
+    let mut roll = crate::rng::mapgen::roll_dice(1, total_weight) - 1;
+    for (index, vault) in vaults.iter().enumerate() {
+        if roll < vault.weight {
+            return index;
+        }
+        roll -= vault.weight;
+    }
+    vaults.len() - 1
+}
+
+/// The vault's template in every way round it's allowed to be placed, without repeats.
+fn vault_orientations(vault: &crate::raws::Vault) -> Vec<Vec<Vec<char>>> {
+    let width = vault.width();
+    let mut grid: Vec<Vec<char>> = vault
+        .template
+        .iter()
+        .map(|row| {
+            let mut row: Vec<char> = row.chars().collect();
+            row.resize(width, ' ');
+            row
+        })
+        .collect();
+
+    let mut orientations = Vec::new();
+    let rotations = if vault.rotate { 4 } else { 1 };
+    for _rotation in 0..rotations {
+        if vault.mirror {
+            let mirrored = grid
+                .iter()
+                .map(|row| row.iter().rev().cloned().collect())
+                .collect();
+            orientations.push(mirrored);
+        }
+        // Rotate a quarter turn clockwise for the next pass
+        let rotated = (0..width_of(&grid))
+            .map(|x| grid.iter().rev().map(|row| row[x]).collect())
+            .collect();
+        orientations.push(std::mem::replace(&mut grid, rotated));
+    }
+
+    let mut unique: Vec<Vec<Vec<char>>> = Vec::new();
+    for orientation in orientations {
+        if !unique.contains(&orientation) {
+            unique.push(orientation);
+        }
+    }
+    unique
+}
+
+fn width_of(grid: &[Vec<char>]) -> usize {
+    grid.first().map(|row| row.len()).unwrap_or(0)
+}
+
+/// Whether the vault fits with its top left at `x`, `y`: entirely on unused floor, clear of the
+/// map's edges.
+fn vault_fits(
+    grid: &[Vec<char>],
+    x: i32,
+    y: i32,
+    used_tiles: &HashSet<usize>,
+    build_data: &BuilderMap,
+) -> bool {
+    let width = width_of(grid) as i32;
+    let height = grid.len() as i32;
+    if x <= 1
+        || x + width >= build_data.map.width - 2
+        || y <= 1
+        || y + height >= build_data.map.height - 2
+    {
+        return false;
+    }
+    for ty in 0..height {
+        for tx in 0..width {
+            let idx = build_data.map.xy_idx(tx + x, ty + y);
+            if build_data.map.tiles[idx] != TileType::FLOOR || used_tiles.contains(&idx) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::BuilderChain;
+    use crate::raws::Vault;
+    use serde_json::json;
+
+    fn vault(template: &[&str], rotate: bool, mirror: bool) -> Vault {
+        serde_json::from_value(json!({
+            "name": "Test Vault",
+            "min_depth": 1,
+            "max_depth": 10,
+            "rotate": rotate,
+            "mirror": mirror,
+            "legend": { "!": { "spawn": "Health Potion" }, "=": { "tile": "Gravel" } },
+            "template": template,
+        }))
+        .unwrap()
+    }
+
+    fn rows(grid: &[Vec<char>]) -> Vec<String> {
+        grid.iter().map(|row| row.iter().collect()).collect()
+    }
+
+    #[test]
+    fn vaults_are_tried_every_way_round_they_allow() {
+        let template = ["!=", "  ", " #"];
+        assert_eq!(vault_orientations(&vault(&template, false, false)).len(), 1);
+
+        let turned = vault_orientations(&vault(&template, true, false));
+        assert_eq!(turned.len(), 4);
+        assert_eq!(rows(&turned[0]), vec!["!=", "  ", " #"]);
+        assert_eq!(rows(&turned[1]), vec!["  !", "# ="]);
+
+        assert_eq!(vault_orientations(&vault(&template, true, true)).len(), 8);
+        let mirrored = vault_orientations(&vault(&template, false, true));
+        assert_eq!(rows(&mirrored[0]), vec!["=!", "  ", "# "]);
+
+        // A vault that looks the same every way round is only tried once
+        let symmetric = ["#!#", "! !", "#!#"];
+        assert_eq!(vault_orientations(&vault(&symmetric, true, true)).len(), 1);
+    }
+
+    #[test]
+    fn short_rows_are_padded_with_floor() {
+        let grids = vault_orientations(&vault(&["!==", "#"], false, false));
+        assert_eq!(rows(&grids[0]), vec!["!==", "#  "]);
+    }
+
+    #[test]
+    fn legends_default_to_floor_and_wall() {
+        let vault = vault(&[" "], false, false);
+        assert_eq!(vault.legend_for(' ').unwrap().tile, "Floor");
+        assert_eq!(vault.legend_for('#').unwrap().tile, "Wall");
+        assert_eq!(vault.legend_for('=').unwrap().tile, "Gravel");
+        let potion = vault.legend_for('!').unwrap();
+        assert_eq!(
+            (potion.tile.as_str(), potion.spawn.as_deref()),
+            ("Floor", Some("Health Potion"))
+        );
+        assert!(vault.legend_for('?').is_none());
+    }
+
+    #[test]
+    fn a_vault_only_fits_on_unused_floor_away_from_the_edge() {
+        crate::raws::load_raws();
+        let mut chain = BuilderChain::new(1, 12, 12, "Vault Test");
+        let map = &mut chain.build_data.map;
+        for y in 1..11 {
+            for x in 1..11 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::FLOOR;
+            }
+        }
+        let grid = &vault_orientations(&vault(&["!=", "  "], false, false))[0];
+        let mut used = HashSet::new();
+        let build_data = &chain.build_data;
+        assert!(vault_fits(grid, 2, 2, &used, build_data));
+        assert!(vault_fits(grid, 7, 7, &used, build_data));
+        // Too close to the edge of the map
+        assert!(!vault_fits(grid, 1, 2, &used, build_data));
+        assert!(!vault_fits(grid, 8, 7, &used, build_data));
+
+        used.insert(build_data.map.xy_idx(3, 3));
+        assert!(!vault_fits(grid, 2, 2, &used, build_data));
+        let wall = build_data.map.xy_idx(5, 5);
+        chain.build_data.map.tiles[wall] = TileType::WALL;
+        assert!(!vault_fits(grid, 4, 4, &HashSet::new(), &chain.build_data));
+    }
+
+    #[test]
+    fn vaults_are_picked_by_weight() {
+        let mut light = vault(&[" "], false, false);
+        light.weight = 0;
+        let heavy = vault(&[" "], false, false);
+        for _ in 0..20 {
+            assert_eq!(pick_vault(&[light.clone(), heavy.clone()]), 1);
+        }
+    }
+
+    #[test]
+    fn only_vaults_for_the_depth_and_biome_are_offered() {
+        crate::raws::load_raws();
+        let raws = crate::raws::RAWS.lock().unwrap();
+        let tags = vec!["cavern".to_string()];
+        for depth in [1, 5, 12] {
+            for vault in crate::raws::vaults_for_depth(&raws, depth, &tags) {
+                assert!(vault.min_depth <= depth && depth <= vault.max_depth);
+                assert!(vault.biome_tags.is_empty() || vault.biome_tags.contains(&tags[0]));
+            }
+            for vault in crate::raws::vaults_for_depth(&raws, depth, &[]) {
+                assert!(vault.biome_tags.is_empty(), "{}", vault.name);
+            }
+        }
+    }
+}
@@ -19,21 +19,21 @@ impl RoomCornerRounder {
         let h = build_data.map.height;
         let idx = build_data.map.xy_idx(x, y);
         let mut neighbor_walls = 0;
-        if x > 0 && build_data.map.tiles[idx - 1] == TileType::Wall {
+        if x > 0 && build_data.map.tiles[idx - 1] == TileType::WALL {
             neighbor_walls += 1;
         }
-        if y > 0 && build_data.map.tiles[idx - w as usize] == TileType::Wall {
+        if y > 0 && build_data.map.tiles[idx - w as usize] == TileType::WALL {
             neighbor_walls += 1;
         }
-        if x < w - 2 && build_data.map.tiles[idx + 1] == TileType::Wall {
+        if x < w - 2 && build_data.map.tiles[idx + 1] == TileType::WALL {
             neighbor_walls += 1;
         }
-        if y < h - 2 && build_data.map.tiles[idx + w as usize] == TileType::Wall {
+        if y < h - 2 && build_data.map.tiles[idx + w as usize] == TileType::WALL {
             neighbor_walls += 1;
         }
 
         if neighbor_walls == 2 {
-            build_data.map.tiles[idx] = TileType::Wall;
+            build_data.map.tiles[idx] = TileType::WALL;
         }
     }
 
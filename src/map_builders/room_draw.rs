@@ -20,7 +20,7 @@ impl RoomDrawer {
             for x in room.x1 + 1..=room.x2 {
                 let idx = build_data.map.xy_idx(x, y);
                 if idx > 0 && idx < ((build_data.map.width * build_data.map.height) - 1) as usize {
-                    build_data.map.tiles[idx] = TileType::Floor;
+                    build_data.map.tiles[idx] = TileType::FLOOR;
                 }
             }
         }
@@ -40,7 +40,7 @@ impl RoomDrawer {
                     && idx < ((build_data.map.width * build_data.map.height) - 1) as usize
                     && distance <= radius
                 {
-                    build_data.map.tiles[idx] = TileType::Floor;
+                    build_data.map.tiles[idx] = TileType::FLOOR;
                 }
             }
         }
@@ -55,7 +55,7 @@ impl RoomDrawer {
         }
 
         for room in rooms.iter() {
-            let room_type = crate::rng::roll_dice(1, 4);
+            let room_type = crate::rng::mapgen::roll_dice(1, 4);
             match room_type {
                 1 => self.circle(build_data, room),
                 _ => self.rectangle(build_data, room),
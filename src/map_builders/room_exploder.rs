@@ -24,7 +24,7 @@ impl RoomExploder {
 
         for room in rooms.iter() {
             let start = room.center();
-            let n_diggers = crate::rng::roll_dice(1, 20) - 5;
+            let n_diggers = crate::rng::mapgen::roll_dice(1, 20) - 5;
             if n_diggers > 0 {
                 for _i in 0..n_diggers {
                     let mut drunk_x = start.0;
@@ -35,13 +35,13 @@ impl RoomExploder {
 
                     while drunk_life > 0 {
                         let drunk_idx = build_data.map.xy_idx(drunk_x, drunk_y);
-                        if build_data.map.tiles[drunk_idx] == TileType::Wall {
+                        if build_data.map.tiles[drunk_idx] == TileType::WALL {
                             did_something = true;
                         }
                         paint(&mut build_data.map, Symmetry::None, 1, drunk_x, drunk_y);
-                        build_data.map.tiles[drunk_idx] = TileType::DownStairs;
+                        build_data.map.tiles[drunk_idx] = TileType::DOWN_STAIRS;
 
-                        let stagger_direction = crate::rng::roll_dice(1, 4);
+                        let stagger_direction = crate::rng::mapgen::roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if drunk_x > 2 {
@@ -72,8 +72,8 @@ impl RoomExploder {
                     }
 
                     for t in build_data.map.tiles.iter_mut() {
-                        if *t == TileType::DownStairs {
-                            *t = TileType::Floor;
+                        if *t == TileType::DOWN_STAIRS {
+                            *t = TileType::FLOOR;
                         }
                     }
                 }
@@ -28,7 +28,7 @@ impl DoglegCorridors {
             if i > 0 {
                 let (new_x, new_y) = room.center();
                 let (prev_x, prev_y) = rooms[i as usize - 1].center();
-                if crate::rng::range(0, 2) == 1 {
+                if crate::rng::mapgen::range(0, 2) == 1 {
                     let mut c1 =
                         apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, prev_y);
                     let mut c2 = apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, new_x);
@@ -51,8 +51,8 @@ impl StraightLineCorridors {
                 let mut corridor = Vec::new();
                 for cell in line.iter() {
                     let idx = build_data.map.xy_idx(cell.x, cell.y);
-                    if build_data.map.tiles[idx] != TileType::Floor {
-                        build_data.map.tiles[idx] = TileType::Floor;
+                    if build_data.map.tiles[idx] != TileType::FLOOR {
+                        build_data.map.tiles[idx] = TileType::FLOOR;
                         corridor.push(idx);
                     }
                 }
@@ -0,0 +1,188 @@
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use crate::spatial::SpatialIndex;
+
+/// Turns some of the doors `DoorPlacement` planned into secret doors, or on levels without doors,
+/// some of the corridors where they squeeze between two walls. A door is only hidden if the stairs
+/// can still be reached without it, so run this after the exit is placed and after any culling.
+pub struct SecretDoors {}
+
+impl MetaMapBuilder for SecretDoors {
+    fn build_map(&mut self, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl SecretDoors {
+    pub fn new() -> Box<SecretDoors> {
+        Box::new(SecretDoors {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let mut candidates: Vec<usize> = build_data
+            .spawn_list
+            .iter()
+            .filter(|(_, name)| name == "Door")
+            .map(|(idx, _)| *idx)
+            .collect();
+        if candidates.is_empty() {
+            if let Some(corridors) = &build_data.corridors {
+                candidates = corridors
+                    .iter()
+                    .filter(|corridor| corridor.len() > 2)
+                    .filter_map(|corridor| {
+                        corridor
+                            .iter()
+                            .find(|idx| self.between_walls(build_data, **idx))
+                            .cloned()
+                    })
+                    .collect();
+            }
+        }
+
+        let start_idx = build_data
+            .starting_position
+            .as_ref()
+            .map(|start| build_data.map.xy_idx(start.x, start.y));
+        for idx in candidates {
+            if crate::rng::mapgen::roll_dice(1, 4) != 1
+                || build_data.map.tiles[idx] != TileType::FLOOR
+                || start_idx == Some(idx)
+            {
+                continue;
+            }
+            build_data.map.tiles[idx] = TileType::SECRET_DOOR;
+            if self.stairs_reachable(build_data) {
+                build_data
+                    .spawn_list
+                    .retain(|(spawn_idx, _)| *spawn_idx != idx);
+            } else {
+                build_data.map.tiles[idx] = TileType::FLOOR;
+            }
+        }
+        build_data.take_snapshot();
+    }
+
+    /// Whether the tile is a gap in a wall, with walls on two opposite sides and floor on the
+    /// other two.
+    fn between_walls(&self, build_data: &BuilderMap, idx: usize) -> bool {
+        let map = &build_data.map;
+        let x = idx as i32 % map.width;
+        let y = idx as i32 / map.width;
+        if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+            return false;
+        }
+        let tile = |dx: i32, dy: i32| map.tiles[map.xy_idx(x + dx, y + dy)];
+        let (west, east, north, south) = (tile(-1, 0), tile(1, 0), tile(0, -1), tile(0, 1));
+        map.tiles[idx] == TileType::FLOOR
+            && ((west == TileType::WALL
+                && east == TileType::WALL
+                && north.is_walkable()
+                && south.is_walkable())
+                || (north == TileType::WALL
+                    && south == TileType::WALL
+                    && west.is_walkable()
+                    && east.is_walkable()))
+    }
+
+    fn stairs_reachable(&self, build_data: &BuilderMap) -> bool {
+        let start = match &build_data.starting_position {
+            Some(start) => build_data.map.xy_idx(start.x, start.y),
+            None => return true,
+        };
+        let index = SpatialIndex::for_map(&build_data.map);
+        let dijkstra_map = rltk::DijkstraMap::new(
+            build_data.map.width as usize,
+            build_data.map.height as usize,
+            &[start],
+            &index.pathing(&build_data.map),
+            3000.0,
+        );
+        build_data
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == TileType::DOWN_STAIRS || **tile == TileType::UP_STAIRS)
+            .all(|(idx, _)| dijkstra_map.map[idx] != f32::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::{BuilderChain, InitialMapBuilder};
+    use crate::Position;
+
+    /// Two rooms joined by a door in each of `corridors`, with the start in one and the exit in
+    /// the other.
+    struct JoinedRooms {
+        corridors: Vec<i32>,
+    }
+
+    impl InitialMapBuilder for JoinedRooms {
+        fn build_map(&mut self, build_data: &mut BuilderMap) {
+            for y in 1..11 {
+                for x in (1..7).chain(12..19) {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = TileType::FLOOR;
+                }
+            }
+            for y in self.corridors.iter() {
+                for x in 7..12 {
+                    let idx = build_data.map.xy_idx(x, *y);
+                    build_data.map.tiles[idx] = TileType::FLOOR;
+                }
+                let door = build_data.map.xy_idx(9, *y);
+                build_data.spawn_list.push((door, "Door".to_string()));
+            }
+            build_data.starting_position = Some(Position { x: 2, y: 5 });
+            let exit = build_data.map.xy_idx(16, 5);
+            build_data.map.tiles[exit] = TileType::DOWN_STAIRS;
+        }
+    }
+
+    /// The secret doors on the map after building with `seed`.
+    fn secret_doors(corridors: &[i32], seed: u64) -> Vec<usize> {
+        crate::raws::load_raws();
+        crate::rng::reseed(&crate::rng::WorldSeed::new(seed));
+        let mut chain = BuilderChain::new(1, 20, 12, "Joined Rooms");
+        chain.start_with(Box::new(JoinedRooms {
+            corridors: corridors.to_vec(),
+        }));
+        chain.with(SecretDoors::new());
+        chain.build_map();
+
+        let build_data = &chain.build_data;
+        let hidden: Vec<usize> = build_data
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == TileType::SECRET_DOOR)
+            .map(|(idx, _)| idx)
+            .collect();
+        for idx in hidden.iter() {
+            assert!(!build_data.spawn_list.iter().any(|(i, _)| i == idx));
+        }
+        hidden
+    }
+
+    #[test]
+    fn the_only_way_to_the_exit_is_never_hidden() {
+        for seed in 0..40 {
+            assert!(secret_doors(&[3], seed).is_empty());
+        }
+    }
+
+    #[test]
+    fn a_door_with_another_way_round_can_be_hidden() {
+        let mut hidden_any = false;
+        for seed in 0..40 {
+            let hidden = secret_doors(&[3, 8], seed);
+            // Hiding both would cut the exit off
+            assert!(hidden.len() <= 1);
+            hidden_any |= !hidden.is_empty();
+        }
+        assert!(hidden_any);
+    }
+}
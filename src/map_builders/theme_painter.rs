@@ -0,0 +1,82 @@
+use super::{BuilderMap, MetaMapBuilder, XStart};
+use crate::map::ThemeRegion;
+use crate::Rect;
+
+/// How much of the map's width a `ThemePainter` covers.
+pub enum ThemeWidth {
+    Half,
+    Columns(i32),
+}
+
+/// Draws a band of the map, the full height of it, with another theme, or with none for the
+/// plain look. Transitional levels use it to set apart the part that belongs to the next biome.
+pub struct ThemePainter {
+    side: XStart,
+    width: ThemeWidth,
+    theme: Option<String>,
+}
+
+impl MetaMapBuilder for ThemePainter {
+    fn build_map(&mut self, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl ThemePainter {
+    pub fn new(side: XStart, width: ThemeWidth, theme: Option<String>) -> Box<ThemePainter> {
+        Box::new(ThemePainter { side, width, theme })
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let map_width = build_data.map.width;
+        let width = match self.width {
+            ThemeWidth::Half => map_width - map_width / 2,
+            ThemeWidth::Columns(columns) => i32::min(columns, map_width),
+        };
+        let x = match self.side {
+            XStart::LEFT => 0,
+            XStart::CENTER => (map_width - width) / 2,
+            XStart::RIGHT => map_width - width,
+        };
+        build_data.map.theme_regions.push(ThemeRegion {
+            area: Rect::new(x, 0, width, build_data.map.height),
+            theme: self.theme.clone(),
+        });
+        build_data.take_snapshot();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::BuilderChain;
+
+    /// The region a painter adds to a 21x10 map.
+    fn painted(side: XStart, width: ThemeWidth) -> ThemeRegion {
+        let mut chain = BuilderChain::new(1, 21, 10, "Painted");
+        ThemePainter::new(side, width, Some("forest".to_string())).build_map(&mut chain.build_data);
+        assert_eq!(chain.build_data.map.theme_regions.len(), 1);
+        chain.build_data.map.theme_regions.pop().unwrap()
+    }
+
+    #[test]
+    fn a_band_the_full_height_of_the_map_is_painted() {
+        let region = painted(XStart::RIGHT, ThemeWidth::Half);
+        assert_eq!(region.area, Rect::new(10, 0, 11, 10));
+        assert_eq!(region.theme, Some("forest".to_string()));
+
+        assert_eq!(
+            painted(XStart::LEFT, ThemeWidth::Columns(5)).area,
+            Rect::new(0, 0, 5, 10)
+        );
+        assert_eq!(
+            painted(XStart::CENTER, ThemeWidth::Columns(5)).area,
+            Rect::new(8, 0, 5, 10)
+        );
+        // A band wider than the map covers all of it
+        assert_eq!(
+            painted(XStart::RIGHT, ThemeWidth::Columns(40)).area,
+            Rect::new(0, 0, 21, 10)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use super::{BuilderChain, BuilderMap, InitialMapBuilder, Position, TileType};
-use std::collections::HashSet;
+use crate::spatial::SpatialIndex;
+use std::collections::BTreeSet;
 
 pub fn town_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
     let mut chain = BuilderChain::new(new_depth, width, height, "The Town of Downdelving");
@@ -43,7 +44,7 @@ impl TownBuilder {
 
         for y in wall_gap_y - 3..wall_gap_y + 4 {
             let exit_idx = build_data.map.xy_idx(build_data.width - 2, y);
-            build_data.map.tiles[exit_idx] = TileType::DownStairs;
+            build_data.map.tiles[exit_idx] = TileType::DOWN_STAIRS;
         }
 
         let building_size = self.sort_buildings(&buildings);
@@ -60,55 +61,61 @@ impl TownBuilder {
     }
 
     fn grass_layer(&mut self, build_data: &mut BuilderMap) {
+        let grass = TileType::named("Grass");
         // We'll start with a nice layer of grass
         for t in build_data.map.tiles.iter_mut() {
-            *t = TileType::Grass;
+            *t = grass;
         }
         build_data.take_snapshot();
     }
 
     fn water_and_piers(&mut self, build_data: &mut BuilderMap) {
-        let mut n = (crate::rng::roll_dice(1, 65535) as f32) / 65535f32;
+        let deep_water = TileType::named("Deep Water");
+        let shallow_water = TileType::named("Shallow Water");
+        let bridge = TileType::named("Bridge");
+        let mut n = (crate::rng::mapgen::roll_dice(1, 65535) as f32) / 65535f32;
         let mut water_width: Vec<i32> = Vec::new();
         for y in 0..build_data.height {
-            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + crate::rng::roll_dice(1, 6);
+            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + crate::rng::mapgen::roll_dice(1, 6);
             water_width.push(n_water);
             n += 0.1;
             for x in 0..n_water {
                 let idx = build_data.map.xy_idx(x, y);
-                build_data.map.tiles[idx] = TileType::DeepWater;
+                build_data.map.tiles[idx] = deep_water;
             }
             for x in n_water..n_water + 3 {
                 let idx = build_data.map.xy_idx(x, y);
-                build_data.map.tiles[idx] = TileType::ShallowWater;
+                build_data.map.tiles[idx] = shallow_water;
             }
         }
         build_data.take_snapshot();
 
         // Add piers
-        for _i in 0..crate::rng::roll_dice(1, 4) + 6 {
-            let y = crate::rng::roll_dice(1, build_data.height) - 1;
-            for x in 2 + crate::rng::roll_dice(1, 6)..water_width[y as usize] + 4 {
+        for _i in 0..crate::rng::mapgen::roll_dice(1, 4) + 6 {
+            let y = crate::rng::mapgen::roll_dice(1, build_data.height) - 1;
+            for x in 2 + crate::rng::mapgen::roll_dice(1, 6)..water_width[y as usize] + 4 {
                 let idx = build_data.map.xy_idx(x, y);
-                build_data.map.tiles[idx] = TileType::Bridge;
+                build_data.map.tiles[idx] = bridge;
             }
         }
         build_data.take_snapshot();
     }
 
-    fn town_walls(&mut self, build_data: &mut BuilderMap) -> (HashSet<usize>, i32) {
-        let mut available_building_tiles: HashSet<usize> = HashSet::new();
-        let wall_gap_y = crate::rng::roll_dice(1, build_data.height - 9) + 5;
+    fn town_walls(&mut self, build_data: &mut BuilderMap) -> (BTreeSet<usize>, i32) {
+        let gravel = TileType::named("Gravel");
+        let road = TileType::named("Road");
+        let mut available_building_tiles: BTreeSet<usize> = BTreeSet::new();
+        let wall_gap_y = crate::rng::mapgen::roll_dice(1, build_data.height - 9) + 5;
         for y in 1..build_data.height - 2 {
             if !(y > wall_gap_y - 4 && y < wall_gap_y + 4) {
                 let idx = build_data.map.xy_idx(30, y);
-                build_data.map.tiles[idx] = TileType::Wall;
-                build_data.map.tiles[idx - 1] = TileType::Floor;
+                build_data.map.tiles[idx] = TileType::WALL;
+                build_data.map.tiles[idx - 1] = TileType::FLOOR;
                 let idx_right = build_data.map.xy_idx(build_data.width - 2, y);
-                build_data.map.tiles[idx_right] = TileType::Wall;
+                build_data.map.tiles[idx_right] = TileType::WALL;
                 for x in 31..build_data.width - 2 {
                     let gravel_idx = build_data.map.xy_idx(x, y);
-                    build_data.map.tiles[gravel_idx] = TileType::Gravel;
+                    build_data.map.tiles[gravel_idx] = gravel;
                     if y > 2 && y < build_data.height - 1 {
                         available_building_tiles.insert(gravel_idx);
                     }
@@ -116,7 +123,7 @@ impl TownBuilder {
             } else {
                 for x in 30..build_data.width {
                     let road_idx = build_data.map.xy_idx(x, y);
-                    build_data.map.tiles[road_idx] = TileType::Road;
+                    build_data.map.tiles[road_idx] = road;
                 }
             }
         }
@@ -124,9 +131,9 @@ impl TownBuilder {
 
         for x in 30..build_data.width - 1 {
             let idx_top = build_data.map.xy_idx(x, 1);
-            build_data.map.tiles[idx_top] = TileType::Wall;
+            build_data.map.tiles[idx_top] = TileType::WALL;
             let idx_bot = build_data.map.xy_idx(x, build_data.height - 2);
-            build_data.map.tiles[idx_bot] = TileType::Wall;
+            build_data.map.tiles[idx_bot] = TileType::WALL;
         }
         build_data.take_snapshot();
 
@@ -136,15 +143,16 @@ impl TownBuilder {
     fn buildings(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) -> Vec<(i32, i32, i32, i32)> {
+        let wood_floor = TileType::named("Wood Floor");
         let mut buildings: Vec<(i32, i32, i32, i32)> = Vec::new();
         let mut n_buildings = 0;
         while n_buildings < 12 {
-            let bx = crate::rng::roll_dice(1, build_data.map.width - 32) + 30;
-            let by = crate::rng::roll_dice(1, build_data.map.height) - 2;
-            let bw = crate::rng::roll_dice(1, 8) + 4;
-            let bh = crate::rng::roll_dice(1, 8) + 4;
+            let bx = crate::rng::mapgen::roll_dice(1, build_data.map.width - 32) + 30;
+            let by = crate::rng::mapgen::roll_dice(1, build_data.map.height) - 2;
+            let bw = crate::rng::mapgen::roll_dice(1, 8) + 4;
+            let bh = crate::rng::mapgen::roll_dice(1, 8) + 4;
             let mut possible = true;
             for y in by..by + bh {
                 for x in bx..bx + bw {
@@ -164,7 +172,7 @@ impl TownBuilder {
                 for y in by..by + bh {
                     for x in bx..bx + bw {
                         let idx = build_data.map.xy_idx(x, y);
-                        build_data.map.tiles[idx] = TileType::WoodFloor;
+                        build_data.map.tiles[idx] = wood_floor;
                         available_building_tiles.remove(&idx);
                         available_building_tiles.remove(&(idx + 1));
                         available_building_tiles.remove(&(idx + build_data.width as usize));
@@ -181,24 +189,22 @@ impl TownBuilder {
         for y in 2..build_data.height - 2 {
             for x in 32..build_data.width - 2 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::WoodFloor {
+                if build_data.map.tiles[idx] == wood_floor {
                     let mut neighbors = 0;
-                    if build_data.map.tiles[idx - 1] != TileType::WoodFloor {
+                    if build_data.map.tiles[idx - 1] != wood_floor {
                         neighbors += 1;
                     }
-                    if build_data.map.tiles[idx + 1] != TileType::WoodFloor {
+                    if build_data.map.tiles[idx + 1] != wood_floor {
                         neighbors += 1;
                     }
-                    if build_data.map.tiles[idx - build_data.width as usize] != TileType::WoodFloor
-                    {
+                    if build_data.map.tiles[idx - build_data.width as usize] != wood_floor {
                         neighbors += 1;
                     }
-                    if build_data.map.tiles[idx + build_data.width as usize] != TileType::WoodFloor
-                    {
+                    if build_data.map.tiles[idx + build_data.width as usize] != wood_floor {
                         neighbors += 1;
                     }
                     if neighbors > 0 {
-                        mapclone.tiles[idx] = TileType::Wall;
+                        mapclone.tiles[idx] = TileType::WALL;
                     }
                 }
             }
@@ -216,7 +222,7 @@ impl TownBuilder {
     ) -> Vec<usize> {
         let mut doors = Vec::new();
         for building in buildings.iter() {
-            let door_x = building.0 + 1 + crate::rng::roll_dice(1, building.2 - 3);
+            let door_x = building.0 + 1 + crate::rng::mapgen::roll_dice(1, building.2 - 3);
             let cy = building.1 + (building.3 / 2);
             let idx = if cy > wall_gap_y {
                 // Door on the north wall
@@ -224,7 +230,7 @@ impl TownBuilder {
             } else {
                 build_data.map.xy_idx(door_x, building.1 + building.3 - 1)
             };
-            build_data.map.tiles[idx] = TileType::Floor;
+            build_data.map.tiles[idx] = TileType::FLOOR;
             build_data.spawn_list.push((idx, "Door".to_string()));
             doors.push(idx);
         }
@@ -233,17 +239,18 @@ impl TownBuilder {
     }
 
     fn add_paths(&mut self, build_data: &mut BuilderMap, doors: &[usize]) {
+        let road = TileType::named("Road");
         let mut roads = Vec::new();
         for y in 0..build_data.height {
             for x in 0..build_data.width {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::Road {
+                if build_data.map.tiles[idx] == road {
                     roads.push(idx);
                 }
             }
         }
 
-        build_data.map.populate_blocked();
+        let index = SpatialIndex::for_map(&build_data.map);
         for door_idx in doors.iter() {
             let mut nearest_roads: Vec<(usize, f32)> = Vec::new();
             let door_pt = rltk::Point::new(
@@ -265,11 +272,11 @@ impl TownBuilder {
             nearest_roads.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
 
             let destination = nearest_roads[0].0;
-            let path = rltk::a_star_search(*door_idx, destination, &build_data.map);
+            let path = rltk::a_star_search(*door_idx, destination, &index.pathing(&build_data.map));
             if path.success {
                 for step in path.steps.iter() {
                     let idx = *step as usize;
-                    build_data.map.tiles[idx] = TileType::Road;
+                    build_data.map.tiles[idx] = road;
                     roads.push(idx);
                 }
             }
@@ -329,12 +336,13 @@ impl TownBuilder {
         to_place: &mut Vec<&str>,
         player_idx: usize,
     ) {
+        let wood_floor = TileType::named("Wood Floor");
         for y in building.1..building.1 + building.3 {
             for x in building.0..building.0 + building.2 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::WoodFloor
+                if build_data.map.tiles[idx] == wood_floor
                     && idx != player_idx
-                    && crate::rng::roll_dice(1, 3) == 1
+                    && crate::rng::mapgen::roll_dice(1, 3) == 1
                     && !to_place.is_empty()
                 {
                     let entity_tag = to_place[0];
@@ -427,12 +435,13 @@ impl TownBuilder {
         building: &(i32, i32, i32, i32),
         build_data: &mut BuilderMap,
     ) {
+        let wood_floor = TileType::named("Wood Floor");
         for y in building.1..building.1 + building.3 {
             for x in building.0..building.0 + building.2 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::WoodFloor
+                if build_data.map.tiles[idx] == wood_floor
                     && idx != 0
-                    && crate::rng::roll_dice(1, 2) == 1
+                    && crate::rng::mapgen::roll_dice(1, 2) == 1
                 {
                     build_data.spawn_list.push((idx, "Rat".to_string()));
                 }
@@ -441,9 +450,10 @@ impl TownBuilder {
     }
 
     fn spawn_dockers(&mut self, build_data: &mut BuilderMap) {
+        let bridge = TileType::named("Bridge");
         for (idx, tt) in build_data.map.tiles.iter().enumerate() {
-            if *tt == TileType::Bridge && crate::rng::roll_dice(1, 12) == 1 {
-                let roll = crate::rng::roll_dice(1, 3);
+            if *tt == bridge && crate::rng::mapgen::roll_dice(1, 12) == 1 {
+                let roll = crate::rng::mapgen::roll_dice(1, 3);
                 match roll {
                     1 => build_data.spawn_list.push((idx, "Dock Worker".to_string())),
                     2 => build_data
@@ -458,11 +468,11 @@ impl TownBuilder {
     fn spawn_townsfolk(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) {
         for idx in available_building_tiles.iter() {
-            if crate::rng::roll_dice(1, 30) == 1 {
-                let roll = crate::rng::roll_dice(1, 4);
+            if crate::rng::mapgen::roll_dice(1, 30) == 1 {
+                let roll = crate::rng::mapgen::roll_dice(1, 4);
                 match roll {
                     1 => build_data.spawn_list.push((*idx, "Peasant".to_string())),
                     2 => build_data.spawn_list.push((*idx, "Drunk".to_string())),
@@ -51,8 +51,8 @@ impl VoronoiCellBuilder {
         let mut voronoi_seeds: Vec<(usize, rltk::Point)> = Vec::new();
 
         while voronoi_seeds.len() < self.n_seeds {
-            let vx = crate::rng::roll_dice(1, build_data.map.width - 1);
-            let vy = crate::rng::roll_dice(1, build_data.map.height - 1);
+            let vx = crate::rng::mapgen::roll_dice(1, build_data.map.width - 1);
+            let vy = crate::rng::mapgen::roll_dice(1, build_data.map.height - 1);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, rltk::Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -110,7 +110,7 @@ impl VoronoiCellBuilder {
                 }
 
                 if neighbors < 2 {
-                    build_data.map.tiles[my_idx] = TileType::Floor;
+                    build_data.map.tiles[my_idx] = TileType::FLOOR;
                 }
             }
             build_data.take_snapshot();
@@ -1,5 +1,5 @@
 use super::{spawner, BuilderMap, MetaMapBuilder, TileType};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct VoronoiSpawning {}
 
@@ -17,8 +17,8 @@ impl VoronoiSpawning {
 
     #[allow(clippy::map_entry)]
     fn build(&mut self, build_data: &mut BuilderMap) {
-        let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
-        let mut noise = rltk::FastNoise::seeded(crate::rng::roll_dice(1, 65536) as u64);
+        let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = rltk::FastNoise::seeded(crate::rng::mapgen::roll_dice(1, 65536) as u64);
         noise.set_noise_type(rltk::NoiseType::Cellular);
         noise.set_frequency(0.08);
         noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
@@ -26,7 +26,7 @@ impl VoronoiSpawning {
         for y in 1..build_data.map.height - 1 {
             for x in 1..build_data.map.width - 1 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::Floor {
+                if build_data.map.tiles[idx] == TileType::FLOOR {
                     let cell_value_f = noise.get_noise(x as f32, y as f32) * 10240.0;
                     let cell_value = cell_value_f as i32;
 
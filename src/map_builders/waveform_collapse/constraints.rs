@@ -64,13 +64,13 @@ pub fn build_patterns(
 
     // Dedupe
     if dedupe {
-        rltk::console::log(format!(
+        crate::console_log(format!(
             "Pre de-duplication, there are {} patterns",
             patterns.len()
         ));
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
-        patterns.extend(set.into_iter());
-        rltk::console::log(format!("There are {} patterns", patterns.len()));
+        let mut seen: HashSet<Vec<TileType>> = HashSet::new();
+        patterns.retain(|pattern| seen.insert(pattern.clone())); // dedup, keeping the order stable
+        crate::console_log(format!("There are {} patterns", patterns.len()));
     }
 
     patterns
@@ -96,25 +96,25 @@ pub fn render_pattern_to_map(
     for (x, northbound) in chunk.exits[0].iter().enumerate() {
         if *northbound {
             let map_idx = map.xy_idx(start_x + x as i32, start_y);
-            map.tiles[map_idx] = TileType::DownStairs;
+            map.tiles[map_idx] = TileType::DOWN_STAIRS;
         }
     }
     for (x, southbound) in chunk.exits[1].iter().enumerate() {
         if *southbound {
             let map_idx = map.xy_idx(start_x + x as i32, start_y + chunk_size - 1);
-            map.tiles[map_idx] = TileType::DownStairs;
+            map.tiles[map_idx] = TileType::DOWN_STAIRS;
         }
     }
     for (x, westbound) in chunk.exits[2].iter().enumerate() {
         if *westbound {
             let map_idx = map.xy_idx(start_x, start_y + x as i32);
-            map.tiles[map_idx] = TileType::DownStairs;
+            map.tiles[map_idx] = TileType::DOWN_STAIRS;
         }
     }
     for (x, eastbound) in chunk.exits[3].iter().enumerate() {
         if *eastbound {
             let map_idx = map.xy_idx(start_x + chunk_size - 1, start_y + x as i32);
-            map.tiles[map_idx] = TileType::DownStairs;
+            map.tiles[map_idx] = TileType::DOWN_STAIRS;
         }
     }
 }
@@ -139,28 +139,28 @@ pub fn patterns_to_constraints(patterns: Vec<Vec<TileType>>, chunk_size: i32) ->
         for x in 0..chunk_size {
             // Check for north-bound exits
             let north_idx = tile_idx_in_chunk(chunk_size, x, 0);
-            if new_chunk.pattern[north_idx] == TileType::Floor {
+            if new_chunk.pattern[north_idx] == TileType::FLOOR {
                 new_chunk.exits[0][x as usize] = true;
                 n_exits += 1;
             }
 
             // Check for south-bound exits
             let south_idx = tile_idx_in_chunk(chunk_size, x, chunk_size - 1);
-            if new_chunk.pattern[south_idx] == TileType::Floor {
+            if new_chunk.pattern[south_idx] == TileType::FLOOR {
                 new_chunk.exits[1][x as usize] = true;
                 n_exits += 1;
             }
 
             // Check for west-bound exits
             let west_idx = tile_idx_in_chunk(chunk_size, 0, x);
-            if new_chunk.pattern[west_idx] == TileType::Floor {
+            if new_chunk.pattern[west_idx] == TileType::FLOOR {
                 new_chunk.exits[2][x as usize] = true;
                 n_exits += 1;
             }
 
             // Check for east-bound exits
             let east_idx = tile_idx_in_chunk(chunk_size, chunk_size - 1, x);
-            if new_chunk.pattern[east_idx] == TileType::Floor {
+            if new_chunk.pattern[east_idx] == TileType::FLOOR {
                 new_chunk.exits[3][x as usize] = true;
                 n_exits += 1;
             }
@@ -32,12 +32,7 @@ impl WaveformCollapseBuilder {
 
         let old_map = build_data.map.clone();
 
-        build_data.map = Map::new(
-            build_data.map.depth,
-            build_data.width,
-            build_data.height,
-            &build_data.map.name,
-        );
+        build_data.map = blank_map(build_data);
         build_data.spawn_list.clear();
         build_data.rooms = None;
         build_data.corridors = None;
@@ -69,12 +64,7 @@ impl WaveformCollapseBuilder {
         chunk_size: i32,
         build_data: &mut BuilderMap,
     ) {
-        build_data.map = Map::new(
-            build_data.map.depth,
-            build_data.width,
-            build_data.height,
-            &build_data.map.name,
-        );
+        build_data.map = blank_map(build_data);
         let mut counter = 0;
         let mut x = 1;
         let mut y = 1;
@@ -90,12 +80,7 @@ impl WaveformCollapseBuilder {
                 if y + chunk_size > build_data.map.height {
                     // Move to the next page
                     build_data.take_snapshot();
-                    build_data.map = Map::new(
-                        build_data.map.depth,
-                        build_data.width,
-                        build_data.height,
-                        &build_data.map.name,
-                    );
+                    build_data.map = blank_map(build_data);
 
                     x = 1;
                     y = 1;
@@ -107,3 +92,15 @@ impl WaveformCollapseBuilder {
         build_data.take_snapshot();
     }
 }
+
+/// An empty map the size of the one being built, drawn with the same theme.
+fn blank_map(build_data: &BuilderMap) -> Map {
+    let mut map = Map::new(
+        build_data.map.depth,
+        build_data.width,
+        build_data.height,
+        &build_data.map.name,
+    );
+    map.theme = build_data.map.theme.clone();
+    map
+}
@@ -0,0 +1,287 @@
+use crate::map_builders::{BuilderChain, BuilderMap};
+use crate::rng::WorldSeed;
+use crate::spatial::SpatialIndex;
+use crate::{LevelId, Map, TileType};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Maps with less of their area walkable than this are reported as degenerate.
+pub const MIN_WALKABLE_RATIO: f32 = 0.1;
+/// Maps whose exit is closer to the start than this are reported as degenerate.
+pub const MIN_PATH_LENGTH: f32 = 10.0;
+
+/// What one run of a builder chain produced.
+#[derive(Serialize, Debug)]
+pub struct MapReport {
+    pub seed: u64,
+    pub map_name: String,
+    /// Why the chain didn't finish, if it panicked.
+    pub failure: Option<String>,
+    pub walkable_ratio: f32,
+    /// Walkable regions just before the first `CullUnreachable`, if the chain has one.
+    pub regions_before_cull: Option<usize>,
+    pub regions: usize,
+    /// The cost of walking from the start to the down stairs, if both exist and are connected.
+    pub path_length: Option<f32>,
+    pub spawns: BTreeMap<String, usize>,
+    /// Anything that makes the map a bad level, even though it was built.
+    pub problems: Vec<String>,
+}
+
+/// Builds a map with the chain `make_chain` returns, seeding map generation from `seed` the way
+/// the game seeds a level at `depth`, and measures it. The built map comes back with the report,
+/// unless the chain failed.
+pub fn analyze_map<F: Fn() -> BuilderChain>(
+    make_chain: &F,
+    depth: i32,
+    seed: u64,
+) -> (MapReport, Option<BuilderMap>) {
+    crate::rng::reseed_level(&WorldSeed::new(seed), &LevelId::main(depth));
+    let mut report = MapReport {
+        seed,
+        map_name: String::new(),
+        failure: None,
+        walkable_ratio: 0.0,
+        regions_before_cull: None,
+        regions: 0,
+        path_length: None,
+        spawns: BTreeMap::new(),
+        problems: Vec::new(),
+    };
+
+    let mut regions_before_cull = None;
+    let built = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut chain = make_chain();
+        chain.build_map_inspecting(|builder, build_data| {
+            if builder == "CullUnreachable" && regions_before_cull.is_none() {
+                regions_before_cull = Some(count_regions(&build_data.map));
+            }
+        });
+        chain.build_data
+    }));
+    report.regions_before_cull = regions_before_cull;
+
+    match built {
+        Ok(build_data) => {
+            measure(&build_data, &mut report);
+            (report, Some(build_data))
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "the builder panicked".to_string());
+            report.failure = Some(message);
+            (report, None)
+        }
+    }
+}
+
+fn measure(build_data: &BuilderMap, report: &mut MapReport) {
+    let map = &build_data.map;
+    report.map_name = map.name.clone();
+    let walkable = map.tiles.iter().filter(|tile| tile.is_walkable()).count();
+    report.walkable_ratio = walkable as f32 / map.tiles.len() as f32;
+    report.regions = count_regions(map);
+    for (_idx, name) in build_data
+        .spawn_list
+        .iter()
+        .filter(|(_idx, name)| name != "None")
+    {
+        *report.spawns.entry(name.clone()).or_insert(0) += 1;
+    }
+
+    let exit = map
+        .tiles
+        .iter()
+        .position(|tile| *tile == TileType::DOWN_STAIRS);
+    match (&build_data.starting_position, exit) {
+        (None, _) => report
+            .problems
+            .push("there's no starting position".to_string()),
+        (Some(_), None) => {
+            // The bottom of a branch is a dead end
+            if crate::raws::level_below(&crate::raws::RAWS.lock().unwrap(), &map.level).is_some() {
+                report.problems.push("there's no exit".to_string())
+            }
+        }
+        (Some(start), Some(exit)) => {
+            let start_idx = map.xy_idx(start.x, start.y);
+            let index = SpatialIndex::for_map(map);
+            let dijkstra_map = rltk::DijkstraMap::new(
+                map.width as usize,
+                map.height as usize,
+                &[start_idx],
+                &index.pathing(map),
+                2000.0,
+            );
+            let distance = dijkstra_map.map[exit];
+            if distance == f32::MAX {
+                report
+                    .problems
+                    .push("the exit can't be reached from the start".to_string());
+            } else {
+                report.path_length = Some(distance);
+                if distance < MIN_PATH_LENGTH {
+                    report
+                        .problems
+                        .push(format!("the exit is only {:.1} from the start", distance));
+                }
+            }
+        }
+    }
+
+    if report.walkable_ratio < MIN_WALKABLE_RATIO {
+        report.problems.push(format!(
+            "only {:.1}% of the map is walkable",
+            report.walkable_ratio * 100.0
+        ));
+    }
+    if report.regions > 1 {
+        report.problems.push(format!(
+            "the walkable area is split into {} regions",
+            report.regions
+        ));
+    }
+}
+
+/// How many separate areas of walkable tiles the map has, moving diagonally as the game does.
+/// Secret doors join the areas either side of them, since they open once they're found.
+fn count_regions(map: &Map) -> usize {
+    let passable =
+        |idx: usize| map.tiles[idx].is_walkable() || map.tiles[idx] == TileType::SECRET_DOOR;
+    let mut seen = vec![false; map.tiles.len()];
+    let mut regions = 0;
+    for start in 0..map.tiles.len() {
+        if seen[start] || !passable(start) {
+            continue;
+        }
+        regions += 1;
+        seen[start] = true;
+        let mut open = VecDeque::new();
+        open.push_back(start);
+        while let Some(idx) = open.pop_front() {
+            let x = idx as i32 % map.width;
+            let y = idx as i32 / map.width;
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= map.width || ny >= map.height {
+                        continue;
+                    }
+                    let next = map.xy_idx(nx, ny);
+                    if !seen[next] && passable(next) {
+                        seen[next] = true;
+                        open.push_back(next);
+                    }
+                }
+            }
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::{CullUnreachable, InitialMapBuilder};
+    use crate::Position;
+
+    /// Two rooms with nothing joining them, and the exit two steps from the start.
+    struct TwoRooms {}
+
+    impl InitialMapBuilder for TwoRooms {
+        fn build_map(&mut self, build_data: &mut BuilderMap) {
+            for y in 2..8 {
+                for x in (1..6).chain(12..18) {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = TileType::FLOOR;
+                }
+            }
+            build_data.starting_position = Some(Position { x: 2, y: 4 });
+            let exit = build_data.map.xy_idx(4, 4);
+            build_data.map.tiles[exit] = TileType::DOWN_STAIRS;
+        }
+    }
+
+    fn two_rooms() -> BuilderChain {
+        let mut chain = BuilderChain::new(1, 20, 10, "Two Rooms");
+        chain.start_with(Box::new(TwoRooms {}));
+        chain
+    }
+
+    #[test]
+    fn a_degenerate_map_has_its_problems_reported() {
+        crate::raws::load_raws();
+        let (report, built) = analyze_map(&two_rooms, 1, 1);
+        assert!(built.is_some());
+        assert_eq!(report.map_name, "Two Rooms");
+        assert_eq!(report.failure, None);
+        assert_eq!(report.walkable_ratio, 66.0 / 200.0);
+        assert_eq!(report.regions, 2);
+        assert_eq!(report.regions_before_cull, None);
+        assert_eq!(report.path_length, Some(2.0));
+        assert_eq!(
+            report.problems,
+            vec![
+                "the exit is only 2.0 from the start".to_string(),
+                "the walkable area is split into 2 regions".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn regions_are_counted_before_they_are_culled() {
+        crate::raws::load_raws();
+        let culled = || {
+            let mut chain = two_rooms();
+            chain.with(CullUnreachable::new());
+            chain
+        };
+        let (report, _) = analyze_map(&culled, 1, 1);
+        assert_eq!(report.regions_before_cull, Some(2));
+        assert_eq!(report.regions, 1);
+        assert_eq!(report.walkable_ratio, 30.0 / 200.0);
+    }
+
+    #[test]
+    fn a_secret_door_joins_the_rooms_either_side() {
+        crate::raws::load_raws();
+        let mut chain = two_rooms();
+        chain.build_map();
+        let mut map = chain.build_data.map;
+        assert_eq!(count_regions(&map), 2);
+        for x in 6..12 {
+            let idx = map.xy_idx(x, 4);
+            map.tiles[idx] = TileType::FLOOR;
+        }
+        let door = map.xy_idx(8, 4);
+        map.tiles[door] = TileType::SECRET_DOOR;
+        assert_eq!(count_regions(&map), 1);
+    }
+
+    #[test]
+    fn a_chain_that_panics_is_reported_as_a_failure() {
+        let no_starter = || BuilderChain::new(1, 20, 10, "Nothing");
+        let (report, built) = analyze_map(&no_starter, 1, 1);
+        assert!(built.is_none());
+        assert_eq!(
+            report.failure.as_deref(),
+            Some("Cannot run a map builder chain without a starting build system")
+        );
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_report() {
+        crate::raws::load_raws();
+        let chain = || crate::map_builders::depth_builder(3, 80, 50);
+        let (first, _) = analyze_map(&chain, 3, 9);
+        let (second, _) = analyze_map(&chain, 3, 9);
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(&second).unwrap()
+        );
+    }
+}
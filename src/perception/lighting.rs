@@ -1,91 +1,126 @@
-use crate::rng;
-use rltk::RGB;
-use std::cmp;
+use super::field_of_view::field_of_view;
+use crate::map::Map;
+use rltk::{Point, RGB};
 
-/// Something that gives off light.
+/// Light shining out from one spot on the map.
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct LightSource {
-    /// The color of light produced.
+pub struct Light {
+    pub position: Point,
     pub color: RGB,
-    /// Radius of lighted area.
-    pub radius: i32,
-    /// The intensity of the light produced.
-    pub intensity: i32,
+    /// How far the light reaches, fading to nothing at the edge.
+    pub range: i32,
+    /// How bright it is where it starts; 1.0 is as bright as daylight.
+    pub intensity: f32,
+    /// How much it flickers, from 0.0 for a steady light to 1.0.
+    pub flicker: f32,
 }
 
-/// Something that gives off light.
-impl LightSource {
-    /// Constructor.
-    pub fn new(color: RGB, radius: i32, intensity: i32) -> LightSource {
-        LightSource {
-            color: color,
-            radius: radius,
-            intensity: intensity,
-        }
+/// How long a flickering light takes to go from one brightness to the next, in milliseconds.
+const FLICKER_STEP_MS: f32 = 120.0;
+
+/// How the light on each tile flickers. Flickering is only for show: the map's light is steady,
+/// and the camera dims it frame by frame from this, so it's never saved and the game never reads
+/// it.
+#[derive(Default)]
+pub struct LightFlicker {
+    /// For each tile, how much of its light can flicker away and where the light doing the most
+    /// flickering there shines from, so everything it lights flickers together.
+    tiles: Vec<Option<(f32, Point)>>,
+    /// How long the flickering has been running.
+    clock_ms: f32,
+}
+
+impl LightFlicker {
+    pub fn advance(&mut self, frame_time_ms: f32) {
+        self.clock_ms = (self.clock_ms + frame_time_ms) % (FLICKER_STEP_MS * 1024.0);
     }
 
-    /// Compute intensity of light at a distance.
-    pub fn intensity_at(&self, x: i32, y: i32, x2: i32, y2: i32) -> i32 {
-        let dx = (x2 - x).abs();
-        let dy = (y2 - y).abs();
-        if dx > self.radius || dy > self.radius {
-            0
-        } else {
-            let distance = ((dx as f64).powi(2) + (dy as f64).powi(2)).sqrt();
-            let coefficient = -(self.intensity as f64) / (self.radius as f64);
-            let result: i32 = (self.intensity as f64 + distance * coefficient) as i32;
-            let result = cmp::max(result, 0);
-            result
+    /// How much of its steady light tile `idx` has right now.
+    pub fn factor(&self, idx: usize) -> f32 {
+        match self.tiles.get(idx).copied().flatten() {
+            Some((amount, source)) => {
+                let step = self.clock_ms / FLICKER_STEP_MS;
+                let from = flicker_noise(source, step as u32);
+                let to = flicker_noise(source, step as u32 + 1);
+                1.0 - amount * (from + (to - from) * step.fract())
+            }
+            None => 1.0,
         }
     }
+}
 
-    /// Transform a color at a specified distance.
-    pub fn transform_color_at(&self, color: RGB, x: i32, y: i32, x2: i32, y2: i32) -> RGB {
-        let intensity = self.intensity_at(x, y, x2, y2);
-        let multiplier = intensity as f64 / 512 as f64;
-        let red = color.r;
-        let green = color.g;
-        let blue = color.b;
-        let r_diff = (self.color.r as i32 - red as i32).abs();
-        let g_diff = (self.color.g as i32 - green as i32).abs();
-        let b_diff = (self.color.b as i32 - blue as i32).abs();
-        let new_r = (red as f64 + (r_diff as f64 * multiplier)) as u8;
-        let new_g = (green as f64 + (g_diff as f64 * multiplier)) as u8;
-        let new_b = (blue as f64 + (b_diff as f64 * multiplier)) as u8;
-        RGB::from_u8(new_r, new_g, new_b)
-    }
+/// A brightness dip between 0.0 and 1.0 for the light at `source` on flicker step `step`; it's
+/// hashed rather than rolled so it never touches the game's random streams.
+fn flicker_noise(source: Point, step: u32) -> f32 {
+    let mut hash = (source.x as u32)
+        .wrapping_mul(0x9E37_79B1)
+        .wrapping_add((source.y as u32).wrapping_mul(0x85EB_CA77))
+        .wrapping_add(step.wrapping_mul(0xC2B2_AE3D));
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+    (hash & 0xFFFF) as f32 / 65535.0
 }
 
-/// A factory.
-#[derive(Clone, Copy, Debug)]
-pub enum Factory {
-    /// A candle provides very little light.
-    Candle,
-    /// A torch provides more and stronger light.
-    Torch,
-    /// A patch of phosphorescent moss.
-    Moss,
-    /// A completely random light source.
-    Random,
+/// How bright `light` is at `point`, which it's assumed to reach.
+pub fn intensity_at(light: &Light, point: Point) -> f32 {
+    let distance = rltk::DistanceAlg::Pythagoras.distance2d(light.position, point);
+    let range = light.range as f32;
+    (light.intensity * (range - distance) / range).max(0.0)
 }
 
-/// A factory.
-impl Factory {
-    /// Creates a light source.
-    pub fn create(self) -> LightSource {
-        match self {
-            Factory::Candle => LightSource::new(RGB::from_u8(255, 127, 255), 6, 64),
-            Factory::Torch => LightSource::new(RGB::from_u8(255, 127, 0), 10, 96),
-            Factory::Moss => LightSource::new(RGB::from_u8(173, 223, 173), 5, 32),
-            Factory::Random => LightSource::new(
-                RGB::from_u8(
-                    rng::range(0, 5) as u8 * 60,
-                    rng::range(0, 5) as u8 * 60,
-                    rng::range(0, 5) as u8 * 60,
-                ),
-                rng::range(10, 15),
-                rng::range(128, 255),
-            ),
+/// Lights the map. Every tile starts with the level's ambient light and gets the light of each
+/// source that can see it added on, so walls cast shadows and colors mix. Where flickering lights
+/// reach, `flicker` notes how much of the light they could take away.
+pub fn illuminate(map: &mut Map, lights: &[Light], flicker: &mut LightFlicker) {
+    let ambient = map.ambient();
+    for light in map.light.iter_mut() {
+        *light = ambient;
+    }
+    // How much each tile is lit, and the strongest flickering there: how much and from where
+    let mut brightness: Vec<f32> = map
+        .light
+        .iter()
+        .map(|light| brightness_of(*light))
+        .collect();
+    let mut flickering: Vec<Option<(f32, Point)>> = vec![None; map.light.len()];
+
+    for light in lights.iter() {
+        let mut lit: Vec<(usize, Point)> =
+            field_of_view(light.position.x, light.position.y, light.range, map)
+                .into_iter()
+                .filter(|p| p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1)
+                .map(|p| (map.xy_idx(p.x, p.y), p))
+                .collect();
+        // The shadowcaster can see a tile from more than one octant
+        lit.sort_by_key(|(idx, _)| *idx);
+        lit.dedup_by_key(|(idx, _)| *idx);
+        for (idx, point) in lit {
+            let added = light.color * intensity_at(light, point);
+            map.light[idx] = map.light[idx] + added;
+            brightness[idx] += brightness_of(added);
+            let flickers = brightness_of(added) * light.flicker;
+            if flickers > 0.0 && flickering[idx].is_none_or(|(most, _)| flickers > most) {
+                flickering[idx] = Some((flickers, light.position));
+            }
         }
     }
+
+    flicker.tiles = flickering
+        .into_iter()
+        .zip(brightness)
+        .map(|(flickering, brightness)| {
+            flickering.map(|(flickers, source)| ((flickers / brightness).min(1.0), source))
+        })
+        .collect();
+
+    for light in map.light.iter_mut() {
+        light.r = light.r.min(1.0);
+        light.g = light.g.min(1.0);
+        light.b = light.b.min(1.0);
+    }
+}
+
+fn brightness_of(light: RGB) -> f32 {
+    (light.r + light.g + light.b) / 3.0
 }
@@ -0,0 +1,73 @@
+use rltk::Point;
+use serde::{Deserialize, Serialize};
+
+/// Everything the player can decide to do on their turn, independent of how it was input.
+/// Entities are referred to by their save markers (see `replay::entity_ref`), so commands can be
+/// written to a replay and read back.
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub enum PlayerCommand {
+    Move {
+        delta_x: i32,
+        delta_y: i32,
+    },
+    SkipTurn,
+    /// Spends a turn looking around the tiles next to the player for secret doors and hidden things.
+    Search,
+    NextLevel,
+    PreviousLevel,
+    PickUp,
+    UseConsumable {
+        slot: i32,
+    },
+    CastSpell {
+        slot: i32,
+    },
+    CycleTarget,
+    Fire,
+    UseItem {
+        item: u32,
+        target: Option<Point>,
+    },
+    CastSpellAt {
+        spell: u32,
+        target: Option<Point>,
+    },
+    DropItem {
+        item: u32,
+    },
+    RemoveItem {
+        item: u32,
+    },
+    RemoveCurse {
+        item: u32,
+    },
+    Identify {
+        item: u32,
+    },
+    SellItem {
+        vendor: u32,
+        item: u32,
+    },
+    BuyItem {
+        vendor: u32,
+        tag: String,
+        price: f32,
+    },
+    Cheat {
+        cheat: Cheat,
+    },
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Cheat {
+    TeleportToExit,
+    Heal,
+    Reveal,
+    GodMode,
+    LevelUp,
+    Eat,
+    /// Reads the raws again, optionally rebuilding the monsters on this level from them.
+    ReloadRaws {
+        rebuild_mobs: bool,
+    },
+}
@@ -1,13 +1,19 @@
 use super::{
-    raws::Reaction, Attributes, BlocksTile, BlocksVisibility, Door, EntityMoved, Equipped, Faction,
-    HungerClock, HungerState, Item, MainGameRunState, Map, Name, Player, Pools, Position,
-    Renderable, RunState, State, Target, TileType, Vendor, Viewshed, WantsToCastSpell,
-    WantsToMelee, WantsToPickupItem, WantsToShoot, Weapon,
+    raws::Reaction, Attributes, BlocksTile, BlocksVisibility, CursedItem, Digger, Door,
+    EntityMoved, Equipped, Faction, Hidden, HungerClock, HungerState, Item, MainGameRunState, Map,
+    MasterDungeonMap, Name, Player, Pools, Position, Renderable, RunState, State, Target, TileType,
+    Vendor, Viewshed, WantsToCastSpell, WantsToDropItem, WantsToMelee, WantsToPickupItem,
+    WantsToRemoveItem, WantsToShoot, WantsToUseItem, Weapon,
 };
+use crate::effects::{add_effect, EffectType, Targets};
+use crate::notices_secret;
+use crate::spatial::SpatialIndex;
 use crate::vendor::VendorMode;
 use rltk::{Point, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 use std::cmp::{max, min};
+mod command;
+pub use command::{Cheat, PlayerCommand};
 
 fn get_player_target_list(ecs: &mut World) -> Vec<(f32, Entity)> {
     let mut possible_targets: Vec<(f32, Entity)> = Vec::new();
@@ -16,6 +22,7 @@ fn get_player_target_list(ecs: &mut World) -> Vec<(f32, Entity)> {
     let equipped = ecs.read_storage::<Equipped>();
     let weapon = ecs.read_storage::<Weapon>();
     let map = ecs.fetch::<Map>();
+    let spatial = ecs.fetch::<SpatialIndex>();
     let positions = ecs.read_storage::<Position>();
     let factions = ecs.read_storage::<Faction>();
     for (equipped, weapon) in (&equipped, &weapon).join() {
@@ -29,7 +36,7 @@ fn get_player_target_list(ecs: &mut World) -> Vec<(f32, Entity)> {
                     let distance_to_target = rltk::DistanceAlg::Pythagoras
                         .distance2d(*tile_point, rltk::Point::new(player_pos.x, player_pos.y));
                     if distance_to_target < range as f32 {
-                        crate::spatial::for_each_tile_content(tile_idx, |possible_target| {
+                        spatial.for_each_tile_content(tile_idx, |possible_target| {
                             if possible_target != *player_entity
                                 && factions.get(possible_target).is_some()
                             {
@@ -73,6 +80,8 @@ fn fire_on_target(ecs: &mut World) -> RunState {
         let names = ecs.read_storage::<Name>();
         if let Some(name) = names.get(target) {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Combat)
+                .entity(target)
                 .append("You fire at")
                 .color(rltk::CYAN)
                 .append(&name.name)
@@ -135,6 +144,7 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let entities = ecs.entities();
     let combat_stats = ecs.read_storage::<Attributes>();
     let map = ecs.fetch::<Map>();
+    let spatial = ecs.fetch::<SpatialIndex>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
     let mut entity_moved = ecs.write_storage::<EntityMoved>();
     let mut doors = ecs.write_storage::<Door>();
@@ -143,6 +153,8 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let mut renderables = ecs.write_storage::<Renderable>();
     let factions = ecs.read_storage::<Faction>();
     let vendors = ecs.read_storage::<Vendor>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let diggers = ecs.read_storage::<Digger>();
     let mut result = RunState::MainGame {
         runstate: MainGameRunState::AwaitingInput,
     };
@@ -163,82 +175,113 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
         }
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
-        result = crate::spatial::for_each_tile_content_with_gamemode(
-            destination_idx,
-            |potential_target| {
-                if let Some(_vendor) = vendors.get(potential_target) {
-                    return Some(RunState::MainGame {
-                        runstate: MainGameRunState::ShowVendor {
-                            vendor: potential_target,
-                            mode: VendorMode::Sell,
-                        },
-                    });
-                }
+        result = spatial.for_each_tile_content_with_gamemode(destination_idx, |potential_target| {
+            if let Some(_vendor) = vendors.get(potential_target) {
+                return Some(RunState::MainGame {
+                    runstate: MainGameRunState::ShowVendor {
+                        vendor: potential_target,
+                        mode: VendorMode::Sell,
+                    },
+                });
+            }
 
-                let mut hostile = true;
-                if combat_stats.get(potential_target).is_some() {
-                    if let Some(faction) = factions.get(potential_target) {
-                        let reaction = crate::raws::faction_reaction(
-                            &faction.name,
-                            "Player",
-                            &crate::raws::RAWS.lock().unwrap(),
-                        );
-                        if reaction != Reaction::Attack {
-                            hostile = false;
-                        }
-                    }
-                }
-                if !hostile {
-                    // Note that we want to move the bystander
-                    swap_entities.push((potential_target, pos.x, pos.y));
-
-                    // Move the player
-                    pos.x = min(map.width - 1, max(0, pos.x + delta_x));
-                    pos.y = min(map.height - 1, max(0, pos.y + delta_y));
-                    entity_moved
-                        .insert(entity, EntityMoved {})
-                        .expect("Unable to insert marker");
-
-                    viewshed.dirty = true;
-                    let mut ppos = ecs.write_resource::<Point>();
-                    ppos.x = pos.x;
-                    ppos.y = pos.y;
-                    return Some(RunState::MainGame {
-                        runstate: MainGameRunState::Ticking,
-                    });
-                } else {
-                    let target = combat_stats.get(potential_target);
-                    if let Some(_target) = target {
-                        wants_to_melee
-                            .insert(
-                                entity,
-                                WantsToMelee {
-                                    target: potential_target,
-                                },
-                            )
-                            .expect("Add target failed");
-                        return Some(RunState::MainGame {
-                            runstate: MainGameRunState::Ticking,
-                        });
+            let mut hostile = true;
+            if combat_stats.get(potential_target).is_some() {
+                if let Some(faction) = factions.get(potential_target) {
+                    let reaction = crate::raws::faction_reaction(
+                        &faction.name,
+                        "Player",
+                        &crate::raws::RAWS.lock().unwrap(),
+                    );
+                    if reaction != Reaction::Attack {
+                        hostile = false;
                     }
                 }
-                let door = doors.get_mut(potential_target);
-                if let Some(door) = door {
-                    door.open = true;
-                    blocks_visibility.remove(potential_target);
-                    blocks_movement.remove(potential_target);
-                    let glyph = renderables.get_mut(potential_target).unwrap();
-                    glyph.glyph = rltk::to_cp437('/');
-                    viewshed.dirty = true;
+            }
+            if !hostile {
+                // Note that we want to move the bystander
+                swap_entities.push((potential_target, pos.x, pos.y));
+
+                // Move the player
+                pos.x = min(map.width - 1, max(0, pos.x + delta_x));
+                pos.y = min(map.height - 1, max(0, pos.y + delta_y));
+                entity_moved
+                    .insert(entity, EntityMoved {})
+                    .expect("Unable to insert marker");
+
+                viewshed.dirty = true;
+                let mut ppos = ecs.write_resource::<Point>();
+                ppos.x = pos.x;
+                ppos.y = pos.y;
+                return Some(RunState::MainGame {
+                    runstate: MainGameRunState::Ticking,
+                });
+            } else {
+                let target = combat_stats.get(potential_target);
+                if let Some(_target) = target {
+                    wants_to_melee
+                        .insert(
+                            entity,
+                            WantsToMelee {
+                                target: potential_target,
+                            },
+                        )
+                        .expect("Add target failed");
                     return Some(RunState::MainGame {
                         runstate: MainGameRunState::Ticking,
                     });
                 }
-                None
-            },
-        );
+            }
+            let door = doors.get_mut(potential_target);
+            if let Some(door) = door {
+                door.open = true;
+                blocks_visibility.remove(potential_target);
+                blocks_movement.remove(potential_target);
+                let glyph = renderables.get_mut(potential_target).unwrap();
+                glyph.glyph = rltk::to_cp437('/');
+                viewshed.dirty = true;
+                return Some(RunState::MainGame {
+                    runstate: MainGameRunState::Ticking,
+                });
+            }
+            None
+        });
+
+        // Walking into rock with a digging tool in hand digs into it
+        let dig_power = (&equipped, &diggers)
+            .join()
+            .filter(|(equipped, _)| equipped.owner == entity)
+            .map(|(_, digger)| digger.power)
+            .max();
+        let nothing_there = result
+            == RunState::MainGame {
+                runstate: MainGameRunState::AwaitingInput,
+            };
+        if let Some(power) = dig_power {
+            if nothing_there && map.dig_hardness(destination_idx).is_some() {
+                add_effect(
+                    Some(entity),
+                    EffectType::Dig { power },
+                    Targets::Tile {
+                        tile_idx: destination_idx as i32,
+                    },
+                );
+                return RunState::MainGame {
+                    runstate: MainGameRunState::Ticking,
+                };
+            }
+            if nothing_there && map.tiles[destination_idx].hardness().is_some() {
+                crate::gamelog::Logger::new()
+                    .append(format!(
+                        "The {} is too hard to dig.",
+                        map.tiles[destination_idx].name().to_lowercase()
+                    ))
+                    .log();
+                return result;
+            }
+        }
 
-        if !crate::spatial::is_blocked(destination_idx) {
+        if !spatial.is_blocked(destination_idx) {
             pos.x = min(map.width - 1, max(0, pos.x + delta_x));
             pos.y = min(map.height - 1, max(0, pos.y + delta_y));
             entity_moved
@@ -253,12 +296,12 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                 runstate: MainGameRunState::Ticking,
             };
             match map.tiles[destination_idx] {
-                TileType::DownStairs => {
+                TileType::DOWN_STAIRS => {
                     result = RunState::MainGame {
                         runstate: MainGameRunState::NextLevel,
                     }
                 }
-                TileType::UpStairs => {
+                TileType::UP_STAIRS => {
                     result = RunState::MainGame {
                         runstate: MainGameRunState::PreviousLevel,
                     }
@@ -283,7 +326,7 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     let player_pos = ecs.fetch::<Point>();
     let map = ecs.fetch::<Map>();
     let player_idx = map.xy_idx(player_pos.x, player_pos.y);
-    if map.tiles[player_idx] == TileType::DownStairs {
+    if map.tiles[player_idx] == TileType::DOWN_STAIRS {
         true
     } else {
         crate::gamelog::Logger::new()
@@ -297,7 +340,7 @@ pub fn try_previous_level(ecs: &mut World) -> bool {
     let player_pos = ecs.fetch::<Point>();
     let map = ecs.fetch::<Map>();
     let player_idx = map.xy_idx(player_pos.x, player_pos.y);
-    if map.tiles[player_idx] == TileType::UpStairs {
+    if map.tiles[player_idx] == TileType::UP_STAIRS {
         true
     } else {
         crate::gamelog::Logger::new()
@@ -323,6 +366,7 @@ fn get_item(ecs: &mut World) {
 
     match target_item {
         None => crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Loot)
             .append("There is nothing here to pick up.")
             .log(),
         Some(item) => {
@@ -340,18 +384,71 @@ fn get_item(ecs: &mut World) {
     }
 }
 
+fn search(ecs: &mut World) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+    let intelligence = ecs
+        .read_storage::<Attributes>()
+        .get(player_entity)
+        .map_or(0, |attr| attr.intelligence.bonus);
+    let map = ecs.fetch::<Map>();
+    let spatial = ecs.fetch::<SpatialIndex>();
+    let names = ecs.read_storage::<Name>();
+    let mut hidden = ecs.write_storage::<Hidden>();
+
+    let mut found = false;
+    for y in player_pos.y - 1..=player_pos.y + 1 {
+        for x in player_pos.x - 1..=player_pos.x + 1 {
+            if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+                continue;
+            }
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::SECRET_DOOR && notices_secret(intelligence, true) {
+                add_effect(
+                    Some(player_entity),
+                    EffectType::RevealSecretDoor,
+                    Targets::Tile {
+                        tile_idx: idx as i32,
+                    },
+                );
+                found = true;
+            }
+            spatial.for_each_tile_content(idx, |entity| {
+                if hidden.get(entity).is_some() && notices_secret(intelligence, true) {
+                    if let Some(name) = names.get(entity) {
+                        crate::gamelog::Logger::new()
+                            .entity(entity)
+                            .append("You spotted:")
+                            .npc_name(&name.name)
+                            .log();
+                    }
+                    hidden.remove(entity);
+                    found = true;
+                }
+            });
+        }
+    }
+
+    if !found {
+        crate::gamelog::Logger::new()
+            .append("You search around, but find nothing.")
+            .log();
+    }
+}
+
 fn skip_turn(ecs: &mut World) -> RunState {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let factions = ecs.read_storage::<Faction>();
 
     let worldmap_resource = ecs.fetch::<Map>();
+    let spatial = ecs.fetch::<SpatialIndex>();
 
     let mut can_heal = true;
     let viewshed = viewshed_components.get(*player_entity).unwrap();
     for tile in viewshed.visible_tiles.iter() {
         let idx = worldmap_resource.xy_idx(tile.x, tile.y);
-        crate::spatial::for_each_tile_content(idx, |entity_id| {
+        spatial.for_each_tile_content(idx, |entity_id| {
             let faction = factions.get(entity_id);
             match faction {
                 None => {}
@@ -393,13 +490,13 @@ fn skip_turn(ecs: &mut World) -> RunState {
     }
 }
 
-fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
+fn use_consumable_hotkey(ecs: &mut World, key: i32) -> RunState {
     use super::{Consumable, InBackpack, WantsToUseItem};
 
-    let consumables = gs.ecs.read_storage::<Consumable>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let entities = gs.ecs.entities();
+    let consumables = ecs.read_storage::<Consumable>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
     let mut carried_consumables = Vec::new();
     for (entity, carried_by, _consumable) in (&entities, &backpack, &consumables).join() {
         if carried_by.owner == *player_entity {
@@ -409,8 +506,7 @@ fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
 
     if (key as usize) < carried_consumables.len() {
         use crate::components::Ranged;
-        if let Some(ranged) = gs
-            .ecs
+        if let Some(ranged) = ecs
             .read_storage::<Ranged>()
             .get(carried_consumables[key as usize])
         {
@@ -421,7 +517,7 @@ fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
                 },
             };
         }
-        let mut intent = gs.ecs.write_storage::<WantsToUseItem>();
+        let mut intent = ecs.write_storage::<WantsToUseItem>();
         intent
             .insert(
                 *player_entity,
@@ -440,23 +536,23 @@ fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
     }
 }
 
-fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
+fn use_spell_hotkey(ecs: &mut World, key: i32) -> RunState {
     use super::raws::find_spell_entity;
     use super::KnownSpells;
 
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let known_spells_storage = gs.ecs.read_storage::<KnownSpells>();
+    let player_entity = ecs.fetch::<Entity>();
+    let known_spells_storage = ecs.read_storage::<KnownSpells>();
     let known_spells = &known_spells_storage.get(*player_entity).unwrap().spells;
 
     if (key as usize) < known_spells.len() {
-        let pools = gs.ecs.read_storage::<Pools>();
+        let pools = ecs.read_storage::<Pools>();
         let player_pools = pools.get(*player_entity).unwrap();
         if player_pools.mana.current >= known_spells[key as usize].mana_cost {
             if let Some(spell_entity) =
-                find_spell_entity(&gs.ecs, &known_spells[key as usize].display_name)
+                find_spell_entity(ecs, &known_spells[key as usize].display_name)
             {
                 use crate::components::Ranged;
-                if let Some(ranged) = gs.ecs.read_storage::<Ranged>().get(spell_entity) {
+                if let Some(ranged) = ecs.read_storage::<Ranged>().get(spell_entity) {
                     return RunState::MainGame {
                         runstate: MainGameRunState::ShowTargeting {
                             range: ranged.range,
@@ -464,7 +560,7 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
                         },
                     };
                 };
-                let mut intent = gs.ecs.write_storage::<WantsToCastSpell>();
+                let mut intent = ecs.write_storage::<WantsToCastSpell>();
                 intent
                     .insert(
                         *player_entity,
@@ -490,6 +586,7 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
     }
 }
 
+/// Translates a key press into a player command, or a menu to open.
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
     // Hotkeys
     if ctx.shift && ctx.key.is_some() {
@@ -506,7 +603,7 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             _ => None,
         };
         if let Some(key) = key {
-            return use_consumable_hotkey(gs, key - 1);
+            return execute_command(&mut gs.ecs, PlayerCommand::UseConsumable { slot: key - 1 });
         }
     }
     if ctx.control && ctx.key.is_some() {
@@ -523,12 +620,12 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             _ => None,
         };
         if let Some(key) = key {
-            return use_spell_hotkey(gs, key - 1);
+            return execute_command(&mut gs.ecs, PlayerCommand::CastSpell { slot: key - 1 });
         }
     }
 
     // Player movement
-    match ctx.key {
+    let command = match ctx.key {
         None => {
             return RunState::MainGame {
                 runstate: MainGameRunState::AwaitingInput,
@@ -536,59 +633,64 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
         } // Nothing happened
         Some(key) => match key {
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                return try_move_player(-1, 0, &mut gs.ecs)
+                PlayerCommand::Move {
+                    delta_x: -1,
+                    delta_y: 0,
+                }
             }
 
             VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                return try_move_player(1, 0, &mut gs.ecs)
+                PlayerCommand::Move {
+                    delta_x: 1,
+                    delta_y: 0,
+                }
             }
 
             VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                return try_move_player(0, -1, &mut gs.ecs)
+                PlayerCommand::Move {
+                    delta_x: 0,
+                    delta_y: -1,
+                }
             }
 
             VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                return try_move_player(0, 1, &mut gs.ecs)
+                PlayerCommand::Move {
+                    delta_x: 0,
+                    delta_y: 1,
+                }
             }
 
             // Diagonals
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => {
-                return try_move_player(1, -1, &mut gs.ecs)
-            }
+            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => PlayerCommand::Move {
+                delta_x: 1,
+                delta_y: -1,
+            },
 
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => {
-                return try_move_player(-1, -1, &mut gs.ecs)
-            }
+            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => PlayerCommand::Move {
+                delta_x: -1,
+                delta_y: -1,
+            },
 
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                return try_move_player(1, 1, &mut gs.ecs)
-            }
+            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => PlayerCommand::Move {
+                delta_x: 1,
+                delta_y: 1,
+            },
 
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                return try_move_player(-1, 1, &mut gs.ecs)
-            }
+            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => PlayerCommand::Move {
+                delta_x: -1,
+                delta_y: 1,
+            },
 
             // Skip Turn
-            VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
+            VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => PlayerCommand::SkipTurn,
+            VirtualKeyCode::S => PlayerCommand::Search,
 
             // Level changes
-            VirtualKeyCode::Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::MainGame {
-                        runstate: MainGameRunState::NextLevel,
-                    };
-                }
-            }
-            VirtualKeyCode::Comma => {
-                if try_previous_level(&mut gs.ecs) {
-                    return RunState::MainGame {
-                        runstate: MainGameRunState::PreviousLevel,
-                    };
-                }
-            }
+            VirtualKeyCode::Period => PlayerCommand::NextLevel,
+            VirtualKeyCode::Comma => PlayerCommand::PreviousLevel,
 
             // Picking up items
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
+            VirtualKeyCode::G => PlayerCommand::PickUp,
             VirtualKeyCode::I => {
                 return RunState::MainGame {
                     runstate: MainGameRunState::ShowInventory,
@@ -605,14 +707,17 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
                 }
             }
 
-            // Ranged
-            VirtualKeyCode::V => {
-                cycle_target(&mut gs.ecs);
+            // Message log
+            VirtualKeyCode::M => {
+                gs.main_game_state.log_viewer.open();
                 return RunState::MainGame {
-                    runstate: MainGameRunState::AwaitingInput,
+                    runstate: MainGameRunState::ShowLog,
                 };
             }
-            VirtualKeyCode::F => return fire_on_target(&mut gs.ecs),
+
+            // Ranged
+            VirtualKeyCode::V => PlayerCommand::CycleTarget,
+            VirtualKeyCode::F => PlayerCommand::Fire,
 
             // Save and Quit
             VirtualKeyCode::Escape => {
@@ -634,8 +739,373 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
                 }
             }
         },
+    };
+
+    execute_command(&mut gs.ecs, command)
+}
+
+/// Applies a player command to the world and returns the state the game should move to. Every
+/// command passes through here, so this is also where input is recorded for replays.
+pub fn execute_command(ecs: &mut World, command: PlayerCommand) -> RunState {
+    crate::replay::record_command(ecs, &command);
+    let newrunstate = apply_command(ecs, command);
+    if newrunstate
+        != (RunState::MainGame {
+            runstate: MainGameRunState::AwaitingInput,
+        })
+    {
+        crate::gamelog::record_event("Turn", 1);
+    }
+    newrunstate
+}
+
+fn apply_command(ecs: &mut World, command: PlayerCommand) -> RunState {
+    match command {
+        PlayerCommand::Move { delta_x, delta_y } => return try_move_player(delta_x, delta_y, ecs),
+        PlayerCommand::SkipTurn => return skip_turn(ecs),
+        PlayerCommand::Search => search(ecs),
+        PlayerCommand::NextLevel => {
+            if try_next_level(ecs) {
+                return RunState::MainGame {
+                    runstate: MainGameRunState::NextLevel,
+                };
+            }
+        }
+        PlayerCommand::PreviousLevel => {
+            if try_previous_level(ecs) {
+                return RunState::MainGame {
+                    runstate: MainGameRunState::PreviousLevel,
+                };
+            }
+        }
+        PlayerCommand::PickUp => get_item(ecs),
+        PlayerCommand::UseConsumable { slot } => return use_consumable_hotkey(ecs, slot),
+        PlayerCommand::CastSpell { slot } => return use_spell_hotkey(ecs, slot),
+        PlayerCommand::CycleTarget => {
+            cycle_target(ecs);
+            return RunState::MainGame {
+                runstate: MainGameRunState::AwaitingInput,
+            };
+        }
+        PlayerCommand::Fire => return fire_on_target(ecs),
+        PlayerCommand::UseItem { item, target } => {
+            let item = match crate::replay::resolve_ref(ecs, item) {
+                Some(item) => item,
+                None => return invalid_command(),
+            };
+            ecs.write_storage::<WantsToUseItem>()
+                .insert(*ecs.fetch::<Entity>(), WantsToUseItem { item, target })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::CastSpellAt { spell, target } => {
+            let spell = match crate::replay::resolve_ref(ecs, spell) {
+                Some(spell) => spell,
+                None => return invalid_command(),
+            };
+            ecs.write_storage::<WantsToCastSpell>()
+                .insert(*ecs.fetch::<Entity>(), WantsToCastSpell { spell, target })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::DropItem { item } => {
+            let item = match crate::replay::resolve_ref(ecs, item) {
+                Some(item) => item,
+                None => return invalid_command(),
+            };
+            ecs.write_storage::<WantsToDropItem>()
+                .insert(*ecs.fetch::<Entity>(), WantsToDropItem { item })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::RemoveItem { item } => {
+            let item = match crate::replay::resolve_ref(ecs, item) {
+                Some(item) => item,
+                None => return invalid_command(),
+            };
+            ecs.write_storage::<WantsToRemoveItem>()
+                .insert(*ecs.fetch::<Entity>(), WantsToRemoveItem { item })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::RemoveCurse { item } => {
+            let item = match crate::replay::resolve_ref(ecs, item) {
+                Some(item) => item,
+                None => return invalid_command(),
+            };
+            ecs.write_storage::<CursedItem>().remove(item);
+        }
+        PlayerCommand::Identify { item } => {
+            let item = match crate::replay::resolve_ref(ecs, item) {
+                Some(item) => item,
+                None => return invalid_command(),
+            };
+            if let Some(name) = ecs.read_storage::<Name>().get(item) {
+                let mut dm = ecs.fetch_mut::<MasterDungeonMap>();
+                dm.identified_items.insert(name.name.clone());
+            }
+        }
+        PlayerCommand::SellItem { vendor, item } => {
+            let (vendor, item) = match (
+                crate::replay::resolve_ref(ecs, vendor),
+                crate::replay::resolve_ref(ecs, item),
+            ) {
+                (Some(vendor), Some(item)) => (vendor, item),
+                _ => return invalid_command(),
+            };
+            crate::vendor::sell_item(ecs, item);
+            return RunState::MainGame {
+                runstate: MainGameRunState::ShowVendor {
+                    vendor,
+                    mode: VendorMode::Sell,
+                },
+            };
+        }
+        PlayerCommand::BuyItem { vendor, tag, price } => {
+            let vendor = match crate::replay::resolve_ref(ecs, vendor) {
+                Some(vendor) => vendor,
+                None => return invalid_command(),
+            };
+            crate::vendor::buy_item(ecs, &tag, price);
+            return RunState::MainGame {
+                runstate: MainGameRunState::ShowVendor {
+                    vendor,
+                    mode: VendorMode::Buy,
+                },
+            };
+        }
+        PlayerCommand::Cheat { cheat } => return apply_cheat(ecs, cheat),
     }
     RunState::MainGame {
         runstate: MainGameRunState::Ticking,
     }
 }
+
+/// A command pointing at something that's gone does nothing, and costs no turn.
+fn invalid_command() -> RunState {
+    RunState::MainGame {
+        runstate: MainGameRunState::AwaitingInput,
+    }
+}
+
+fn apply_cheat(ecs: &mut World, cheat: Cheat) -> RunState {
+    let player = *ecs.fetch::<Entity>();
+    match cheat {
+        Cheat::TeleportToExit => {
+            return RunState::MainGame {
+                runstate: MainGameRunState::NextLevel,
+            }
+        }
+        Cheat::Heal => {
+            let mut pools = ecs.write_storage::<Pools>();
+            let player_pools = pools.get_mut(player).unwrap();
+            player_pools.hit_points.current = player_pools.hit_points.max;
+        }
+        Cheat::Reveal => {
+            let mut map = ecs.fetch_mut::<Map>();
+            for v in map.revealed_tiles.iter_mut() {
+                *v = true;
+            }
+        }
+        Cheat::GodMode => {
+            let mut pools = ecs.write_storage::<Pools>();
+            let player_pools = pools.get_mut(player).unwrap();
+            player_pools.god_mode = true;
+        }
+        Cheat::LevelUp => {
+            crate::effects::add_effect(
+                None,
+                crate::effects::EffectType::AddExperienceLevel,
+                crate::effects::Targets::Single { target: player },
+            );
+            return RunState::MainGame {
+                runstate: MainGameRunState::Ticking,
+            };
+        }
+        Cheat::Eat => {
+            crate::effects::add_effect(
+                None,
+                crate::effects::EffectType::WellFed,
+                crate::effects::Targets::Single { target: player },
+            );
+            return RunState::MainGame {
+                runstate: MainGameRunState::Ticking,
+            };
+        }
+        Cheat::ReloadRaws { rebuild_mobs } => match crate::raws::reload_raws() {
+            Ok(()) => {
+                crate::raws::respawn_all_spells(ecs);
+                if rebuild_mobs {
+                    crate::raws::respawn_mobs_on_level(ecs);
+                }
+                crate::gamelog::Logger::new()
+                    .append("The raws have been reloaded.")
+                    .log();
+            }
+            Err(problems) => {
+                crate::gamelog::Logger::new()
+                    .color(rltk::RED)
+                    .append("Unable to reload the raws:")
+                    .log();
+                for problem in problems {
+                    crate::console_log(&problem);
+                    crate::gamelog::Logger::new()
+                        .color(rltk::RED)
+                        .append(problem)
+                        .log();
+                }
+            }
+        },
+    }
+    RunState::MainGame {
+        runstate: MainGameRunState::AwaitingInput,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_locals::with_game_locals;
+    use crate::headless::Simulation;
+    use crate::raws::{spawn_named_entity, SpawnType, RAWS};
+    use crate::Cheat;
+
+    fn logged(ecs: &mut World, text: &str) -> bool {
+        with_game_locals(ecs, |_| {
+            crate::gamelog::clone_log()
+                .iter()
+                .any(|entry| entry.fragments.iter().any(|fragment| fragment.text == text))
+        })
+    }
+
+    /// Stands the player at `(x, y)` with a pickaxe in hand, and turns the tile to their east to
+    /// `east`.
+    fn digger_at(simulation: &mut Simulation, x: i32, y: i32, east: TileType) {
+        simulation.step(PlayerCommand::Cheat {
+            cheat: Cheat::GodMode,
+        });
+        let ecs = &mut simulation.state.ecs;
+        let player = *ecs.fetch::<Entity>();
+        spawn_named_entity(
+            &RAWS.lock().unwrap(),
+            ecs,
+            "Pickaxe",
+            SpawnType::Equipped { by: player },
+        );
+
+        let mut map = ecs.fetch_mut::<Map>();
+        let mut spatial = ecs.fetch_mut::<SpatialIndex>();
+        let mut positions = ecs.write_storage::<Position>();
+        let pos = positions.get(player).unwrap();
+        let (from, to, beside) = (
+            map.xy_idx(pos.x, pos.y),
+            map.xy_idx(x, y),
+            map.xy_idx(x + 1, y),
+        );
+        // Clear whatever was there out of the way
+        for idx in [to, beside] {
+            for entity in spatial.get_tile_content_clone(idx) {
+                if entity == player {
+                    continue;
+                }
+                spatial.remove_entity(entity, idx);
+                positions.remove(entity);
+            }
+        }
+        map.tiles[to] = TileType::FLOOR;
+        map.tiles[beside] = east;
+        spatial.update_terrain(&map, to);
+        spatial.update_terrain(&map, beside);
+        let pos = positions.get_mut(player).unwrap();
+        pos.x = x;
+        pos.y = y;
+        spatial.move_entity(player, from, to);
+        *ecs.write_resource::<Point>() = Point::new(x, y);
+    }
+
+    #[test]
+    fn searching_next_to_a_secret_door_finds_it() {
+        let mut simulation = Simulation::with_seed(42);
+        let (door, x, y) = {
+            let player_pos = *simulation.state.ecs.fetch::<Point>();
+            let mut map = simulation.state.ecs.fetch_mut::<Map>();
+            let (x, y) = (player_pos.x + 1, player_pos.y);
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::SECRET_DOOR;
+            (idx, x, y)
+        };
+
+        for _ in 0..20 {
+            if simulation.state.ecs.fetch::<Map>().tiles[door] != TileType::SECRET_DOOR {
+                break;
+            }
+            simulation.step(PlayerCommand::Search);
+        }
+
+        let ecs = &mut simulation.state.ecs;
+        assert_eq!(ecs.fetch::<Map>().tiles[door], TileType::FLOOR);
+        assert!(ecs.fetch::<Map>().revealed_tiles[door]);
+        assert!(
+            (&ecs.read_storage::<Name>(), &ecs.read_storage::<Position>())
+                .join()
+                .any(|(name, pos)| name.name == "Door" && pos.x == x && pos.y == y)
+        );
+        assert!(logged(ecs, "You found a secret door!"));
+    }
+
+    #[test]
+    fn walking_into_rock_with_a_pickaxe_digs_through_it_over_a_few_turns() {
+        let mut simulation = Simulation::with_seed(42);
+        digger_at(&mut simulation, 5, 5, TileType::WALL);
+        let wall = simulation.state.ecs.fetch::<Map>().xy_idx(6, 5);
+        let east = PlayerCommand::Move {
+            delta_x: 1,
+            delta_y: 0,
+        };
+
+        // A wall is as hard as three swings of a pickaxe
+        simulation.step(east.clone());
+        simulation.step(east.clone());
+        {
+            let map = simulation.state.ecs.fetch::<Map>();
+            assert_eq!(map.tiles[wall], TileType::WALL);
+            assert_eq!(map.dig_progress.get(&wall), Some(&4));
+        }
+        assert!(logged(&mut simulation.state.ecs, "You dig into the wall."));
+
+        simulation.step(east.clone());
+        {
+            let map = simulation.state.ecs.fetch::<Map>();
+            assert_eq!(map.tiles[wall], TileType::named("Gravel"));
+            assert!(map.dig_progress.is_empty());
+            assert!(!simulation
+                .state
+                .ecs
+                .fetch::<SpatialIndex>()
+                .is_blocked(wall));
+        }
+        assert!(logged(
+            &mut simulation.state.ecs,
+            "The wall crumbles into gravel."
+        ));
+
+        simulation.step(east);
+        assert_eq!(*simulation.state.ecs.fetch::<Point>(), Point::new(6, 5));
+    }
+
+    #[test]
+    fn the_walls_around_the_edge_of_the_map_are_too_hard_to_dig() {
+        let mut simulation = Simulation::with_seed(42);
+        let width = simulation.state.ecs.fetch::<Map>().width;
+        digger_at(&mut simulation, width - 2, 5, TileType::WALL);
+        let edge = simulation.state.ecs.fetch::<Map>().xy_idx(width - 1, 5);
+        for _ in 0..5 {
+            simulation.step(PlayerCommand::Move {
+                delta_x: 1,
+                delta_y: 0,
+            });
+        }
+
+        let ecs = &mut simulation.state.ecs;
+        assert_eq!(ecs.fetch::<Map>().tiles[edge], TileType::WALL);
+        assert!(ecs.fetch::<Map>().dig_progress.is_empty());
+        assert_eq!(*ecs.fetch::<Point>(), Point::new(width - 2, 5));
+        assert!(logged(ecs, "The wall is too hard to dig."));
+    }
+}
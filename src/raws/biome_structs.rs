@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A kind of level, and the builder chain that makes it.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Biome {
+    pub name: String,
+    /// What the level is called in game. Several biomes can share one.
+    pub map_name: String,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    /// Lets vaults and other content pick the biomes they belong in.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only built for the branches that name it, never picked for a depth on the main line.
+    #[serde(default)]
+    pub branch_only: bool,
+    /// The theme from `raws/tiles.json` the level is drawn with; without one it has the plain look.
+    pub theme: Option<String>,
+    /// The light everywhere on the level, as a #rrggbb color; without it the level is in daylight,
+    /// or dark if it's underground.
+    pub ambient_light: Option<String>,
+    pub initial: BuilderStep,
+    /// Run in order after `initial`; this is also where the start and exit are placed.
+    #[serde(default)]
+    pub builders: Vec<BuilderStep>,
+}
+
+/// A map builder by name, such as `{ "builder" : "DrunkardsWalkBuilder", "preset" :
+/// "winding_passages" }`. Any other fields are the builder's parameters.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BuilderStep {
+    pub builder: String,
+    #[serde(flatten)]
+    pub params: HashMap<String, String>,
+}
+
+/// A side branch of the dungeon, entered by an extra staircase down from a level of its parent.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Branch {
+    pub name: String,
+    /// The branch the entrance is in; `Dungeon` is the main line down from the town.
+    pub parent: String,
+    /// The level of `parent` the entrance is on.
+    pub parent_level: i32,
+    /// The biome each level is built as, from the top down. The branch is as deep as the list.
+    pub levels: Vec<String>,
+}
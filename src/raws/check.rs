@@ -0,0 +1,600 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// Everything wrong with `raws`, one problem per line: names that are used twice, references to
+/// things that don't exist, and values that can't be parsed. Nothing stops at the first problem,
+/// so authors can fix them all in one go.
+pub fn check_raws(raws: &Raws) -> Vec<String> {
+    let mut check = Checker::new(raws);
+    check.duplicate_names();
+    check.spawn_table();
+    check.loot_tables();
+    check.factions();
+    check.items();
+    check.mobs();
+    check.props();
+    check.effects();
+    check.dice();
+    check.biomes();
+    check.vaults();
+    check.branches();
+    check.tiles();
+    check.spells_taught();
+    check.problems.sort();
+    check.problems.dedup();
+    check.problems
+}
+
+struct Checker<'a> {
+    raws: &'a Raws,
+    problems: Vec<String>,
+    items: HashSet<&'a str>,
+    /// The magic and traited versions of templated items, which are made when the raws load.
+    generated_items: HashSet<String>,
+    spells: HashSet<&'a str>,
+    loot_tables: HashSet<&'a str>,
+    factions: HashSet<&'a str>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(raws: &'a Raws) -> Checker<'a> {
+        Checker {
+            raws,
+            problems: Vec::new(),
+            items: raws.items.iter().map(|item| item.name.as_str()).collect(),
+            generated_items: generated_item_names(raws),
+            spells: raws
+                .spells
+                .iter()
+                .map(|spell| spell.name.as_str())
+                .collect(),
+            loot_tables: raws
+                .loot_tables
+                .iter()
+                .map(|loot| loot.name.as_str())
+                .collect(),
+            factions: raws
+                .faction_table
+                .iter()
+                .map(|faction| faction.name.as_str())
+                .collect(),
+        }
+    }
+
+    fn problem<T: ToString>(&mut self, owner: &str, problem: T) {
+        self.problems
+            .push(format!("{}: {}", owner, problem.to_string()));
+    }
+
+    fn duplicate_names(&mut self) {
+        // Items, mobs and props are all spawned by name, so they share one set of names.
+        let spawnable = self
+            .raws
+            .items
+            .iter()
+            .map(|item| ("Item", item.name.as_str()))
+            .chain(self.raws.mobs.iter().map(|mob| ("Mob", mob.name.as_str())))
+            .chain(
+                self.raws
+                    .props
+                    .iter()
+                    .map(|prop| ("Prop", prop.name.as_str())),
+            )
+            .collect::<Vec<_>>();
+        self.duplicates(spawnable);
+
+        let spells = self
+            .raws
+            .spells
+            .iter()
+            .map(|spell| ("Spell", spell.name.as_str()));
+        self.duplicates(spells.collect());
+        let loot = self
+            .raws
+            .loot_tables
+            .iter()
+            .map(|loot| ("Loot table", loot.name.as_str()));
+        self.duplicates(loot.collect());
+        let factions = self
+            .raws
+            .faction_table
+            .iter()
+            .map(|faction| ("Faction", faction.name.as_str()));
+        self.duplicates(factions.collect());
+        let traits = self
+            .raws
+            .weapon_traits
+            .iter()
+            .map(|weapon_trait| ("Weapon trait", weapon_trait.name.as_str()));
+        self.duplicates(traits.collect());
+        let biomes = self
+            .raws
+            .biomes
+            .iter()
+            .map(|biome| ("Biome", biome.name.as_str()));
+        self.duplicates(biomes.collect());
+        let vaults = self
+            .raws
+            .vaults
+            .iter()
+            .map(|vault| ("Vault", vault.name.as_str()));
+        self.duplicates(vaults.collect());
+        let branches = self
+            .raws
+            .branches
+            .iter()
+            .map(|branch| ("Branch", branch.name.as_str()))
+            .chain(std::iter::once(("Branch", crate::map::MAIN_BRANCH)));
+        self.duplicates(branches.collect());
+        let tiles = self
+            .raws
+            .tiles
+            .iter()
+            .map(|tile| ("Tile", tile.name.as_str()));
+        self.duplicates(tiles.collect());
+    }
+
+    fn duplicates(&mut self, names: Vec<(&str, &str)>) {
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for (kind, name) in names {
+            if let Some(first_kind) = seen.insert(name, kind) {
+                self.problem(
+                    &format!("{} {}", kind, name),
+                    format!(
+                        "the name is already used by a {}",
+                        first_kind.to_lowercase()
+                    ),
+                );
+            }
+        }
+    }
+
+    fn is_item(&self, name: &str) -> bool {
+        self.items.contains(name) || self.generated_items.contains(name)
+    }
+
+    fn is_spawnable(&self, name: &str) -> bool {
+        self.is_item(name)
+            || self.raws.mobs.iter().any(|mob| mob.name == name)
+            || self.raws.props.iter().any(|prop| prop.name == name)
+    }
+
+    fn spawn_table(&mut self) {
+        for spawn in self.raws.spawn_table.iter() {
+            if !self.is_spawnable(&spawn.name) {
+                self.problem("Spawn table", format!("{} doesn't exist", spawn.name));
+            }
+        }
+    }
+
+    fn loot_tables(&mut self) {
+        for loot in self.raws.loot_tables.iter() {
+            for drop in loot.drops.iter() {
+                if !self.is_item(&drop.name) {
+                    self.problem(
+                        &format!("Loot table {}", loot.name),
+                        format!("item {} doesn't exist", drop.name),
+                    );
+                }
+            }
+        }
+    }
+
+    fn factions(&mut self) {
+        for faction in self.raws.faction_table.iter() {
+            for other in faction.responses.keys() {
+                if other != "Default" && !self.factions.contains(other.as_str()) {
+                    self.problem(
+                        &format!("Faction {}", faction.name),
+                        format!("faction {} doesn't exist", other),
+                    );
+                }
+            }
+        }
+    }
+
+    fn items(&mut self) {
+        for item in self.raws.items.iter() {
+            let owner = format!("Item {}", item.name);
+            if let Some(renderable) = &item.renderable {
+                self.renderable(&owner, renderable);
+            }
+            if let Some(weapon) = &item.weapon {
+                if weapon.range != "melee" && weapon.range.parse::<i32>().is_err() {
+                    self.problem(
+                        &owner,
+                        format!("range {} isn't melee or a number", weapon.range),
+                    );
+                }
+            }
+            if let Some(wearable) = &item.wearable {
+                if !EQUIPMENT_SLOTS.contains(&wearable.slot.as_str()) {
+                    self.problem(&owner, format!("slot {} doesn't exist", wearable.slot));
+                }
+            }
+            self.digs(&owner, item.digs);
+        }
+    }
+
+    fn mobs(&mut self) {
+        for mob in self.raws.mobs.iter() {
+            let owner = format!("Mob {}", mob.name);
+            if let Some(renderable) = &mob.renderable {
+                self.renderable(&owner, renderable);
+            }
+            for item in mob.equipped.iter().flatten() {
+                if !self.is_item(item) {
+                    self.problem(&owner, format!("equipped item {} doesn't exist", item));
+                }
+            }
+            if let Some(loot_table) = &mob.loot_table {
+                if !self.loot_tables.contains(loot_table.as_str()) {
+                    self.problem(&owner, format!("loot table {} doesn't exist", loot_table));
+                }
+            }
+            self.digs(&owner, mob.digs);
+            if let Some(faction) = &mob.faction {
+                if !self.factions.contains(faction.as_str()) {
+                    self.problem(&owner, format!("faction {} doesn't exist", faction));
+                }
+            }
+            for ability in mob.abilities.iter().chain(mob.on_death.iter()).flatten() {
+                if !self.spells.contains(ability.spell.as_str()) {
+                    self.problem(&owner, format!("spell {} doesn't exist", ability.spell));
+                }
+            }
+            if let Some(light) = &mob.light {
+                self.light(&owner, light);
+            }
+            if let Some(color) = mob.bleeds.as_ref().and_then(|b| b.color.as_ref()) {
+                self.color(&owner, color);
+            }
+            if !MOVEMENT_MODES.contains(&mob.movement.as_str()) {
+                self.problem(&owner, format!("movement {} doesn't exist", mob.movement));
+            }
+        }
+    }
+
+    fn props(&mut self) {
+        for prop in self.raws.props.iter() {
+            let owner = format!("Prop {}", prop.name);
+            if let Some(renderable) = &prop.renderable {
+                self.renderable(&owner, renderable);
+            }
+            if let Some(light) = &prop.light {
+                self.light(&owner, light);
+            }
+        }
+    }
+
+    fn effects(&mut self) {
+        let mut effect_lists: Vec<(String, &HashMap<String, String>)> = Vec::new();
+        for item in self.raws.items.iter() {
+            if let Some(consumable) = &item.consumable {
+                effect_lists.push((format!("Item {}", item.name), &consumable.effects));
+            }
+            if let Some(proc_effects) = item.weapon.as_ref().and_then(|w| w.proc_effects.as_ref()) {
+                effect_lists.push((format!("Item {}", item.name), proc_effects));
+            }
+        }
+        for prop in self.raws.props.iter() {
+            if let Some(entry_trigger) = &prop.entry_trigger {
+                effect_lists.push((format!("Prop {}", prop.name), &entry_trigger.effects));
+            }
+        }
+        for spell in self.raws.spells.iter() {
+            effect_lists.push((format!("Spell {}", spell.name), &spell.effects));
+        }
+        for weapon_trait in self.raws.weapon_traits.iter() {
+            effect_lists.push((
+                format!("Weapon trait {}", weapon_trait.name),
+                &weapon_trait.effects,
+            ));
+        }
+
+        for (owner, effects) in effect_lists {
+            for (name, value) in effects.iter() {
+                if let Err(err) = crate::effects::EFFECTS.check(name, value) {
+                    self.problem(&owner, err);
+                }
+            }
+        }
+    }
+
+    fn dice(&mut self) {
+        for item in self.raws.items.iter() {
+            if let Some(weapon) = &item.weapon {
+                self.dice_roll(&format!("Item {}", item.name), &weapon.base_damage);
+            }
+        }
+        for mob in self.raws.mobs.iter() {
+            let owner = format!("Mob {}", mob.name);
+            if let Some(gold) = &mob.gold {
+                self.dice_roll(&owner, gold);
+            }
+            for attack in mob.natural.iter().flat_map(|n| n.attacks.iter().flatten()) {
+                self.dice_roll(&owner, &attack.damage);
+            }
+        }
+    }
+
+    fn biomes(&mut self) {
+        use crate::map_builders::named;
+        for biome in self.raws.biomes.iter() {
+            let owner = format!("Biome {}", biome.name);
+            if biome.min_depth > biome.max_depth {
+                self.problem(&owner, "min_depth is deeper than max_depth");
+            }
+            if let Err(err) = named::initial_builder(&biome.initial) {
+                self.problem(&owner, err);
+            }
+            for step in biome.builders.iter() {
+                if let Err(err) = named::meta_builder(step) {
+                    self.problem(&owner, err);
+                }
+            }
+            if let Some(color) = &biome.ambient_light {
+                self.color(&owner, color);
+            }
+            let painted = biome
+                .builders
+                .iter()
+                .filter(|step| step.builder == "ThemePainter")
+                .filter_map(|step| step.params.get("theme"));
+            for theme in biome.theme.iter().chain(painted) {
+                if !self
+                    .raws
+                    .tiles
+                    .iter()
+                    .any(|tile| tile.themes.contains_key(theme))
+                {
+                    self.problem(&owner, format!("no tile has the theme {}", theme));
+                }
+            }
+        }
+    }
+
+    fn vaults(&mut self) {
+        for vault in self.raws.vaults.iter() {
+            let owner = format!("Vault {}", vault.name);
+            if vault.min_depth > vault.max_depth {
+                self.problem(&owner, "min_depth is deeper than max_depth");
+            }
+            if vault.template.is_empty() || vault.width() == 0 {
+                self.problem(&owner, "the template is empty");
+            }
+            if vault
+                .template
+                .iter()
+                .any(|row| row.chars().count() != vault.width())
+            {
+                self.problem(&owner, "the template's rows aren't all the same width");
+            }
+            for ch in vault.template.iter().flat_map(|row| row.chars()) {
+                if vault.legend_for(ch).is_none() {
+                    self.problem(&owner, format!("'{}' isn't in the legend", ch));
+                }
+            }
+            for spawn in vault
+                .legend
+                .values()
+                .filter_map(|legend| legend.spawn.as_ref())
+            {
+                if !self.is_spawnable(spawn) {
+                    self.problem(&owner, format!("{} doesn't exist", spawn));
+                }
+            }
+            for legend in vault.legend.values() {
+                if !self.raws.tiles.iter().any(|tile| tile.name == legend.tile) {
+                    self.problem(&owner, format!("tile {} doesn't exist", legend.tile));
+                }
+            }
+        }
+    }
+
+    fn branches(&mut self) {
+        use crate::map::MAIN_BRANCH;
+        for branch in self.raws.branches.iter() {
+            let owner = format!("Branch {}", branch.name);
+            if branch.levels.is_empty() {
+                self.problem(&owner, "it has no levels");
+            }
+            for level in branch.levels.iter() {
+                if !self.raws.biomes.iter().any(|biome| biome.name == *level) {
+                    self.problem(&owner, format!("biome {} doesn't exist", level));
+                }
+            }
+            if branch.parent_level < 1 {
+                self.problem(&owner, "parent_level is above the first level");
+            }
+            if branch.parent != MAIN_BRANCH {
+                match self.raws.branches.iter().find(|b| b.name == branch.parent) {
+                    None => self.problem(&owner, format!("branch {} doesn't exist", branch.parent)),
+                    Some(parent) if branch.parent_level as usize > parent.levels.len() => self
+                        .problem(
+                            &owner,
+                            format!(
+                                "branch {} has no level {}",
+                                parent.name, branch.parent_level
+                            ),
+                        ),
+                    Some(_) => {}
+                }
+            }
+
+            // Follow the parents back to the main line
+            let mut ancestor = branch.parent.as_str();
+            let mut steps = 0;
+            while ancestor != MAIN_BRANCH {
+                steps += 1;
+                if steps > self.raws.branches.len() {
+                    self.problem(&owner, "it's inside itself");
+                    break;
+                }
+                match self.raws.branches.iter().find(|b| b.name == ancestor) {
+                    Some(parent) => ancestor = parent.parent.as_str(),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    fn spells_taught(&mut self) {
+        for item in self.raws.items.iter() {
+            let taught = item
+                .consumable
+                .as_ref()
+                .and_then(|consumable| consumable.effects.get("teach_spell"));
+            if let Some(spell) = taught {
+                if !self.spells.contains(spell.as_str()) {
+                    self.problem(
+                        &format!("Item {}", item.name),
+                        format!("spell {} doesn't exist", spell),
+                    );
+                }
+            }
+        }
+    }
+
+    fn tiles(&mut self) {
+        for name in crate::map::CORE_TILES.iter() {
+            if !self.raws.tiles.iter().any(|tile| tile.name == *name) {
+                self.problem(
+                    &format!("Tile {}", name),
+                    "the game needs it, but it's missing",
+                );
+            }
+        }
+        for name in crate::map::BUILDER_TILES.iter() {
+            if !self.raws.tiles.iter().any(|tile| tile.name == *name) {
+                self.problem(
+                    &format!("Tile {}", name),
+                    "the map builders lay it, but it's missing",
+                );
+            }
+        }
+        for tile in self.raws.tiles.iter() {
+            let owner = format!("Tile {}", tile.name);
+            if tile.movement_cost <= 0.0 {
+                self.problem(&owner, "movement_cost isn't above 0");
+            }
+            if let Some(digging) = &tile.digging {
+                if digging.hardness <= 0 {
+                    self.problem(&owner, "the digging hardness isn't above 0");
+                }
+                match self.raws.tiles.iter().find(|t| t.name == digging.rubble) {
+                    Some(rubble) if !rubble.walkable => self.problem(
+                        &owner,
+                        format!("the rubble {} isn't walkable", digging.rubble),
+                    ),
+                    Some(_) => {}
+                    None => self.problem(
+                        &owner,
+                        format!("the rubble {} isn't a tile", digging.rubble),
+                    ),
+                }
+            }
+            if let Some(burns_to) = &tile.burns_to {
+                if !tile.flammable {
+                    self.problem(&owner, "it burns to a tile but isn't flammable");
+                }
+                match self.raws.tiles.iter().find(|t| t.name == *burns_to) {
+                    Some(burnt) if burnt.flammable => self.problem(
+                        &owner,
+                        format!("it burns to {}, which is flammable too", burns_to),
+                    ),
+                    Some(burnt) if tile.walkable && !burnt.walkable => self.problem(
+                        &owner,
+                        format!("it burns to {}, which isn't walkable", burns_to),
+                    ),
+                    Some(_) => {}
+                    None => self.problem(
+                        &owner,
+                        format!("it burns to {}, which isn't a tile", burns_to),
+                    ),
+                }
+            }
+            self.tile_renderable(&owner, &tile.renderable);
+            for renderable in tile.themes.values() {
+                self.tile_renderable(&owner, renderable);
+            }
+        }
+    }
+
+    fn digs(&mut self, owner: &str, digs: Option<i32>) {
+        if digs.is_some_and(|digs| digs <= 0) {
+            self.problem(owner, "digs isn't above 0");
+        }
+    }
+
+    fn tile_renderable(&mut self, owner: &str, renderable: &TileRenderable) {
+        if renderable.glyph.is_empty() {
+            self.problem(owner, "the glyph is empty");
+        }
+        self.color(owner, &renderable.fg);
+        self.color(owner, &renderable.bg);
+    }
+
+    fn renderable(&mut self, owner: &str, renderable: &Renderable) {
+        if renderable.glyph.is_empty() {
+            self.problem(owner, "the glyph is empty");
+        }
+        self.color(owner, &renderable.fg);
+        self.color(owner, &renderable.bg);
+    }
+
+    fn color(&mut self, owner: &str, color: &str) {
+        if rltk::RGB::from_hex(color).is_err() {
+            self.problem(owner, format!("{} isn't a #rrggbb color", color));
+        }
+    }
+
+    fn light(&mut self, owner: &str, light: &MobLight) {
+        self.color(owner, &light.color);
+        if light.range <= 0 {
+            self.problem(owner, "the light's range isn't above 0");
+        }
+        if light.intensity.is_some_and(|intensity| intensity <= 0.0) {
+            self.problem(owner, "the light's intensity isn't above 0");
+        }
+        if light
+            .flicker
+            .is_some_and(|flicker| !(0.0..=1.0).contains(&flicker))
+        {
+            self.problem(owner, "the light's flicker isn't between 0 and 1");
+        }
+    }
+
+    fn dice_roll(&mut self, owner: &str, dice: &str) {
+        if let Err(err) = dice.parse::<crate::dice::Dice>() {
+            self.problem(owner, format!("bad dice {}: {}", dice, err));
+        }
+    }
+}
+
+/// Mirrors how `RawMaster` names the items it builds from `template_magic`.
+fn generated_item_names(raws: &Raws) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for item in raws.items.iter() {
+        if let Some(template) = &item.template_magic {
+            if item.weapon.is_none() && item.wearable.is_none() {
+                continue;
+            }
+            if template.include_cursed {
+                names.insert(format!("{} -1", item.name));
+            }
+            for bonus in template.bonus_min..=template.bonus_max {
+                names.insert(format!("{} +{}", item.name, bonus));
+                if item.weapon.is_some() && bonus > 0 {
+                    for weapon_trait in raws.weapon_traits.iter() {
+                        names.insert(format!("{} {} +{}", weapon_trait.name, item.name, bonus));
+                    }
+                }
+            }
+        }
+    }
+    names
+}
+
+const EQUIPMENT_SLOTS: [&str; 7] = ["Shield", "Head", "Torso", "Legs", "Feet", "Hands", "Melee"];
+const MOVEMENT_MODES: [&str; 3] = ["static", "random", "random_waypoint"];
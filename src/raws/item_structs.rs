@@ -15,6 +15,7 @@ pub struct Item {
     pub magic: Option<MagicItem>,
     pub attributes: Option<ItemAttributeBonus>,
     pub template_magic: Option<ItemMagicTemplate>,
+    pub digs: Option<i32>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -0,0 +1,134 @@
+use super::*;
+use std::collections::HashSet;
+
+/// Raws entries are identified, and overridden, by name.
+pub trait Named {
+    fn name(&self) -> &str;
+}
+
+macro_rules! impl_named {
+    ( $($t:ty),* ) => {
+        $(
+            impl Named for $t {
+                fn name(&self) -> &str {
+                    &self.name
+                }
+            }
+        )*
+    };
+}
+
+impl_named!(
+    Item,
+    Mob,
+    Prop,
+    SpawnTableEntry,
+    LootTable,
+    FactionInfo,
+    Spell,
+    WeaponTrait,
+    Biome,
+    Vault,
+    Branch,
+    Tile
+);
+
+impl Raws {
+    /// Adds `other` on top of these raws. An entry whose name is already present replaces every
+    /// existing entry with that name, where the first of them was; new names are added at the end.
+    pub fn merge(&mut self, other: Raws) {
+        merge_entries(&mut self.items, other.items);
+        merge_entries(&mut self.mobs, other.mobs);
+        merge_entries(&mut self.props, other.props);
+        merge_entries(&mut self.spawn_table, other.spawn_table);
+        merge_entries(&mut self.loot_tables, other.loot_tables);
+        merge_entries(&mut self.faction_table, other.faction_table);
+        merge_entries(&mut self.spells, other.spells);
+        merge_entries(&mut self.weapon_traits, other.weapon_traits);
+        merge_entries(&mut self.biomes, other.biomes);
+        merge_entries(&mut self.vaults, other.vaults);
+        merge_entries(&mut self.branches, other.branches);
+        merge_entries(&mut self.tiles, other.tiles);
+    }
+}
+
+fn merge_entries<T: Named>(entries: &mut Vec<T>, additions: Vec<T>) {
+    // A name can have several entries, like a spawn table entry per depth range. Only the first
+    // one from `additions` replaces what's there; the rest go alongside it.
+    let mut added: HashSet<String> = HashSet::new();
+    for addition in additions {
+        let name = addition.name().to_string();
+        let existing = entries.iter().position(|entry| entry.name() == name);
+        match existing {
+            Some(position) if !added.contains(&name) => {
+                entries.retain(|entry| entry.name() != name);
+                entries.insert(position, addition);
+            }
+            _ => entries.push(addition),
+        }
+        added.insert(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_table(entries: &[(&str, i32)]) -> Raws {
+        let entries = entries
+            .iter()
+            .map(|(name, weight)| {
+                format!(
+                    r#"{{ "name": "{}", "weight": {}, "min_depth": 0, "max_depth": 100 }}"#,
+                    name, weight
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        parse_raws(format!(r#"{{ "spawn_table": [{}] }}"#, entries).as_bytes()).unwrap()
+    }
+
+    fn entries(raws: &Raws) -> Vec<(&str, i32)> {
+        raws.spawn_table
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.weight))
+            .collect()
+    }
+
+    #[test]
+    fn later_raws_replace_entries_where_they_were() {
+        let mut raws = spawn_table(&[("Goblin", 1), ("Orc", 2), ("Orc", 3), ("Rat", 4)]);
+        raws.merge(spawn_table(&[("Orc", 5), ("Kobold", 6), ("Orc", 7)]));
+        assert_eq!(
+            entries(&raws),
+            vec![
+                ("Goblin", 1),
+                ("Orc", 5),
+                ("Rat", 4),
+                ("Kobold", 6),
+                ("Orc", 7)
+            ]
+        );
+
+        // A mod applied after that one wins over both
+        raws.merge(spawn_table(&[("Rat", 8), ("Goblin", 9)]));
+        assert_eq!(
+            entries(&raws),
+            vec![
+                ("Goblin", 9),
+                ("Orc", 5),
+                ("Rat", 8),
+                ("Kobold", 6),
+                ("Orc", 7)
+            ]
+        );
+    }
+
+    #[test]
+    fn merging_leaves_the_other_lists_alone() {
+        let mut raws = parse_raws(br#"{ "spawn_table": [], "tiles": [] }"#).unwrap();
+        raws.merge(spawn_table(&[("Goblin", 1)]));
+        assert_eq!(entries(&raws), vec![("Goblin", 1)]);
+        assert!(raws.items.is_empty() && raws.tiles.is_empty());
+    }
+}
@@ -25,6 +25,7 @@ pub struct Mob {
     pub abilities: Option<Vec<MobAbility>>,
     pub on_death: Option<Vec<MobAbility>>,
     pub bleeds: Option<MobBleeds>,
+    pub digs: Option<i32>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -52,6 +53,10 @@ pub struct NaturalAttack {
 pub struct MobLight {
     pub range: i32,
     pub color: String,
+    /// How bright it is where it starts; 1.0, as bright as daylight, if it doesn't say.
+    pub intensity: Option<f32>,
+    /// How much it flickers, from 0.0 for a steady light to 1.0.
+    pub flicker: Option<f32>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -14,20 +14,61 @@ mod spell_structs;
 pub use spell_structs::Spell;
 mod weapon_traits;
 pub use weapon_traits::*;
+mod biome_structs;
+pub use biome_structs::*;
+mod vault_structs;
+pub use vault_structs::*;
+mod tile_structs;
+pub use tile_structs::*;
 
 mod rawmaster;
 pub use rawmaster::*;
+mod merge;
+pub use merge::*;
+mod check;
+pub use check::*;
+#[cfg(not(target_arch = "wasm32"))]
+mod mods;
 use serde::Deserialize;
 use std::str;
 use std::sync::Mutex;
 
-rltk::embedded_resource!(RAW_FILE, "../../raws/spawns.json");
+rltk::embedded_resource!(SPAWN_TABLE_RAWS, "../../raws/spawn_table.json");
+rltk::embedded_resource!(LOOT_RAWS, "../../raws/loot.json");
+rltk::embedded_resource!(FACTION_RAWS, "../../raws/factions.json");
+rltk::embedded_resource!(ITEM_RAWS, "../../raws/items.json");
+rltk::embedded_resource!(MOB_RAWS, "../../raws/mobs.json");
+rltk::embedded_resource!(PROP_RAWS, "../../raws/props.json");
+rltk::embedded_resource!(SPELL_RAWS, "../../raws/spells.json");
+rltk::embedded_resource!(WEAPON_TRAIT_RAWS, "../../raws/weapon_traits.json");
+rltk::embedded_resource!(BIOME_RAWS, "../../raws/biomes.json");
+rltk::embedded_resource!(VAULT_RAWS, "../../raws/vaults.json");
+rltk::embedded_resource!(BRANCH_RAWS, "../../raws/branches.json");
+rltk::embedded_resource!(TILE_RAWS, "../../raws/tiles.json");
+
+/// The raws the game ships with, in the order they're merged.
+const RAW_FILES: [&str; 12] = [
+    "../../raws/tiles.json",
+    "../../raws/spawn_table.json",
+    "../../raws/loot.json",
+    "../../raws/factions.json",
+    "../../raws/items.json",
+    "../../raws/mobs.json",
+    "../../raws/props.json",
+    "../../raws/spells.json",
+    "../../raws/weapon_traits.json",
+    "../../raws/biomes.json",
+    "../../raws/vaults.json",
+    "../../raws/branches.json",
+];
 
 lazy_static! {
     pub static ref RAWS: Mutex<RawMaster> = Mutex::new(RawMaster::empty());
 }
 
-#[derive(Deserialize, Debug)]
+/// Every kind of raws entry. A raws file can hold any of them, so each list is optional.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Raws {
     pub items: Vec<Item>,
     pub mobs: Vec<Mob>,
@@ -37,18 +78,90 @@ pub struct Raws {
     pub faction_table: Vec<FactionInfo>,
     pub spells: Vec<Spell>,
     pub weapon_traits: Vec<WeaponTrait>,
+    pub biomes: Vec<Biome>,
+    pub vaults: Vec<Vault>,
+    pub branches: Vec<Branch>,
+    pub tiles: Vec<Tile>,
 }
 
-pub fn load_raws() {
-    rltk::link_resource!(RAW_FILE, "../../raws/spawns.json");
+fn parse_raws(data: &[u8]) -> Result<Raws, String> {
+    let raw_string = str::from_utf8(data).map_err(|err| err.to_string())?;
+    serde_json::from_str(raw_string).map_err(|err| err.to_string())
+}
 
+/// Reads the raws the game ships with, then, on native builds, any mods on top of them.
+pub fn read_raws() -> Result<Raws, Vec<String>> {
+    rltk::link_resource!(SPAWN_TABLE_RAWS, "../../raws/spawn_table.json");
+    rltk::link_resource!(LOOT_RAWS, "../../raws/loot.json");
+    rltk::link_resource!(FACTION_RAWS, "../../raws/factions.json");
+    rltk::link_resource!(ITEM_RAWS, "../../raws/items.json");
+    rltk::link_resource!(MOB_RAWS, "../../raws/mobs.json");
+    rltk::link_resource!(PROP_RAWS, "../../raws/props.json");
+    rltk::link_resource!(SPELL_RAWS, "../../raws/spells.json");
+    rltk::link_resource!(WEAPON_TRAIT_RAWS, "../../raws/weapon_traits.json");
+    rltk::link_resource!(BIOME_RAWS, "../../raws/biomes.json");
+    rltk::link_resource!(VAULT_RAWS, "../../raws/vaults.json");
+    rltk::link_resource!(BRANCH_RAWS, "../../raws/branches.json");
+    rltk::link_resource!(TILE_RAWS, "../../raws/tiles.json");
+
+    read_raws_with(embedded_raw)
+}
+
+/// Reads the raws like `read_raws`, but takes each file the game ships with from `source`.
+fn read_raws_with(source: fn(&str) -> Result<Vec<u8>, String>) -> Result<Raws, Vec<String>> {
+    let mut raws = Raws::default();
+    let mut problems = Vec::new();
+    for path in RAW_FILES.iter() {
+        match source(path).and_then(|raw_data| parse_raws(&raw_data)) {
+            Ok(file_raws) => raws.merge(file_raws),
+            Err(err) => problems.push(format!("{}: {}", path.trim_start_matches("../../"), err)),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    mods::apply_mods(&mut raws, &mut problems);
+
+    if problems.is_empty() {
+        Ok(raws)
+    } else {
+        Err(problems)
+    }
+}
+
+fn embedded_raw(path: &str) -> Result<Vec<u8>, String> {
     // Retrieve the raw data as an array of u8 (8-bit unsigned chars)
-    let raw_data = rltk::embedding::EMBED
+    rltk::embedding::EMBED
         .lock()
-        .get_resource("../../raws/spawns.json".to_string())
-        .unwrap();
-    let raw_string = str::from_utf8(&raw_data).expect("Unable to convert to a valid UTF-8 string.");
-    let decoder: Raws = serde_json::from_str(&raw_string).expect("Unable to parse JSON");
+        .get_resource(path.to_string())
+        .map(|raw_data| raw_data.to_vec())
+        .ok_or_else(|| "isn't embedded".to_string())
+}
+
+/// Prefers the copy in `./raws`, so the files can be edited while the game runs.
+#[cfg(not(target_arch = "wasm32"))]
+fn raw_from_disk(path: &str) -> Result<Vec<u8>, String> {
+    let file = std::path::Path::new(path.trim_start_matches("../../"));
+    if file.exists() {
+        std::fs::read(file).map_err(|err| err.to_string())
+    } else {
+        embedded_raw(path)
+    }
+}
 
-    RAWS.lock().unwrap().load(decoder);
+/// Reads the raws again, from disk where it can, and replaces the loaded ones. Anything spawned
+/// afterwards uses the new definitions. If they have problems, the old raws are kept.
+pub fn reload_raws() -> Result<(), Vec<String>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let raws = read_raws_with(raw_from_disk);
+    #[cfg(target_arch = "wasm32")]
+    let raws = read_raws_with(embedded_raw);
+
+    raws.and_then(|raws| RAWS.lock().unwrap().load(raws))
+}
+
+pub fn load_raws() {
+    let loaded = read_raws().and_then(|raws| RAWS.lock().unwrap().load(raws));
+    if let Err(problems) = loaded {
+        panic!("Unable to load the raws:\n{}", problems.join("\n"));
+    }
 }
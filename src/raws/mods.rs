@@ -0,0 +1,51 @@
+use super::Raws;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MODS_DIRECTORY: &str = "./mods";
+
+/// Every mod, in the order they're applied: each directory under `./mods`, sorted by name.
+fn mod_directories() -> Vec<PathBuf> {
+    let mut directories = fs::read_dir(MODS_DIRECTORY)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    directories.sort();
+    directories
+}
+
+/// The raws files in a mod, sorted by name.
+fn mod_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files = fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Merges every mod's files on top of `raws`, noting any that can't be read in `problems`.
+pub fn apply_mods(raws: &mut Raws, problems: &mut Vec<String>) {
+    for directory in mod_directories() {
+        crate::console_log(format!("Loading mod {}", directory.display()));
+        for file in mod_files(&directory) {
+            match fs::read(&file) {
+                Ok(data) => match super::parse_raws(&data) {
+                    Ok(mod_raws) => raws.merge(mod_raws),
+                    Err(err) => problems.push(format!("{}: {}", file.display(), err)),
+                },
+                Err(err) => problems.push(format!("{}: {}", file.display(), err)),
+            }
+        }
+    }
+}
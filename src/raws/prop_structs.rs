@@ -10,6 +10,7 @@ pub struct Prop {
     pub blocks_tile: Option<bool>,
     pub blocks_visibility: Option<bool>,
     pub door_open: Option<bool>,
+    pub flammable: Option<bool>,
     pub entry_trigger: Option<EntryTrigger>,
     pub light: Option<super::mob_structs::MobLight>,
 }
@@ -1,40 +1,16 @@
 use super::{faction_structs::Reaction, Raws};
 use crate::components::*;
+use crate::dice::Dice;
+use crate::map::{Diggable, LevelId, TileAppearance, TileKind};
 use crate::random_table::{MasterTable, RandomTable};
 use crate::{attr_bonus, mana_at_level, npc_hp};
-use regex::Regex;
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use std::collections::{HashMap, HashSet};
 
-pub fn parse_dice_string(dice: &str) -> (i32, i32, i32) {
-    lazy_static! {
-        static ref DICE_RE: Regex = Regex::new(r"(\d+)d(\d+)([\+\-]\d+)?").unwrap();
-    }
-    let mut n_dice = 1;
-    let mut die_type = 4;
-    let mut die_bonus = 0;
-    for cap in DICE_RE.captures_iter(dice) {
-        if let Some(group) = cap.get(1) {
-            n_dice = group
-                .as_str()
-                .parse::<i32>()
-                .expect("Not parseable as a number");
-        }
-        if let Some(group) = cap.get(2) {
-            die_type = group
-                .as_str()
-                .parse::<i32>()
-                .expect("Not parseable as a number");
-        }
-        if let Some(group) = cap.get(3) {
-            die_bonus = group
-                .as_str()
-                .parse::<i32>()
-                .expect("Not parseable as a number");
-        }
-    }
-    (n_dice, die_type, die_bonus)
+/// Dice from the raws. They're checked when the raws are loaded, so this doesn't fail.
+fn parse_dice(dice: &str) -> Dice {
+    dice.parse().unwrap()
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone)]
@@ -62,16 +38,7 @@ struct NewMagicItem {
 impl RawMaster {
     pub fn empty() -> RawMaster {
         RawMaster {
-            raws: Raws {
-                items: Vec::new(),
-                mobs: Vec::new(),
-                props: Vec::new(),
-                spawn_table: Vec::new(),
-                loot_tables: Vec::new(),
-                faction_table: Vec::new(),
-                spells: Vec::new(),
-                weapon_traits: Vec::new(),
-            },
+            raws: Raws::default(),
             item_index: HashMap::new(),
             mob_index: HashMap::new(),
             prop_index: HashMap::new(),
@@ -97,7 +64,7 @@ impl RawMaster {
                     });
                 }
             } else {
-                rltk::console::log(format!(
+                crate::console_log(format!(
                     "{} is marked as templated, but isn't a weapon or armor.",
                     item.name
                 ));
@@ -140,13 +107,9 @@ impl RawMaster {
         }
         if let Some(mut weapon) = base_item_copy.weapon.as_mut() {
             weapon.hit_bonus += nmw.bonus;
-            let (n, die, plus) = parse_dice_string(&weapon.base_damage);
-            let final_bonus = plus + nmw.bonus;
-            if final_bonus > 0 {
-                weapon.base_damage = format!("{}d{}+{}", n, die, final_bonus);
-            } else if final_bonus < 0 {
-                weapon.base_damage = format!("{}d{}-{}", n, die, i32::abs(final_bonus));
-            }
+            weapon.base_damage = parse_dice(&weapon.base_damage)
+                .with_bonus(nmw.bonus)
+                .to_string();
         }
         if let Some(mut armor) = base_item_copy.wearable.as_mut() {
             armor.armor_class += nmw.bonus as f32;
@@ -205,15 +168,22 @@ impl RawMaster {
             });
     }
 
-    pub fn load(&mut self, raws: Raws) {
+    /// Replaces the loaded raws. Raws with any problem `check_raws` finds are refused with a list
+    /// of what's wrong, since most of them would otherwise panic when a level is built.
+    pub fn load(&mut self, raws: Raws) -> Result<(), Vec<String>> {
+        let problems = super::check_raws(&raws);
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        *self = RawMaster::empty();
         self.raws = raws;
-        self.item_index = HashMap::new();
         let mut used_names: HashSet<String> = HashSet::new();
         let mut items_to_build = Vec::new();
 
         for (i, item) in self.raws.items.iter().enumerate() {
             if used_names.contains(&item.name) {
-                rltk::console::log(format!(
+                crate::console_log(format!(
                     "WARNING -  duplicate item name in raws [{}]",
                     item.name
                 ));
@@ -225,7 +195,7 @@ impl RawMaster {
         }
         for (i, mob) in self.raws.mobs.iter().enumerate() {
             if used_names.contains(&mob.name) {
-                rltk::console::log(format!(
+                crate::console_log(format!(
                     "WARNING -  duplicate mob name in raws [{}]",
                     mob.name
                 ));
@@ -235,7 +205,7 @@ impl RawMaster {
         }
         for (i, prop) in self.raws.props.iter().enumerate() {
             if used_names.contains(&prop.name) {
-                rltk::console::log(format!(
+                crate::console_log(format!(
                     "WARNING -  duplicate prop name in raws [{}]",
                     prop.name
                 ));
@@ -246,7 +216,7 @@ impl RawMaster {
 
         for spawn in self.raws.spawn_table.iter() {
             if !used_names.contains(&spawn.name) {
-                rltk::console::log(format!(
+                crate::console_log(format!(
                     "WARNING - Spawn tables references unspecified entity {}",
                     spawn.name
                 ));
@@ -276,8 +246,52 @@ impl RawMaster {
             self.spell_index.insert(spell.name.clone(), i);
         }
 
+        crate::map::register_tile_kinds(self.raws.tiles.iter().map(get_tile_kind).collect());
+
         self.build_magic_weapon_or_armor(&items_to_build);
         self.build_traited_weapons(&items_to_build);
+        Ok(())
+    }
+}
+
+fn get_light_source(light: &super::MobLight) -> LightSource {
+    LightSource {
+        range: light.range,
+        color: rltk::RGB::from_hex(&light.color).expect("Bad color"),
+        intensity: light.intensity.unwrap_or(1.0),
+        flicker: light.flicker.unwrap_or(0.0),
+    }
+}
+
+fn get_tile_appearance(renderable: &super::TileRenderable) -> TileAppearance {
+    TileAppearance {
+        glyph: rltk::to_cp437(renderable.glyph.chars().next().unwrap()),
+        fg: rltk::RGB::from_hex(&renderable.fg).expect("Invalid RGB"),
+        bg: rltk::RGB::from_hex(&renderable.bg).expect("Invalid RGB"),
+        joins: renderable.joins,
+    }
+}
+
+fn get_tile_kind(tile: &super::Tile) -> TileKind {
+    TileKind {
+        name: tile.name.clone(),
+        walkable: tile.walkable,
+        opaque: tile.opaque,
+        movement_cost: tile.movement_cost,
+        liquid: tile.liquid,
+        flammable: tile.flammable,
+        burns_to: tile.burns_to.clone(),
+        description: tile.description.clone(),
+        digging: tile.digging.as_ref().map(|digging| Diggable {
+            hardness: digging.hardness,
+            rubble: digging.rubble.clone(),
+        }),
+        appearance: get_tile_appearance(&tile.renderable),
+        themes: tile
+            .themes
+            .iter()
+            .map(|(theme, renderable)| (theme.clone(), get_tile_appearance(renderable)))
+            .collect(),
     }
 }
 
@@ -409,95 +423,16 @@ pub fn string_to_slot(slot: &str) -> EquipmentSlot {
         "Hands" => EquipmentSlot::Hands,
         "Melee" => EquipmentSlot::Melee,
         _ => {
-            rltk::console::log(format!("Warning: unknown equipment slot type [{}])", slot));
+            crate::console_log(format!("Warning: unknown equipment slot type [{}])", slot));
             EquipmentSlot::Melee
         }
     }
 }
 
-fn parse_particle_line(n: &str) -> SpawnParticleLine {
-    let tokens: Vec<_> = n.split(';').collect();
-    SpawnParticleLine {
-        glyph: rltk::to_cp437(tokens[0].chars().next().unwrap()),
-        color: rltk::RGB::from_hex(tokens[1]).expect("Bad RGB"),
-        lifetime_ms: tokens[2].parse::<f32>().unwrap(),
-    }
-}
-
-fn parse_particle(n: &str) -> SpawnParticleBurst {
-    let tokens: Vec<_> = n.split(';').collect();
-    SpawnParticleBurst {
-        glyph: rltk::to_cp437(tokens[0].chars().next().unwrap()),
-        color: rltk::RGB::from_hex(tokens[1]).expect("Bad RGB"),
-        lifetime_ms: tokens[2].parse::<f32>().unwrap(),
-    }
-}
-
 macro_rules! apply_effects {
     ( $effects:expr, $eb:expr ) => {
-        for effect in $effects.iter() {
-            let effect_name = effect.0.as_str();
-            match effect_name {
-                "provides_healing" => {
-                    $eb = $eb.with(ProvidesHealing {
-                        heal_amount: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "provides_mana" => {
-                    $eb = $eb.with(ProvidesMana {
-                        mana_amount: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "teach_spell" => {
-                    $eb = $eb.with(TeachesSpell {
-                        spell: effect.1.to_string(),
-                    })
-                }
-                "ranged" => {
-                    $eb = $eb.with(Ranged {
-                        range: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "damage" => {
-                    $eb = $eb.with(InflictsDamage {
-                        damage: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "area_of_effect" => {
-                    $eb = $eb.with(AreaOfEffect {
-                        radius: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "confusion" => {
-                    $eb = $eb.with(Confusion {});
-                    $eb = $eb.with(Duration {
-                        turns: effect.1.parse::<i32>().unwrap(),
-                    });
-                }
-                "magic_mapping" => $eb = $eb.with(MagicMapper {}),
-                "town_portal" => $eb = $eb.with(TownPortal {}),
-                "food" => $eb = $eb.with(ProvidesFood {}),
-                "single_activation" => $eb = $eb.with(SingleActivation {}),
-                "particle_line" => $eb = $eb.with(parse_particle_line(&effect.1)),
-                "particle" => $eb = $eb.with(parse_particle(&effect.1)),
-                "remove_curse" => $eb = $eb.with(ProvidesRemoveCurse {}),
-                "identify" => $eb = $eb.with(ProvidesIdentification {}),
-                "slow" => {
-                    $eb = $eb.with(Slow {
-                        initiative_penalty: effect.1.parse::<f32>().unwrap(),
-                    })
-                }
-                "damage_over_time" => {
-                    $eb = $eb.with(DamageOverTime {
-                        damage: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "target_self" => $eb = $eb.with(AlwaysTargetsSelf {}),
-                _ => rltk::console::log(format!(
-                    "Warning: consumable effect {} not implemented.",
-                    effect_name
-                )),
-            }
+        for (name, value) in $effects.iter() {
+            $eb = crate::effects::EFFECTS.build($eb, name, value);
         }
     };
 }
@@ -555,7 +490,6 @@ pub fn spawn_named_item(
             eb = eb.with(Equippable {
                 slot: EquipmentSlot::Melee,
             });
-            let (n_dice, die_type, bonus) = parse_dice_string(&weapon.base_damage);
             let mut wpn = Weapon {
                 range: if weapon.range == "melee" {
                     None
@@ -563,9 +497,7 @@ pub fn spawn_named_item(
                     Some(weapon.range.parse::<i32>().expect("Not a number"))
                 },
                 attribute: WeaponAttribute::Might,
-                damage_n_dice: n_dice,
-                damage_die_type: die_type,
-                damage_bonus: bonus,
+                damage: parse_dice(&weapon.base_damage),
                 hit_bonus: weapon.hit_bonus,
                 proc_chance: weapon.proc_chance,
                 proc_target: weapon.proc_target.clone(),
@@ -633,6 +565,10 @@ pub fn spawn_named_item(
             });
         }
 
+        if let Some(power) = item_template.digs {
+            eb = eb.with(Digger { power });
+        }
+
         return Some(eb.build());
     }
     None
@@ -777,8 +713,7 @@ pub fn spawn_named_mob(
             total_weight: 0.0,
             total_initiative_penalty: 0.0,
             gold: if let Some(gold) = &mob_template.gold {
-                let (n, d, b) = parse_dice_string(&gold);
-                (crate::rng::roll_dice(n, d) + b) as f32
+                parse_dice(gold).roll_with(crate::rng::mapgen::roll_dice) as f32
             } else {
                 0.0
             },
@@ -806,7 +741,7 @@ pub fn spawn_named_mob(
                         skills.skills.insert(Skill::Magic, *sk.1);
                     }
                     _ => {
-                        rltk::console::log(format!("Unknown skill referenced: [{}]", sk.0));
+                        crate::console_log(format!("Unknown skill referenced: [{}]", sk.0));
                     }
                 }
             }
@@ -826,13 +761,10 @@ pub fn spawn_named_mob(
             };
             if let Some(attacks) = &na.attacks {
                 for nattack in attacks.iter() {
-                    let (n, d, b) = parse_dice_string(&nattack.damage);
                     let attack = NaturalAttack {
                         name: nattack.name.clone(),
                         hit_bonus: nattack.hit_bonus,
-                        damage_n_dice: n,
-                        damage_die_type: d,
-                        damage_bonus: b,
+                        damage: parse_dice(&nattack.damage),
                     };
                     nature.attacks.push(attack);
                 }
@@ -847,10 +779,7 @@ pub fn spawn_named_mob(
         }
 
         if let Some(light) = &mob_template.light {
-            eb = eb.with(LightSource {
-                range: light.range,
-                color: rltk::RGB::from_hex(&light.color).expect("Bad color"),
-            });
+            eb = eb.with(get_light_source(light));
         }
 
         if let Some(bleeds) = &mob_template.bleeds {
@@ -866,6 +795,10 @@ pub fn spawn_named_mob(
             })
         }
 
+        if let Some(power) = mob_template.digs {
+            eb = eb.with(Digger { power });
+        }
+
         if let Some(faction) = &mob_template.faction {
             eb = eb.with(Faction {
                 name: faction.clone(),
@@ -973,20 +906,17 @@ pub fn spawn_named_prop(
         if let Some(door_open) = prop_template.door_open {
             eb = eb.with(Door { open: door_open });
         }
+        if let Some(flammable) = prop_template.flammable {
+            if flammable {
+                eb = eb.with(Flammable {})
+            };
+        }
         if let Some(entry_trigger) = &prop_template.entry_trigger {
             eb = eb.with(EntryTrigger {});
             apply_effects!(entry_trigger.effects, eb);
         }
         if let Some(light) = &prop_template.light {
-            eb = eb.with(LightSource {
-                range: light.range,
-                color: rltk::RGB::from_hex(&light.color).expect("Bad color"),
-            });
-            eb = eb.with(Viewshed {
-                range: light.range,
-                dirty: true,
-                visible_tiles: Vec::new(),
-            });
+            eb = eb.with(get_light_source(light));
         }
 
         return Some(eb.build());
@@ -1019,6 +949,76 @@ pub fn spawn_all_spells(ecs: &mut World) {
     }
 }
 
+/// Replaces the spell entities with ones built from the current raws, and updates the mana costs
+/// of the spells everyone already knows.
+pub fn respawn_all_spells(ecs: &mut World) {
+    let old_spells = (&ecs.entities(), &ecs.read_storage::<SpellTemplate>())
+        .join()
+        .map(|(entity, _template)| entity)
+        .collect::<Vec<Entity>>();
+    for spell in old_spells {
+        ecs.delete_entity(spell).expect("Unable to delete spell");
+    }
+    spawn_all_spells(ecs);
+
+    let raws = &super::RAWS.lock().unwrap();
+    for known in (&mut ecs.write_storage::<KnownSpells>()).join() {
+        for spell in known.spells.iter_mut() {
+            if let Some(i) = raws.spell_index.get(&spell.display_name) {
+                spell.mana_cost = raws.raws.spells[*i].mana_cost;
+            }
+        }
+    }
+}
+
+/// Rebuilds every monster on the current level from its template, in the same place. Whatever
+/// they were carrying is replaced by what the template gives them.
+pub fn respawn_mobs_on_level(ecs: &mut World) {
+    let mut mobs: Vec<(Entity, String, Position)> = Vec::new();
+    {
+        let raws = &super::RAWS.lock().unwrap();
+        let entities = ecs.entities();
+        let names = ecs.read_storage::<Name>();
+        let positions = ecs.read_storage::<Position>();
+        let players = ecs.read_storage::<Player>();
+        for (entity, name, pos, _player) in (&entities, &names, &positions, !&players).join() {
+            if raws.mob_index.contains_key(&name.name) {
+                mobs.push((entity, name.name.clone(), pos.clone()));
+            }
+        }
+    }
+
+    let mut to_delete: Vec<Entity> = mobs.iter().map(|mob| mob.0).collect();
+    {
+        let entities = ecs.entities();
+        let equipped = ecs.read_storage::<Equipped>();
+        let carried = ecs.read_storage::<InBackpack>();
+        for (entity, equipped) in (&entities, &equipped).join() {
+            if mobs.iter().any(|mob| mob.0 == equipped.owner) {
+                to_delete.push(entity);
+            }
+        }
+        for (entity, backpack) in (&entities, &carried).join() {
+            if mobs.iter().any(|mob| mob.0 == backpack.owner) {
+                to_delete.push(entity);
+            }
+        }
+    }
+    for entity in to_delete {
+        ecs.delete_entity(entity).expect("Unable to delete mob");
+    }
+
+    let raws = &super::RAWS.lock().unwrap();
+    for (_entity, name, pos) in mobs {
+        spawn_named_mob(
+            raws,
+            ecs,
+            &name,
+            SpawnType::AtPosition { x: pos.x, y: pos.y },
+        );
+    }
+}
+
 pub fn find_spell_entity(ecs: &World, name: &str) -> Option<Entity> {
     let names = ecs.read_storage::<Name>();
     let spell_templates = ecs.read_storage::<SpellTemplate>();
@@ -1101,6 +1101,114 @@ pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> MasterTable {
     rt
 }
 
+pub fn get_biome(raws: &RawMaster, name: &str) -> Option<super::Biome> {
+    raws.raws.biomes.iter().find(|b| b.name == name).cloned()
+}
+
+/// The biome a level at `depth` is built as. When several can appear there, one is picked at
+/// random; when none can, the level is left to the random builder.
+pub fn biome_for_depth(raws: &RawMaster, depth: i32) -> Option<super::Biome> {
+    let available_options: Vec<&super::Biome> = raws
+        .raws
+        .biomes
+        .iter()
+        .filter(|b| !b.branch_only && depth >= b.min_depth && depth <= b.max_depth)
+        .collect();
+
+    match available_options.len() {
+        0 => None,
+        1 => Some(available_options[0].clone()),
+        n => {
+            let roll = crate::rng::mapgen::roll_dice(1, n as i32) - 1;
+            Some(available_options[roll as usize].clone())
+        }
+    }
+}
+
+pub fn get_branch(raws: &RawMaster, name: &str) -> Option<super::Branch> {
+    raws.raws.branches.iter().find(|b| b.name == name).cloned()
+}
+
+/// How deep `level` is: its depth on the main line, or for a branch, the depth of the level its
+/// entrance is on plus how far down the branch it is.
+pub fn level_depth(raws: &RawMaster, level: &LevelId) -> i32 {
+    let mut depth = level.level;
+    let mut branch = get_branch(raws, &level.branch);
+    let mut visited = 0;
+    while let Some(b) = branch {
+        // A branch that is its own ancestor is refused by the raws check; don't loop forever on it
+        visited += 1;
+        if visited > raws.raws.branches.len() {
+            break;
+        }
+        depth += b.parent_level;
+        branch = get_branch(raws, &b.parent);
+    }
+    depth
+}
+
+/// The biome a level in a side branch is built as, or `None` on the main line.
+pub fn biome_for_level(raws: &RawMaster, level: &LevelId) -> Option<super::Biome> {
+    let branch = get_branch(raws, &level.branch)?;
+    let name = branch.levels.get(usize::try_from(level.level - 1).ok()?)?;
+    get_biome(raws, name)
+}
+
+/// The level the ordinary down stairs of `level` lead to, if there's one below it.
+pub fn level_below(raws: &RawMaster, level: &LevelId) -> Option<LevelId> {
+    if level.is_main() {
+        return Some(LevelId::main(level.level + 1));
+    }
+    let branch = get_branch(raws, &level.branch)?;
+    if (level.level as usize) < branch.levels.len() {
+        Some(LevelId::new(&level.branch, level.level + 1))
+    } else {
+        None
+    }
+}
+
+/// The level the up stairs of `level` lead to: the one above it in its branch, or the level the
+/// branch's entrance is on.
+pub fn level_above(raws: &RawMaster, level: &LevelId) -> Option<LevelId> {
+    if level.level > 1 {
+        return Some(LevelId::new(&level.branch, level.level - 1));
+    }
+    if level.is_main() {
+        return None;
+    }
+    let branch = get_branch(raws, &level.branch)?;
+    Some(LevelId::new(&branch.parent, branch.parent_level))
+}
+
+/// The first level of a branch that's built as the biome `name`.
+pub fn branch_level_for_biome(raws: &RawMaster, name: &str) -> Option<LevelId> {
+    raws.raws.branches.iter().find_map(|branch| {
+        let position = branch.levels.iter().position(|level| level == name)?;
+        Some(LevelId::new(&branch.name, position as i32 + 1))
+    })
+}
+
+/// The first levels of the branches whose entrances are on `level`.
+pub fn branch_entrances(raws: &RawMaster, level: &LevelId) -> Vec<LevelId> {
+    raws.raws
+        .branches
+        .iter()
+        .filter(|b| b.parent == level.branch && b.parent_level == level.level)
+        .map(|b| LevelId::new(&b.name, 1))
+        .collect()
+}
+
+/// The vaults that can appear at `depth` in a biome tagged with `biome_tags`.
+pub fn vaults_for_depth(raws: &RawMaster, depth: i32, biome_tags: &[String]) -> Vec<super::Vault> {
+    raws.raws
+        .vaults
+        .iter()
+        .filter(|v| depth >= v.min_depth && depth <= v.max_depth && v.weight > 0)
+        .filter(|v| v.biome_tags.is_empty() || v.biome_tags.iter().any(|t| biome_tags.contains(t)))
+        .cloned()
+        .collect()
+}
+
 pub fn get_item_drop(raws: &RawMaster, table: &str) -> Option<String> {
     if raws.loot_index.contains_key(table) {
         let mut rt = RandomTable::new();
@@ -1114,3 +1222,107 @@ pub fn get_item_drop(raws: &RawMaster, table: &str) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raws_with_any_problem_are_refused() {
+        let mut master = RawMaster::empty();
+        assert!(master.load(crate::raws::read_raws().unwrap()).is_ok());
+
+        let mut raws = crate::raws::read_raws().unwrap();
+        raws.branches[0].levels.push("Nowhere".to_string());
+        raws.vaults[0].legend.values_mut().next().unwrap().tile = "Nothing".to_string();
+        let problems = master.load(raws).unwrap_err();
+        assert!(problems
+            .iter()
+            .any(|p| p.ends_with("biome Nowhere doesn't exist")));
+        assert!(problems
+            .iter()
+            .any(|p| p.ends_with("tile Nothing doesn't exist")));
+        // The raws that were loaded are kept
+        assert!(!master.raws.branches[0]
+            .levels
+            .contains(&"Nowhere".to_string()));
+    }
+
+    /// The game's raws, with a two-level branch hanging off the first level of the Dragon's Lair.
+    fn nested_branches() -> RawMaster {
+        let mut raws = crate::raws::read_raws().unwrap();
+        raws.branches.push(super::super::Branch {
+            name: "Dragon's Hoard".to_string(),
+            parent: "Dragon's Lair".to_string(),
+            parent_level: 1,
+            levels: vec!["Dragon's Lair".to_string(), "Dragon's Lair".to_string()],
+        });
+        let mut master = RawMaster::empty();
+        master.load(raws).unwrap();
+        master
+    }
+
+    #[test]
+    fn levels_are_as_deep_as_their_branches_go() {
+        let master = nested_branches();
+        assert_eq!(level_depth(&master, &LevelId::main(4)), 4);
+        assert_eq!(level_depth(&master, &LevelId::new("Dragon's Lair", 1)), 7);
+        assert_eq!(level_depth(&master, &LevelId::new("Dragon's Hoard", 2)), 9);
+    }
+
+    #[test]
+    fn stairs_lead_along_and_out_of_branches() {
+        let master = nested_branches();
+        let lair = LevelId::new("Dragon's Lair", 1);
+        let hoard = |level| LevelId::new("Dragon's Hoard", level);
+
+        assert_eq!(
+            level_below(&master, &LevelId::main(6)),
+            Some(LevelId::main(7))
+        );
+        assert_eq!(level_below(&master, &lair), None);
+        assert_eq!(level_below(&master, &hoard(1)), Some(hoard(2)));
+        assert_eq!(level_below(&master, &hoard(2)), None);
+
+        assert_eq!(level_above(&master, &LevelId::main(1)), None);
+        assert_eq!(
+            level_above(&master, &LevelId::main(2)),
+            Some(LevelId::main(1))
+        );
+        assert_eq!(level_above(&master, &lair), Some(LevelId::main(6)));
+        assert_eq!(level_above(&master, &hoard(1)), Some(lair.clone()));
+        assert_eq!(level_above(&master, &hoard(2)), Some(hoard(1)));
+
+        assert_eq!(
+            branch_entrances(&master, &LevelId::main(6)),
+            vec![lair.clone()]
+        );
+        assert!(branch_entrances(&master, &LevelId::main(5)).is_empty());
+        assert_eq!(branch_entrances(&master, &lair), vec![hoard(1)]);
+    }
+
+    #[test]
+    fn branch_levels_are_built_as_the_biomes_they_name() {
+        let master = nested_branches();
+        assert!(biome_for_level(&master, &LevelId::main(6)).is_none());
+        assert_eq!(
+            biome_for_level(&master, &LevelId::new("Dragon's Hoard", 2))
+                .unwrap()
+                .name,
+            "Dragon's Lair"
+        );
+        assert!(biome_for_level(&master, &LevelId::new("Dragon's Hoard", 3)).is_none());
+        assert_eq!(
+            branch_level_for_biome(&master, "Dragon's Lair"),
+            Some(LevelId::new("Dragon's Lair", 1))
+        );
+
+        // Only the branch builds the Dragon's Lair, whatever its depths say
+        crate::rng::reseed(&crate::rng::WorldSeed::new(7));
+        for _ in 0..50 {
+            if let Some(biome) = biome_for_depth(&master, 7) {
+                assert_ne!(biome.name, "Dragon's Lair");
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A kind of map tile. Builders lay tiles by name.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Tile {
+    pub name: String,
+    #[serde(default)]
+    pub walkable: bool,
+    #[serde(default)]
+    pub opaque: bool,
+    /// How much walking onto the tile costs, relative to plain floor.
+    #[serde(default = "default_movement_cost")]
+    pub movement_cost: f32,
+    #[serde(default)]
+    pub liquid: bool,
+    #[serde(default)]
+    pub flammable: bool,
+    /// The tile left once a flammable tile has burned; floor if it doesn't say.
+    pub burns_to: Option<String>,
+    /// Shown when the mouse is over the tile; tiles without one have no tooltip.
+    #[serde(default)]
+    pub description: String,
+    /// Tiles without it can't be dug through.
+    pub digging: Option<TileDigging>,
+    pub renderable: TileRenderable,
+    /// How the tile looks under each theme that draws it differently.
+    #[serde(default)]
+    pub themes: HashMap<String, TileRenderable>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TileRenderable {
+    pub glyph: String,
+    pub fg: String,
+    #[serde(default = "default_bg")]
+    pub bg: String,
+    /// Draws the tile as a line that joins up with its neighbours, like walls. `glyph` is only
+    /// used at the edge of the map.
+    #[serde(default)]
+    pub joins: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TileDigging {
+    /// How much digging it takes to break through; a pickaxe digs 2 a turn.
+    pub hardness: i32,
+    /// The tile left behind.
+    pub rubble: String,
+}
+
+fn default_movement_cost() -> f32 {
+    1.0
+}
+
+fn default_bg() -> String {
+    "#000000".to_string()
+}
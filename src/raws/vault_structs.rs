@@ -0,0 +1,73 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A hand-made room that the vault builder stamps onto floor space it fits in.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Vault {
+    pub name: String,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    /// How often the vault is picked, relative to the others that can appear.
+    #[serde(default = "default_weight")]
+    pub weight: i32,
+    /// The vault only appears in biomes with one of these tags; with none, it can appear anywhere.
+    #[serde(default)]
+    pub biome_tags: Vec<String>,
+    #[serde(default)]
+    pub rotate: bool,
+    #[serde(default)]
+    pub mirror: bool,
+    /// What each character in the template stands for. A space is floor and `#` is wall unless
+    /// the legend says otherwise.
+    #[serde(default)]
+    pub legend: HashMap<char, VaultLegend>,
+    /// The rows of the vault, all the same width.
+    pub template: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct VaultLegend {
+    /// The name of the tile to lay.
+    #[serde(default = "default_tile")]
+    pub tile: String,
+    /// An item, mob or prop to place on the tile.
+    pub spawn: Option<String>,
+}
+
+fn default_weight() -> i32 {
+    1
+}
+
+fn default_tile() -> String {
+    "Floor".to_string()
+}
+
+impl Vault {
+    pub fn width(&self) -> usize {
+        self.template
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.template.len()
+    }
+
+    /// What the template character `ch` places, if it's one the vault knows.
+    pub fn legend_for(&self, ch: char) -> Option<VaultLegend> {
+        match (self.legend.get(&ch), ch) {
+            (Some(legend), _) => Some(legend.clone()),
+            (None, ' ') => Some(VaultLegend {
+                tile: "Floor".to_string(),
+                spawn: None,
+            }),
+            (None, '#') => Some(VaultLegend {
+                tile: "Wall".to_string(),
+                spawn: None,
+            }),
+            _ => None,
+        }
+    }
+}
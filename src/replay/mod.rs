@@ -0,0 +1,190 @@
+use crate::components::SerializeMe;
+use crate::headless::Simulation;
+use crate::player::PlayerCommand;
+use crate::rng::WorldSeed;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use specs::saveload::{Marker, MarkerAllocator, SimpleMarker, SimpleMarkerAllocator};
+use std::collections::HashMap;
+
+pub const REPLAY_PATH: &str = "./replay.json";
+
+/// Every command the player has issued since the game began; an ECS resource.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct InputRecording {
+    pub commands: Vec<PlayerCommand>,
+}
+
+/// Everything needed to play a game back and check that it ended the same way.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    pub version: String,
+    pub seed: u64,
+    pub commands: Vec<PlayerCommand>,
+    pub events: HashMap<String, i32>,
+    pub checksum: u64,
+}
+
+pub fn record_command(ecs: &mut World, command: &PlayerCommand) {
+    if let Some(mut recording) = ecs.try_fetch_mut::<InputRecording>() {
+        recording.commands.push(command.clone());
+    }
+}
+
+/// How a command refers to an entity: by its save marker, which stays the same through saves,
+/// loads and replays, where its entity id doesn't.
+pub fn entity_ref(ecs: &World, entity: Entity) -> u32 {
+    ecs.read_storage::<SimpleMarker<SerializeMe>>()
+        .get(entity)
+        .map_or(u32::MAX, |marker| marker.id() as u32)
+}
+
+/// The entity a command refers to, if it's still around.
+pub fn resolve_ref(ecs: &World, id: u32) -> Option<Entity> {
+    ecs.fetch::<SimpleMarkerAllocator<SerializeMe>>()
+        .retrieve_entity_internal(id as u64)
+        .filter(|entity| ecs.entities().is_alive(*entity))
+}
+
+/// Builds a replay of the game so far.
+pub fn capture(ecs: &World) -> Replay {
+    Replay {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        seed: ecs.fetch::<WorldSeed>().seed,
+        commands: ecs.fetch::<InputRecording>().commands.clone(),
+        events: crate::game_locals::events(ecs),
+        checksum: state_checksum(ecs),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_replay(_ecs: &World) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_replay(ecs: &World) {
+    let replay = capture(ecs);
+    let data = serde_json::to_string(&replay).unwrap();
+    if let Err(err) = std::fs::write(REPLAY_PATH, data) {
+        crate::console_log(format!("Unable to write {}: {}", REPLAY_PATH, err));
+    }
+}
+
+pub fn load_replay(path: &str) -> Result<Replay, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|err| format!("Unable to read {}: {}", path, err))?;
+    serde_json::from_str(&data).map_err(|err| format!("Unable to parse {}: {}", path, err))
+}
+
+/// Hashes everything a save would hold of the world, with the same hash on every build, so a
+/// replay recorded with one toolchain still verifies with the next. Particles, which come and go
+/// with the frame rate, aren't saved and so aren't hashed.
+pub fn state_checksum(ecs: &World) -> u64 {
+    let state = crate::saveload_system::world_state(ecs).expect("Unable to serialize the world");
+    crate::rng::stable_hash(state.to_string().as_bytes())
+}
+
+/// Plays a replay back headlessly, returning every way in which it differs from the original.
+pub fn verify(replay: &Replay) -> Result<Simulation, Vec<String>> {
+    if replay.version != env!("CARGO_PKG_VERSION") {
+        crate::console_log(format!(
+            "Warning: replay was recorded with version {}, this is {}",
+            replay.version,
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+
+    let mut simulation = Simulation::with_seed(replay.seed);
+    simulation.run(&replay.commands);
+
+    let mut problems = Vec::new();
+    let events = crate::game_locals::events(&simulation.state.ecs);
+    let mut names: Vec<&String> = replay.events.keys().chain(events.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let expected = replay.events.get(name).copied().unwrap_or(0);
+        let actual = events.get(name).copied().unwrap_or(0);
+        if expected != actual {
+            problems.push(format!(
+                "Event '{}' happened {} times, expected {}",
+                name, actual, expected
+            ));
+        }
+    }
+
+    let checksum = state_checksum(&simulation.state.ecs);
+    if checksum != replay.checksum {
+        problems.push(format!(
+            "World checksum is {:x}, expected {:x}",
+            checksum, replay.checksum
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(simulation)
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cheat;
+
+    fn recorded_game() -> Simulation {
+        let mut simulation = Simulation::with_seed(3);
+        let mut commands = vec![
+            PlayerCommand::Cheat {
+                cheat: Cheat::GodMode,
+            },
+            PlayerCommand::Move {
+                delta_x: -1,
+                delta_y: 0,
+            },
+            PlayerCommand::Cheat {
+                cheat: Cheat::TeleportToExit,
+            },
+        ];
+        commands.extend(std::iter::repeat_n(PlayerCommand::SkipTurn, 8));
+        simulation.run(&commands);
+        simulation
+    }
+
+    #[test]
+    fn a_replay_plays_back_the_same() {
+        let simulation = recorded_game();
+        let replay = capture(&simulation.state.ecs);
+        assert_eq!(replay.seed, 3);
+        assert_eq!(replay.commands.len(), 11);
+        assert!(replay.events.get("Turn").is_some_and(|turns| *turns > 0));
+
+        let replayed = verify(&replay).unwrap();
+        assert_eq!(state_checksum(&replayed.state.ecs), replay.checksum);
+    }
+
+    #[test]
+    fn a_diverging_replay_says_how() {
+        let mut replay = capture(&recorded_game().state.ecs);
+        replay.commands.pop();
+        let problems = verify(&replay).err().unwrap();
+        assert!(problems
+            .iter()
+            .any(|problem| problem.starts_with("Event 'Turn' happened")));
+
+        let mut replay = capture(&recorded_game().state.ecs);
+        replay.checksum ^= 1;
+        assert_eq!(verify(&replay).err().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn commands_refer_to_entities_by_marker() {
+        let simulation = recorded_game();
+        let ecs = &simulation.state.ecs;
+        let player = *ecs.fetch::<Entity>();
+        let id = entity_ref(ecs, player);
+        assert_ne!(id, u32::MAX);
+        assert_eq!(resolve_ref(ecs, id), Some(player));
+        assert_eq!(resolve_ref(ecs, u32::MAX), None);
+    }
+}
@@ -1,18 +1,154 @@
 use rltk::prelude::*;
-use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 
-lazy_static! {
-    static ref RNG: Mutex<RandomNumberGenerator> = Mutex::new(RandomNumberGenerator::new());
+/// The seed a whole game is generated from. Every random stream, and every level, derives its own
+/// seed from this one.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WorldSeed {
+    pub seed: u64,
 }
 
-pub fn reseed(seed: u64) {
-    *RNG.lock().unwrap() = RandomNumberGenerator::seeded(seed);
+impl WorldSeed {
+    pub fn new(seed: u64) -> WorldSeed {
+        WorldSeed { seed }
+    }
+
+    /// Picks a fresh seed from the clock, for when the player didn't ask for one.
+    pub fn random() -> WorldSeed {
+        WorldSeed::new(RandomNumberGenerator::new().next_u64())
+    }
+
+    /// The seed used to build the level at `depth`, regardless of the order levels are visited in.
+    pub fn depth_seed(&self, depth: i32) -> u64 {
+        derive_seed(self.seed, LEVEL_SALT.wrapping_add(depth as u64))
+    }
+
+    /// The seed used to build `level`. Main line levels use their depth's seed; a branch's levels
+    /// mix in the branch name, so they don't repeat the main line level at the same number.
+    pub fn level_seed(&self, level: &crate::map::LevelId) -> u64 {
+        let seed = self.depth_seed(level.level);
+        if level.is_main() {
+            seed
+        } else {
+            derive_seed(seed, name_hash(&level.branch))
+        }
+    }
+}
+
+const GENERAL_SALT: u64 = 1;
+const COMBAT_SALT: u64 = 2;
+const AI_SALT: u64 = 3;
+const LEVEL_SALT: u64 = 1 << 32;
+
+/// FNV-1a, which unlike the standard library's hasher is the same on every build.
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+fn name_hash(name: &str) -> u64 {
+    stable_hash(name.as_bytes())
+}
+
+/// SplitMix64, used to scatter the world seed into unrelated sub-seeds.
+fn derive_seed(seed: u64, salt: u64) -> u64 {
+    let mut z = seed ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Each kind of roll draws from its own stream, so that an extra combat roll doesn't change the
+/// layout of the next level.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RngStreams {
+    general: RandomNumberGenerator,
+    combat: RandomNumberGenerator,
+    ai: RandomNumberGenerator,
+    mapgen: RandomNumberGenerator,
+}
+
+impl std::fmt::Debug for RngStreams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RngStreams").finish_non_exhaustive()
+    }
+}
+
+impl RngStreams {
+    fn new() -> RngStreams {
+        RngStreams {
+            general: RandomNumberGenerator::new(),
+            combat: RandomNumberGenerator::new(),
+            ai: RandomNumberGenerator::new(),
+            mapgen: RandomNumberGenerator::new(),
+        }
+    }
+
+    pub fn seeded(world_seed: &WorldSeed) -> RngStreams {
+        RngStreams {
+            general: RandomNumberGenerator::seeded(derive_seed(world_seed.seed, GENERAL_SALT)),
+            combat: RandomNumberGenerator::seeded(derive_seed(world_seed.seed, COMBAT_SALT)),
+            ai: RandomNumberGenerator::seeded(derive_seed(world_seed.seed, AI_SALT)),
+            mapgen: RandomNumberGenerator::seeded(world_seed.depth_seed(0)),
+        }
+    }
+}
+
+thread_local! {
+    /// The streams of the game running on this thread; see `game_locals`.
+    static RNG: RefCell<RngStreams> = RefCell::new(RngStreams::new());
+}
+
+/// Makes `streams` the active ones, handing back the streams that were active.
+pub(crate) fn swap_streams(streams: RngStreams) -> RngStreams {
+    RNG.with(|rng| rng.replace(streams))
+}
+
+/// Reseeds every stream from the world seed; called when a new game starts.
+pub fn reseed(world_seed: &WorldSeed) {
+    RNG.with(|rng| *rng.borrow_mut() = RngStreams::seeded(world_seed));
+}
+
+/// Restarts the map generation stream for a level, so the same level always builds the same way.
+pub fn reseed_level(world_seed: &WorldSeed, level: &crate::map::LevelId) {
+    RNG.with(|rng| {
+        rng.borrow_mut().mapgen = RandomNumberGenerator::seeded(world_seed.level_seed(level))
+    });
+}
+
+/// Captures the current position of every stream, for the save file.
+pub fn clone_streams() -> RngStreams {
+    RNG.with(|rng| rng.borrow().clone())
+}
+
+pub fn restore_streams(streams: RngStreams) {
+    RNG.with(|rng| *rng.borrow_mut() = streams);
 }
 
 pub fn roll_dice(n: i32, die_type: i32) -> i32 {
-    RNG.lock().unwrap().roll_dice(n, die_type)
+    RNG.with(|rng| rng.borrow_mut().general.roll_dice(n, die_type))
 }
 
 pub fn range(min: i32, max: i32) -> i32 {
-    RNG.lock().unwrap().range(min, max)
+    RNG.with(|rng| rng.borrow_mut().general.range(min, max))
 }
+
+macro_rules! rng_stream {
+    ($name:ident) => {
+        pub mod $name {
+            pub fn roll_dice(n: i32, die_type: i32) -> i32 {
+                super::RNG.with(|rng| rng.borrow_mut().$name.roll_dice(n, die_type))
+            }
+
+            pub fn range(min: i32, max: i32) -> i32 {
+                super::RNG.with(|rng| rng.borrow_mut().$name.range(min, max))
+            }
+        }
+    };
+}
+
+rng_stream!(combat);
+rng_stream!(ai);
+rng_stream!(mapgen);
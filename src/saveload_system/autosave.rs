@@ -0,0 +1,124 @@
+use super::format::{save_format, SaveFormat, SAVE_FORMATS};
+use super::slots::{read_header, remove_if_present, save_files};
+use super::{current_slot_name, load_from, save_to, SaveError, SAVE_DIRECTORY};
+use specs::prelude::*;
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How many player turns can pass on one level before the game is autosaved anyway.
+pub const AUTOSAVE_INTERVAL: i32 = 100;
+const AUTOSAVE_SUBDIRECTORY: &str = "autosave";
+const PREVIOUS_SUFFIX: &str = ".previous";
+
+/// Decides when the main game should autosave: after every level change, and every
+/// `AUTOSAVE_INTERVAL` turns in between.
+pub struct AutosaveSchedule {
+    pub enabled: bool,
+    turns_since_save: i32,
+    level_changed: bool,
+}
+
+impl AutosaveSchedule {
+    pub fn new() -> AutosaveSchedule {
+        AutosaveSchedule {
+            enabled: true,
+            turns_since_save: 0,
+            level_changed: false,
+        }
+    }
+
+    pub fn level_changed(&mut self) {
+        self.level_changed = true;
+    }
+
+    pub fn turn_passed(&mut self) {
+        self.turns_since_save += 1;
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.enabled && (self.level_changed || self.turns_since_save >= AUTOSAVE_INTERVAL)
+    }
+
+    pub fn reset(&mut self) {
+        self.turns_since_save = 0;
+        self.level_changed = false;
+    }
+}
+
+impl Default for AutosaveSchedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn autosave_directory() -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join(AUTOSAVE_SUBDIRECTORY)
+}
+
+fn autosave_path(slot: &str, suffix: &str, format: SaveFormat) -> PathBuf {
+    autosave_directory().join(format!("{}{}.{}", slot, suffix, format.extension()))
+}
+
+/// Autosaves the current game. The autosave it replaces is kept as the previous one, in case the
+/// new one turns out to be unreadable.
+pub fn autosave(ecs: &mut World) -> Result<(), SaveError> {
+    let slot = current_slot_name(ecs);
+    let format = save_format();
+    let latest = autosave_path(&slot, "", format);
+    if latest.exists() {
+        fs::rename(&latest, autosave_path(&slot, PREVIOUS_SUFFIX, format))?;
+    }
+    save_to(ecs, &latest)
+}
+
+/// Every autosave on disk, newest first; ones whose header can't be read go last. Timestamps only
+/// go down to the second, so an autosave and the previous one it replaced often tie, and then the
+/// latest goes first.
+fn list_autosaves() -> Vec<PathBuf> {
+    let mut autosaves = save_files(&autosave_directory())
+        .into_iter()
+        .map(|path| {
+            let timestamp = read_header(&path)
+                .map(|header| header.metadata.timestamp)
+                .unwrap_or(0);
+            let previous = path
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().ends_with(PREVIOUS_SUFFIX));
+            (Reverse(timestamp), previous, path)
+        })
+        .collect::<Vec<_>>();
+    autosaves.sort();
+    autosaves.into_iter().map(|(_, _, path)| path).collect()
+}
+
+pub fn does_autosave_exist() -> bool {
+    !save_files(&autosave_directory()).is_empty()
+}
+
+/// Loads the newest autosave that can be read, falling back to older ones when it can't.
+pub fn continue_game(ecs: &mut World) -> Result<(), SaveError> {
+    let mut last_error = SaveError::Corrupt("there are no autosaves".to_string());
+    for path in list_autosaves() {
+        match load_from(ecs, &path) {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                crate::console_log(format!("Unable to load {}: {}", path.display(), err));
+                last_error = err;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// Removes the current character's autosaves, so a dead character can't be continued.
+pub fn delete_autosaves(ecs: &World) -> io::Result<()> {
+    let slot = current_slot_name(ecs);
+    for suffix in ["", PREVIOUS_SUFFIX].iter() {
+        for format in SAVE_FORMATS.iter() {
+            remove_if_present(&autosave_path(&slot, suffix, *format))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use super::{SaveError, SaveHeader};
+use serde_json::{json, Map, Value};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// How a save is encoded on disk, chosen by its file extension. Both hold exactly the same data,
+/// so a save can be converted back and forth without losing anything.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SaveFormat {
+    /// Readable JSON: the header followed by one value per component type.
+    Json,
+    /// MessagePack, with the map layers packed: tiles and light run-length encoded, and the
+    /// revealed and visible flags as bitsets.
+    Binary,
+}
+
+pub const SAVE_FORMATS: [SaveFormat; 2] = [SaveFormat::Json, SaveFormat::Binary];
+
+/// Written at the start of binary saves, so they can't be mistaken for anything else.
+const BINARY_MAGIC: &[u8] = b"DDSAVE";
+
+impl SaveFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveFormat::Json => "json",
+            SaveFormat::Binary => "sav",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<SaveFormat> {
+        SAVE_FORMATS
+            .iter()
+            .copied()
+            .find(|format| format.extension() == extension)
+    }
+
+    /// Anything that isn't recognisably binary is treated as JSON.
+    pub fn from_path(path: &Path) -> SaveFormat {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(SaveFormat::from_extension)
+            .unwrap_or(SaveFormat::Json)
+    }
+}
+
+lazy_static! {
+    static ref SAVE_FORMAT: Mutex<SaveFormat> = Mutex::new(SaveFormat::Json);
+}
+
+/// Chooses the format new saves and autosaves are written in.
+pub fn set_save_format(format: SaveFormat) {
+    *SAVE_FORMAT.lock().unwrap() = format;
+}
+
+pub fn save_format() -> SaveFormat {
+    *SAVE_FORMAT.lock().unwrap()
+}
+
+pub(super) fn write(
+    path: &Path,
+    format: SaveFormat,
+    header: &SaveHeader,
+    components: &[Value],
+) -> Result<(), SaveError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        SaveFormat::Json => {
+            serde_json::to_writer(&mut writer, header)?;
+            for component in components.iter() {
+                serde_json::to_writer(&mut writer, component)?;
+            }
+        }
+        SaveFormat::Binary => {
+            let packed = components.iter().map(pack).collect::<Vec<Value>>();
+            writer.write_all(BINARY_MAGIC)?;
+            rmp_serde::encode::write_named(&mut writer, header).map_err(binary_error)?;
+            rmp_serde::encode::write_named(&mut writer, &packed).map_err(binary_error)?;
+        }
+    }
+    let file = writer.into_inner().map_err(|err| err.into_error())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Reads a save as the header followed by one value per component type, whatever its format.
+pub(super) fn read(path: &Path) -> Result<Vec<Value>, SaveError> {
+    match SaveFormat::from_path(path) {
+        SaveFormat::Json => {
+            let data = fs::read_to_string(path)?;
+            Ok(serde_json::Deserializer::from_str(&data)
+                .into_iter::<Value>()
+                .collect::<Result<Vec<Value>, _>>()?)
+        }
+        SaveFormat::Binary => {
+            let mut reader = BufReader::new(File::open(path)?);
+            let mut magic = [0; BINARY_MAGIC.len()];
+            reader.read_exact(&mut magic)?;
+            if magic != BINARY_MAGIC {
+                return Err(SaveError::Corrupt("it isn't a binary save".to_string()));
+            }
+            let header: Value = rmp_serde::from_read(&mut reader).map_err(binary_error)?;
+            let components: Vec<Value> = rmp_serde::from_read(&mut reader).map_err(binary_error)?;
+            let mut values = vec![header];
+            for component in components.iter() {
+                values.push(unpack(component)?);
+            }
+            Ok(values)
+        }
+    }
+}
+
+/// Rewrites a save in the format its new extension calls for. The save isn't migrated, so this
+/// works on saves from any version.
+pub fn convert_save(input: &Path, output: &Path) -> Result<(), SaveError> {
+    let mut values = read(input)?.into_iter();
+    let header = values
+        .next()
+        .ok_or_else(|| SaveError::Corrupt("the file is empty".to_string()))?;
+    let header = serde_json::from_value(header)?;
+    let components = values.collect::<Vec<Value>>();
+    write(output, SaveFormat::from_path(output), &header, &components)
+}
+
+fn binary_error<E: std::fmt::Display>(err: E) -> SaveError {
+    SaveError::Corrupt(err.to_string())
+}
+
+/// The layers of a saved `Map` that are packed, and how.
+const RUN_LENGTH_LAYERS: [&str; 2] = ["tiles", "light"];
+const BITSET_LAYERS: [&str; 2] = ["revealed_tiles", "visible_tiles"];
+
+/// Whether a saved object is a `Map`.
+pub(super) fn is_map(object: &Map<String, Value>) -> bool {
+    object.contains_key("tiles") && object.contains_key("revealed_tiles")
+}
+
+/// Packs the layers of every map found in `value`; everything else is copied as it is.
+fn pack(value: &Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.iter().map(pack).collect()),
+        Value::Object(object) => {
+            let map = is_map(object);
+            let mut packed = Map::new();
+            for (key, value) in object.iter() {
+                let value = match value {
+                    Value::Array(layer) if map && RUN_LENGTH_LAYERS.contains(&key.as_str()) => {
+                        run_length_encode(layer)
+                    }
+                    Value::Array(layer) if map && BITSET_LAYERS.contains(&key.as_str()) => {
+                        bitset_encode(layer)
+                    }
+                    _ => pack(value),
+                };
+                packed.insert(key.clone(), value);
+            }
+            Value::Object(packed)
+        }
+        _ => value.clone(),
+    }
+}
+
+/// The inverse of `pack`.
+fn unpack(value: &Value) -> Result<Value, SaveError> {
+    Ok(match value {
+        Value::Array(values) => Value::Array(values.iter().map(unpack).collect::<Result<_, _>>()?),
+        Value::Object(object) => {
+            let map = is_map(object);
+            let mut unpacked = Map::new();
+            for (key, value) in object.iter() {
+                let value = if map && RUN_LENGTH_LAYERS.contains(&key.as_str()) {
+                    run_length_decode(value)?
+                } else if map && BITSET_LAYERS.contains(&key.as_str()) {
+                    bitset_decode(value)?
+                } else {
+                    unpack(value)?
+                };
+                unpacked.insert(key.clone(), value);
+            }
+            Value::Object(unpacked)
+        }
+        _ => value.clone(),
+    })
+}
+
+/// `[[value, count], ...]`
+fn run_length_encode(layer: &[Value]) -> Value {
+    let mut runs: Vec<(&Value, u64)> = Vec::new();
+    for value in layer.iter() {
+        match runs.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            _ => runs.push((value, 1)),
+        }
+    }
+    Value::Array(
+        runs.into_iter()
+            .map(|(value, count)| json!([value, count]))
+            .collect(),
+    )
+}
+
+fn run_length_decode(packed: &Value) -> Result<Value, SaveError> {
+    let damaged = || SaveError::Corrupt("a run-length encoded map layer is damaged".to_string());
+    let mut layer = Vec::new();
+    for run in packed.as_array().ok_or_else(damaged)? {
+        let value = run.get(0).ok_or_else(damaged)?;
+        let count = run.get(1).and_then(Value::as_u64).ok_or_else(damaged)?;
+        layer.extend(std::iter::repeat_n(value.clone(), count as usize));
+    }
+    Ok(Value::Array(layer))
+}
+
+/// `{"len": n, "bits": [byte, ...]}`, least significant bit first.
+fn bitset_encode(layer: &[Value]) -> Value {
+    let mut bytes = vec![0u8; layer.len().div_ceil(8)];
+    for (i, value) in layer.iter().enumerate() {
+        if value.as_bool().unwrap_or(false) {
+            bytes[i / 8] |= 1 << (i % 8);
+        }
+    }
+    json!({ "len": layer.len(), "bits": bytes })
+}
+
+fn bitset_decode(packed: &Value) -> Result<Value, SaveError> {
+    let damaged = || SaveError::Corrupt("a map bitset is damaged".to_string());
+    let len = packed
+        .get("len")
+        .and_then(Value::as_u64)
+        .ok_or_else(damaged)? as usize;
+    let bytes = packed
+        .get("bits")
+        .and_then(Value::as_array)
+        .ok_or_else(damaged)?
+        .iter()
+        .map(|byte| byte.as_u64().map(|byte| byte as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(damaged)?;
+    if bytes.len() * 8 < len {
+        return Err(damaged());
+    }
+    Ok(Value::Array(
+        (0..len)
+            .map(|i| Value::Bool(bytes[i / 8] & (1 << (i % 8)) != 0))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saveload_system::{SaveMetadata, SAVE_FORMAT_VERSION};
+
+    fn saved_map() -> Value {
+        json!({
+            "tiles": ["Wall", "Wall", "Wall", "Floor", "Floor", "Wall", "Down Stairs"],
+            "light": [[1.0, 1.0, 1.0], [1.0, 1.0, 1.0], [0.5, 0.5, 0.5], [0.5, 0.5, 0.5],
+                [0.5, 0.5, 0.5], [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]],
+            "revealed_tiles": [true, false, false, true, true, true, true, false, true],
+            "visible_tiles": [],
+            "width": 7,
+            "stair_links": { "6": { "x": 1, "y": 1 } },
+        })
+    }
+
+    #[test]
+    fn layers_come_back_as_they_were_packed() {
+        let layer = saved_map()["tiles"].as_array().unwrap().clone();
+        let packed = run_length_encode(&layer);
+        assert_eq!(
+            packed,
+            json!([["Wall", 3], ["Floor", 2], ["Wall", 1], ["Down Stairs", 1]])
+        );
+        assert_eq!(run_length_decode(&packed).unwrap(), Value::Array(layer));
+
+        for len in [0usize, 1, 7, 8, 9, 17] {
+            let layer: Vec<Value> = (0..len).map(|i| Value::Bool(i % 3 == 0)).collect();
+            let packed = bitset_encode(&layer);
+            assert_eq!(packed["bits"].as_array().unwrap().len(), len.div_ceil(8));
+            assert_eq!(bitset_decode(&packed).unwrap(), Value::Array(layer));
+        }
+    }
+
+    #[test]
+    fn only_map_layers_are_packed() {
+        let save = json!([{
+            "marker": [1],
+            "components": [{ "map": saved_map() }, { "tiles": [1, 1, 1] }],
+        }]);
+        let packed = pack(&save);
+        let map = &packed[0]["components"][0]["map"];
+        assert_eq!(map["revealed_tiles"]["len"], json!(9));
+        assert_eq!(map["light"].as_array().unwrap().len(), 3);
+        assert_eq!(map["stair_links"], saved_map()["stair_links"]);
+        assert_eq!(packed[0]["components"][1], save[0]["components"][1]);
+        assert_eq!(unpack(&packed).unwrap(), save);
+    }
+
+    #[test]
+    fn damaged_layers_are_refused() {
+        let too_short = json!({ "len": 9, "bits": [255] });
+        assert!(matches!(
+            bitset_decode(&too_short),
+            Err(SaveError::Corrupt(_))
+        ));
+        let no_count = json!([["Wall"]]);
+        assert!(matches!(
+            run_length_decode(&no_count),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn both_formats_read_back_what_was_written() {
+        let header = SaveHeader {
+            format_version: SAVE_FORMAT_VERSION,
+            metadata: SaveMetadata {
+                character_name: "Tester".to_string(),
+                depth: 3,
+                turns: 120,
+                timestamp: 0,
+            },
+        };
+        let components = vec![json!([{ "marker": [1], "components": [{ "map": saved_map() }] }])];
+        for format in SAVE_FORMATS {
+            let path = std::env::temp_dir().join(format!(
+                "downdelving-format-test-{}.{}",
+                std::process::id(),
+                format.extension()
+            ));
+            write(&path, format, &header, &components).unwrap();
+            let values = read(&path);
+            fs::remove_file(&path).ok();
+            let values = values.unwrap();
+            assert_eq!(values[0]["metadata"]["turns"], json!(120));
+            assert_eq!(values[1..], components[..]);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use super::format::is_map;
+use super::{SaveError, SaveHeader, SaveMetadata, SAVE_FORMAT_VERSION};
+use crate::rng::{RngStreams, WorldSeed};
+use serde_json::{json, Map, Value};
+
+/// A save that has been read from disk and brought up to the current format, but not yet loaded
+/// into the world. `components` holds one entry per component type, in the order they're saved.
+pub struct SaveData {
+    pub header: SaveHeader,
+    pub components: Vec<Value>,
+}
+
+type Migration = fn(&mut SaveData) -> Result<(), SaveError>;
+
+/// Where `LightSource` is in the list of saved component types. Nothing has been saved ahead of
+/// it since version 1, so it's there in every version.
+const LIGHT_SOURCE_STORAGE: usize = 42;
+
+/// `MIGRATIONS[n]` upgrades a save from format version `n + 1` to `n + 2`.
+const MIGRATIONS: [Migration; 11] = [
+    v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7, v7_to_v8, v8_to_v9, v9_to_v10,
+    v10_to_v11, v11_to_v12,
+];
+
+/// Takes the values a save was read as, the header followed by one per component type, and
+/// brings them up to the current format.
+pub fn parse(mut values: Vec<Value>) -> Result<SaveData, SaveError> {
+    if values.is_empty() {
+        return Err(SaveError::Corrupt("the file is empty".to_string()));
+    }
+
+    // Version 1 saves had no header; they start straight into the component data.
+    let version = match values[0].get("format_version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| SaveError::Corrupt("the format version isn't a number".to_string()))?
+            as u32,
+        None => 1,
+    };
+    if version == 0 || version > SAVE_FORMAT_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+
+    let mut save = if version == 1 {
+        SaveData {
+            header: SaveHeader {
+                format_version: 1,
+                metadata: SaveMetadata {
+                    character_name: "Player".to_string(),
+                    depth: 0,
+                    turns: 0,
+                    timestamp: 0,
+                },
+            },
+            components: values,
+        }
+    } else {
+        let header = serde_json::from_value(values.remove(0))?;
+        SaveData {
+            header,
+            components: values,
+        }
+    };
+
+    for migration in MIGRATIONS.iter().skip(version as usize - 1) {
+        migration(&mut save)?;
+        save.header.format_version += 1;
+    }
+
+    Ok(save)
+}
+
+/// Finds the saved copy of a singleton helper component, recognised by a field it contains.
+fn helper_mut<'a>(components: &'a mut [Value], field: &str) -> Option<&'a mut Value> {
+    components
+        .iter_mut()
+        .filter_map(|storage| storage.as_array_mut())
+        .flat_map(|entries| entries.iter_mut())
+        .filter_map(|entry| entry.pointer_mut("/components/0"))
+        .find(|component| component.pointer(field).is_some())
+}
+
+/// Calls `f` on every object in `value`, outermost first.
+fn for_each_object(value: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+    match value {
+        Value::Object(object) => {
+            f(object);
+            for child in object.values_mut() {
+                for_each_object(child, f);
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                for_each_object(item, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Calls `f` on every saved `Map` in `value`: the current level's and each one in the dungeon.
+fn for_each_map(value: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+    for_each_object(value, &mut |object| {
+        if is_map(object) {
+            f(object);
+        }
+    });
+}
+
+/// Version 2 added the world seed, the random number streams and the input recording to the
+/// dungeon helper, and the header with its metadata.
+fn v1_to_v2(save: &mut SaveData) -> Result<(), SaveError> {
+    let missing = |what: &str| SaveError::Corrupt(format!("the {} is missing", what));
+
+    let helper = helper_mut(&mut save.components, "/log").ok_or_else(|| missing("dungeon"))?;
+    let world_seed = WorldSeed::new(0);
+    helper["world_seed"] = serde_json::to_value(world_seed)?;
+    helper["rng"] = serde_json::to_value(RngStreams::seeded(&world_seed))?;
+    helper["recording"] = json!({ "commands": [] });
+    save.header.metadata.turns = helper
+        .pointer("/events/Turn")
+        .and_then(Value::as_i64)
+        .unwrap_or(0) as i32;
+
+    let helper = helper_mut(&mut save.components, "/map/depth").ok_or_else(|| missing("map"))?;
+    save.header.metadata.depth = helper["map"]["depth"].as_i64().unwrap_or(0) as i32;
+
+    Ok(())
+}
+
+/// Version 3 turned each log entry from a list of fragments into a tagged entry. Old entries are
+/// filed as system messages from turn 0.
+fn v2_to_v3(save: &mut SaveData) -> Result<(), SaveError> {
+    let helper = helper_mut(&mut save.components, "/log")
+        .ok_or_else(|| SaveError::Corrupt("the dungeon is missing".to_string()))?;
+    let entries = helper["log"]
+        .as_array()
+        .ok_or_else(|| SaveError::Corrupt("the log isn't a list".to_string()))?
+        .iter()
+        .map(|fragments| {
+            json!({
+                "turn": 0,
+                "category": "System",
+                "entities": [],
+                "fragments": fragments,
+            })
+        })
+        .collect::<Vec<Value>>();
+    helper["log"] = Value::Array(entries);
+    Ok(())
+}
+
+/// Version 4 replaced the dice count, die type and bonus of weapons and natural attacks with a
+/// single dice expression.
+fn v3_to_v4(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_object(storage, &mut |object| {
+            let dice = (
+                object.get("damage_n_dice").and_then(Value::as_i64),
+                object.get("damage_die_type").and_then(Value::as_i64),
+                object.get("damage_bonus").and_then(Value::as_i64),
+            );
+            if let (Some(n_dice), Some(die_type), Some(bonus)) = dice {
+                object.remove("damage_n_dice");
+                object.remove("damage_die_type");
+                object.remove("damage_bonus");
+                let dice = crate::dice::Dice::new(n_dice as i32, die_type as i32, bonus as i32);
+                object.insert("damage".to_string(), Value::String(dice.to_string()));
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Version 5 links each staircase to the one it leads to. Older levels have no links, so their
+/// stairs go to a staircase of the other kind, as they used to.
+fn v4_to_v5(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_map(storage, &mut |map| {
+            map.entry("stair_links").or_insert_with(|| json!({}));
+        });
+    }
+    Ok(())
+}
+
+/// Version 6 identified levels by branch and level instead of by depth. Everything older is on
+/// the main line, at the level its depth was, and the dungeon's maps became a list.
+fn v5_to_v6(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_object(storage, &mut |object| {
+            if is_map(object) {
+                let level = main_level(object.get("depth"));
+                object.entry("level").or_insert(level);
+                object.entry("branch_stairs").or_insert_with(|| json!({}));
+            } else if object.contains_key("x") && object.contains_key("y") {
+                // Positions on other levels, and teleports to them
+                if let Some(depth) = object.remove("depth") {
+                    object.insert("level".to_string(), main_level(Some(&depth)));
+                }
+            } else if let Some(depth) = object.remove("dest_depth") {
+                object.insert("dest_level".to_string(), main_level(Some(&depth)));
+            }
+        });
+    }
+
+    let helper = helper_mut(&mut save.components, "/map/maps")
+        .ok_or_else(|| SaveError::Corrupt("the dungeon is missing".to_string()))?;
+    let maps = helper["map"]["maps"]
+        .as_object()
+        .ok_or_else(|| SaveError::Corrupt("the dungeon's maps aren't a table".to_string()))?
+        .values()
+        .cloned()
+        .collect::<Vec<Value>>();
+    helper["map"]["maps"] = Value::Array(maps);
+    Ok(())
+}
+
+fn main_level(depth: Option<&Value>) -> Value {
+    json!({
+        "branch": crate::map::MAIN_BRANCH,
+        "level": depth.and_then(Value::as_i64).unwrap_or(1),
+    })
+}
+
+/// Version 7 moved the tile kinds into the raws, where some of them have names with spaces.
+fn v6_to_v7(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_map(storage, &mut |map| {
+            if let Some(Value::Array(tiles)) = map.get_mut("tiles") {
+                for tile in tiles.iter_mut() {
+                    let renamed = match tile.as_str() {
+                        Some("DownStairs") => "Down Stairs",
+                        Some("UpStairs") => "Up Stairs",
+                        Some("ShallowWater") => "Shallow Water",
+                        Some("DeepWater") => "Deep Water",
+                        Some("WoodFloor") => "Wood Floor",
+                        Some("SecretDoor") => "Secret Door",
+                        _ => continue,
+                    };
+                    *tile = Value::String(renamed.to_string());
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Version 8 gave each map its own theme, where they used to be drawn by depth. Maps get the
+/// themes their depth was drawn with, including the plain parts of the levels in between.
+fn v7_to_v8(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_map(storage, &mut |map| {
+            let on_main_line = map
+                .get("level")
+                .and_then(|level| level.get("branch"))
+                .and_then(Value::as_str)
+                == Some(crate::map::MAIN_BRANCH);
+            let depth = map.get("depth").and_then(Value::as_i64).unwrap_or(0);
+            let width = map.get("width").and_then(Value::as_i64).unwrap_or(0);
+            let height = map.get("height").and_then(Value::as_i64).unwrap_or(0);
+            let plain_region = |x1: i64| {
+                json!([{
+                    "area": { "x1": x1, "x2": width, "y1": 0, "y2": height },
+                    "theme": null,
+                }])
+            };
+            let (theme, regions) = match depth {
+                _ if !on_main_line => (Value::Null, json!([])),
+                2 => (json!("forest"), json!([])),
+                3 | 4 => (json!("cavern"), json!([])),
+                5 => (json!("cavern"), plain_region(width / 2)),
+                7 => (json!("mushroom"), plain_region(width - 15)),
+                8 | 9 => (json!("mushroom"), json!([])),
+                _ => (Value::Null, json!([])),
+            };
+            map.entry("theme").or_insert(theme);
+            map.entry("theme_regions").or_insert(regions);
+        });
+    }
+    Ok(())
+}
+
+/// Version 9 let tiles be dug through, keeping track of partly dug tiles on each map, and added
+/// the component for things that dig, which nothing in an older save has.
+fn v8_to_v9(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_map(storage, &mut |map| {
+            map.entry("dig_progress").or_insert_with(|| json!({}));
+        });
+    }
+    save.components.push(json!([]));
+    Ok(())
+}
+
+/// Version 10 added fire, gas and spilled liquid to each map, and the components for things that
+/// burn and things that leave hazards behind.
+fn v9_to_v10(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_map(storage, &mut |map| {
+            map.entry("hazards").or_insert_with(|| json!({}));
+        });
+    }
+    save.components.push(json!([]));
+    save.components.push(json!([]));
+    Ok(())
+}
+
+/// Version 11 gave levels their biome's ambient light and light sources an intensity and flicker.
+/// Older levels keep the light they had, and older lights shine steadily at full strength.
+fn v10_to_v11(save: &mut SaveData) -> Result<(), SaveError> {
+    for storage in save.components.iter_mut() {
+        for_each_object(storage, &mut |object| {
+            if is_map(object) {
+                object.entry("ambient_light").or_insert(Value::Null);
+            }
+        });
+    }
+
+    let lights = save
+        .components
+        .get_mut(LIGHT_SOURCE_STORAGE)
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Corrupt("the light sources aren't a list".to_string()))?;
+    for light in lights
+        .iter_mut()
+        .filter_map(|entry| entry.pointer_mut("/components/0"))
+        .filter_map(Value::as_object_mut)
+    {
+        light.entry("intensity").or_insert_with(|| json!(1.0));
+        light.entry("flicker").or_insert_with(|| json!(0.0));
+    }
+    Ok(())
+}
+
+/// Version 12 saved which marker the next entity gets. Older saves carry on from their highest
+/// marker, as they always did.
+fn v11_to_v12(save: &mut SaveData) -> Result<(), SaveError> {
+    let helper = helper_mut(&mut save.components, "/log")
+        .ok_or_else(|| SaveError::Corrupt("the dungeon is missing".to_string()))?;
+    helper["next_marker"] = json!(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version_1_map(depth: i64) -> Value {
+        json!({
+            "tiles": ["Wall", "Floor", "DownStairs", "WoodFloor", "Wall", "Wall"],
+            "width": 80,
+            "height": 50,
+            "revealed_tiles": [true, true, true, false, false, false],
+            "visible_tiles": [false, false, false, false, false, false],
+            "depth": depth,
+            "bloodstains": {},
+            "view_blocked": [],
+            "name": "Old Level",
+            "outdoors": false,
+            "light": [],
+        })
+    }
+
+    /// A save from before there were headers, with one of everything the migrations touch.
+    fn version_1_save() -> Vec<Value> {
+        let entity = |marker: u64, component: Value| json!([{ "marker": [marker], "components": [component] }]);
+        let mut save = vec![
+            entity(1, json!({ "map": version_1_map(5) })),
+            entity(
+                2,
+                json!({
+                    "map": {
+                        "maps": { "4": version_1_map(4), "5": version_1_map(5) },
+                        "identified_items": [],
+                        "scroll_mappings": {},
+                        "potion_mappings": {},
+                    },
+                    "log": [[{ "color": [1.0, 1.0, 1.0], "text": "Welcome" }]],
+                    "events": { "Turn": 37 },
+                }),
+            ),
+            entity(
+                3,
+                json!({
+                    "range": null,
+                    "attribute": "Might",
+                    "damage_n_dice": 1,
+                    "damage_die_type": 8,
+                    "damage_bonus": 2,
+                    "hit_bonus": 0,
+                }),
+            ),
+            entity(4, json!({ "x": 3, "y": 4, "depth": 4 })),
+            // Something else with a colour and a range, which isn't a light
+            entity(5, json!({ "color": [0.0, 0.0, 1.0], "range": 2 })),
+        ];
+        save.resize(LIGHT_SOURCE_STORAGE, json!([]));
+        save.push(entity(6, json!({ "color": [1.0, 0.5, 0.0], "range": 6 })));
+        save
+    }
+
+    fn component(save: &SaveData, storage: usize) -> &Value {
+        &save.components[storage][0]["components"][0]
+    }
+
+    #[test]
+    fn a_version_1_save_is_brought_up_to_date() {
+        let save = parse(version_1_save()).unwrap();
+        assert_eq!(save.header.format_version, SAVE_FORMAT_VERSION);
+        assert_eq!(save.header.metadata.turns, 37);
+        assert_eq!(save.header.metadata.depth, 5);
+        // Storages for diggers, flammables and hazard leavers are added on the way
+        assert_eq!(save.components.len(), LIGHT_SOURCE_STORAGE + 4);
+
+        let map = &component(&save, 0)["map"];
+        assert_eq!(
+            map["tiles"],
+            json!(["Wall", "Floor", "Down Stairs", "Wood Floor", "Wall", "Wall"])
+        );
+        assert_eq!(map["level"], json!({ "branch": "Dungeon", "level": 5 }));
+        assert_eq!(map["theme"], json!("cavern"));
+        assert_eq!(map["theme_regions"][0]["area"]["x1"], json!(40));
+        for layer in ["stair_links", "branch_stairs", "dig_progress", "hazards"] {
+            assert_eq!(map[layer], json!({}), "{}", layer);
+        }
+        assert_eq!(map["ambient_light"], Value::Null);
+
+        let dungeon = component(&save, 1);
+        let maps = dungeon["map"]["maps"].as_array().unwrap();
+        assert_eq!(maps.len(), 2);
+        assert!(maps
+            .iter()
+            .all(|map| map["tiles"][2] == json!("Down Stairs")));
+        assert_eq!(dungeon["log"][0]["category"], json!("System"));
+        assert_eq!(dungeon["log"][0]["fragments"][0]["text"], json!("Welcome"));
+        assert_eq!(dungeon["recording"], json!({ "commands": [] }));
+        assert_eq!(dungeon["next_marker"], json!(0));
+
+        let weapon = component(&save, 2);
+        assert_eq!(weapon["damage"], json!("1d8+2"));
+        assert!(weapon.get("damage_n_dice").is_none());
+        assert_eq!(
+            component(&save, 3)["level"],
+            json!({ "branch": "Dungeon", "level": 4 })
+        );
+        assert!(component(&save, 4).get("intensity").is_none());
+        let light = component(&save, LIGHT_SOURCE_STORAGE);
+        assert_eq!(light["intensity"], json!(1.0));
+        assert_eq!(light["flicker"], json!(0.0));
+    }
+
+    #[test]
+    fn a_save_part_way_along_only_takes_the_later_migrations() {
+        let mut values = parse(version_1_save()).map(|save| save.components).unwrap();
+        let header = json!({
+            "format_version": SAVE_FORMAT_VERSION,
+            "metadata": { "character_name": "Tester", "depth": 5, "turns": 37, "timestamp": 0 },
+        });
+        values.insert(0, header);
+        let save = parse(values.clone()).unwrap();
+        assert_eq!(save.components[..], values[1..]);
+    }
+
+    #[test]
+    fn unreadable_versions_are_refused() {
+        let from_the_future = json!({
+            "format_version": SAVE_FORMAT_VERSION + 1,
+            "metadata": { "character_name": "Tester", "depth": 1, "turns": 0, "timestamp": 0 },
+        });
+        assert!(matches!(
+            parse(vec![from_the_future]),
+            Err(SaveError::UnsupportedVersion(version)) if version == SAVE_FORMAT_VERSION + 1
+        ));
+        assert!(matches!(parse(Vec::new()), Err(SaveError::Corrupt(_))));
+        let no_dungeon =
+            vec![json!([{ "marker": [1], "components": [{ "map": version_1_map(1) }] }])];
+        assert!(matches!(parse(no_dungeon), Err(SaveError::Corrupt(_))));
+    }
+}
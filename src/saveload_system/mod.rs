@@ -2,31 +2,101 @@ use super::components::*;
 use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{
-    DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+    DeserializeComponents, MarkedBuilder, Marker, MarkerAllocator, SerializeComponents,
+    SimpleMarker, SimpleMarkerAllocator,
 };
 use std::fs;
-use std::fs::File;
 use std::path::Path;
+mod autosave;
+mod format;
+mod migrations;
+mod slots;
+pub use autosave::*;
+pub use format::{convert_save, set_save_format, SaveFormat};
+use slots::delete_other_formats;
+pub use slots::*;
+
+/// Bump this, and add a migration, whenever a saved component changes shape.
+pub const SAVE_FORMAT_VERSION: u32 = 12;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    UnsupportedVersion(u32),
+    Corrupt(String),
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "The save couldn't be read: {}", err),
+            SaveError::Parse(err) => write!(f, "The save is damaged: {}", err),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "The save is format {}, but this version only understands up to {}",
+                version, SAVE_FORMAT_VERSION
+            ),
+            SaveError::Corrupt(reason) => write!(f, "The save is damaged: {}", reason),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(err: std::io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> Self {
+        SaveError::Parse(err)
+    }
+}
 
 macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
-        $(
-        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
-            &( $ecs.read_storage::<$type>(), ),
-            &$data.0,
-            &$data.1,
-            &mut $ser,
-        )
-        .unwrap();
-        )*
+    ($ecs:expr, $data:expr, $( $type:ty),*) => {
+        vec![
+            $(
+            SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+                &( $ecs.read_storage::<$type>(), ),
+                &$data.0,
+                &$data.1,
+                serde_json::value::Serializer,
+            )?,
+            )*
+        ]
     };
 }
 
+/// Saves the current game into its slot, replacing whatever was there.
+pub fn save_game(ecs: &mut World) -> Result<(), SaveError> {
+    let slot = current_slot_name(ecs);
+    save_to(ecs, &slot_path(&slot))?;
+    // The save itself worked, so a stale copy left behind isn't worth failing it over
+    if let Err(err) = delete_other_formats(&slot) {
+        crate::console_log(format!(
+            "Unable to remove the old copies of {}: {}",
+            slot, err
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(target_arch = "wasm32")]
-pub fn save_game(_ecs: &mut World) {}
+fn save_to(_ecs: &mut World, _path: &Path) -> Result<(), SaveError> {
+    Ok(())
+}
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn save_game(ecs: &mut World) {
+fn save_to(ecs: &mut World, path: &Path) -> Result<(), SaveError> {
+    crate::game_locals::with_game_locals(ecs, |ecs| save_with_locals(ecs, path))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_with_locals(ecs: &mut World, path: &Path) -> Result<(), SaveError> {
+    let header = SaveHeader::new(ecs);
+
     // Create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
     let dungeon_master = ecs
@@ -38,152 +108,283 @@ pub fn save_game(ecs: &mut World) {
         .with(SerializationHelper { map: mapcopy })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
+    let world_seed = *ecs.fetch::<crate::rng::WorldSeed>();
+    let recording = ecs
+        .fetch::<crate::replay::InputRecording>()
+        .commands
+        .clone();
+    let log = log_for_save(ecs, crate::gamelog::clone_log());
+    // The helpers are marked too, but the markers they take are handed back afterwards
+    let allocator = (*ecs.fetch::<SimpleMarkerAllocator<SerializeMe>>()).clone();
+    let next_marker = allocator
+        .clone()
+        .allocate(*ecs.fetch::<Entity>(), None)
+        .id();
     let savehelper2 = ecs
         .create_entity()
         .with(DMSerializationHelper {
             map: dungeon_master,
-            log: crate::gamelog::clone_log(),
+            log,
             events: crate::gamelog::clone_events(),
+            world_seed,
+            rng: crate::rng::clone_streams(),
+            recording: crate::replay::InputRecording {
+                commands: recording,
+            },
+            next_marker,
         })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
     // Actually serialize
-    {
-        let data = (
-            ecs.entities(),
-            ecs.read_storage::<SimpleMarker<SerializeMe>>(),
-        );
+    let result = write_save(ecs, path, &header);
 
-        let writer = File::create("./savegame.json").unwrap();
-        let mut serializer = serde_json::Serializer::new(writer);
-        serialize_individually!(
-            ecs,
-            serializer,
-            data,
-            Position,
-            Renderable,
-            Player,
-            Viewshed,
-            Name,
-            BlocksTile,
-            WantsToMelee,
-            Item,
-            Consumable,
-            Ranged,
-            InflictsDamage,
-            AreaOfEffect,
-            Confusion,
-            ProvidesHealing,
-            InBackpack,
-            WantsToPickupItem,
-            WantsToUseItem,
-            WantsToDropItem,
-            SerializationHelper,
-            Equippable,
-            Equipped,
-            Weapon,
-            Wearable,
-            WantsToRemoveItem,
-            ParticleLifetime,
-            HungerClock,
-            ProvidesFood,
-            MagicMapper,
-            Hidden,
-            EntryTrigger,
-            EntityMoved,
-            SingleActivation,
-            BlocksVisibility,
-            Door,
-            Quips,
-            Attributes,
-            Skills,
-            Pools,
-            NaturalAttackDefense,
-            LootTable,
-            OtherLevelPosition,
-            DMSerializationHelper,
-            LightSource,
-            Initiative,
-            MyTurn,
-            Faction,
-            WantsToApproach,
-            WantsToFlee,
-            MoveMode,
-            Chasing,
-            EquipmentChanged,
-            Vendor,
-            TownPortal,
-            TeleportTo,
-            ApplyMove,
-            ApplyTeleport,
-            MagicItem,
-            ObfuscatedName,
-            IdentifiedItem,
-            SpawnParticleBurst,
-            SpawnParticleLine,
-            CursedItem,
-            ProvidesRemoveCurse,
-            ProvidesIdentification,
-            AttributeBonus,
-            StatusEffect,
-            Duration,
-            KnownSpells,
-            SpellTemplate,
-            WantsToCastSpell,
-            TeachesSpell,
-            ProvidesMana,
-            Slow,
-            DamageOverTime,
-            SpecialAbilities,
-            TileSize,
-            OnDeath,
-            AlwaysTargetsSelf,
-            Target,
-            WantsToShoot,
-            Bleeds
-        );
+    // Clean up, newest first, so the entity allocator hands out the same ids afterwards as it
+    // would have without the save, and the marker allocator the same markers. Autosaves happen
+    // mid-game, and replays depend on that.
+    ecs.delete_entity(savehelper2).expect("Crash on cleanup");
+    ecs.delete_entity(savehelper).expect("Crash on cleanup");
+    *ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>() = allocator;
+
+    result
+}
+
+/// `log`, with the entities it mentions identified by their save markers instead of their ids,
+/// which don't survive a reload. Entities that aren't saved are left out.
+fn log_for_save(
+    ecs: &World,
+    mut log: Vec<crate::gamelog::LogEntry>,
+) -> Vec<crate::gamelog::LogEntry> {
+    let entities = ecs.entities();
+    let markers = ecs.read_storage::<SimpleMarker<SerializeMe>>();
+    for entry in log.iter_mut() {
+        entry.entities = entry
+            .entities
+            .iter()
+            .map(|id| entities.entity(*id))
+            .filter(|entity| entities.is_alive(*entity))
+            .filter_map(|entity| markers.get(entity))
+            .map(|marker| marker.id() as u32)
+            .collect();
     }
+    log
+}
 
-    // Clean up
-    ecs.delete_entity(savehelper).expect("Crash on cleanup");
-    ecs.delete_entity(savehelper2).expect("Crash on cleanup");
+/// The inverse of `log_for_save`, once the saved entities have been loaded.
+fn log_from_save(
+    ecs: &World,
+    mut log: Vec<crate::gamelog::LogEntry>,
+) -> Vec<crate::gamelog::LogEntry> {
+    let allocator = ecs.fetch::<SimpleMarkerAllocator<SerializeMe>>();
+    for entry in log.iter_mut() {
+        entry.entities = entry
+            .entities
+            .iter()
+            .filter_map(|marker| allocator.retrieve_entity_internal(*marker as u64))
+            .map(|entity| entity.id())
+            .collect();
+    }
+    log
+}
+
+/// Writes the save next to its destination and then renames it into place, so a crash part way
+/// through never leaves a damaged save behind.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_save(ecs: &World, path: &Path, header: &SaveHeader) -> Result<(), SaveError> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let components = serialize_components(ecs)?;
+    let format = SaveFormat::from_path(path);
+    let temp_path = path.with_extension(format!("{}.tmp", format.extension()));
+    let result = format::write(&temp_path, format, header, &components)
+        .and_then(|()| fs::rename(&temp_path, path).map_err(SaveError::from));
+    if result.is_err() {
+        fs::remove_file(&temp_path).ok();
+    }
+    result
+}
+
+fn serialize_components(ecs: &World) -> Result<Vec<serde_json::Value>, SaveError> {
+    let data = (
+        ecs.entities(),
+        ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+    );
+
+    let components = serialize_individually!(
+        ecs,
+        data,
+        Position,
+        Renderable,
+        Player,
+        Viewshed,
+        Name,
+        BlocksTile,
+        WantsToMelee,
+        Item,
+        Consumable,
+        Ranged,
+        InflictsDamage,
+        AreaOfEffect,
+        Confusion,
+        ProvidesHealing,
+        InBackpack,
+        WantsToPickupItem,
+        WantsToUseItem,
+        WantsToDropItem,
+        SerializationHelper,
+        Equippable,
+        Equipped,
+        Weapon,
+        Wearable,
+        WantsToRemoveItem,
+        ParticleLifetime,
+        HungerClock,
+        ProvidesFood,
+        MagicMapper,
+        Hidden,
+        EntryTrigger,
+        EntityMoved,
+        SingleActivation,
+        BlocksVisibility,
+        Door,
+        Quips,
+        Attributes,
+        Skills,
+        Pools,
+        NaturalAttackDefense,
+        LootTable,
+        OtherLevelPosition,
+        DMSerializationHelper,
+        LightSource,
+        Initiative,
+        MyTurn,
+        Faction,
+        WantsToApproach,
+        WantsToFlee,
+        MoveMode,
+        Chasing,
+        EquipmentChanged,
+        Vendor,
+        TownPortal,
+        TeleportTo,
+        ApplyMove,
+        ApplyTeleport,
+        MagicItem,
+        ObfuscatedName,
+        IdentifiedItem,
+        SpawnParticleBurst,
+        SpawnParticleLine,
+        CursedItem,
+        ProvidesRemoveCurse,
+        ProvidesIdentification,
+        AttributeBonus,
+        StatusEffect,
+        Duration,
+        KnownSpells,
+        SpellTemplate,
+        WantsToCastSpell,
+        TeachesSpell,
+        ProvidesMana,
+        Slow,
+        DamageOverTime,
+        SpecialAbilities,
+        TileSize,
+        OnDeath,
+        AlwaysTargetsSelf,
+        Target,
+        WantsToShoot,
+        Bleeds,
+        Digger,
+        Flammable,
+        LeavesHazard
+    );
+
+    Ok(components)
 }
 
-pub fn does_save_exist() -> bool {
-    Path::new("./savegame.json").exists()
+/// Everything a save holds of the world, as JSON that doesn't depend on entity ids or on the
+/// order things happened to be created in: entities are listed by their save markers. Replays
+/// are checked against a hash of it.
+pub fn world_state(ecs: &World) -> Result<serde_json::Value, SaveError> {
+    let mut components = serialize_components(ecs)?;
+    for storage in components.iter_mut() {
+        if let Some(entries) = storage.as_array_mut() {
+            entries.sort_by_key(|entry| entry.pointer("/marker/0").and_then(|id| id.as_u64()));
+        }
+    }
+    let next_marker = (*ecs.fetch::<SimpleMarkerAllocator<SerializeMe>>())
+        .clone()
+        .allocate(*ecs.fetch::<Entity>(), None)
+        .id();
+    Ok(serde_json::json!({
+        "components": components,
+        "map": *ecs.fetch::<super::map::Map>(),
+        "dungeon": *ecs.fetch::<super::map::MasterDungeonMap>(),
+        "log": log_for_save(ecs, crate::game_locals::log(ecs)),
+        "events": crate::game_locals::events(ecs),
+        "world_seed": *ecs.fetch::<crate::rng::WorldSeed>(),
+        "rng": crate::game_locals::rng_streams(ecs),
+        "recording": *ecs.fetch::<crate::replay::InputRecording>(),
+        "next_marker": next_marker,
+    }))
 }
 
 macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+    ($ecs:expr, $values:expr, $data:expr, $( $type:ty),*) => {
         $(
+        let value = $values.next().ok_or_else(|| {
+            SaveError::Corrupt(format!("there is no {} data", stringify!($type)))
+        })?;
         DeserializeComponents::<NoError, _>::deserialize(
             &mut ( &mut $ecs.write_storage::<$type>(), ),
             &$data.0, // entities
             &mut $data.1, // marker
             &mut $data.2, // allocater
-            &mut $de,
+            value,
         )
-        .unwrap();
+        .map_err(|err| SaveError::Corrupt(format!("{}: {}", stringify!($type), err)))?;
         )*
     };
 }
 
-pub fn load_game(ecs: &mut World) {
-    {
-        // Delete everything
-        let mut to_delete = Vec::new();
-        for e in ecs.entities().join() {
-            to_delete.push(e);
-        }
-        for del in to_delete.iter() {
-            ecs.delete_entity(*del).expect("Deletion failed");
-        }
+/// Replaces the world with the contents of a save slot. Saves from older versions are migrated
+/// first. If the save can't be read or migrated the world is left untouched, but if it turns out
+/// to be damaged while it's being loaded, the half-loaded world is replaced by a new game.
+pub fn load_game(ecs: &mut World, slot: &SaveSlot) -> Result<(), SaveError> {
+    load_from(ecs, &slot.path)
+}
+
+fn load_from(ecs: &mut World, path: &Path) -> Result<(), SaveError> {
+    crate::game_locals::with_game_locals(ecs, |ecs| load_with_locals(ecs, path))
+}
+
+fn load_with_locals(ecs: &mut World, path: &Path) -> Result<(), SaveError> {
+    let save = migrations::parse(format::read(path)?)?;
+
+    clear_world(ecs);
+    if let Err(err) = load_components(ecs, save) {
+        // Don't leave a half-loaded world behind.
+        let world_seed = *ecs.fetch::<crate::rng::WorldSeed>();
+        new_game(ecs, world_seed);
+        return Err(err);
     }
+    Ok(())
+}
 
-    let data = fs::read_to_string("./savegame.json").unwrap();
-    let mut de = serde_json::Deserializer::from_str(&data);
+fn clear_world(ecs: &mut World) {
+    // Delete everything
+    let mut to_delete = Vec::new();
+    for e in ecs.entities().join() {
+        to_delete.push(e);
+    }
+    for del in to_delete.iter() {
+        ecs.delete_entity(*del).expect("Deletion failed");
+    }
+}
 
+fn load_components(ecs: &mut World, save: migrations::SaveData) -> Result<(), SaveError> {
+    let mut values = save.components.into_iter();
     {
         let mut d = (
             &mut ecs.entities(),
@@ -193,7 +394,7 @@ pub fn load_game(ecs: &mut World) {
 
         deserialize_individually!(
             ecs,
-            de,
+            values,
             d,
             Position,
             Renderable,
@@ -275,12 +476,16 @@ pub fn load_game(ecs: &mut World) {
             AlwaysTargetsSelf,
             Target,
             WantsToShoot,
-            Bleeds
+            Bleeds,
+            Digger,
+            Flammable,
+            LeavesHazard
         );
     }
 
     let mut deleteme: Option<Entity> = None;
     let mut deleteme2: Option<Entity> = None;
+    let mut next_marker = 0;
     {
         let entities = ecs.entities();
         let helper = ecs.read_storage::<SerializationHelper>();
@@ -290,15 +495,22 @@ pub fn load_game(ecs: &mut World) {
         for (e, h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
-            crate::spatial::set_size((worldmap.height * worldmap.width) as usize);
+            *ecs.write_resource::<crate::spatial::SpatialIndex>() =
+                crate::spatial::SpatialIndex::for_map(&worldmap);
+            ecs.write_resource::<crate::spatial::FrozenSpatialIndexes>()
+                .clear();
             deleteme = Some(e);
         }
         for (e, h) in (&entities, &helper2).join() {
             let mut dungeonmaster = ecs.write_resource::<super::map::MasterDungeonMap>();
             *dungeonmaster = h.map.clone();
             deleteme2 = Some(e);
-            crate::gamelog::restore_log(&mut h.log.clone());
+            crate::gamelog::restore_log(&mut log_from_save(ecs, h.log.clone()));
             crate::gamelog::load_events(h.events.clone());
+            *ecs.write_resource::<crate::rng::WorldSeed>() = h.world_seed;
+            crate::rng::restore_streams(h.rng.clone());
+            *ecs.write_resource::<crate::replay::InputRecording>() = h.recording.clone();
+            next_marker = h.next_marker;
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
             let mut ppos = ecs.write_resource::<rltk::Point>();
@@ -307,28 +519,47 @@ pub fn load_game(ecs: &mut World) {
             *player_resource = e;
         }
     }
-    ecs.delete_entity(deleteme.unwrap())
+    let missing = |what: &str| SaveError::Corrupt(format!("the {} is missing", what));
+    let deleteme = deleteme.ok_or_else(|| missing("map"))?;
+    let deleteme2 = deleteme2.ok_or_else(|| missing("dungeon"))?;
+
+    // Carry on numbering markers from exactly where the saved game had got to, past any entities
+    // that were deleted before it was saved and before the helpers' markers, so commands recorded
+    // after this refer to the same things in a replay
+    if next_marker > 0 {
+        let mut allocator = SimpleMarkerAllocator::<SerializeMe>::new();
+        allocator.allocate(deleteme2, Some(next_marker - 1));
+        ecs.insert(allocator);
+    }
+    ecs.delete_entity(deleteme)
         .expect("Unable to delete helper");
-    ecs.delete_entity(deleteme2.unwrap())
+    ecs.delete_entity(deleteme2)
         .expect("Unable to delete helper");
+    ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>()
+        .maintain(
+            &ecs.entities(),
+            &ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+        );
+    Ok(())
 }
 
-pub fn new_game(ecs: &mut World) {
-    {
-        // Delete everything
-        let mut to_delete = Vec::new();
-        for e in ecs.entities().join() {
-            to_delete.push(e);
-        }
-        for del in to_delete.iter() {
-            ecs.delete_entity(*del).expect("Deletion failed");
-        }
-    }
+pub fn new_game(ecs: &mut World, world_seed: crate::rng::WorldSeed) {
+    ecs.insert(world_seed);
+    crate::game_locals::with_game_locals(ecs, |_| {
+        crate::rng::reseed(&world_seed);
+        crate::gamelog::clear_events();
+    });
+    ecs.insert(crate::replay::InputRecording::default());
+    clear_world(ecs);
     {
         let mut dungeonmaster = ecs.write_resource::<super::map::MasterDungeonMap>();
         *dungeonmaster = super::map::MasterDungeonMap::new();
         let mut worldmap = ecs.write_resource::<super::map::Map>();
         *worldmap = super::map::Map::new(1, 64, 64, "New Map");
+        *ecs.write_resource::<crate::spatial::SpatialIndex>() =
+            crate::spatial::SpatialIndex::for_map(&worldmap);
+        ecs.write_resource::<crate::spatial::FrozenSpatialIndexes>()
+            .clear();
         crate::gamelog::clear_log();
         let mut ppos = ecs.write_resource::<rltk::Point>();
         *ppos = rltk::Point::new(0, 0);
@@ -338,8 +569,101 @@ pub fn new_game(ecs: &mut World) {
     *player_resource = player_entity;
 }
 
-pub fn delete_save() {
-    if Path::new("./savegame.json").exists() {
-        std::fs::remove_file("./savegame.json").expect("Unable to delete file");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::Simulation;
+    use crate::replay::state_checksum;
+    use crate::{Cheat, PlayerCommand};
+
+    fn temp_save(name: &str, format: SaveFormat) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "downdelving-{}-{}.{}",
+            name,
+            std::process::id(),
+            format.extension()
+        ))
+    }
+
+    fn played_game() -> Simulation {
+        let mut simulation = Simulation::with_seed(5);
+        let mut commands = vec![
+            PlayerCommand::Cheat {
+                cheat: Cheat::GodMode,
+            },
+            PlayerCommand::Move {
+                delta_x: 0,
+                delta_y: 1,
+            },
+            PlayerCommand::Cheat {
+                cheat: Cheat::TeleportToExit,
+            },
+        ];
+        commands.extend(std::iter::repeat_n(PlayerCommand::SkipTurn, 5));
+        simulation.run(&commands);
+        simulation
+    }
+
+    #[test]
+    fn a_saved_game_loads_as_it_was_and_plays_on_the_same() {
+        for format in format::SAVE_FORMATS {
+            let mut played = played_game();
+            let path = temp_save("round-trip", format);
+            save_to(&mut played.state.ecs, &path).unwrap();
+            let mut loaded = Simulation::with_seed(6);
+            let result = load_from(&mut loaded.state.ecs, &path);
+            fs::remove_file(&path).ok();
+            result.unwrap();
+
+            let (played_ecs, loaded_ecs) = (&played.state.ecs, &loaded.state.ecs);
+            assert_eq!(state_checksum(played_ecs), state_checksum(loaded_ecs));
+            assert_eq!(
+                crate::game_locals::events(played_ecs),
+                crate::game_locals::events(loaded_ecs)
+            );
+            assert_eq!(
+                played_ecs.fetch::<crate::rng::WorldSeed>().seed,
+                loaded_ecs.fetch::<crate::rng::WorldSeed>().seed
+            );
+
+            let more = vec![PlayerCommand::SkipTurn; 10];
+            played.run(&more);
+            loaded.run(&more);
+            assert_eq!(
+                state_checksum(&played.state.ecs),
+                state_checksum(&loaded.state.ecs)
+            );
+        }
+    }
+
+    #[test]
+    fn a_save_that_cant_be_read_leaves_the_world_alone() {
+        let mut simulation = played_game();
+        let before = state_checksum(&simulation.state.ecs);
+        let path = temp_save("unreadable", SaveFormat::Json);
+        fs::write(&path, "{ this isn't a save").unwrap();
+        let result = load_from(&mut simulation.state.ecs, &path);
+        fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SaveError::Parse(_))));
+        assert_eq!(state_checksum(&simulation.state.ecs), before);
+    }
+
+    #[test]
+    fn deleting_a_save_that_is_already_gone_succeeds() {
+        let path = temp_save("delete", SaveFormat::Json);
+        fs::write(&path, "{}").unwrap();
+        let slot = SaveSlot {
+            name: "delete".to_string(),
+            path: path.clone(),
+            header: Err("not read".to_string()),
+        };
+        assert!(delete_save(&slot).is_ok());
+        assert!(!path.exists());
+        assert!(delete_save(&slot).is_ok());
+
+        // Anything else that stops the delete is reported rather than panicking
+        let directory = std::env::temp_dir();
+        assert!(slots::remove_if_present(&directory).is_err());
+        assert!(directory.exists());
     }
 }
@@ -0,0 +1,198 @@
+use super::format::{self, save_format, SaveFormat, SAVE_FORMATS};
+use super::{migrations, SaveError, SAVE_FORMAT_VERSION};
+use crate::components::Name;
+use crate::map::Map;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SAVE_DIRECTORY: &str = "./saves";
+const LEGACY_SAVE_PATH: &str = "./savegame.json";
+const LEGACY_SLOT_NAME: &str = "savegame";
+
+/// What the load menu shows about a save without having to load it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveMetadata {
+    pub character_name: String,
+    pub depth: i32,
+    pub turns: i32,
+    pub timestamp: u64,
+}
+
+impl SaveMetadata {
+    pub fn describe(&self) -> String {
+        format!(
+            "{}, depth {}, turn {}, {}",
+            self.character_name,
+            self.depth,
+            self.turns,
+            describe_age(self.timestamp)
+        )
+    }
+}
+
+/// Written ahead of the component data in every save.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveHeader {
+    pub format_version: u32,
+    pub metadata: SaveMetadata,
+}
+
+impl SaveHeader {
+    pub fn new(ecs: &World) -> SaveHeader {
+        let player_entity = ecs.fetch::<Entity>();
+        let character_name = ecs
+            .read_storage::<Name>()
+            .get(*player_entity)
+            .map(|name| name.name.clone())
+            .unwrap_or_else(|| "Unknown".to_string());
+        SaveHeader {
+            format_version: SAVE_FORMAT_VERSION,
+            metadata: SaveMetadata {
+                character_name,
+                depth: ecs.fetch::<Map>().depth,
+                // Turns are counted over the whole game, not just the current level
+                turns: crate::game_locals::events(ecs)
+                    .get("Turn")
+                    .copied()
+                    .unwrap_or(0),
+                timestamp: now(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveSlot {
+    pub name: String,
+    pub path: PathBuf,
+    pub header: Result<SaveHeader, String>,
+}
+
+/// Where a slot is saved to, in the current save format.
+pub fn slot_path(slot: &str) -> PathBuf {
+    slot_path_in(slot, save_format())
+}
+
+pub(super) fn slot_path_in(slot: &str, format: SaveFormat) -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join(format!("{}.{}", slot, format.extension()))
+}
+
+/// The slot the current game saves into; one per character and world.
+pub fn current_slot_name(ecs: &World) -> String {
+    let player_entity = ecs.fetch::<Entity>();
+    let name = ecs
+        .read_storage::<Name>()
+        .get(*player_entity)
+        .map(|name| name.name.to_lowercase())
+        .unwrap_or_else(|| "unknown".to_string());
+    let seed = ecs.fetch::<crate::rng::WorldSeed>().seed;
+    format!("{}-{}", name, seed)
+}
+
+/// Every save on disk, newest first. Saves that can't be read are still listed, with the reason.
+pub fn list_saves() -> Vec<SaveSlot> {
+    adopt_legacy_save();
+    let mut slots = Vec::new();
+    for path in save_files(Path::new(SAVE_DIRECTORY)) {
+        if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+            slots.push(SaveSlot {
+                name: name.to_string(),
+                path: path.clone(),
+                header: read_header(&path).map_err(|err| err.to_string()),
+            });
+        }
+    }
+    slots.sort_by_key(|slot| {
+        std::cmp::Reverse(
+            slot.header
+                .as_ref()
+                .map(|header| header.metadata.timestamp)
+                .unwrap_or(0),
+        )
+    });
+    slots
+}
+
+pub fn does_save_exist() -> bool {
+    Path::new(LEGACY_SAVE_PATH).exists() || !save_files(Path::new(SAVE_DIRECTORY)).is_empty()
+}
+
+/// The save files in `directory`, without reading them.
+pub(super) fn save_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(dir) = fs::read_dir(directory) {
+        for entry in dir.flatten() {
+            let path = entry.path();
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            if extension.and_then(SaveFormat::from_extension).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Removes the slot's save. A save that's already gone counts as removed.
+pub fn delete_save(slot: &SaveSlot) -> io::Result<()> {
+    remove_if_present(&slot.path)
+}
+
+/// Removes any copies of `slot` saved in a format other than the current one.
+pub(super) fn delete_other_formats(slot: &str) -> io::Result<()> {
+    for format in SAVE_FORMATS
+        .iter()
+        .filter(|format| **format != save_format())
+    {
+        remove_if_present(&slot_path_in(slot, *format))?;
+    }
+    Ok(())
+}
+
+/// Removes the file at `path`, treating one that isn't there as already removed.
+pub(super) fn remove_if_present(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+pub(super) fn read_header(path: &Path) -> Result<SaveHeader, SaveError> {
+    Ok(migrations::parse(format::read(path)?)?.header)
+}
+
+/// Older builds wrote a single JSON save next to the executable; move it in with the slots. It
+/// stays JSON whatever the current format is, since the extension decides how it's read.
+fn adopt_legacy_save() {
+    if Path::new(LEGACY_SAVE_PATH).exists() && fs::create_dir_all(SAVE_DIRECTORY).is_ok() {
+        let destination = slot_path_in(LEGACY_SLOT_NAME, SaveFormat::Json);
+        if !destination.exists() {
+            if let Err(err) = fs::rename(LEGACY_SAVE_PATH, destination) {
+                crate::console_log(format!("Unable to move {}: {}", LEGACY_SAVE_PATH, err));
+            }
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn describe_age(timestamp: u64) -> String {
+    if timestamp == 0 {
+        return "date unknown".to_string();
+    }
+    let age = now().saturating_sub(timestamp);
+    match age {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", age / 60),
+        3600..=86399 => format!("{} hr ago", age / 3600),
+        _ => format!("{} days ago", age / 86400),
+    }
+}
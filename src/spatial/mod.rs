@@ -1,138 +1,296 @@
-use crate::{MainGameRunState, Map, RunState};
+use crate::{LevelId, MainGameRunState, Map, RunState};
+use rltk::{Algorithm2D, BaseMap, Point};
 use specs::prelude::*;
-use std::sync::Mutex;
+use std::collections::HashMap;
 
-struct SpatialMap {
+/// What is on, and what blocks, each tile of a map. The current level's index is a resource,
+/// rebuilt every turn by the map indexing system.
+#[derive(Default, Clone, Debug)]
+pub struct SpatialIndex {
     blocked: Vec<(bool, bool)>,
     tile_content: Vec<Vec<(Entity, bool)>>,
 }
 
-impl SpatialMap {
-    fn new() -> Self {
-        Self {
-            blocked: Vec::new(),
-            tile_content: Vec::new(),
-        }
+impl SpatialIndex {
+    pub fn new(map_tile_count: usize) -> Self {
+        let mut index = SpatialIndex::default();
+        index.set_size(map_tile_count);
+        index
     }
-}
-
-lazy_static! {
-    static ref SPATIAL_MAP: Mutex<SpatialMap> = Mutex::new(SpatialMap::new());
-}
 
-pub fn set_size(map_tile_count: usize) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked = vec![(false, false); map_tile_count];
-    lock.tile_content = vec![Vec::new(); map_tile_count];
-}
+    /// An index of `map` with its terrain blocking filled in, and no entities.
+    pub fn for_map(map: &Map) -> Self {
+        let mut index = SpatialIndex::new(map.tiles.len());
+        index.populate_blocked_from_map(map);
+        index
+    }
 
-pub fn clear() {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked.iter_mut().for_each(|b| {
-        b.0 = false;
-        b.1 = false;
-    });
-    for content in lock.tile_content.iter_mut() {
-        content.clear();
+    pub fn size(&self) -> usize {
+        self.blocked.len()
     }
-}
 
-pub fn populate_blocked_from_map(map: &Map) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    for (i, tile) in map.tiles.iter().enumerate() {
-        lock.blocked[i].0 = !tile.is_walkable();
+    pub fn set_size(&mut self, map_tile_count: usize) {
+        self.blocked = vec![(false, false); map_tile_count];
+        self.tile_content = vec![Vec::new(); map_tile_count];
     }
-}
 
-pub fn index_entity(entity: Entity, idx: usize, blocks_tile: bool) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.tile_content[idx].push((entity, blocks_tile));
-    if blocks_tile {
-        lock.blocked[idx].1 = true;
+    pub fn clear(&mut self) {
+        self.blocked.iter_mut().for_each(|b| {
+            b.0 = false;
+            b.1 = false;
+        });
+        for content in self.tile_content.iter_mut() {
+            content.clear();
+        }
     }
-}
 
-pub fn is_blocked(idx: usize) -> bool {
-    let lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked[idx].0 || lock.blocked[idx].1
-}
+    pub fn populate_blocked_from_map(&mut self, map: &Map) {
+        for (i, tile) in map.tiles.iter().enumerate() {
+            self.blocked[i].0 = !tile.is_walkable();
+        }
+    }
 
-pub fn set_blocked(idx: usize, blocked: bool) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked[idx] = (lock.blocked[idx].0, blocked);
-}
+    /// Also blocks every tile where an entity `width` by `height` tiles wouldn't fit.
+    pub fn populate_blocked_multi(&mut self, map: &Map, width: i32, height: i32) {
+        self.populate_blocked_from_map(map);
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                if !self.is_blocked(idx) {
+                    for cy in 0..height {
+                        for cx in 0..width {
+                            let tx = x + cx;
+                            let ty = y + cy;
+                            if tx < map.width - 1 && ty < map.height - 1 {
+                                let tidx = map.xy_idx(tx, ty);
+                                if self.is_blocked(tidx) {
+                                    self.set_blocked(idx, true);
+                                }
+                            } else {
+                                self.set_blocked(idx, true);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
 
-pub fn for_each_tile_content<F>(idx: usize, mut f: F)
-where
-    F: FnMut(Entity),
-{
-    let lock = SPATIAL_MAP.lock().unwrap();
-    for entity in lock.tile_content[idx].iter() {
-        f(entity.0);
+    /// Catches up with a change to the terrain at `idx`, such as a wall being dug through.
+    pub fn update_terrain(&mut self, map: &Map, idx: usize) {
+        self.blocked[idx].0 = !map.tiles[idx].is_walkable();
     }
-}
 
-pub fn for_each_tile_content_with_gamemode<F>(idx: usize, mut f: F) -> RunState
-where
-    F: FnMut(Entity) -> Option<RunState>,
-{
-    let lock = SPATIAL_MAP.lock().unwrap();
-    for entity in lock.tile_content[idx].iter() {
-        if let Some(rs) = f(entity.0) {
-            return rs;
+    pub fn index_entity(&mut self, entity: Entity, idx: usize, blocks_tile: bool) {
+        self.tile_content[idx].push((entity, blocks_tile));
+        if blocks_tile {
+            self.blocked[idx].1 = true;
         }
     }
 
-    RunState::MainGame {
-        runstate: MainGameRunState::AwaitingInput,
+    pub fn is_blocked(&self, idx: usize) -> bool {
+        self.blocked[idx].0 || self.blocked[idx].1
     }
-}
 
-pub fn get_tile_content_clone(idx: usize) -> Vec<Entity> {
-    let lock = SPATIAL_MAP.lock().unwrap();
-    lock.tile_content[idx].iter().map(|(e, _)| *e).collect()
-}
+    pub fn set_blocked(&mut self, idx: usize, blocked: bool) {
+        self.blocked[idx] = (self.blocked[idx].0, blocked);
+    }
+
+    pub fn for_each_tile_content<F>(&self, idx: usize, mut f: F)
+    where
+        F: FnMut(Entity),
+    {
+        for entity in self.tile_content[idx].iter() {
+            f(entity.0);
+        }
+    }
+
+    pub fn for_each_tile_content_with_gamemode<F>(&self, idx: usize, mut f: F) -> RunState
+    where
+        F: FnMut(Entity) -> Option<RunState>,
+    {
+        for entity in self.tile_content[idx].iter() {
+            if let Some(rs) = f(entity.0) {
+                return rs;
+            }
+        }
 
-pub fn move_entity(entity: Entity, moving_from: usize, moving_to: usize) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    let mut entity_blocks = false;
-    lock.tile_content[moving_from].retain(|(e, blocks)| {
-        if *e == entity {
-            entity_blocks = *blocks;
-            false
-        } else {
-            true
+        RunState::MainGame {
+            runstate: MainGameRunState::AwaitingInput,
         }
-    });
-    lock.tile_content[moving_to].push((entity, entity_blocks));
-
-    // Recalculate blocks for both tiles
-    let mut from_blocked = false;
-    let mut to_blocked = false;
-    lock.tile_content[moving_from]
-        .iter()
-        .for_each(|(_, blocks)| {
+    }
+
+    pub fn get_tile_content_clone(&self, idx: usize) -> Vec<Entity> {
+        self.tile_content[idx].iter().map(|(e, _)| *e).collect()
+    }
+
+    pub fn move_entity(&mut self, entity: Entity, moving_from: usize, moving_to: usize) {
+        let mut entity_blocks = false;
+        self.tile_content[moving_from].retain(|(e, blocks)| {
+            if *e == entity {
+                entity_blocks = *blocks;
+                false
+            } else {
+                true
+            }
+        });
+        self.tile_content[moving_to].push((entity, entity_blocks));
+
+        // Recalculate blocks for both tiles
+        let mut from_blocked = false;
+        let mut to_blocked = false;
+        self.tile_content[moving_from]
+            .iter()
+            .for_each(|(_, blocks)| {
+                if *blocks {
+                    from_blocked = true;
+                }
+            });
+        self.tile_content[moving_to].iter().for_each(|(_, blocks)| {
+            if *blocks {
+                to_blocked = true;
+            }
+        });
+        self.blocked[moving_from].1 = from_blocked;
+        self.blocked[moving_to].1 = to_blocked;
+    }
+
+    pub fn remove_entity(&mut self, entity: Entity, idx: usize) {
+        self.tile_content[idx].retain(|(e, _)| *e != entity);
+        let mut from_blocked = false;
+        self.tile_content[idx].iter().for_each(|(_, blocks)| {
             if *blocks {
                 from_blocked = true;
             }
         });
-    lock.tile_content[moving_to].iter().for_each(|(_, blocks)| {
-        if *blocks {
-            to_blocked = true;
+        self.blocked[idx].1 = from_blocked;
+    }
+
+    /// `map` as seen by the pathfinding algorithms, routing around whatever this index blocks.
+    pub fn pathing<'a>(&'a self, map: &'a Map) -> PathingMap<'a> {
+        PathingMap {
+            map,
+            index: self,
+            digging: false,
+        }
+    }
+
+    /// `map` as seen by something that digs, which can also go through the terrain it could dig.
+    pub fn digging_pathing<'a>(&'a self, map: &'a Map) -> PathingMap<'a> {
+        PathingMap {
+            map,
+            index: self,
+            digging: true,
         }
-    });
-    lock.blocked[moving_from].1 = from_blocked;
-    lock.blocked[moving_to].1 = to_blocked;
+    }
 }
 
-pub fn remove_entity(entity: Entity, idx: usize) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.tile_content[idx].retain(|(e, _)| *e != entity);
-    let mut from_blocked = false;
-    lock.tile_content[idx].iter().for_each(|(_, blocks)| {
-        if *blocks {
-            from_blocked = true;
+/// A map paired with the index that says which of its tiles are blocked.
+pub struct PathingMap<'a> {
+    pub map: &'a Map,
+    pub index: &'a SpatialIndex,
+    /// Paths may go through diggable terrain, at a cost of its hardness.
+    pub digging: bool,
+}
+
+impl<'a> PathingMap<'a> {
+    fn is_exit_valid(&self, x: i32, y: i32) -> bool {
+        if x < 1 || x > self.map.width - 1 || y < 1 || y > self.map.height - 1 {
+            return false;
+        }
+        let idx = self.map.xy_idx(x, y);
+        !self.index.is_blocked(idx) || self.dig_cost(idx).is_some()
+    }
+
+    /// What going through the tile costs on top of moving, if it has to be dug first.
+    fn dig_cost(&self, idx: usize) -> Option<f32> {
+        if !self.digging || self.index.blocked[idx].1 {
+            return None;
+        }
+        self.map.dig_hardness(idx).map(|hardness| hardness as f32)
+    }
+
+    fn exit_cost(&self, idx: usize, cost: f32) -> f32 {
+        cost + self.dig_cost(idx).unwrap_or(0.0)
+    }
+}
+
+impl<'a> BaseMap for PathingMap<'a> {
+    fn is_opaque(&self, idx: usize) -> bool {
+        self.map.is_opaque(idx)
+    }
+
+    fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
+        const DIAGONAL_COST: f32 = 1.5;
+        let mut exits = rltk::SmallVec::new();
+        let x = idx as i32 % self.map.width;
+        let y = idx as i32 / self.map.width;
+        let tt = self.map.tiles[idx];
+        let w = self.map.width as usize;
+
+        // Cardinal directions
+        if self.is_exit_valid(x - 1, y) {
+            exits.push((idx - 1, self.exit_cost(idx - 1, tt.get_movement_cost())))
+        };
+        if self.is_exit_valid(x + 1, y) {
+            exits.push((idx + 1, self.exit_cost(idx + 1, tt.get_movement_cost())))
+        };
+        if self.is_exit_valid(x, y - 1) {
+            exits.push((idx - w, self.exit_cost(idx - w, tt.get_movement_cost())))
+        };
+        if self.is_exit_valid(x, y + 1) {
+            exits.push((idx + w, self.exit_cost(idx + w, tt.get_movement_cost())))
+        };
+
+        // Diagonals
+        let diagonal_cost = tt.get_movement_cost() * DIAGONAL_COST;
+        if self.is_exit_valid(x - 1, y - 1) {
+            exits.push(((idx - w) - 1, self.exit_cost((idx - w) - 1, diagonal_cost)));
+        }
+        if self.is_exit_valid(x + 1, y - 1) {
+            exits.push(((idx - w) + 1, self.exit_cost((idx - w) + 1, diagonal_cost)));
         }
-    });
-    lock.blocked[idx].1 = from_blocked;
+        if self.is_exit_valid(x - 1, y + 1) {
+            exits.push(((idx + w) - 1, self.exit_cost((idx + w) - 1, diagonal_cost)));
+        }
+        if self.is_exit_valid(x + 1, y + 1) {
+            exits.push(((idx + w) + 1, self.exit_cost((idx + w) + 1, diagonal_cost)));
+        }
+
+        exits
+    }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+}
+
+impl<'a> Algorithm2D for PathingMap<'a> {
+    fn dimensions(&self) -> Point {
+        self.map.dimensions()
+    }
+}
+
+/// The indexes of levels the player has left, kept as they were when the level was frozen.
+#[derive(Default)]
+pub struct FrozenSpatialIndexes {
+    levels: HashMap<LevelId, SpatialIndex>,
+}
+
+impl FrozenSpatialIndexes {
+    pub fn freeze(&mut self, level: LevelId, index: SpatialIndex) {
+        self.levels.insert(level, index);
+    }
+
+    pub fn thaw(&mut self, level: &LevelId) -> Option<SpatialIndex> {
+        self.levels.remove(level)
+    }
+
+    pub fn get(&self, level: &LevelId) -> Option<&SpatialIndex> {
+        self.levels.get(level)
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+    }
 }
@@ -1,15 +1,15 @@
 use super::{
     random_table::MasterTable, raws::*, Attribute, AttributeBonus, Attributes, Duration,
     EntryTrigger, EquipmentChanged, Faction, HungerClock, HungerState, Initiative, KnownSpells,
-    LightSource, Map, MasterDungeonMap, Name, OtherLevelPosition, Player, Pool, Pools, Position,
-    Rect, Renderable, SerializeMe, SingleActivation, Skill, Skills, StatusEffect, TeleportTo,
-    TileType, Viewshed,
+    LevelId, LightSource, Map, MasterDungeonMap, Name, OtherLevelPosition, Player, Pool, Pools,
+    Position, Rect, Renderable, SerializeMe, SingleActivation, Skill, Skills, StatusEffect,
+    TeleportTo, TileType, Viewshed,
 };
 use crate::{attr_bonus, mana_at_level, player_hp_at_level};
 use rltk::RGB;
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Spawns the player and returns his/her entity object.
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
@@ -90,6 +90,8 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .with(LightSource {
             color: rltk::RGB::from_f32(1.0, 1.0, 0.5),
             range: 8,
+            intensity: 1.0,
+            flicker: 0.0,
         })
         .with(Initiative { current: 0 })
         .with(Faction {
@@ -176,7 +178,7 @@ pub fn spawn_room(map: &Map, room: &Rect, map_depth: i32, spawn_list: &mut Vec<(
         for y in room.y1 + 1..room.y2 {
             for x in room.x1 + 1..room.x2 {
                 let idx = map.xy_idx(x, y);
-                if map.tiles[idx] == TileType::Floor {
+                if map.tiles[idx] == TileType::FLOOR {
                     possible_targets.push(idx);
                 }
             }
@@ -194,14 +196,14 @@ pub fn spawn_region(
     spawn_list: &mut Vec<(usize, String)>,
 ) {
     let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+    let mut spawn_points: BTreeMap<usize, String> = BTreeMap::new();
     let mut areas: Vec<usize> = Vec::from(area);
 
     // Scope to keep the borrow checker happy
     {
         let num_spawns = i32::min(
             areas.len() as i32,
-            crate::rng::roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
+            crate::rng::mapgen::roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
         );
         if num_spawns == 0 {
             return;
@@ -211,7 +213,7 @@ pub fn spawn_region(
             let array_index = if areas.len() == 1 {
                 0usize
             } else {
-                (crate::rng::roll_dice(1, areas.len() as i32) - 1) as usize
+                (crate::rng::mapgen::roll_dice(1, areas.len() as i32) - 1) as usize
             };
 
             let map_idx = areas[array_index];
@@ -245,7 +247,7 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     }
 
     if spawn.1 != "None" {
-        rltk::console::log(format!(
+        crate::console_log(format!(
             "WARNING: We don't know how to spawn [{}]!",
             spawn.1
         ));
@@ -255,7 +257,7 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
 pub fn spawn_town_portal(ecs: &mut World) {
     // Get current position & depth
     let map = ecs.fetch::<Map>();
-    let player_depth = map.depth;
+    let player_level = map.level.clone();
     let player_pos = ecs.fetch::<rltk::Point>();
     let player_x = player_pos.x;
     let player_y = player_pos.y;
@@ -264,10 +266,10 @@ pub fn spawn_town_portal(ecs: &mut World) {
 
     // Find part of the town for the portal
     let dm = ecs.fetch::<MasterDungeonMap>();
-    let town_map = dm.get_map(1).unwrap();
+    let town_map = dm.get_map(&LevelId::main(1)).unwrap();
     let mut stairs_idx = 0;
     for (idx, tt) in town_map.tiles.iter().enumerate() {
-        if *tt == TileType::DownStairs {
+        if *tt == TileType::DOWN_STAIRS {
             stairs_idx = idx;
         }
     }
@@ -281,7 +283,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
         .with(OtherLevelPosition {
             x: portal_x,
             y: portal_y,
-            depth: 1,
+            level: LevelId::main(1),
         })
         .with(Renderable {
             glyph: rltk::to_cp437('♥'),
@@ -293,7 +295,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
         .with(TeleportTo {
             x: player_x,
             y: player_y,
-            depth: player_depth,
+            level: player_level,
             player_only: true,
         })
         .with(SingleActivation {})
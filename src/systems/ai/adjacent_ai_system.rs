@@ -1,3 +1,4 @@
+use crate::spatial::SpatialIndex;
 use crate::{raws::Reaction, Faction, Map, MyTurn, Position, TileSize, WantsToMelee};
 use specs::prelude::*;
 
@@ -10,6 +11,7 @@ impl<'a> System<'a> for AdjacentAI {
         ReadStorage<'a, Faction>,
         ReadStorage<'a, Position>,
         ReadExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         WriteStorage<'a, WantsToMelee>,
         Entities<'a>,
         ReadExpect<'a, Entity>,
@@ -17,7 +19,8 @@ impl<'a> System<'a> for AdjacentAI {
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, factions, positions, map, mut want_melee, entities, player, sizes) = data;
+        let (mut turns, factions, positions, map, spatial, mut want_melee, entities, player, sizes) =
+            data;
 
         let mut turn_done: Vec<Entity> = Vec::new();
         for (entity, _turn, my_faction, pos) in (&entities, &turns, &factions, &positions).join() {
@@ -40,7 +43,7 @@ impl<'a> System<'a> for AdjacentAI {
                                 let target_idx = map.xy_idx(t.0, t.1);
                                 evaluate(
                                     target_idx,
-                                    &map,
+                                    &spatial,
                                     &factions,
                                     &my_faction.name,
                                     &mut reactions,
@@ -50,15 +53,27 @@ impl<'a> System<'a> for AdjacentAI {
                 } else {
                     // Add possible reactions to adjacents for each direction
                     if pos.x > 0 {
-                        evaluate(idx - 1, &map, &factions, &my_faction.name, &mut reactions);
+                        evaluate(
+                            idx - 1,
+                            &spatial,
+                            &factions,
+                            &my_faction.name,
+                            &mut reactions,
+                        );
                     }
                     if pos.x < w - 1 {
-                        evaluate(idx + 1, &map, &factions, &my_faction.name, &mut reactions);
+                        evaluate(
+                            idx + 1,
+                            &spatial,
+                            &factions,
+                            &my_faction.name,
+                            &mut reactions,
+                        );
                     }
                     if pos.y > 0 {
                         evaluate(
                             idx - w as usize,
-                            &map,
+                            &spatial,
                             &factions,
                             &my_faction.name,
                             &mut reactions,
@@ -67,7 +82,7 @@ impl<'a> System<'a> for AdjacentAI {
                     if pos.y < h - 1 {
                         evaluate(
                             idx + w as usize,
-                            &map,
+                            &spatial,
                             &factions,
                             &my_faction.name,
                             &mut reactions,
@@ -76,7 +91,7 @@ impl<'a> System<'a> for AdjacentAI {
                     if pos.y > 0 && pos.x > 0 {
                         evaluate(
                             (idx - w as usize) - 1,
-                            &map,
+                            &spatial,
                             &factions,
                             &my_faction.name,
                             &mut reactions,
@@ -85,7 +100,7 @@ impl<'a> System<'a> for AdjacentAI {
                     if pos.y > 0 && pos.x < w - 1 {
                         evaluate(
                             (idx - w as usize) + 1,
-                            &map,
+                            &spatial,
                             &factions,
                             &my_faction.name,
                             &mut reactions,
@@ -94,7 +109,7 @@ impl<'a> System<'a> for AdjacentAI {
                     if pos.y < h - 1 && pos.x > 0 {
                         evaluate(
                             (idx + w as usize) - 1,
-                            &map,
+                            &spatial,
                             &factions,
                             &my_faction.name,
                             &mut reactions,
@@ -103,7 +118,7 @@ impl<'a> System<'a> for AdjacentAI {
                     if pos.y < h - 1 && pos.x < w - 1 {
                         evaluate(
                             (idx + w as usize) + 1,
-                            &map,
+                            &spatial,
                             &factions,
                             &my_faction.name,
                             &mut reactions,
@@ -136,12 +151,12 @@ impl<'a> System<'a> for AdjacentAI {
 
 fn evaluate(
     idx: usize,
-    _map: &Map,
+    spatial: &SpatialIndex,
     factions: &ReadStorage<Faction>,
     my_faction: &str,
     reactions: &mut Vec<(Entity, Reaction)>,
 ) {
-    crate::spatial::for_each_tile_content(idx, |other_entity| {
+    spatial.for_each_tile_content(idx, |other_entity| {
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 other_entity,
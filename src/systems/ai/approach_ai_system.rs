@@ -1,3 +1,4 @@
+use crate::spatial::SpatialIndex;
 use crate::{ApplyMove, Map, MyTurn, Position, WantsToApproach};
 use specs::prelude::*;
 
@@ -9,13 +10,15 @@ impl<'a> System<'a> for ApproachAI {
         WriteStorage<'a, MyTurn>,
         WriteStorage<'a, WantsToApproach>,
         ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         Entities<'a>,
         WriteStorage<'a, ApplyMove>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, mut want_approach, positions, mut map, entities, mut apply_move) = data;
+        let (mut turns, mut want_approach, positions, map, spatial, entities, mut apply_move) =
+            data;
 
         let mut turn_done: Vec<Entity> = Vec::new();
         for (entity, pos, approach, _myturn) in
@@ -25,7 +28,7 @@ impl<'a> System<'a> for ApproachAI {
             let path = rltk::a_star_search(
                 map.xy_idx(pos.x, pos.y),
                 map.xy_idx(approach.idx % map.width, approach.idx / map.width),
-                &mut *map,
+                &spatial.pathing(&map),
             );
             if path.success && path.steps.len() > 1 {
                 apply_move
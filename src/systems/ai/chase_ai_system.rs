@@ -1,4 +1,6 @@
-use crate::{ApplyMove, Chasing, Map, MyTurn, Position, TileSize};
+use super::default_move_system::dig;
+use crate::spatial::SpatialIndex;
+use crate::{ApplyMove, Chasing, Digger, Map, MyTurn, Position, TileSize};
 use specs::prelude::*;
 use std::collections::HashMap;
 
@@ -10,14 +12,26 @@ impl<'a> System<'a> for ChaseAI {
         WriteStorage<'a, MyTurn>,
         WriteStorage<'a, Chasing>,
         ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         Entities<'a>,
         WriteStorage<'a, ApplyMove>,
         ReadStorage<'a, TileSize>,
+        ReadStorage<'a, Digger>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, mut chasing, positions, mut map, entities, mut apply_move, sizes) = data;
+        let (
+            mut turns,
+            mut chasing,
+            positions,
+            map,
+            spatial,
+            entities,
+            mut apply_move,
+            sizes,
+            diggers,
+        ) = data;
 
         let mut targets: HashMap<Entity, (i32, i32)> = HashMap::new();
         let mut end_chase: Vec<Entity> = Vec::new();
@@ -40,23 +54,37 @@ impl<'a> System<'a> for ChaseAI {
             turn_done.push(entity);
             let target_pos = targets[&entity];
             let path;
+            let digger = diggers.get(entity);
 
             if let Some(size) = sizes.get(entity) {
-                let mut map_copy = map.clone();
-                map_copy.populate_blocked_multi(size.x, size.y);
+                let mut spatial_copy = (*spatial).clone();
+                spatial_copy.populate_blocked_multi(&map, size.x, size.y);
                 path = rltk::a_star_search(
-                    map_copy.xy_idx(pos.x, pos.y),
-                    map_copy.xy_idx(target_pos.0, target_pos.1),
-                    &mut map_copy,
+                    map.xy_idx(pos.x, pos.y),
+                    map.xy_idx(target_pos.0, target_pos.1),
+                    &spatial_copy.pathing(&map),
+                );
+            } else if digger.is_some() {
+                path = rltk::a_star_search(
+                    map.xy_idx(pos.x, pos.y),
+                    map.xy_idx(target_pos.0, target_pos.1),
+                    &spatial.digging_pathing(&map),
                 );
             } else {
                 path = rltk::a_star_search(
                     map.xy_idx(pos.x, pos.y),
                     map.xy_idx(target_pos.0, target_pos.1),
-                    &mut *map,
+                    &spatial.pathing(&map),
                 );
             }
             if path.success && path.steps.len() > 1 && path.steps.len() < 15 {
+                if let Some(digger) = digger {
+                    if spatial.is_blocked(path.steps[1]) {
+                        // Tunnel towards the target rather than walking round
+                        dig(entity, digger, &map, path.steps[1]);
+                        continue;
+                    }
+                }
                 apply_move
                     .insert(
                         entity,
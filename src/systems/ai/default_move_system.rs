@@ -1,4 +1,6 @@
-use crate::{ApplyMove, Map, MoveMode, Movement, MyTurn, Position};
+use crate::effects::{add_effect, EffectType, Targets};
+use crate::spatial::SpatialIndex;
+use crate::{ApplyMove, Digger, Map, MoveMode, Movement, MyTurn, Position};
 use specs::prelude::*;
 
 pub struct DefaultMoveAI {}
@@ -9,19 +11,23 @@ impl<'a> System<'a> for DefaultMoveAI {
         WriteStorage<'a, MyTurn>,
         WriteStorage<'a, MoveMode>,
         ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         WriteStorage<'a, ApplyMove>,
         Entities<'a>,
+        ReadStorage<'a, Digger>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, mut move_mode, positions, mut map, mut apply_move, entities) = data;
+        let (mut turns, mut move_mode, positions, map, spatial, mut apply_move, entities, diggers) =
+            data;
 
         let mut turn_done: Vec<Entity> = Vec::new();
         for (entity, pos, mut mode, _myturn) in
             (&entities, &positions, &mut move_mode, &turns).join()
         {
             turn_done.push(entity);
+            let digger = diggers.get(entity);
 
             match &mut mode.mode {
                 Movement::Static => {}
@@ -29,7 +35,7 @@ impl<'a> System<'a> for DefaultMoveAI {
                 Movement::Random => {
                     let mut x = pos.x;
                     let mut y = pos.y;
-                    let move_roll = crate::rng::roll_dice(1, 5);
+                    let move_roll = crate::rng::ai::roll_dice(1, 5);
                     match move_roll {
                         1 => x -= 1,
                         2 => x += 1,
@@ -40,11 +46,13 @@ impl<'a> System<'a> for DefaultMoveAI {
 
                     if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
                         let dest_idx = map.xy_idx(x, y);
-                        if !crate::spatial::is_blocked(dest_idx) {
+                        if !spatial.is_blocked(dest_idx) {
                             apply_move
                                 .insert(entity, ApplyMove { dest_idx })
                                 .expect("Unable to insert");
                             turn_done.push(entity);
+                        } else if let Some(digger) = digger {
+                            dig(entity, digger, &map, dest_idx);
                         }
                     }
                 }
@@ -53,27 +61,31 @@ impl<'a> System<'a> for DefaultMoveAI {
                     if let Some(path) = path {
                         // We have a target - go there
                         if path.len() > 1 {
-                            if !crate::spatial::is_blocked(path[1] as usize) {
+                            if !spatial.is_blocked(path[1] as usize) {
                                 apply_move
                                     .insert(entity, ApplyMove { dest_idx: path[1] })
                                     .expect("Unable to insert");
                                 path.remove(0); // Remove the first step in the path
                                 turn_done.push(entity);
+                            } else if let Some(digger) = digger {
+                                // Diggers tunnel through; once the rock is gone they move on
+                                dig(entity, digger, &map, path[1]);
                             }
                             // Otherwise we wait a turn to see if the path clears up
                         } else {
                             mode.mode = Movement::RandomWaypoint { path: None };
                         }
                     } else {
-                        let target_x = crate::rng::roll_dice(1, map.width - 2);
-                        let target_y = crate::rng::roll_dice(1, map.height - 2);
+                        let target_x = crate::rng::ai::roll_dice(1, map.width - 2);
+                        let target_y = crate::rng::ai::roll_dice(1, map.height - 2);
                         let idx = map.xy_idx(target_x, target_y);
                         if map.tiles[idx].is_walkable() {
-                            let path = rltk::a_star_search(
-                                map.xy_idx(pos.x, pos.y),
-                                map.xy_idx(target_x, target_y),
-                                &mut *map,
-                            );
+                            let start = map.xy_idx(pos.x, pos.y);
+                            let path = if digger.is_some() {
+                                rltk::a_star_search(start, idx, &spatial.digging_pathing(&map))
+                            } else {
+                                rltk::a_star_search(start, idx, &spatial.pathing(&map))
+                            };
                             if path.success && path.steps.len() > 1 {
                                 mode.mode = Movement::RandomWaypoint {
                                     path: Some(path.steps),
@@ -91,3 +103,18 @@ impl<'a> System<'a> for DefaultMoveAI {
         }
     }
 }
+
+/// Digs into the tile at `idx`, if it's something that can be dug.
+pub(super) fn dig(entity: Entity, digger: &Digger, map: &Map, idx: usize) {
+    if map.dig_hardness(idx).is_some() {
+        add_effect(
+            Some(entity),
+            EffectType::Dig {
+                power: digger.power,
+            },
+            Targets::Tile {
+                tile_idx: idx as i32,
+            },
+        );
+    }
+}
@@ -43,8 +43,8 @@ impl<'a> System<'a> for EncumbranceSystem {
         }
 
         struct ItemUpdate {
-            weight: f32,
-            initiative: f32,
+            weight: Vec<f32>,
+            initiative: Vec<f32>,
             might: i32,
             fitness: i32,
             quickness: i32,
@@ -57,8 +57,8 @@ impl<'a> System<'a> for EncumbranceSystem {
             to_update.insert(
                 entity,
                 ItemUpdate {
-                    weight: 0.0,
-                    initiative: 0.0,
+                    weight: Vec::new(),
+                    initiative: Vec::new(),
                     might: 0,
                     fitness: 0,
                     quickness: 0,
@@ -74,8 +74,8 @@ impl<'a> System<'a> for EncumbranceSystem {
         for (item, equipped, entity) in (&items, &wielded, &entities).join() {
             if to_update.contains_key(&equipped.owner) {
                 let totals = to_update.get_mut(&equipped.owner).unwrap();
-                totals.weight += item.weight_lbs;
-                totals.initiative += item.initiative_penalty;
+                totals.weight.push(item.weight_lbs);
+                totals.initiative.push(item.initiative_penalty);
                 if let Some(attr) = attrbonus.get(entity) {
                     totals.might += attr.might.unwrap_or(0);
                     totals.fitness += attr.fitness.unwrap_or(0);
@@ -89,8 +89,8 @@ impl<'a> System<'a> for EncumbranceSystem {
         for (item, carried) in (&items, &backpacks).join() {
             if to_update.contains_key(&carried.owner) {
                 let totals = to_update.get_mut(&carried.owner).unwrap();
-                totals.weight += item.weight_lbs;
-                totals.initiative += item.initiative_penalty;
+                totals.weight.push(item.weight_lbs);
+                totals.initiative.push(item.initiative_penalty);
             }
         }
 
@@ -109,15 +109,15 @@ impl<'a> System<'a> for EncumbranceSystem {
         for (status, slow) in (&statuses, &slowed).join() {
             if to_update.contains_key(&status.target) {
                 let totals = to_update.get_mut(&status.target).unwrap();
-                totals.initiative += slow.initiative_penalty;
+                totals.initiative.push(slow.initiative_penalty);
             }
         }
 
         // Apply the data to Pools
         for (entity, item) in to_update.iter() {
             if let Some(pool) = pools.get_mut(*entity) {
-                pool.total_weight = item.weight;
-                pool.total_initiative_penalty = item.initiative;
+                pool.total_weight = sorted_sum(&item.weight);
+                pool.total_initiative_penalty = sorted_sum(&item.initiative);
 
                 if let Some(attr) = attributes.get_mut(*entity) {
                     attr.might.modifiers = item.might;
@@ -149,3 +149,11 @@ impl<'a> System<'a> for EncumbranceSystem {
         }
     }
 }
+
+/// Adds the values up smallest first, so the total doesn't depend on the order the
+/// entities were created in (which changes when a game is saved and loaded).
+fn sorted_sum(values: &[f32]) -> f32 {
+    let mut values = values.to_vec();
+    values.sort_by(f32::total_cmp);
+    values.iter().sum()
+}
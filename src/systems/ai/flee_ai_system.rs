@@ -1,3 +1,4 @@
+use crate::spatial::SpatialIndex;
 use crate::{ApplyMove, Map, MyTurn, Position, WantsToFlee};
 use specs::prelude::*;
 
@@ -9,29 +10,32 @@ impl<'a> System<'a> for FleeAI {
         WriteStorage<'a, MyTurn>,
         WriteStorage<'a, WantsToFlee>,
         WriteStorage<'a, Position>,
-        WriteExpect<'a, Map>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, SpatialIndex>,
         Entities<'a>,
         WriteStorage<'a, ApplyMove>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, mut want_flee, positions, mut map, entities, mut apply_move) = data;
+        let (mut turns, mut want_flee, positions, map, mut spatial, entities, mut apply_move) =
+            data;
 
         let mut turn_done: Vec<Entity> = Vec::new();
         for (entity, pos, flee, _myturn) in (&entities, &positions, &want_flee, &turns).join() {
             turn_done.push(entity);
             let my_idx = map.xy_idx(pos.x, pos.y);
-            map.populate_blocked();
+            spatial.populate_blocked_from_map(&map);
+            let pathing = spatial.pathing(&map);
             let flee_map = rltk::DijkstraMap::new(
                 map.width as usize,
                 map.height as usize,
                 &flee.indices,
-                &*map,
+                &pathing,
                 100.0,
             );
-            let flee_target = rltk::DijkstraMap::find_highest_exit(&flee_map, my_idx, &*map);
+            let flee_target = rltk::DijkstraMap::find_highest_exit(&flee_map, my_idx, &pathing);
             if let Some(flee_target) = flee_target {
-                if !crate::spatial::is_blocked(flee_target as usize) {
+                if !spatial.is_blocked(flee_target as usize) {
                     apply_move
                         .insert(
                             entity,
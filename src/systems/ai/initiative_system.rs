@@ -59,7 +59,7 @@ impl<'a> System<'a> for InitiativeSystem {
                 let mut myturn = true;
 
                 // Re-roll
-                initiative.current = 6 + crate::rng::roll_dice(1, 6);
+                initiative.current = 6 + crate::rng::ai::roll_dice(1, 6);
 
                 // Give a bonus for quickness
                 if let Some(attr) = attributes.get(entity) {
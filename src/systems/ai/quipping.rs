@@ -11,23 +11,28 @@ impl<'a> System<'a> for QuipSystem {
         ReadStorage<'a, MyTurn>,
         ReadExpect<'a, rltk::Point>,
         ReadStorage<'a, Viewshed>,
+        Entities<'a>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut quips, names, turns, player_pos, viewsheds) = data;
+        let (mut quips, names, turns, player_pos, viewsheds, entities) = data;
 
-        for (quip, name, viewshed, _turn) in (&mut quips, &names, &viewsheds, &turns).join() {
+        for (entity, quip, name, viewshed, _turn) in
+            (&entities, &mut quips, &names, &viewsheds, &turns).join()
+        {
             if !quip.available.is_empty()
                 && viewshed.visible_tiles.contains(&player_pos)
-                && crate::rng::roll_dice(1, 6) == 1
+                && crate::rng::ai::roll_dice(1, 6) == 1
             {
                 let quip_index = if quip.available.len() == 1 {
                     0
                 } else {
-                    (crate::rng::roll_dice(1, quip.available.len() as i32) - 1) as usize
+                    (crate::rng::ai::roll_dice(1, quip.available.len() as i32) - 1) as usize
                 };
 
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Quips)
+                    .entity(entity)
                     .npc_name(&name.name)
                     .append("says")
                     .item_name(&quip.available[quip_index])
@@ -1,3 +1,4 @@
+use crate::spatial::SpatialIndex;
 use crate::{
     raws::Reaction, Chasing, Equipped, Faction, Map, MyTurn, Name, Position, SpecialAbilities,
     SpellTemplate, Viewshed, WantsToApproach, WantsToCastSpell, WantsToFlee, WantsToShoot, Weapon,
@@ -13,6 +14,7 @@ impl<'a> System<'a> for VisibleAI {
         ReadStorage<'a, Faction>,
         ReadStorage<'a, Position>,
         ReadExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         WriteStorage<'a, WantsToApproach>,
         WriteStorage<'a, WantsToFlee>,
         Entities<'a>,
@@ -34,6 +36,7 @@ impl<'a> System<'a> for VisibleAI {
             factions,
             positions,
             map,
+            spatial,
             mut want_approach,
             mut want_flee,
             entities,
@@ -59,7 +62,7 @@ impl<'a> System<'a> for VisibleAI {
                 for visible_tile in viewshed.visible_tiles.iter() {
                     let idx = map.xy_idx(visible_tile.x, visible_tile.y);
                     if my_idx != idx {
-                        evaluate(idx, &map, &factions, &my_faction.name, &mut reactions);
+                        evaluate(idx, &spatial, &factions, &my_faction.name, &mut reactions);
                     }
                 }
 
@@ -78,7 +81,7 @@ impl<'a> System<'a> for VisibleAI {
                                 for ability in abilities.abilities.iter() {
                                     if range >= ability.min_range
                                         && range <= ability.range
-                                        && crate::rng::roll_dice(1, 100)
+                                        && crate::rng::ai::roll_dice(1, 100)
                                             <= (ability.chance * 100.0) as i32
                                     {
                                         use crate::raws::find_spell_entity_by_name;
@@ -159,12 +162,12 @@ impl<'a> System<'a> for VisibleAI {
 
 fn evaluate(
     idx: usize,
-    _map: &Map,
+    spatial: &SpatialIndex,
     factions: &ReadStorage<Faction>,
     my_faction: &str,
     reactions: &mut Vec<(usize, Reaction, Entity)>,
 ) {
-    crate::spatial::for_each_tile_content(idx, |other_entity| {
+    spatial.for_each_tile_content(idx, |other_entity| {
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 idx,
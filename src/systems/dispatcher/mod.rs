@@ -24,6 +24,7 @@ construct_dispatcher!(
     (VisibilitySystem, "visibility", &[]),
     (EncumbranceSystem, "encumbrance", &[]),
     (InitiativeSystem, "initiative", &[]),
+    (HazardSystem, "hazards", &[]),
     (TurnStatusSystem, "turnstatus", &[]),
     (QuipSystem, "quips", &[]),
     (AdjacentAI, "adjacent", &[]),
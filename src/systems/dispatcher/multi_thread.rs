@@ -36,7 +36,9 @@ pub struct MultiThreadedDispatcher {
 impl<'a> UnifiedDispatcher for MultiThreadedDispatcher {
     fn run_now(&mut self, ecs: *mut World) {
         unsafe {
-            self.dispatcher.dispatch(&mut *ecs);
+            // Systems share the random number streams and the effects queue, so they must run
+            // in a fixed order for games to be reproducible from their seed and input.
+            self.dispatcher.dispatch_seq(&mut *ecs);
             crate::effects::run_effects_queue(&mut *ecs);
         }
     }
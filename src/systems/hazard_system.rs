@@ -0,0 +1,480 @@
+use crate::effects::{add_effect, EffectType, Targets};
+use crate::map::{Hazard, HazardKind};
+use crate::spatial::SpatialIndex;
+use crate::{
+    DamageOverTime, Flammable, MainGameRunState, Map, Name, Pools, RunState, StatusEffect, Viewshed,
+};
+use rltk::Point;
+use specs::prelude::*;
+use std::collections::HashSet;
+
+/// Spreads and burns out the fire, gas and liquid on the map, once a turn, and hurts whatever is
+/// standing in them.
+pub struct HazardSystem {}
+
+impl<'a> System<'a> for HazardSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, SpatialIndex>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Flammable>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, DamageOverTime>,
+        WriteStorage<'a, Viewshed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map,
+            runstate,
+            spatial,
+            player,
+            entities,
+            pools,
+            flammable,
+            names,
+            statuses,
+            dots,
+            mut viewsheds,
+        ) = data;
+
+        // The initiative system hands the player their turn once a turn has gone by
+        if *runstate
+            != (RunState::MainGame {
+                runstate: MainGameRunState::AwaitingInput,
+            })
+            || map.hazards.is_empty()
+        {
+            return;
+        }
+
+        let poisoned: HashSet<Entity> = (&statuses, &dots)
+            .join()
+            .map(|(status, _)| status.target)
+            .collect();
+
+        // Hazards are visited in order of their tiles so that games replay the same way
+        let old = map.hazards.clone();
+        let mut tiles: Vec<usize> = old.keys().copied().collect();
+        tiles.sort_unstable();
+        // Tiles that started or stopped blocking sight this turn
+        let mut vision_changed: HashSet<usize> = HashSet::new();
+
+        for idx in tiles {
+            // Fire may already have spread onto it this turn
+            let mut hazard = match map.hazards.get(&idx) {
+                Some(hazard) if hazard.kind == old[&idx].kind => *hazard,
+                _ => continue,
+            };
+            let content = spatial.get_tile_content_clone(idx);
+
+            // Hurt whatever is standing in it
+            for entity in content.iter().filter(|e| pools.get(**e).is_some()) {
+                let damage = match hazard.kind {
+                    HazardKind::Fire => crate::rng::combat::roll_dice(1, 4) + 1,
+                    HazardKind::Acid => crate::rng::combat::roll_dice(1, 3),
+                    HazardKind::PoisonGas if !poisoned.contains(entity) => {
+                        add_effect(
+                            None,
+                            EffectType::DamageOverTime { damage: 1 },
+                            Targets::Single { target: *entity },
+                        );
+                        0
+                    }
+                    _ => 0,
+                };
+                if damage > 0 {
+                    add_effect(
+                        None,
+                        EffectType::Damage { amount: damage },
+                        Targets::Single { target: *entity },
+                    );
+                }
+                if *entity == *player && hazard.kind != HazardKind::Smoke {
+                    crate::gamelog::Logger::new()
+                        .color(rltk::ORANGE)
+                        .append(match hazard.kind {
+                            HazardKind::Fire => "You are burning!",
+                            HazardKind::Acid => "The acid burns you!",
+                            _ => "You choke on the poison gas!",
+                        })
+                        .log();
+                }
+            }
+
+            match hazard.kind {
+                HazardKind::Fire => {
+                    // Burn whatever will burn here, which keeps the fire going for longer
+                    for entity in content.iter().filter(|e| flammable.get(**e).is_some()) {
+                        if map.visible_tiles[idx] {
+                            if let Some(name) = names.get(*entity) {
+                                crate::gamelog::Logger::new()
+                                    .npc_name(&name.name)
+                                    .append("burns away.")
+                                    .log();
+                            }
+                        }
+                        entities.delete(*entity).expect("Unable to delete");
+                        hazard.strength += HazardKind::Fire.strength();
+                        vision_changed.insert(idx);
+                    }
+
+                    for neighbour in neighbours(&map, idx) {
+                        let catches = match map.hazards.get(&neighbour) {
+                            Some(other) if other.kind == HazardKind::Fire => false,
+                            Some(other) if other.kind.is_flammable() => true,
+                            _ => {
+                                (map.tiles[neighbour].is_flammable()
+                                    || spatial
+                                        .get_tile_content_clone(neighbour)
+                                        .iter()
+                                        .any(|e| flammable.get(*e).is_some()))
+                                    && crate::rng::roll_dice(1, 4) == 1
+                            }
+                        };
+                        if catches {
+                            if map.hazards.get(&neighbour).is_some_and(|h| h.kind.is_gas()) {
+                                vision_changed.insert(neighbour);
+                            }
+                            map.hazards.insert(neighbour, Hazard::new(HazardKind::Fire));
+                        }
+                    }
+
+                    hazard.strength -= 1;
+                    if hazard.strength < 1 {
+                        // It burns out, leaving ash and smoke behind
+                        if map.tiles[idx].is_flammable() {
+                            map.tiles[idx] = map.tiles[idx].burnt();
+                        }
+                        hazard = Hazard::new(HazardKind::Smoke);
+                        vision_changed.insert(idx);
+                    }
+                    map.hazards.insert(idx, hazard);
+                }
+
+                HazardKind::Smoke | HazardKind::PoisonGas | HazardKind::Acid => {
+                    // Gas drifts and liquid runs, spreading itself thinner; once it stops
+                    // spreading it fades away
+                    let gas = hazard.kind.is_gas();
+                    let mut spread = false;
+                    if hazard.strength > 1 {
+                        let open: Vec<usize> = neighbours(&map, idx)
+                            .into_iter()
+                            .filter(|n| {
+                                let tile = map.tiles[*n];
+                                !map.hazards.contains_key(n)
+                                    && !tile.is_opaque()
+                                    && !map.view_blocked.contains(n)
+                                    && (gas || (tile.is_walkable() && !tile.is_liquid()))
+                            })
+                            .collect();
+                        if !open.is_empty() {
+                            let roll = crate::rng::roll_dice(1, open.len() as i32 + 1) - 1;
+                            if let Some(dest) = open.get(roll as usize) {
+                                let moved = hazard.strength / 2;
+                                hazard.strength -= moved;
+                                spread = true;
+                                if gas {
+                                    vision_changed.insert(*dest);
+                                }
+                                map.hazards.insert(
+                                    *dest,
+                                    Hazard {
+                                        kind: hazard.kind,
+                                        strength: moved,
+                                    },
+                                );
+                            }
+                        }
+                    }
+
+                    if !spread {
+                        hazard.strength -= 1;
+                    }
+                    if hazard.strength < 1 {
+                        map.hazards.remove(&idx);
+                        if gas {
+                            vision_changed.insert(idx);
+                        }
+                    } else {
+                        map.hazards.insert(idx, hazard);
+                    }
+                }
+            }
+        }
+
+        // Only those who could see one of them need to look again
+        if !vision_changed.is_empty() {
+            let width = map.width as usize;
+            let changed: HashSet<Point> = vision_changed
+                .iter()
+                .map(|idx| Point::new(idx % width, idx / width))
+                .collect();
+            for viewshed in (&mut viewsheds).join() {
+                if viewshed.visible_tiles.iter().any(|pt| changed.contains(pt)) {
+                    viewshed.dirty = true;
+                }
+            }
+        }
+    }
+}
+
+/// The tiles next to `idx`, leaving out the edge of the map.
+fn neighbours(map: &Map, idx: usize) -> Vec<usize> {
+    let x = idx as i32 % map.width;
+    let y = idx as i32 / map.width;
+    [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+        .iter()
+        .filter(|(x, y)| *x > 0 && *x < map.width - 1 && *y > 0 && *y < map.height - 1)
+        .map(|(x, y)| map.xy_idx(*x, *y))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::{swap_effect_queue, EffectSpawner};
+    use crate::game_locals::{with_game_locals, GameLocals};
+    use crate::map::TileType;
+    use crate::rng::WorldSeed;
+    use crate::Pool;
+    use std::collections::VecDeque;
+
+    const CENTRE: (i32, i32) = (4, 4);
+
+    /// A 7x7 room of the tile named `floor` inside a ring of wall, with its own seeded random
+    /// streams.
+    fn room(floor: &str) -> World {
+        crate::raws::load_raws();
+        let floor = TileType::named(floor);
+        let mut ecs = World::new();
+        crate::components::register_all(&mut ecs);
+        let mut map = Map::new(1, 9, 9, "Hazard Test");
+        for y in 1..8 {
+            for x in 1..8 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = floor;
+            }
+        }
+        ecs.insert(SpatialIndex::for_map(&map));
+        ecs.insert(map);
+        ecs.insert(RunState::MainGame {
+            runstate: MainGameRunState::AwaitingInput,
+        });
+        let player = ecs.create_entity().build();
+        ecs.insert(player);
+        ecs.insert(GameLocals::new(&WorldSeed::new(7)));
+        ecs
+    }
+
+    fn idx(ecs: &World, (x, y): (i32, i32)) -> usize {
+        ecs.fetch::<Map>().xy_idx(x, y)
+    }
+
+    fn place(ecs: &mut World, (x, y): (i32, i32), kind: HazardKind, strength: i32) {
+        let idx = idx(ecs, (x, y));
+        ecs.fetch_mut::<Map>()
+            .hazards
+            .insert(idx, Hazard { kind, strength });
+    }
+
+    /// Something that can be hurt, standing at `pos`.
+    fn creature(ecs: &mut World, pos: (i32, i32)) -> Entity {
+        let pool = Pool {
+            max: 10,
+            current: 10,
+        };
+        let entity = ecs
+            .create_entity()
+            .with(Pools {
+                hit_points: pool.clone(),
+                mana: pool,
+                xp: 0,
+                level: 1,
+                total_weight: 0.0,
+                total_initiative_penalty: 0.0,
+                gold: 0.0,
+                god_mode: false,
+            })
+            .build();
+        let idx = idx(ecs, pos);
+        ecs.fetch_mut::<SpatialIndex>()
+            .index_entity(entity, idx, true);
+        entity
+    }
+
+    /// Runs the system for a turn, returning the effects it queued.
+    fn turn(ecs: &mut World) -> VecDeque<EffectSpawner> {
+        with_game_locals(ecs, |ecs| {
+            HazardSystem {}.run_now(ecs);
+            ecs.maintain();
+            swap_effect_queue(VecDeque::new())
+        })
+    }
+
+    fn hazards_of(ecs: &World, kind: HazardKind) -> Vec<(usize, i32)> {
+        let map = ecs.fetch::<Map>();
+        let mut found: Vec<(usize, i32)> = map
+            .hazards
+            .iter()
+            .filter(|(_, hazard)| hazard.kind == kind)
+            .map(|(idx, hazard)| (*idx, hazard.strength))
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    #[test]
+    fn fire_on_bare_floor_burns_out_into_smoke() {
+        let mut ecs = room("Floor");
+        let centre = idx(&ecs, CENTRE);
+        place(
+            &mut ecs,
+            CENTRE,
+            HazardKind::Fire,
+            HazardKind::Fire.strength(),
+        );
+        // Something flammable feeds the fire and burns away
+        let brush = ecs.create_entity().with(Flammable {}).build();
+        ecs.fetch_mut::<SpatialIndex>()
+            .index_entity(brush, centre, false);
+
+        turn(&mut ecs);
+        assert!(!ecs.entities().is_alive(brush));
+        let fed = 2 * HazardKind::Fire.strength() - 1;
+        assert_eq!(hazards_of(&ecs, HazardKind::Fire), vec![(centre, fed)]);
+
+        for _ in 1..fed {
+            turn(&mut ecs);
+        }
+        // Nothing around it would burn, so it never spread
+        assert_eq!(hazards_of(&ecs, HazardKind::Fire), vec![(centre, 1)]);
+        turn(&mut ecs);
+        assert!(hazards_of(&ecs, HazardKind::Fire).is_empty());
+        assert_eq!(
+            hazards_of(&ecs, HazardKind::Smoke),
+            vec![(centre, HazardKind::Smoke.strength())]
+        );
+    }
+
+    #[test]
+    fn fire_spreads_over_grass_and_leaves_it_burnt() {
+        let mut ecs = room("Grass");
+        place(
+            &mut ecs,
+            CENTRE,
+            HazardKind::Fire,
+            HazardKind::Fire.strength(),
+        );
+
+        let mut burned = HashSet::new();
+        for _ in 0..200 {
+            turn(&mut ecs);
+            burned.extend(
+                hazards_of(&ecs, HazardKind::Fire)
+                    .iter()
+                    .map(|(idx, _)| *idx),
+            );
+            if hazards_of(&ecs, HazardKind::Fire).is_empty() {
+                break;
+            }
+        }
+        assert!(hazards_of(&ecs, HazardKind::Fire).is_empty());
+        assert!(burned.len() > 1);
+        let map = ecs.fetch::<Map>();
+        for idx in burned {
+            assert_eq!(map.tiles[idx], TileType::FLOOR);
+        }
+        // The walls around the room never catch
+        assert!(map
+            .hazards
+            .keys()
+            .all(|idx| map.tiles[*idx] != TileType::WALL));
+    }
+
+    #[test]
+    fn gas_spreads_thinner_and_fades_away() {
+        let mut ecs = room("Floor");
+        let strength = HazardKind::PoisonGas.strength();
+        place(&mut ecs, CENTRE, HazardKind::PoisonGas, strength);
+
+        let mut widest = 1;
+        let mut total = strength;
+        for _ in 0..500 {
+            turn(&mut ecs);
+            let gas = hazards_of(&ecs, HazardKind::PoisonGas);
+            let now: i32 = gas.iter().map(|(_, strength)| strength).sum();
+            // Spreading shares it out; it only ever thins
+            assert!(now <= total);
+            total = now;
+            widest = widest.max(gas.len());
+            if gas.is_empty() {
+                break;
+            }
+        }
+        assert!(widest > 1);
+        assert!(ecs.fetch::<Map>().hazards.is_empty());
+    }
+
+    #[test]
+    fn whatever_stands_in_a_hazard_is_hurt() {
+        let mut ecs = room("Floor");
+        let burning = creature(&mut ecs, (2, 2));
+        let choking = creature(&mut ecs, (6, 6));
+        let in_smoke = creature(&mut ecs, (2, 6));
+        place(&mut ecs, (2, 2), HazardKind::Fire, 3);
+        place(&mut ecs, (6, 6), HazardKind::PoisonGas, 1);
+        place(&mut ecs, (2, 6), HazardKind::Smoke, 1);
+
+        let effects = turn(&mut ecs);
+        let on = |target: Entity| -> Vec<&EffectType> {
+            effects
+                .iter()
+                .filter(
+                    |effect| matches!(effect.targets, Targets::Single { target: t } if t == target),
+                )
+                .map(|effect| &effect.effect_type)
+                .collect()
+        };
+        assert!(matches!(
+            on(burning)[..],
+            [EffectType::Damage { amount }] if (2..=5).contains(amount)
+        ));
+        assert!(matches!(
+            on(choking)[..],
+            [EffectType::DamageOverTime { damage: 1 }]
+        ));
+        assert!(on(in_smoke).is_empty());
+    }
+
+    #[test]
+    fn only_those_who_saw_the_gas_go_look_again() {
+        let mut ecs = room("Floor");
+        // A last wisp of smoke, which fades this turn
+        place(&mut ecs, CENTRE, HazardKind::Smoke, 1);
+        let looking = |tiles: Vec<Point>| Viewshed {
+            visible_tiles: tiles,
+            range: 8,
+            dirty: false,
+        };
+        let near = ecs
+            .create_entity()
+            .with(looking(vec![Point::new(CENTRE.0, CENTRE.1)]))
+            .build();
+        let far = ecs
+            .create_entity()
+            .with(looking(vec![Point::new(1, 1)]))
+            .build();
+
+        turn(&mut ecs);
+        assert!(ecs.fetch::<Map>().hazards.is_empty());
+        let viewsheds = ecs.read_storage::<Viewshed>();
+        assert!(viewsheds.get(near).unwrap().dirty);
+        assert!(!viewsheds.get(far).unwrap().dirty);
+    }
+}
@@ -49,6 +49,8 @@ impl<'a> System<'a> for ItemCollectionSystem {
 
             if pickup.collected_by == *player_entity {
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Loot)
+                    .entity(pickup.item)
                     .append("You pick up the")
                     .item_name(super::obfuscate_name(
                         pickup.item,
@@ -58,6 +58,8 @@ impl<'a> System<'a> for ItemDropSystem {
 
             if entity == *player_entity {
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Loot)
+                    .entity(to_drop.item)
                     .append("You drop the")
                     .item_name(super::obfuscate_name(
                         to_drop.item,
@@ -49,6 +49,8 @@ impl<'a> System<'a> for ItemEquipOnUse {
                     if already_equipped.owner == target && already_equipped.slot == target_slot {
                         if cursed.get(item_entity).is_some() {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Loot)
+                                .entity(item_entity)
                                 .append("You cannot unequip")
                                 .item_name(&name.name)
                                 .append("- it is cursed!")
@@ -58,6 +60,8 @@ impl<'a> System<'a> for ItemEquipOnUse {
                             to_unequip.push(item_entity);
                             if target == *player_entity {
                                 crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Loot)
+                                    .entity(item_entity)
                                     .append("You unequip")
                                     .item_name(&name.name)
                                     .log();
@@ -99,6 +103,8 @@ impl<'a> System<'a> for ItemEquipOnUse {
                     backpack.remove(useitem.item);
                     if target == *player_entity {
                         crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Loot)
+                            .entity(useitem.item)
                             .append("You equip")
                             .item_name(&names.get(useitem.item).unwrap().name)
                             .log();
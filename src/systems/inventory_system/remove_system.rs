@@ -22,6 +22,8 @@ impl<'a> System<'a> for ItemRemoveSystem {
         for (entity, to_remove) in (&entities, &wants_remove).join() {
             if cursed.get(to_remove.item).is_some() {
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Loot)
+                    .entity(to_remove.item)
                     .append("You cannot remove")
                     .item_name(&names.get(to_remove.item).unwrap().name)
                     .append(" - it is cursed.")
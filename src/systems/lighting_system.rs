@@ -1,5 +1,7 @@
-use crate::{LightSource, Map, Position, Viewshed};
-use rltk::RGB;
+use crate::map::HazardKind;
+use crate::perception::lighting::{illuminate, Light, LightFlicker};
+use crate::{LightSource, Map, Position};
+use rltk::{Point, RGB};
 use specs::prelude::*;
 
 pub struct LightingSystem {}
@@ -8,35 +10,162 @@ impl<'a> System<'a> for LightingSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
-        ReadStorage<'a, Viewshed>,
+        WriteExpect<'a, LightFlicker>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, LightSource>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, viewshed, positions, lighting) = data;
+        let (mut map, mut flicker, positions, lighting) = data;
 
-        if map.outdoors {
-            return;
+        let mut lights: Vec<Light> = (&positions, &lighting)
+            .join()
+            .map(|(pos, light)| Light {
+                position: Point::new(pos.x, pos.y),
+                color: light.color,
+                range: light.range,
+                intensity: light.intensity,
+                flicker: light.flicker,
+            })
+            .collect();
+
+        // Burning ground lights up its surroundings too
+        let fires: Vec<usize> = map
+            .hazards
+            .iter()
+            .filter(|(_, hazard)| hazard.kind == HazardKind::Fire)
+            .map(|(idx, _)| *idx)
+            .collect();
+        for idx in fires {
+            lights.push(Light {
+                position: Point::new(idx as i32 % map.width, idx as i32 / map.width),
+                color: RGB::named(rltk::ORANGE),
+                range: 3,
+                intensity: 0.8,
+                flicker: 0.3,
+            });
         }
 
-        let black = RGB::from_f32(0.0, 0.0, 0.0);
-        for l in map.light.iter_mut() {
-            *l = black;
+        illuminate(&mut map, &lights, &mut flicker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileType;
+
+    /// An 11x11 room of floor inside a ring of wall, lit by `lights` as `(x, y, color)` torches.
+    fn lit_room(outdoors: bool, ambient: Option<RGB>, lights: &[(i32, i32, RGB)]) -> World {
+        crate::raws::load_raws();
+        let mut ecs = World::new();
+        crate::components::register_all(&mut ecs);
+        let mut map = Map::new(1, 13, 13, "Lighting Test");
+        for y in 1..12 {
+            for x in 1..12 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::FLOOR;
+            }
         }
+        map.outdoors = outdoors;
+        map.ambient_light = ambient;
+        ecs.insert(map);
+        ecs.insert(LightFlicker::default());
+        for (x, y, color) in lights.iter() {
+            ecs.create_entity()
+                .with(Position { x: *x, y: *y })
+                .with(LightSource {
+                    color: *color,
+                    range: 8,
+                    intensity: 1.0,
+                    flicker: 0.0,
+                })
+                .build();
+        }
+        ecs
+    }
 
-        for (viewshed, pos, light) in (&viewshed, &positions, &lighting).join() {
-            let light_point = rltk::Point::new(pos.x, pos.y);
-            let range_f = light.range as f32;
-            for t in viewshed.visible_tiles.iter() {
-                if t.x > 0 && t.x < map.width && t.y > 0 && t.y < map.height {
-                    let idx = map.xy_idx(t.x, t.y);
-                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(light_point, *t);
-                    let intensity = (range_f - distance) / range_f;
-
-                    map.light[idx] = map.light[idx] + (light.color * intensity);
-                }
+    fn light_at(ecs: &mut World, x: i32, y: i32) -> RGB {
+        LightingSystem {}.run_now(ecs);
+        let map = ecs.fetch::<Map>();
+        map.light[map.xy_idx(x, y)]
+    }
+
+    #[test]
+    fn each_level_starts_with_its_biomes_ambient_light() {
+        crate::raws::load_raws();
+        let biomes = crate::raws::read_raws().unwrap().biomes;
+        let mut lit = 0;
+        for biome in biomes.iter() {
+            if let Some(color) = &biome.ambient_light {
+                let map = crate::biome::biome_builder(biome, 1, 20, 20).build_data.map;
+                assert_eq!(
+                    map.ambient(),
+                    RGB::from_hex(color).unwrap(),
+                    "{}",
+                    biome.name
+                );
+                lit += 1;
             }
         }
+        assert!(lit > 0);
+
+        // Without one, a level is dark underground and in daylight outdoors
+        let mut ecs = lit_room(false, None, &[]);
+        assert_eq!(light_at(&mut ecs, 6, 6), RGB::from_f32(0.0, 0.0, 0.0));
+        let mut ecs = lit_room(true, None, &[]);
+        assert_eq!(light_at(&mut ecs, 6, 6), RGB::from_f32(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn walls_cast_shadows() {
+        let white = RGB::from_f32(1.0, 1.0, 1.0);
+        let mut ecs = lit_room(false, None, &[(3, 6, white)]);
+        {
+            let mut map = ecs.fetch_mut::<Map>();
+            let idx = map.xy_idx(5, 6);
+            map.tiles[idx] = TileType::WALL;
+        }
+        let beside = light_at(&mut ecs, 3, 8);
+        assert!(beside.r > 0.0);
+        assert_eq!(beside.r, beside.b);
+        // Just as far away, but behind the wall
+        assert_eq!(light_at(&mut ecs, 7, 6), RGB::from_f32(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn lights_of_different_colors_mix() {
+        let red = RGB::from_f32(1.0, 0.0, 0.0);
+        let blue = RGB::from_f32(0.0, 0.0, 1.0);
+        let mut ecs = lit_room(false, None, &[(4, 6, red), (8, 6, blue)]);
+        let between = light_at(&mut ecs, 6, 6);
+        assert!(between.r > 0.0 && between.b > 0.0);
+        assert_eq!(between.g, 0.0);
+        assert!((between.r - between.b).abs() < 0.001);
+
+        // Near one light, its color wins out
+        let near_red = light_at(&mut ecs, 4, 7);
+        assert!(near_red.r > near_red.b);
+        // Light adds up, but never past full brightness
+        let mut ecs = lit_room(false, None, &[(6, 6, red), (6, 6, red)]);
+        assert_eq!(light_at(&mut ecs, 6, 6).r, 1.0);
+    }
+
+    #[test]
+    fn an_outdoor_level_at_night_is_dark_away_from_its_lights() {
+        crate::raws::load_raws();
+        let forest = crate::raws::get_biome(&crate::raws::RAWS.lock().unwrap(), "Forest").unwrap();
+        let night = RGB::from_hex(forest.ambient_light.unwrap()).unwrap();
+        let torch = RGB::from_f32(1.0, 0.8, 0.5);
+        let mut ecs = lit_room(true, Some(night), &[(2, 2, torch)]);
+
+        let far = light_at(&mut ecs, 10, 10);
+        assert_eq!(far, night);
+        assert!(brightness(far) < 0.3);
+        assert!(brightness(light_at(&mut ecs, 3, 2)) > brightness(far) + 0.5);
+    }
+
+    fn brightness(light: RGB) -> f32 {
+        (light.r + light.g + light.b) / 3.0
     }
 }
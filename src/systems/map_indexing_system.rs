@@ -1,4 +1,5 @@
-use crate::{spatial, BlocksTile, Map, Pools, Position, TileSize};
+use crate::spatial::SpatialIndex;
+use crate::{BlocksTile, Map, Pools, Position, TileSize};
 use specs::prelude::*;
 
 pub struct MapIndexingSystem {}
@@ -7,6 +8,7 @@ impl<'a> System<'a> for MapIndexingSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadExpect<'a, Map>,
+        WriteExpect<'a, SpatialIndex>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
         ReadStorage<'a, Pools>,
@@ -15,10 +17,14 @@ impl<'a> System<'a> for MapIndexingSystem {
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (map, position, blockers, pools, sizes, entities) = data;
+        let (map, mut spatial, position, blockers, pools, sizes, entities) = data;
 
-        spatial::clear();
-        spatial::populate_blocked_from_map(&*map);
+        if spatial.size() != map.tiles.len() {
+            spatial.set_size(map.tiles.len());
+        } else {
+            spatial.clear();
+        }
+        spatial.populate_blocked_from_map(&map);
         for (entity, position) in (&entities, &position).join() {
             let mut alive = true;
             if let Some(pools) = pools.get(entity) {
@@ -33,14 +39,14 @@ impl<'a> System<'a> for MapIndexingSystem {
                         for x in position.x..position.x + size.x {
                             if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
                                 let idx = map.xy_idx(x, y);
-                                spatial::index_entity(entity, idx, blockers.get(entity).is_some());
+                                spatial.index_entity(entity, idx, blockers.get(entity).is_some());
                             }
                         }
                     }
                 } else {
                     // Single tile
                     let idx = map.xy_idx(position.x, position.y);
-                    spatial::index_entity(entity, idx, blockers.get(entity).is_some());
+                    spatial.index_entity(entity, idx, blockers.get(entity).is_some());
                 }
             }
         }
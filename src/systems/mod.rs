@@ -22,6 +22,8 @@ mod hunger_system;
 use hunger_system::HungerSystem;
 pub mod particle_system;
 use particle_system::ParticleSpawnSystem;
+mod hazard_system;
+use hazard_system::HazardSystem;
 mod lighting_system;
 use lighting_system::LightingSystem;
 
@@ -1,3 +1,4 @@
+use crate::spatial::SpatialIndex;
 use crate::{
     ApplyMove, ApplyTeleport, BlocksTile, EntityMoved, MainGameRunState, Map, OtherLevelPosition,
     Position, RunState, Viewshed,
@@ -10,6 +11,7 @@ impl<'a> System<'a> for MovementSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
+        WriteExpect<'a, SpatialIndex>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
         Entities<'a>,
@@ -25,6 +27,7 @@ impl<'a> System<'a> for MovementSystem {
     fn run(&mut self, data: Self::SystemData) {
         let (
             map,
+            mut spatial,
             mut position,
             _blockers,
             entities,
@@ -39,7 +42,7 @@ impl<'a> System<'a> for MovementSystem {
 
         // Apply teleports
         for (entity, teleport) in (&entities, &apply_teleport).join() {
-            if teleport.dest_depth == map.depth {
+            if teleport.dest_level == map.level {
                 apply_move
                     .insert(
                         entity,
@@ -49,24 +52,31 @@ impl<'a> System<'a> for MovementSystem {
                     )
                     .expect("Unable to insert");
             } else if entity == *player_entity {
+                // The run state can't hold the level, so the player is given their destination
+                other_level
+                    .insert(
+                        entity,
+                        OtherLevelPosition {
+                            x: teleport.dest_x,
+                            y: teleport.dest_y,
+                            level: teleport.dest_level.clone(),
+                        },
+                    )
+                    .expect("Unable to insert");
                 *runstate = RunState::MainGame {
-                    runstate: MainGameRunState::TeleportingToOtherLevel {
-                        x: teleport.dest_x,
-                        y: teleport.dest_y,
-                        depth: teleport.dest_depth,
-                    },
+                    runstate: MainGameRunState::TeleportingToOtherLevel,
                 };
             } else if let Some(pos) = position.get(entity) {
                 let idx = map.xy_idx(pos.x, pos.y);
                 let dest_idx = map.xy_idx(teleport.dest_x, teleport.dest_y);
-                crate::spatial::move_entity(entity, idx, dest_idx);
+                spatial.move_entity(entity, idx, dest_idx);
                 other_level
                     .insert(
                         entity,
                         OtherLevelPosition {
                             x: teleport.dest_x,
                             y: teleport.dest_y,
-                            depth: teleport.dest_depth,
+                            level: teleport.dest_level.clone(),
                         },
                     )
                     .expect("Unable to insert");
@@ -79,7 +89,7 @@ impl<'a> System<'a> for MovementSystem {
         for (entity, movement, mut pos) in (&entities, &apply_move, &mut position).join() {
             let start_idx = map.xy_idx(pos.x, pos.y);
             let dest_idx = movement.dest_idx as usize;
-            crate::spatial::move_entity(entity, start_idx, dest_idx);
+            spatial.move_entity(entity, start_idx, dest_idx);
             pos.x = movement.dest_idx as i32 % map.width;
             pos.y = movement.dest_idx as i32 / map.width;
             if let Some(vs) = viewsheds.get_mut(entity) {
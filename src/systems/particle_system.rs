@@ -1,8 +1,8 @@
-use crate::{ParticleLifetime, Position, Renderable, Rltk};
+use crate::{ParticleLifetime, Position, Renderable};
 use rltk::RGB;
 use specs::prelude::*;
 
-pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
+pub fn update_particles(ecs: &mut World, frame_time_ms: f32) {
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
         // Age out particles
@@ -10,7 +10,7 @@ pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
         let entities = ecs.entities();
         for (entity, mut particle) in (&entities, &mut particles).join() {
             if let Some(animation) = &mut particle.animation {
-                animation.timer += ctx.frame_time_ms;
+                animation.timer += frame_time_ms;
                 if animation.timer > animation.step_time
                     && animation.current_step < animation.path.len() - 2
                 {
@@ -23,7 +23,7 @@ pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
                 }
             }
 
-            particle.lifetime_ms -= ctx.frame_time_ms;
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
@@ -1,7 +1,7 @@
 use crate::{
-    effects::*, skill_bonus, Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, Map,
-    Name, NaturalAttackDefense, Pools, Position, Skill, Skills, WantsToShoot, Weapon,
-    WeaponAttribute, Wearable,
+    dice::Dice, effects::*, skill_bonus, Attributes, EquipmentSlot, Equipped, HungerClock,
+    HungerState, Map, Name, NaturalAttackDefense, Pools, Position, Skill, Skills, WantsToShoot,
+    Weapon, WeaponAttribute, Wearable,
 };
 use rltk::{to_cp437, Point, RGB};
 use specs::prelude::*;
@@ -87,9 +87,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                     range: None,
                     attribute: WeaponAttribute::Might,
                     hit_bonus: 0,
-                    damage_n_dice: 1,
-                    damage_die_type: 4,
-                    damage_bonus: 0,
+                    damage: Dice::new(1, 4, 0),
                     proc_chance: None,
                     proc_target: None,
                 };
@@ -99,12 +97,10 @@ impl<'a> System<'a> for RangedCombatSystem {
                         let attack_index = if nat.attacks.len() == 1 {
                             0
                         } else {
-                            crate::rng::roll_dice(1, nat.attacks.len() as i32) as usize - 1
+                            crate::rng::combat::roll_dice(1, nat.attacks.len() as i32) as usize - 1
                         };
                         weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
-                        weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
-                        weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
-                        weapon_info.damage_bonus = nat.attacks[attack_index].damage_bonus;
+                        weapon_info.damage = nat.attacks[attack_index].damage.clone();
                     }
                 }
 
@@ -116,7 +112,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                     }
                 }
 
-                let natural_roll = crate::rng::roll_dice(1, 20);
+                let natural_roll = crate::rng::combat::roll_dice(1, 20);
                 let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
                     attacker_attributes.might.bonus
                 } else {
@@ -158,22 +154,15 @@ impl<'a> System<'a> for RangedCombatSystem {
                 //println!("Armor class: {}", armor_class);
                 if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
                     // Target hit! Until we support weapons, we're going with 1d4
-                    let base_damage = crate::rng::roll_dice(
-                        weapon_info.damage_n_dice,
-                        weapon_info.damage_die_type,
-                    );
+                    let weapon_damage = weapon_info.damage.roll_with(crate::rng::combat::roll_dice);
                     let attr_damage_bonus = attacker_attributes.might.bonus;
                     let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
-                    let weapon_damage_bonus = weapon_info.damage_bonus;
 
-                    let damage = i32::max(
-                        0,
-                        base_damage + attr_damage_bonus + skill_damage_bonus + weapon_damage_bonus,
-                    );
+                    let damage =
+                        i32::max(0, weapon_damage + attr_damage_bonus + skill_damage_bonus);
 
-                    /*println!("Damage: {} + {}attr + {}skill + {}weapon = {}",
-                        base_damage, attr_damage_bonus, skill_damage_bonus,
-                        weapon_damage_bonus, damage
+                    /*println!("Damage: {} weapon + {}attr + {}skill = {}",
+                        weapon_damage, attr_damage_bonus, skill_damage_bonus, damage
                     );*/
                     add_effect(
                         Some(entity),
@@ -183,6 +172,9 @@ impl<'a> System<'a> for RangedCombatSystem {
                         },
                     );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
+                        .entity(entity)
+                        .entity(wants_shoot.target)
                         .npc_name(&name.name)
                         .append("hits")
                         .npc_name(&target_name.name)
@@ -193,7 +185,7 @@ impl<'a> System<'a> for RangedCombatSystem {
 
                     // Proc effects
                     if let Some(chance) = &weapon_info.proc_chance {
-                        let roll = crate::rng::roll_dice(1, 100);
+                        let roll = crate::rng::combat::roll_dice(1, 100);
                         //println!("Roll {}, Chance {}", roll, chance);
                         if roll <= (chance * 100.0) as i32 {
                             //println!("Proc!");
@@ -216,6 +208,9 @@ impl<'a> System<'a> for RangedCombatSystem {
                 } else if natural_roll == 1 {
                     // Natural 1 miss
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
+                        .entity(entity)
+                        .entity(wants_shoot.target)
                         .npc_name(&name.name)
                         .append("considers attacking")
                         .npc_name(&target_name.name)
@@ -236,6 +231,9 @@ impl<'a> System<'a> for RangedCombatSystem {
                 } else {
                     // Miss
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
+                        .entity(entity)
+                        .entity(wants_shoot.target)
                         .npc_name(&name.name)
                         .append("attacks")
                         .npc_name(&target_name.name)
@@ -1,3 +1,4 @@
+use crate::spatial::SpatialIndex;
 use crate::{effects::*, AreaOfEffect, EntityMoved, EntryTrigger, Map, Name, Position};
 use specs::prelude::*;
 
@@ -7,6 +8,7 @@ impl<'a> System<'a> for TriggerSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         WriteStorage<'a, EntityMoved>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, EntryTrigger>,
@@ -16,13 +18,21 @@ impl<'a> System<'a> for TriggerSystem {
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (map, mut entity_moved, position, entry_trigger, names, entities, area_of_effect) =
-            data;
+        let (
+            map,
+            spatial,
+            mut entity_moved,
+            position,
+            entry_trigger,
+            names,
+            entities,
+            area_of_effect,
+        ) = data;
 
         // Iterate the entities that moved and their final position
         for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &position).join() {
             let idx = map.xy_idx(pos.x, pos.y);
-            crate::spatial::for_each_tile_content(idx, |entity_id| {
+            spatial.for_each_tile_content(idx, |entity_id| {
                 if entity != entity_id {
                     // Do not bother to check yourself for being a trap!
                     let maybe_trigger = entry_trigger.get(entity_id);
@@ -33,6 +43,8 @@ impl<'a> System<'a> for TriggerSystem {
                             let name = names.get(entity_id);
                             if let Some(name) = name {
                                 crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Combat)
+                                    .entity(entity_id)
                                     .item_name(&name.name)
                                     .append("triggers!")
                                     .log();
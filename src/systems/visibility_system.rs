@@ -1,5 +1,10 @@
+use crate::effects::{add_effect, EffectType, Targets};
 use crate::perception::field_of_view::field_of_view;
-use crate::{BlocksVisibility, Hidden, Map, Name, Player, Position, Viewshed};
+use crate::spatial::SpatialIndex;
+use crate::{
+    notices_secret, Attributes, BlocksVisibility, Hidden, Map, Name, Player, Position, TileType,
+    Viewshed,
+};
 use specs::prelude::*;
 
 pub struct VisibilitySystem {}
@@ -8,6 +13,7 @@ impl<'a> System<'a> for VisibilitySystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
+        ReadExpect<'a, SpatialIndex>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Position>,
@@ -15,11 +21,22 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Hidden>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, BlocksVisibility>,
+        ReadStorage<'a, Attributes>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player, mut hidden, names, blocks_visibility) =
-            data;
+        let (
+            mut map,
+            spatial,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            mut hidden,
+            names,
+            blocks_visibility,
+            attributes,
+        ) = data;
 
         map.view_blocked.clear();
         for (block_pos, _block) in (&pos, &blocks_visibility).join() {
@@ -45,14 +62,31 @@ impl<'a> System<'a> for VisibilitySystem {
                             map.revealed_tiles[idx] = true;
                             map.visible_tiles[idx] = true;
 
+                            // Chance to notice a secret door
+                            if map.tiles[idx] == TileType::SECRET_DOOR {
+                                let intelligence = attributes
+                                    .get(ent)
+                                    .map_or(0, |attr| attr.intelligence.bonus);
+                                if notices_secret(intelligence, false) {
+                                    add_effect(
+                                        Some(ent),
+                                        EffectType::RevealSecretDoor,
+                                        Targets::Tile {
+                                            tile_idx: idx as i32,
+                                        },
+                                    );
+                                }
+                            }
+
                             // Chance to reveal hidden things
-                            crate::spatial::for_each_tile_content(idx, |e| {
+                            spatial.for_each_tile_content(idx, |e| {
                                 let maybe_hidden = hidden.get(e);
                                 if let Some(_maybe_hidden) = maybe_hidden {
                                     if crate::rng::roll_dice(1, 24) == 1 {
                                         let name = names.get(e);
                                         if let Some(name) = name {
                                             crate::gamelog::Logger::new()
+                                                .entity(e)
                                                 .append("You spotted:")
                                                 .npc_name(&name.name)
                                                 .log();